@@ -3,6 +3,7 @@ use clap::{Parser, ValueEnum};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use tg_contract_api::payments::handle::TgHandle;
 use tg_utils::path::repo_root;
 use wavs_types::ChainKey;
 
@@ -35,6 +36,268 @@ pub enum CliCommand {
         #[arg(long, default_value_t = AuthKind::ServiceManager)]
         auth_kind: AuthKind,
 
+        /// Seconds before a payment to an unregistered handle can be refunded
+        /// None means it is held until the recipient registers
+        #[arg(long)]
+        pending_expiry_seconds: Option<u64>,
+
+        /// Can change the configuration later on
+        /// None means the CLI mnemonic address
+        #[arg(long)]
+        owner: Option<String>,
+
+        #[clap(flatten)]
+        fee: FeeArgs,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Propose a new owner for the Payments contract, it must accept before taking over
+    PaymentsTransferOwnership {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long)]
+        new_owner: String,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Accept ownership of the Payments contract, must be the proposed owner
+    PaymentsAcceptOwnership {
+        #[arg(long)]
+        contract_address: String,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Withdraw a proposed ownership transfer of the Payments contract
+    PaymentsCancelOwnershipTransfer {
+        #[arg(long)]
+        contract_address: String,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Allow more denoms for payments
+    PaymentsAddDenoms {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long, required = true, num_args = 1..)]
+        denoms: Vec<String>,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Stop accepting denoms for payments, held payments can still be released or refunded
+    PaymentsRemoveDenoms {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long, required = true, num_args = 1..)]
+        denoms: Vec<String>,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Rotate the service manager or admin of the Payments contract, or switch between them
+    PaymentsSetAuth {
+        #[arg(long)]
+        contract_address: String,
+
+        /// Same as for InstantiatePayments
+        #[arg(long)]
+        auth_address: Option<String>,
+
+        #[arg(long)]
+        auth_kind: AuthKind,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Change the fee the Payments contract charges on sends
+    PaymentsSetFee {
+        #[arg(long)]
+        contract_address: String,
+
+        /// Stop charging a fee, instead of setting one
+        #[arg(long, conflicts_with_all = ["fee_basis_points", "fee_flat", "fee_treasury"])]
+        no_fee: bool,
+
+        #[clap(flatten)]
+        fee: FeeArgs,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Pause or resume sends and registrations, refunds and queries keep working
+    PaymentsSetPaused {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long, action = clap::ArgAction::Set)]
+        paused: bool,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Hold payments for handles after they register, until the registered address claims them
+    PaymentsSetPullClaims {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long, action = clap::ArgAction::Set)]
+        enabled: bool,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Claim what is held for a handle, from the address registered to receive for it
+    PaymentsClaimPending {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long)]
+        tg_handle: TgHandle,
+
+        /// Pay out here instead of to the registered address
+        #[arg(long)]
+        to: Option<String>,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Hold sends in the contract for a while before they go out, so senders can /undo them.
+    /// Neither flag sends them right away again.
+    PaymentsSetEscrowWindow {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long, conflicts_with = "seconds")]
+        blocks: Option<u64>,
+
+        #[arg(long)]
+        seconds: Option<u64>,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// How long payments to unregistered handles are held before they can be refunded.
+    /// Neither flag holds them forever.
+    PaymentsSetPendingExpiry {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long, conflicts_with = "seconds")]
+        blocks: Option<u64>,
+
+        #[arg(long)]
+        seconds: Option<u64>,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Pay out held sends whose window has passed, anyone can do this
+    PaymentsReleaseMatured {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long)]
+        limit: Option<u32>,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Stop handles and addresses from sending, receiving or registering
+    PaymentsBlock {
+        #[arg(long)]
+        contract_address: String,
+
+        #[clap(flatten)]
+        blocklist: BlocklistArgs,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Take handles and addresses off the blocklist
+    PaymentsUnblock {
+        #[arg(long)]
+        contract_address: String,
+
+        #[clap(flatten)]
+        blocklist: BlocklistArgs,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Let members receive payments on another chain, over an IBC transfer channel
+    PaymentsSetIbcRoute {
+        #[arg(long)]
+        contract_address: String,
+
+        /// Bech32 prefix of the other chain's addresses
+        #[arg(long)]
+        prefix: String,
+
+        /// Transfer channel on this chain
+        #[arg(long)]
+        channel_id: String,
+
+        /// Chain id of the other chain, shown to users
+        #[arg(long)]
+        chain_id: String,
+
+        /// How long a transfer has to arrive before it is refunded
+        #[arg(long, default_value_t = 600)]
+        timeout_seconds: u64,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Stop paying addresses with this prefix
+    PaymentsRemoveIbcRoute {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long)]
+        prefix: String,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Let users type and see amounts in this denom as a symbol with decimals, e.g. 1.5 NTRN
+    PaymentsSetDenomMetadata {
+        #[arg(long)]
+        contract_address: String,
+
+        /// Base denom, or CW20 contract address
+        #[arg(long)]
+        denom: String,
+
+        /// Shown in place of the denom
+        #[arg(long)]
+        symbol: String,
+
+        /// Decimals between the denom and the symbol
+        #[arg(long)]
+        exponent: u32,
+
+        /// Other names users can type for it
+        #[arg(long, num_args = 1..)]
+        aliases: Vec<String>,
+
+        #[clap(flatten)]
+        args: CliArgs,
+    },
+    /// Show amounts in this denom in base units again
+    PaymentsRemoveDenomMetadata {
+        #[arg(long)]
+        contract_address: String,
+
+        #[arg(long)]
+        denom: String,
+
         #[clap(flatten)]
         args: CliArgs,
     },
@@ -93,6 +356,10 @@ pub enum CliCommand {
         #[arg(long)]
         activate: bool,
 
+        /// CW20 tokens users can name by symbol in `/send`, as `SYMBOL=address,SYMBOL=address`
+        #[arg(long)]
+        cw20_symbols: Option<String>,
+
         #[clap(flatten)]
         args: CliArgs,
     },
@@ -200,6 +467,33 @@ pub struct CliArgs {
     pub output_format: OutputFormat,
 }
 
+/// Fee charged on sends by the Payments contract, none unless a treasury is given
+#[derive(Clone, Debug, Parser)]
+pub struct FeeArgs {
+    /// Share of each send, in hundredths of a percent
+    #[arg(long, requires = "fee_treasury")]
+    pub fee_basis_points: Option<u16>,
+
+    /// Flat amounts charged on top, one per denom, e.g. 100untrn
+    #[arg(long, num_args = 1.., requires = "fee_treasury")]
+    pub fee_flat: Vec<String>,
+
+    /// Where fees are paid to
+    #[arg(long)]
+    pub fee_treasury: Option<String>,
+}
+
+/// Entries to add to or remove from the Payments blocklist, at least one is required
+#[derive(Clone, Debug, Parser)]
+#[group(required = true, multiple = true)]
+pub struct BlocklistArgs {
+    #[arg(long, num_args = 1..)]
+    pub handles: Vec<TgHandle>,
+
+    #[arg(long, num_args = 1..)]
+    pub addresses: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, ValueEnum)]
 #[clap(rename_all = "snake_case")]
 #[serde(rename_all = "snake_case")]
@@ -28,6 +28,25 @@ impl CliContext {
             CliCommand::UploadContract { args, .. } => args,
             CliCommand::FaucetTap { args, .. } => args,
             CliCommand::InstantiatePayments { args, .. } => args,
+            CliCommand::PaymentsTransferOwnership { args, .. } => args,
+            CliCommand::PaymentsAcceptOwnership { args, .. } => args,
+            CliCommand::PaymentsCancelOwnershipTransfer { args, .. } => args,
+            CliCommand::PaymentsAddDenoms { args, .. } => args,
+            CliCommand::PaymentsRemoveDenoms { args, .. } => args,
+            CliCommand::PaymentsSetAuth { args, .. } => args,
+            CliCommand::PaymentsSetFee { args, .. } => args,
+            CliCommand::PaymentsSetPaused { args, .. } => args,
+            CliCommand::PaymentsSetPullClaims { args, .. } => args,
+            CliCommand::PaymentsClaimPending { args, .. } => args,
+            CliCommand::PaymentsSetEscrowWindow { args, .. } => args,
+            CliCommand::PaymentsSetPendingExpiry { args, .. } => args,
+            CliCommand::PaymentsReleaseMatured { args, .. } => args,
+            CliCommand::PaymentsBlock { args, .. } => args,
+            CliCommand::PaymentsUnblock { args, .. } => args,
+            CliCommand::PaymentsSetIbcRoute { args, .. } => args,
+            CliCommand::PaymentsRemoveIbcRoute { args, .. } => args,
+            CliCommand::PaymentsSetDenomMetadata { args, .. } => args,
+            CliCommand::PaymentsRemoveDenomMetadata { args, .. } => args,
             CliCommand::UploadComponent { args, .. } => args,
             CliCommand::UploadService { args, .. } => args,
             CliCommand::AssertAccountExists { args, .. } => args,
@@ -9,6 +9,9 @@ use cosmwasm_std::Uint256;
 use layer_climb::prelude::EvmAddr;
 use reqwest::Url;
 use serde::{de::DeserializeOwned, Deserialize};
+use tg_contract_api::payments::msg::{
+    Auth, CustomExecuteMsg, DenomMetadata, ExecuteMsg, FeeConfig, IbcRoute,
+};
 use tg_utils::{
     faucet, telegram::messenger::any_client::TelegramMessengerExt, tracing::tracing_init,
 };
@@ -18,7 +21,7 @@ use wavs_types::{
 };
 
 use crate::{
-    command::{AuthKind, CliCommand, ContractKind},
+    command::{AuthKind, CliCommand, ContractKind, FeeArgs},
     context::CliContext,
     ipfs::IpfsFile,
     output::{
@@ -215,29 +218,29 @@ async fn main() {
             allowed_denoms,
             auth_address,
             auth_kind,
+            pending_expiry_seconds,
+            owner,
+            fee,
             args,
             code_id,
         } => {
             let client = ctx.signing_client().await.unwrap();
 
-            let auth = match auth_kind {
-                AuthKind::ServiceManager => {
-                    tg_contract_api::payments::msg::Auth::ServiceManager(match auth_address {
-                        Some(addr) => ctx.parse_address(&addr).await.unwrap().to_string(),
-                        None => {
-                            panic!("Service manager auth requires an address to be provided")
-                        }
-                    })
-                }
-                AuthKind::User => tg_contract_api::payments::msg::Auth::Admin(match auth_address {
-                    Some(addr) => ctx.parse_address(&addr).await.unwrap().to_string(),
-                    None => ctx.wallet_addr().await.unwrap().to_string(),
-                }),
+            let auth = payments_auth(&ctx, auth_kind, auth_address).await;
+
+            let owner = match owner {
+                Some(addr) => Some(ctx.parse_address(&addr).await.unwrap().to_string()),
+                None => None,
             };
 
+            let fee = payments_fee(&ctx, fee).await;
+
             let instantiate_msg = tg_contract_api::payments::msg::InstantiateMsg {
                 allowed_denoms,
                 auth,
+                pending_expiry: pending_expiry_seconds.map(cw_utils::Duration::Time),
+                owner,
+                fee,
             };
 
             let (contract_addr, tx_resp) = client
@@ -263,6 +266,270 @@ async fn main() {
                 .await
                 .unwrap();
         }
+        CliCommand::PaymentsTransferOwnership {
+            contract_address,
+            new_owner,
+            args: _,
+        } => {
+            let new_owner = ctx.parse_address(&new_owner).await.unwrap().to_string();
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::TransferOwnership { new_owner },
+            )
+            .await;
+        }
+        CliCommand::PaymentsAcceptOwnership {
+            contract_address,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::AcceptOwnership {},
+            )
+            .await;
+        }
+        CliCommand::PaymentsCancelOwnershipTransfer {
+            contract_address,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::CancelOwnershipTransfer {},
+            )
+            .await;
+        }
+        CliCommand::PaymentsAddDenoms {
+            contract_address,
+            denoms,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::AddDenoms { denoms },
+            )
+            .await;
+        }
+        CliCommand::PaymentsRemoveDenoms {
+            contract_address,
+            denoms,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::RemoveDenoms { denoms },
+            )
+            .await;
+        }
+        CliCommand::PaymentsSetAuth {
+            contract_address,
+            auth_address,
+            auth_kind,
+            args: _,
+        } => {
+            let auth = payments_auth(&ctx, auth_kind, auth_address).await;
+            payments_execute(&ctx, &contract_address, CustomExecuteMsg::SetAuth { auth }).await;
+        }
+        CliCommand::PaymentsSetFee {
+            contract_address,
+            no_fee,
+            fee,
+            args: _,
+        } => {
+            let fee = if no_fee {
+                None
+            } else {
+                Some(
+                    payments_fee(&ctx, fee)
+                        .await
+                        .expect("--fee-treasury is required, or --no-fee to stop charging one"),
+                )
+            };
+            payments_execute(&ctx, &contract_address, CustomExecuteMsg::SetFee { fee }).await;
+        }
+        CliCommand::PaymentsSetPaused {
+            contract_address,
+            paused,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::SetPaused { paused },
+            )
+            .await;
+        }
+        CliCommand::PaymentsSetPullClaims {
+            contract_address,
+            enabled,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::SetPullClaims { enabled },
+            )
+            .await;
+        }
+        CliCommand::PaymentsClaimPending {
+            contract_address,
+            tg_handle,
+            to,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::ClaimPending { tg_handle, to },
+            )
+            .await;
+        }
+        CliCommand::PaymentsSetEscrowWindow {
+            contract_address,
+            blocks,
+            seconds,
+            args: _,
+        } => {
+            let window = blocks
+                .map(cw_utils::Duration::Height)
+                .or(seconds.map(cw_utils::Duration::Time));
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::SetEscrowWindow { window },
+            )
+            .await;
+        }
+        CliCommand::PaymentsSetPendingExpiry {
+            contract_address,
+            blocks,
+            seconds,
+            args: _,
+        } => {
+            let expiry = blocks
+                .map(cw_utils::Duration::Height)
+                .or(seconds.map(cw_utils::Duration::Time));
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::SetPendingExpiry { expiry },
+            )
+            .await;
+        }
+        CliCommand::PaymentsReleaseMatured {
+            contract_address,
+            limit,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::ReleaseMatured { limit },
+            )
+            .await;
+        }
+        CliCommand::PaymentsBlock {
+            contract_address,
+            blocklist,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::Block {
+                    handles: blocklist.handles,
+                    addresses: blocklist.addresses,
+                },
+            )
+            .await;
+        }
+        CliCommand::PaymentsUnblock {
+            contract_address,
+            blocklist,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::Unblock {
+                    handles: blocklist.handles,
+                    addresses: blocklist.addresses,
+                },
+            )
+            .await;
+        }
+        CliCommand::PaymentsSetIbcRoute {
+            contract_address,
+            prefix,
+            channel_id,
+            chain_id,
+            timeout_seconds,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::SetIbcRoute {
+                    route: IbcRoute {
+                        prefix,
+                        channel_id,
+                        chain_id,
+                        timeout_seconds,
+                    },
+                },
+            )
+            .await;
+        }
+        CliCommand::PaymentsRemoveIbcRoute {
+            contract_address,
+            prefix,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::RemoveIbcRoute { prefix },
+            )
+            .await;
+        }
+        CliCommand::PaymentsSetDenomMetadata {
+            contract_address,
+            denom,
+            symbol,
+            exponent,
+            aliases,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::SetDenomMetadata {
+                    metadata: DenomMetadata {
+                        denom,
+                        symbol,
+                        exponent,
+                        aliases,
+                    },
+                },
+            )
+            .await;
+        }
+        CliCommand::PaymentsRemoveDenomMetadata {
+            contract_address,
+            denom,
+            args: _,
+        } => {
+            payments_execute(
+                &ctx,
+                &contract_address,
+                CustomExecuteMsg::RemoveDenomMetadata { denom },
+            )
+            .await;
+        }
         CliCommand::FaucetTap {
             addr,
             amount,
@@ -343,6 +610,7 @@ async fn main() {
             middleware_instantiation_file,
             aggregator_url,
             activate,
+            cw20_symbols,
         } => {
             let output_directory = args.output().directory();
 
@@ -429,7 +697,17 @@ async fn main() {
                 },
                 fuel_limit: None,
                 time_limit_seconds: None,
-                config: Default::default(),
+                // The chain and contract are for reading the denom metadata amounts are typed with
+                config: [
+                    (
+                        "PAYMENTS_CONTRACT_ADDRESS".to_string(),
+                        contract_payments.address.clone(),
+                    ),
+                    ("CHAIN".to_string(), args.chain.to_string()),
+                ]
+                .into_iter()
+                .chain(cw20_symbols.map(|symbols| ("CW20_SYMBOLS".to_string(), symbols)))
+                .collect(),
                 env_keys: ["WAVS_ENV_OPERATOR_TELEGRAM_BOT_TOKEN".to_string()]
                     .into_iter()
                     .collect(),
@@ -549,6 +827,304 @@ async fn main() {
                     event_type: tg_contract_api::payments::event::ConnectEvent::EVENT_TYPE
                         .to_string(),
                 },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_5 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::SplitPaymentEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_6 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::InvoiceCreatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_7 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::InvoicePaidEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_8 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::InvoiceDeclinedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_9 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::PaymentFailedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_10 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::PauseUpdatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_11 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::BlocklistUpdatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_12 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::PaymentHeldEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_13 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::PaymentReleasedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_14 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::PaymentCancelledEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_15 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::ScheduleCreatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_16 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::ScheduleEndedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_17 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type:
+                        tg_contract_api::payments::event::ScheduleRunFailedEvent::EVENT_TYPE
+                            .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_18 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::TreasuryCreatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_19 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::TreasuryFundedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_20 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::ProposalCreatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_21 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::ProposalVotedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_22 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::ProposalExecutedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_23 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::ProposalRejectedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_24 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::GiftCreatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_25 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::GiftClaimedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_26 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::GiftRefundedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_27 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::GiveawayCreatedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_28 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::GiveawayClaimedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_29 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::GiveawayRefundedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_30 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::PayoutReturnedEvent::EVENT_TYPE
+                        .to_string(),
+                },
+                component: operator_reporter_component.clone(),
+                submit: submit_messenger.clone(),
+            };
+
+            let workflow_31 = Workflow {
+                trigger: Trigger::CosmosContractEvent {
+                    address: contract_payments.address.parse().unwrap(),
+                    chain: args.chain.clone(),
+                    event_type: tg_contract_api::payments::event::GiftReservedEvent::EVENT_TYPE
+                        .to_string(),
+                },
                 component: operator_reporter_component,
                 submit: submit_messenger,
             };
@@ -560,6 +1136,33 @@ async fn main() {
                     ("workflow-2".parse().unwrap(), workflow_2),
                     ("workflow-3".parse().unwrap(), workflow_3),
                     ("workflow-4".parse().unwrap(), workflow_4),
+                    ("workflow-5".parse().unwrap(), workflow_5),
+                    ("workflow-6".parse().unwrap(), workflow_6),
+                    ("workflow-7".parse().unwrap(), workflow_7),
+                    ("workflow-8".parse().unwrap(), workflow_8),
+                    ("workflow-9".parse().unwrap(), workflow_9),
+                    ("workflow-10".parse().unwrap(), workflow_10),
+                    ("workflow-11".parse().unwrap(), workflow_11),
+                    ("workflow-12".parse().unwrap(), workflow_12),
+                    ("workflow-13".parse().unwrap(), workflow_13),
+                    ("workflow-14".parse().unwrap(), workflow_14),
+                    ("workflow-15".parse().unwrap(), workflow_15),
+                    ("workflow-16".parse().unwrap(), workflow_16),
+                    ("workflow-17".parse().unwrap(), workflow_17),
+                    ("workflow-18".parse().unwrap(), workflow_18),
+                    ("workflow-19".parse().unwrap(), workflow_19),
+                    ("workflow-20".parse().unwrap(), workflow_20),
+                    ("workflow-21".parse().unwrap(), workflow_21),
+                    ("workflow-22".parse().unwrap(), workflow_22),
+                    ("workflow-23".parse().unwrap(), workflow_23),
+                    ("workflow-24".parse().unwrap(), workflow_24),
+                    ("workflow-25".parse().unwrap(), workflow_25),
+                    ("workflow-26".parse().unwrap(), workflow_26),
+                    ("workflow-27".parse().unwrap(), workflow_27),
+                    ("workflow-28".parse().unwrap(), workflow_28),
+                    ("workflow-29".parse().unwrap(), workflow_29),
+                    ("workflow-30".parse().unwrap(), workflow_30),
+                    ("workflow-31".parse().unwrap(), workflow_31),
                 ]
                 .into_iter()
                 .collect(),
@@ -681,3 +1284,49 @@ async fn main() {
         }
     }
 }
+
+async fn payments_auth(
+    ctx: &CliContext,
+    auth_kind: AuthKind,
+    auth_address: Option<String>,
+) -> Auth {
+    match auth_kind {
+        AuthKind::ServiceManager => Auth::ServiceManager(match auth_address {
+            Some(addr) => ctx.parse_address(&addr).await.unwrap().to_string(),
+            None => {
+                panic!("Service manager auth requires an address to be provided")
+            }
+        }),
+        AuthKind::User => Auth::Admin(match auth_address {
+            Some(addr) => ctx.parse_address(&addr).await.unwrap().to_string(),
+            None => ctx.wallet_addr().await.unwrap().to_string(),
+        }),
+    }
+}
+
+async fn payments_fee(ctx: &CliContext, fee: FeeArgs) -> Option<FeeConfig> {
+    let treasury = fee.fee_treasury?;
+
+    Some(FeeConfig {
+        basis_points: fee.fee_basis_points.unwrap_or_default(),
+        flat: fee
+            .fee_flat
+            .iter()
+            .map(|coin| coin.parse().unwrap())
+            .collect(),
+        treasury: ctx.parse_address(&treasury).await.unwrap().to_string(),
+    })
+}
+
+/// Executes an owner message on the Payments contract, signed by the CLI mnemonic
+async fn payments_execute(ctx: &CliContext, contract_address: &str, msg: CustomExecuteMsg) {
+    let client = ctx.signing_client().await.unwrap();
+    let contract_address = ctx.parse_address(contract_address).await.unwrap();
+
+    let tx_resp = client
+        .contract_execute(&contract_address, &ExecuteMsg::Custom(msg), vec![], None)
+        .await
+        .unwrap();
+
+    println!("Executed on Payments contract, tx hash: {}", tx_resp.txhash);
+}
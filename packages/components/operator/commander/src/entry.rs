@@ -2,12 +2,17 @@ use crate::{
     host::{self, LogLevel},
     parse::{map_command_to_contract, parse_update},
     state::{acquire_lock, get_offset, release_lock, set_offset},
-    tg_helpers::get_updates,
+    tg_helpers::{bot_token, get_updates},
     wavs::types::events::TriggerData,
     TriggerAction, WasmResponse,
 };
-use anyhow::Result;
-use tg_contract_api::payments::msg::WavsPayload;
+use anyhow::{anyhow, Result};
+use layer_climb::prelude::*;
+use tg_contract_api::payments::msg::{DenomMetadata, ScheduleRun, WavsPayload, MAX_BATCH_SIZE};
+use tg_utils::{
+    client::payments::PaymentsQuerier,
+    telegram::api::bot::{Cw20Symbols, DenomRegistry},
+};
 
 // the WasmResponse payload is Vec<ComponentMsg>
 pub fn handle_action(trigger_action: TriggerAction) -> Result<Option<WasmResponse>> {
@@ -23,19 +28,22 @@ pub fn handle_action(trigger_action: TriggerAction) -> Result<Option<WasmRespons
                     }
                 }
                 "read-commands" => {
+                    let denoms = denom_registry()?;
                     let commands = get_updates(None, None)?
                         .into_iter()
-                        .filter_map(parse_update)
+                        .filter_map(|update| parse_update(update, &denoms))
                         .collect::<Vec<_>>();
                     for command in commands {
                         println!("Command: {:?}", command);
                     }
                 }
                 "read-real" => {
+                    let denoms = denom_registry()?;
+                    let bot_token = bot_token()?;
                     let commands = get_updates(None, None)?
                         .into_iter()
-                        .filter_map(parse_update)
-                        .filter_map(map_command_to_contract)
+                        .filter_map(|update| parse_update(update, &denoms))
+                        .filter_map(|command| map_command_to_contract(command, &bot_token))
                         .collect::<Vec<_>>();
                     for command in commands {
                         println!("Command: {:?}", command);
@@ -106,40 +114,178 @@ pub fn handle_action(trigger_action: TriggerAction) -> Result<Option<WasmRespons
     }
 }
 
+/// The payments contract's denom metadata, so amounts can be typed like `1.5 NTRN`, and the
+/// optional `CW20_SYMBOLS` config var, e.g. `COMM=neutron1...`, which names CW20s by symbol
+fn denom_registry() -> Result<DenomRegistry> {
+    let cw20_symbols = match host::config_var("CW20_SYMBOLS") {
+        Some(symbols) => symbols.parse()?,
+        None => Cw20Symbols::default(),
+    };
+
+    // Amounts typed in base units still work without it, so don't hold up the commands
+    let denoms = denom_metadata().unwrap_or_else(|e| {
+        host::log(
+            LogLevel::Warn,
+            &format!("failed to load denom metadata, amounts are in base units: {e:?}"),
+        );
+        Vec::new()
+    });
+
+    Ok(DenomRegistry::new(denoms).with_cw20_symbols(cw20_symbols))
+}
+
+fn denom_metadata() -> Result<Vec<DenomMetadata>> {
+    wstd::runtime::block_on(async move { payments_querier().await?.denom_metadata().await })
+}
+
+/// How many held sends are due, so the cron can release them when there are no commands
+fn matured_escrows() -> Result<u32> {
+    wstd::runtime::block_on(async move { payments_querier().await?.matured_escrows().await })
+}
+
+/// The recurring payments due now, each for the period it is waiting on
+fn due_schedules() -> Result<Vec<ScheduleRun>> {
+    wstd::runtime::block_on(async move {
+        let schedules = payments_querier().await?.due_schedules(None).await?;
+        Ok(schedules
+            .into_iter()
+            .map(|schedule| ScheduleRun {
+                id: schedule.id,
+                due: schedule.next_due,
+            })
+            .collect())
+    })
+}
+
+/// Needs the `CHAIN` and `PAYMENTS_CONTRACT_ADDRESS` config vars
+async fn payments_querier() -> Result<PaymentsQuerier> {
+    let chain = host::config_var("CHAIN").ok_or_else(|| anyhow!("CHAIN config var is required"))?;
+    let payments_addr = host::config_var("PAYMENTS_CONTRACT_ADDRESS")
+        .ok_or_else(|| anyhow!("PAYMENTS_CONTRACT_ADDRESS config var is required"))?;
+    let chain_config = host::get_cosmos_chain_config(&chain)
+        .ok_or_else(|| anyhow!("failed to get chain config for {chain}"))?;
+
+    let chain_config = ChainConfig {
+        chain_id: ChainId::new(chain_config.chain_id),
+        rpc_endpoint: chain_config.rpc_endpoint,
+        grpc_endpoint: chain_config.grpc_endpoint,
+        grpc_web_endpoint: chain_config.grpc_web_endpoint,
+        gas_price: chain_config.gas_price,
+        gas_denom: chain_config.gas_denom,
+        address_kind: AddrKind::Cosmos {
+            prefix: chain_config.bech32_prefix,
+        },
+    };
+    let payments_addr = CosmosAddr::new_str(&payments_addr, None)?;
+
+    let query_client = QueryClient::new(chain_config, None).await?;
+    Ok(PaymentsQuerier::new(
+        query_client.into(),
+        payments_addr.into(),
+    ))
+}
+
+/// Optional `BATCH_SIZE` config var, how many commands go out in one envelope at most. The
+/// contract rejects a whole batch that is bigger than [`MAX_BATCH_SIZE`], and with none no
+/// command would ever go out, so anything else is refused.
+fn batch_size() -> Result<usize> {
+    let Some(size) = host::config_var("BATCH_SIZE") else {
+        return Ok(DEFAULT_BATCH_SIZE);
+    };
+    let size: usize = size.parse()?;
+    if !(1..=MAX_BATCH_SIZE).contains(&size) {
+        return Err(anyhow!(
+            "BATCH_SIZE must be between 1 and {MAX_BATCH_SIZE}, got {size}"
+        ));
+    }
+    Ok(size)
+}
+
+const DEFAULT_BATCH_SIZE: usize = 10;
+
+/// Drains up to [`batch_size`] valid commands. A lone command goes out as is, several go out as
+/// a [`WavsPayload::Batch`] where each one succeeds or fails on its own. With no commands, it
+/// releases any held sends whose window has passed, or else runs the recurring payments due.
 fn get_next_command() -> Result<Option<WavsPayload>> {
-    loop {
+    let denoms = denom_registry()?;
+    let batch_size = batch_size()?;
+    let bot_token = bot_token()?;
+    let mut commands = Vec::new();
+    while commands.len() < batch_size {
         let latest_offset: Option<i64> = get_offset()?;
 
         println!("LATEST OFFSET: {:?}", latest_offset);
 
-        let update = match get_updates(latest_offset, Some(1))?.into_iter().next() {
-            Some(update) => update,
-            None => {
-                // no more updates
-                return Ok(None);
+        // Only as many as could still fit, so none are skipped by moving the offset past them
+        let limit = (batch_size - commands.len()) as u32;
+        let updates = get_updates(latest_offset, Some(limit))?;
+        if updates.is_empty() {
+            // no more updates
+            break;
+        }
+
+        for update in updates {
+            println!("UPDATE: {:?}", update);
+
+            if let Err(e) = set_offset(update.update_id + 1) {
+                host::log(
+                    LogLevel::Error,
+                    &format!("failed to set latest offset after getting update: {e:?}"),
+                );
             }
-        };
 
-        println!("UPDATE: {:?}", update);
+            match parse_update(update, &denoms) {
+                Some(command) => {
+                    println!("COMMAND: {:?}", command);
+                    if let Some(contract_msg) = map_command_to_contract(command, &bot_token) {
+                        // got a real command
+                        commands.push(contract_msg);
+                    }
+                }
+                None => {
+                    host::log(LogLevel::Warn, "No valid message found in the update");
+                }
+            };
+        }
+    }
+
+    match commands.len() {
+        0 => Ok(upkeep()),
+        1 => Ok(commands.pop()),
+        _ => Ok(Some(WavsPayload::Batch(commands))),
+    }
+}
+
+/// Work the contract has due on its own, one kind per tick. Every operator submits the same
+/// runs, and the contract pays each period once however many of them land.
+fn upkeep() -> Option<WavsPayload> {
+    match matured_escrows() {
+        Ok(0) => {}
+        Ok(count) => {
+            host::log(LogLevel::Info, &format!("{count} held payments to release"));
+            return Some(WavsPayload::ReleaseMatured { limit: None });
+        }
+        Err(e) => host::log(
+            LogLevel::Warn,
+            &format!("failed to check for held payments to release: {e:?}"),
+        ),
+    }
 
-        if let Err(e) = set_offset(update.update_id + 1) {
+    match due_schedules() {
+        Ok(runs) if runs.is_empty() => None,
+        Ok(runs) => {
             host::log(
-                LogLevel::Error,
-                &format!("failed to set latest offset after getting update: {e:?}"),
+                LogLevel::Info,
+                &format!("{} recurring payments due", runs.len()),
             );
+            Some(WavsPayload::RunSchedules(runs))
+        }
+        Err(e) => {
+            host::log(
+                LogLevel::Warn,
+                &format!("failed to check for recurring payments due: {e:?}"),
+            );
+            None
         }
-
-        match parse_update(update) {
-            Some(command) => {
-                println!("COMMAND: {:?}", command);
-                if let Some(contract_msg) = map_command_to_contract(command) {
-                    // got a real command
-                    return Ok(Some(contract_msg));
-                }
-            }
-            None => {
-                host::log(LogLevel::Warn, "No valid message found in the update");
-            }
-        };
     }
 }
@@ -1,23 +1,46 @@
-use tg_contract_api::payments::msg::{RegisterReceiveMsg, SendPaymentMsg, WavsPayload};
+use tg_contract_api::payments::{
+    handle::TgHandle,
+    msg::{
+        CancelPaymentMsg, ClaimGiftMsg, ClaimGiveawayMsg, CreateGiftMsg, CreateGiveawayMsg,
+        CreateTreasuryMsg, FundTreasuryMsg, Gift, InvoiceActionMsg, ProposePayoutMsg,
+        RegisterReceiveMsg, RequestPaymentMsg, SchedulePaymentMsg, SendPaymentMsg, SplitPaymentMsg,
+        TipMsg, UnscheduleMsg, VoteMsg, WavsPayload,
+    },
+};
 use tg_utils::telegram::api::{
-    bot::{TelegramBotCommand, TelegramWavsCommand},
+    bot::{gift_secret, DenomRegistry, StartParam, TelegramBotCommand, TelegramWavsCommand},
     native::{TelegramMessage, TelegramUpdate},
 };
 
-pub fn parse_update(update: TelegramUpdate) -> Option<TelegramBotCommand> {
-    update_into_message(update).and_then(|text| TelegramBotCommand::try_from(text).ok())
+pub fn parse_update(update: TelegramUpdate, denoms: &DenomRegistry) -> Option<TelegramBotCommand> {
+    // Buttons pressed on an invoice prompt
+    if let Some(query) = update.callback_query {
+        return TelegramBotCommand::from_callback(query).ok();
+    }
+
+    update_into_message(update).and_then(|text| TelegramBotCommand::parse(text, denoms).ok())
 }
 
+/// `bot_token` derives the secret behind a gift link, see [`gift_secret`]
 pub fn map_command_to_contract(
     TelegramBotCommand { command, raw }: TelegramBotCommand,
+    bot_token: &str,
 ) -> Option<WavsPayload> {
-    let from_handle = raw.from.username?;
+    // The numeric id is the identity, the username is only an alias and may be missing
+    let from_id = raw.from.id;
+    // Telegram only hands out valid usernames, but never trust it to key the contract
+    let from_handle = raw
+        .from
+        .username
+        .and_then(|username| TgHandle::new(&username).ok());
 
     match command {
         TelegramWavsCommand::Receive { address } => {
             Some(WavsPayload::Register(RegisterReceiveMsg {
+                chat_id: raw.chat.id,
                 message_id: raw.message_id,
                 chain_addr: address.to_string(),
+                tg_user_id: from_id,
                 tg_handle: from_handle,
             }))
         }
@@ -26,12 +49,202 @@ pub fn map_command_to_contract(
             amount,
             denom,
         } => Some(WavsPayload::SendPayment(SendPaymentMsg {
+            chat_id: raw.chat.id,
             message_id: raw.message_id,
+            from_tg_id: from_id,
             from_tg: from_handle,
             to_tg: to_handle,
             amount: amount.into(),
             denom,
         })),
+        TelegramWavsCommand::Split {
+            amount,
+            denom,
+            recipients,
+        } => Some(WavsPayload::SplitPayment(SplitPaymentMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            from_tg_id: from_id,
+            from_tg: from_handle,
+            amount,
+            denom,
+            recipients,
+        })),
+        TelegramWavsCommand::Tip { amount, denom } => {
+            // Tip whoever wrote the replied-to message, by id since they may have no username
+            let to = raw.reply_to_message?.from;
+            Some(WavsPayload::Tip(TipMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                from_tg_id: from_id,
+                from_tg: from_handle,
+                to_tg_id: to.id,
+                to_tg: to
+                    .username
+                    .and_then(|username| TgHandle::new(&username).ok()),
+                amount,
+                denom,
+            }))
+        }
+        TelegramWavsCommand::Request {
+            handle,
+            amount,
+            denom,
+            memo,
+        } => Some(WavsPayload::RequestPayment(RequestPaymentMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            from_tg_id: from_id,
+            from_tg: from_handle,
+            payer_tg: handle,
+            amount,
+            denom,
+            memo,
+        })),
+        TelegramWavsCommand::PayInvoice { invoice_id } => {
+            Some(WavsPayload::PayInvoice(InvoiceActionMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                from_tg_id: from_id,
+                from_tg: from_handle,
+                invoice_id,
+            }))
+        }
+        TelegramWavsCommand::DeclineInvoice { invoice_id } => {
+            Some(WavsPayload::DeclineInvoice(InvoiceActionMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                from_tg_id: from_id,
+                from_tg: from_handle,
+                invoice_id,
+            }))
+        }
+        TelegramWavsCommand::Undo { escrow_id } => {
+            Some(WavsPayload::CancelPayment(CancelPaymentMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                from_tg_id: from_id,
+                escrow_id,
+            }))
+        }
+        TelegramWavsCommand::Schedule {
+            handle,
+            amount,
+            denom,
+            interval_seconds,
+            until,
+        } => Some(WavsPayload::SchedulePayment(SchedulePaymentMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            from_tg_id: from_id,
+            from_tg: from_handle,
+            to_tg: handle,
+            amount,
+            denom,
+            interval_seconds,
+            until,
+        })),
+        TelegramWavsCommand::Unschedule { schedule_id } => {
+            Some(WavsPayload::Unschedule(UnscheduleMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                from_tg_id: from_id,
+                schedule_id,
+            }))
+        }
+        TelegramWavsCommand::Gift {
+            amount,
+            denom,
+            expires_in_seconds,
+        } => Some(WavsPayload::CreateGift(CreateGiftMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            from_tg_id: from_id,
+            from_tg: from_handle,
+            amount,
+            denom,
+            hash: Gift::hash_secret(&gift_secret(bot_token, raw.chat.id, raw.message_id)),
+            expires_in_seconds,
+        })),
+        TelegramWavsCommand::Start {
+            param: Some(StartParam::ClaimGift { secret }),
+        } => Some(WavsPayload::ClaimGift(ClaimGiftMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            to_tg_id: from_id,
+            to_tg: from_handle,
+            secret,
+        })),
+        TelegramWavsCommand::Giveaway {
+            amount,
+            denom,
+            count,
+            split,
+        } => Some(WavsPayload::CreateGiveaway(CreateGiveawayMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            from_tg_id: from_id,
+            from_tg: from_handle,
+            amount,
+            denom,
+            count,
+            split,
+        })),
+        TelegramWavsCommand::ClaimGiveaway { giveaway_id } => {
+            Some(WavsPayload::ClaimGiveaway(ClaimGiveawayMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                to_tg_id: from_id,
+                to_tg: from_handle,
+                giveaway_id,
+            }))
+        }
+        TelegramWavsCommand::CreateTreasury { threshold, members } => {
+            Some(WavsPayload::CreateTreasury(CreateTreasuryMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                from_tg_id: from_id,
+                from_tg: from_handle,
+                members,
+                threshold,
+            }))
+        }
+        TelegramWavsCommand::FundTreasury { amount, denom } => {
+            Some(WavsPayload::FundTreasury(FundTreasuryMsg {
+                chat_id: raw.chat.id,
+                message_id: raw.message_id,
+                from_tg_id: from_id,
+                from_tg: from_handle,
+                amount,
+                denom,
+            }))
+        }
+        TelegramWavsCommand::Propose {
+            handle,
+            amount,
+            denom,
+            memo,
+        } => Some(WavsPayload::ProposePayout(ProposePayoutMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            from_tg_id: from_id,
+            from_tg: from_handle,
+            to_tg: handle,
+            amount,
+            denom,
+            memo,
+        })),
+        TelegramWavsCommand::Vote {
+            proposal_id,
+            approve,
+        } => Some(WavsPayload::Vote(VoteMsg {
+            chat_id: raw.chat.id,
+            message_id: raw.message_id,
+            from_tg_id: from_id,
+            from_tg: from_handle,
+            proposal_id,
+            approve,
+        })),
         _ => None,
     }
 }
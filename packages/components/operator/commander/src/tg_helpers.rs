@@ -4,7 +4,7 @@ use tg_utils::telegram::{
     messenger::{any_client::TelegramMessengerExt, wasi_client::TelegramMessenger},
 };
 
-pub fn get_updates(offset: Option<i64>, limit: Option<u32>) -> Result<Vec<TelegramUpdate>> {
+pub fn bot_token() -> Result<String> {
     let bot_token = std::env::var("WAVS_ENV_OPERATOR_TELEGRAM_BOT_TOKEN").unwrap_or_default();
 
     if bot_token.is_empty() {
@@ -13,7 +13,11 @@ pub fn get_updates(offset: Option<i64>, limit: Option<u32>) -> Result<Vec<Telegr
         ));
     }
 
-    let tg_messenger = TelegramMessenger::new(bot_token);
+    Ok(bot_token)
+}
+
+pub fn get_updates(offset: Option<i64>, limit: Option<u32>) -> Result<Vec<TelegramUpdate>> {
+    let tg_messenger = TelegramMessenger::new(bot_token()?);
 
     Ok(wstd::runtime::block_on(async move {
         tg_messenger.get_updates(offset, limit, None, None).await
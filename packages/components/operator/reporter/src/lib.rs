@@ -1,5 +1,14 @@
 use tg_components_shared::ReportEvent;
-use tg_contract_api::payments::event::{ConnectEvent, RegistrationEvent, SendPaymentEvent};
+use tg_contract_api::payments::event::{
+    BlocklistUpdatedEvent, ConnectEvent, GiftClaimedEvent, GiftCreatedEvent, GiftRefundedEvent,
+    GiftReservedEvent, GiveawayClaimedEvent, GiveawayCreatedEvent, GiveawayRefundedEvent,
+    InvoiceCreatedEvent, InvoiceDeclinedEvent, InvoicePaidEvent, PauseUpdatedEvent,
+    PaymentCancelledEvent, PaymentFailedEvent, PaymentHeldEvent, PaymentReleasedEvent,
+    PayoutReturnedEvent, ProposalCreatedEvent, ProposalExecutedEvent, ProposalRejectedEvent,
+    ProposalVotedEvent, RegistrationEvent, ScheduleCreatedEvent, ScheduleEndedEvent,
+    ScheduleRunFailedEvent, SendPaymentEvent, SplitPaymentEvent, TreasuryCreatedEvent,
+    TreasuryFundedEvent,
+};
 
 use crate::{host::LogLevel, wavs::types::events::TriggerData};
 
@@ -18,37 +27,63 @@ impl Guest for Component {
                 let event = cosmwasm_std::Event::new(event_data.event.ty)
                     .add_attributes(event_data.event.attributes);
 
-                let as_registration =
-                    RegistrationEvent::try_from(&event).map(ReportEvent::Registration);
-                let as_payment = SendPaymentEvent::try_from(&event).map(ReportEvent::SendPayment);
-                let as_connect = ConnectEvent::try_from(&event).map(ReportEvent::Connect);
+                // Each event type has its own workflow, so exactly one of these should match
+                let mut reports = [
+                    RegistrationEvent::try_from(&event).map(ReportEvent::Registration),
+                    SendPaymentEvent::try_from(&event).map(ReportEvent::SendPayment),
+                    SplitPaymentEvent::try_from(&event).map(ReportEvent::SplitPayment),
+                    ConnectEvent::try_from(&event).map(ReportEvent::Connect),
+                    InvoiceCreatedEvent::try_from(&event).map(ReportEvent::InvoiceCreated),
+                    InvoicePaidEvent::try_from(&event).map(ReportEvent::InvoicePaid),
+                    InvoiceDeclinedEvent::try_from(&event).map(ReportEvent::InvoiceDeclined),
+                    PaymentFailedEvent::try_from(&event).map(ReportEvent::PaymentFailed),
+                    PauseUpdatedEvent::try_from(&event).map(ReportEvent::PauseUpdated),
+                    BlocklistUpdatedEvent::try_from(&event).map(ReportEvent::BlocklistUpdated),
+                    PaymentHeldEvent::try_from(&event).map(ReportEvent::PaymentHeld),
+                    PaymentReleasedEvent::try_from(&event).map(ReportEvent::PaymentReleased),
+                    PaymentCancelledEvent::try_from(&event).map(ReportEvent::PaymentCancelled),
+                    ScheduleCreatedEvent::try_from(&event).map(ReportEvent::ScheduleCreated),
+                    ScheduleEndedEvent::try_from(&event).map(ReportEvent::ScheduleEnded),
+                    ScheduleRunFailedEvent::try_from(&event).map(ReportEvent::ScheduleRunFailed),
+                    TreasuryCreatedEvent::try_from(&event).map(ReportEvent::TreasuryCreated),
+                    TreasuryFundedEvent::try_from(&event).map(ReportEvent::TreasuryFunded),
+                    ProposalCreatedEvent::try_from(&event).map(ReportEvent::ProposalCreated),
+                    ProposalVotedEvent::try_from(&event).map(ReportEvent::ProposalVoted),
+                    ProposalExecutedEvent::try_from(&event).map(ReportEvent::ProposalExecuted),
+                    ProposalRejectedEvent::try_from(&event).map(ReportEvent::ProposalRejected),
+                    GiftCreatedEvent::try_from(&event).map(ReportEvent::GiftCreated),
+                    GiftClaimedEvent::try_from(&event).map(ReportEvent::GiftClaimed),
+                    GiftRefundedEvent::try_from(&event).map(ReportEvent::GiftRefunded),
+                    GiftReservedEvent::try_from(&event).map(ReportEvent::GiftReserved),
+                    GiveawayCreatedEvent::try_from(&event).map(ReportEvent::GiveawayCreated),
+                    GiveawayClaimedEvent::try_from(&event).map(ReportEvent::GiveawayClaimed),
+                    GiveawayRefundedEvent::try_from(&event).map(ReportEvent::GiveawayRefunded),
+                    PayoutReturnedEvent::try_from(&event).map(ReportEvent::PayoutReturned),
+                ]
+                .into_iter()
+                .filter_map(Result::ok)
+                .collect::<Vec<_>>();
 
-                let is_registration_ok = as_registration.is_ok();
-                let is_payment_ok = as_payment.is_ok();
-                let is_connect_ok = as_connect.is_ok();
-
-                match (as_registration, as_payment, as_connect) {
-                    (Ok(report), Err(_), Err(_))
-                    | (Err(_), Ok(report), Err(_))
-                    | (Err(_), Err(_), Ok(report)) => {
+                match (reports.pop(), reports.is_empty()) {
+                    (Some(report), true) => {
                         let wasm_response = WasmResponse {
                             payload: serde_json::to_vec(&report).map_err(|e| e.to_string())?,
                             ordering: None,
                         };
                         Ok(Some(wasm_response))
                     }
-                    _ => {
-                        if is_registration_ok || is_payment_ok || is_connect_ok {
-                            host::log(
-                                LogLevel::Error,
-                                "Ambiguous event: parsed as multiple event types",
-                            );
-                        } else {
-                            host::log(
-                                LogLevel::Warn,
-                                "Could not parse event as RegistrationEvent, SendPaymentEvent, or ConnectEvent",
-                            );
-                        }
+                    (Some(_), false) => {
+                        host::log(
+                            LogLevel::Error,
+                            "Ambiguous event: parsed as multiple event types",
+                        );
+                        Ok(None)
+                    }
+                    (None, _) => {
+                        host::log(
+                            LogLevel::Warn,
+                            "Could not parse event as any of the reported event types",
+                        );
                         Ok(None)
                     }
                 }
@@ -1,11 +1,55 @@
 use serde::{Deserialize, Serialize};
-use tg_contract_api::payments::event::{ConnectEvent, RegistrationEvent, SendPaymentEvent};
+use tg_contract_api::payments::event::{
+    BlocklistUpdatedEvent, ConnectEvent, GiftClaimedEvent, GiftCreatedEvent, GiftRefundedEvent,
+    GiftReservedEvent, GiveawayClaimedEvent, GiveawayCreatedEvent, GiveawayRefundedEvent,
+    InvoiceCreatedEvent, InvoiceDeclinedEvent, InvoicePaidEvent, PauseUpdatedEvent,
+    PaymentCancelledEvent, PaymentFailedEvent, PaymentHeldEvent, PaymentReleasedEvent,
+    PayoutReturnedEvent, ProposalCreatedEvent, ProposalExecutedEvent, ProposalRejectedEvent,
+    ProposalVotedEvent, RegistrationEvent, ScheduleCreatedEvent, ScheduleEndedEvent,
+    ScheduleRunFailedEvent, SendPaymentEvent, SplitPaymentEvent, TreasuryCreatedEvent,
+    TreasuryFundedEvent,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ReportEvent {
     Registration(RegistrationEvent),
     SendPayment(SendPaymentEvent),
+    SplitPayment(SplitPaymentEvent),
     Connect(ConnectEvent),
+    InvoiceCreated(InvoiceCreatedEvent),
+    InvoicePaid(InvoicePaidEvent),
+    InvoiceDeclined(InvoiceDeclinedEvent),
+    /// Relayed to the sender only, not the group
+    PaymentFailed(PaymentFailedEvent),
+    PauseUpdated(PauseUpdatedEvent),
+    BlocklistUpdated(BlocklistUpdatedEvent),
+    PaymentHeld(PaymentHeldEvent),
+    PaymentReleased(PaymentReleasedEvent),
+    PaymentCancelled(PaymentCancelledEvent),
+    ScheduleCreated(ScheduleCreatedEvent),
+    ScheduleEnded(ScheduleEndedEvent),
+    /// Relayed to the sender only, not the group
+    ScheduleRunFailed(ScheduleRunFailedEvent),
+    TreasuryCreated(TreasuryCreatedEvent),
+    TreasuryFunded(TreasuryFundedEvent),
+    ProposalCreated(ProposalCreatedEvent),
+    ProposalVoted(ProposalVotedEvent),
+    ProposalExecuted(ProposalExecutedEvent),
+    ProposalRejected(ProposalRejectedEvent),
+    /// Relayed to the sender only, with the link to share
+    GiftCreated(GiftCreatedEvent),
+    /// Relayed to the sender only, not the group
+    GiftClaimed(GiftClaimedEvent),
+    /// Relayed to the sender only, not the group
+    GiftRefunded(GiftRefundedEvent),
+    /// Relayed to the claimer only, so they know to register
+    GiftReserved(GiftReservedEvent),
+    /// Posted with the Claim button
+    GiveawayCreated(GiveawayCreatedEvent),
+    GiveawayClaimed(GiveawayClaimedEvent),
+    GiveawayRefunded(GiveawayRefundedEvent),
+    /// Relayed to the recipient only, if we know who they are
+    PayoutReturned(PayoutReturnedEvent),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,45 @@
+//! The part of the CW20 interface the payments contract relies on.
+//!
+//! Wire compatible with the `cw20` spec, which has no CosmWasm 3 release yet.
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Uint128;
+use cw_utils::Expiration;
+
+#[cw_serde]
+pub enum Cw20ExecuteMsg {
+    /// Moves tokens from the sender to `recipient`
+    Transfer { recipient: String, amount: Uint128 },
+    /// Moves tokens from `owner` to `recipient`, using the allowance `owner` gave the sender
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Lets `spender` move up to `amount` more of the sender's tokens
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum Cw20QueryMsg {
+    #[returns(BalanceResponse)]
+    Balance { address: String },
+    #[returns(AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+}
+
+#[cw_serde]
+pub struct BalanceResponse {
+    pub balance: Uint128,
+}
+
+#[cw_serde]
+pub struct AllowanceResponse {
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
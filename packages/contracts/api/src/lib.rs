@@ -1 +1,2 @@
+pub mod cw20;
 pub mod payments;
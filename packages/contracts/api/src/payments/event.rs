@@ -1,26 +1,41 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint256};
+use cosmwasm_std::{Addr, Coin, Timestamp, Uint256};
+use cw_utils::{Duration, Expiration};
+
+use crate::payments::handle::TgHandle;
+use crate::payments::msg::{Auth, DenomMetadata, FeeConfig, GiveawaySplit, IbcRoute};
 
 #[cw_serde]
 pub struct RegistrationEvent {
-    pub tg_handle: String,
+    pub tg_user_id: i64,
+    /// Not every Telegram user has a username
+    pub tg_handle: Option<TgHandle>,
     pub address: Addr,
 }
 
 impl RegistrationEvent {
     pub const EVENT_TYPE: &'static str = "registration";
+    pub const EVENT_ATTR_KEY_TG_USER_ID: &'static str = "tg-user-id";
     pub const EVENT_ATTR_KEY_TG_HANDLE: &'static str = "tg-handle";
     pub const EVENT_ATTR_KEY_ADDRESS: &'static str = "address";
 }
 
 impl From<RegistrationEvent> for cosmwasm_std::Event {
     fn from(src: RegistrationEvent) -> Self {
-        cosmwasm_std::Event::new(RegistrationEvent::EVENT_TYPE)
-            .add_attribute(RegistrationEvent::EVENT_ATTR_KEY_TG_HANDLE, src.tg_handle)
+        let event = cosmwasm_std::Event::new(RegistrationEvent::EVENT_TYPE)
+            .add_attribute(
+                RegistrationEvent::EVENT_ATTR_KEY_TG_USER_ID,
+                src.tg_user_id.to_string(),
+            )
             .add_attribute(
                 RegistrationEvent::EVENT_ATTR_KEY_ADDRESS,
                 src.address.to_string(),
-            )
+            );
+        add_opt_attr(
+            event,
+            RegistrationEvent::EVENT_ATTR_KEY_TG_HANDLE,
+            src.tg_handle.map(String::from),
+        )
     }
 }
 
@@ -28,72 +43,57 @@ impl TryFrom<&cosmwasm_std::Event> for RegistrationEvent {
     type Error = anyhow::Error;
 
     fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
-        if event.ty != Self::EVENT_TYPE && event.ty != format!("wasm-{}", Self::EVENT_TYPE) {
-            return Err(anyhow::anyhow!(
-                "Expected event type {}, found {}",
-                Self::EVENT_TYPE,
-                event.ty
-            ));
-        }
-
-        let mut tg_handle = None;
-        let mut address = None;
-
-        for attr in event.attributes.iter() {
-            match attr.key.as_str() {
-                Self::EVENT_ATTR_KEY_TG_HANDLE => tg_handle = Some(attr.value.to_string()),
-                Self::EVENT_ATTR_KEY_ADDRESS => {
-                    address = Some(Addr::unchecked(attr.value.to_string()))
-                }
-                _ => {}
-            }
-        }
+        check_event_type(event, Self::EVENT_TYPE)?;
 
-        match (tg_handle, address) {
-            (Some(tg_handle), Some(address)) => Ok(Self { tg_handle, address }),
-            (Some(_), None) => Err(anyhow::anyhow!(
-                "Missing attribute {}",
-                Self::EVENT_ATTR_KEY_ADDRESS
-            )),
-            (None, Some(_)) => Err(anyhow::anyhow!(
-                "Missing attribute {}",
-                Self::EVENT_ATTR_KEY_TG_HANDLE
-            )),
-            _ => Err(anyhow::anyhow!("Missing required attributes")),
-        }
+        Ok(Self {
+            tg_user_id: parse_attr(event, Self::EVENT_ATTR_KEY_TG_USER_ID)?,
+            tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TG_HANDLE)?,
+            address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_ADDRESS)?),
+        })
     }
 }
 
 #[cw_serde]
 pub struct SendPaymentEvent {
-    pub from_tg_handle: String,
-    pub to_tg_handle: String,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    /// None when the recipient is not registered and the payment is held by the contract
+    pub to_tg_id: Option<i64>,
+    /// None when tipping a registered user who has no username
+    pub to_tg_handle: Option<TgHandle>,
     pub from_address: Addr,
     pub to_address: Addr,
     pub amount: Uint256,
     pub denom: String,
+    /// Paid to the treasury on top of `amount`, in the same denom. None when there was no fee.
+    pub fee: Option<Uint256>,
+    /// The transfer channel, when `to_address` is on another chain
+    pub ibc_channel: Option<String>,
+    /// Chain id of the other chain, when `to_address` is on one
+    pub to_chain: Option<String>,
 }
 
 impl SendPaymentEvent {
     pub const EVENT_TYPE: &'static str = "send-payment";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
     pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
     pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
     pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
     pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
     pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
     pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+    pub const EVENT_ATTR_KEY_IBC_CHANNEL: &'static str = "ibc-channel";
+    pub const EVENT_ATTR_KEY_TO_CHAIN: &'static str = "to-chain";
 }
 
 impl From<SendPaymentEvent> for cosmwasm_std::Event {
     fn from(src: SendPaymentEvent) -> Self {
-        cosmwasm_std::Event::new(SendPaymentEvent::EVENT_TYPE)
+        let event = cosmwasm_std::Event::new(SendPaymentEvent::EVENT_TYPE)
             .add_attribute(
-                SendPaymentEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
-                src.from_tg_handle,
-            )
-            .add_attribute(
-                SendPaymentEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
-                src.to_tg_handle,
+                SendPaymentEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
             )
             .add_attribute(
                 SendPaymentEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
@@ -101,7 +101,37 @@ impl From<SendPaymentEvent> for cosmwasm_std::Event {
             )
             .add_attribute(SendPaymentEvent::EVENT_ATTR_KEY_TO_ADDRESS, src.to_address)
             .add_attribute(SendPaymentEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
-            .add_attribute(SendPaymentEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(SendPaymentEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            SendPaymentEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            SendPaymentEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+            src.to_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            SendPaymentEvent::EVENT_ATTR_KEY_TO_TG_ID,
+            src.to_tg_id.map(|id| id.to_string()),
+        );
+        let event = add_opt_attr(
+            event,
+            SendPaymentEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        );
+        let event = add_opt_attr(
+            event,
+            SendPaymentEvent::EVENT_ATTR_KEY_IBC_CHANNEL,
+            src.ibc_channel,
+        );
+        add_opt_attr(
+            event,
+            SendPaymentEvent::EVENT_ATTR_KEY_TO_CHAIN,
+            src.to_chain,
+        )
     }
 }
 
@@ -109,167 +139,3179 @@ impl TryFrom<&cosmwasm_std::Event> for SendPaymentEvent {
     type Error = anyhow::Error;
 
     fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
-        if event.ty != Self::EVENT_TYPE && event.ty != format!("wasm-{}", Self::EVENT_TYPE) {
-            return Err(anyhow::anyhow!(
-                "Expected event type {}, found {}",
-                Self::EVENT_TYPE,
-                event.ty
-            ));
-        }
+        check_event_type(event, Self::EVENT_TYPE)?;
 
-        let mut from_tg_handle = None;
-        let mut to_tg_handle = None;
-        let mut from_address = None;
-        let mut to_address = None;
-        let mut amount = None;
-        let mut denom = None;
-
-        for attr in event.attributes.iter() {
-            match attr.key.as_str() {
-                Self::EVENT_ATTR_KEY_FROM_TG_HANDLE => {
-                    from_tg_handle = Some(attr.value.to_string())
-                }
-                Self::EVENT_ATTR_KEY_TO_TG_HANDLE => to_tg_handle = Some(attr.value.to_string()),
-                Self::EVENT_ATTR_KEY_FROM_ADDRESS => from_address = Some(attr.value.to_string()),
-                Self::EVENT_ATTR_KEY_TO_ADDRESS => to_address = Some(attr.value.to_string()),
-                Self::EVENT_ATTR_KEY_AMOUNT => amount = Some(attr.value.to_string()),
-                Self::EVENT_ATTR_KEY_DENOM => denom = Some(attr.value.to_string()),
-                _ => {}
-            }
-        }
+        Ok(Self {
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_id: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+            ibc_channel: get_opt_attr(event, Self::EVENT_ATTR_KEY_IBC_CHANNEL),
+            to_chain: get_opt_attr(event, Self::EVENT_ATTR_KEY_TO_CHAIN),
+        })
+    }
+}
 
-        let from_tg_handle = match from_tg_handle {
-            Some(val) => val,
-            None => {
-                return Err(anyhow::anyhow!(
-                    "Missing attribute {}",
-                    Self::EVENT_ATTR_KEY_FROM_TG_HANDLE
-                ))
-            }
-        };
+/// A send whose transfer failed, e.g. for a missing grant or an empty balance. Nothing moved and
+/// nothing was recorded, the sender is told why instead of the whole message reverting.
+#[cw_serde]
+pub struct PaymentFailedEvent {
+    /// The Telegram message that asked for the payment
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_id: Option<i64>,
+    pub to_tg_handle: Option<TgHandle>,
+    pub amount: Uint256,
+    pub denom: String,
+    /// As reported by the chain
+    pub reason: String,
+}
 
-        let to_tg_handle = match to_tg_handle {
-            Some(val) => val,
-            None => {
-                return Err(anyhow::anyhow!(
-                    "Missing attribute {}",
-                    Self::EVENT_ATTR_KEY_TO_TG_HANDLE
-                ))
-            }
-        };
+impl PaymentFailedEvent {
+    pub const EVENT_TYPE: &'static str = "payment-failed";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_MESSAGE_ID: &'static str = "message-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_REASON: &'static str = "reason";
+}
 
-        let from_address = match from_address {
-            Some(val) => Addr::unchecked(val),
-            None => {
-                return Err(anyhow::anyhow!(
-                    "Missing attribute {}",
-                    Self::EVENT_ATTR_KEY_FROM_ADDRESS
-                ))
-            }
-        };
+impl From<PaymentFailedEvent> for cosmwasm_std::Event {
+    fn from(src: PaymentFailedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PaymentFailedEvent::EVENT_TYPE)
+            .add_attribute(
+                PaymentFailedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                PaymentFailedEvent::EVENT_ATTR_KEY_MESSAGE_ID,
+                src.message_id.to_string(),
+            )
+            .add_attribute(
+                PaymentFailedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(PaymentFailedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(PaymentFailedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(PaymentFailedEvent::EVENT_ATTR_KEY_REASON, src.reason);
+        let event = add_opt_attr(
+            event,
+            PaymentFailedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            PaymentFailedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+            src.to_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            PaymentFailedEvent::EVENT_ATTR_KEY_TO_TG_ID,
+            src.to_tg_id.map(|id| id.to_string()),
+        )
+    }
+}
 
-        let to_address = match to_address {
-            Some(val) => Addr::unchecked(val),
-            None => {
-                return Err(anyhow::anyhow!(
-                    "Missing attribute {}",
-                    Self::EVENT_ATTR_KEY_TO_ADDRESS
-                ))
-            }
-        };
+impl TryFrom<&cosmwasm_std::Event> for PaymentFailedEvent {
+    type Error = anyhow::Error;
 
-        let amount = match amount {
-            Some(val) => val.parse::<Uint256>().map_err(|_| {
-                anyhow::anyhow!("Invalid attribute {}: {}", Self::EVENT_ATTR_KEY_AMOUNT, val)
-            })?,
-            None => {
-                return Err(anyhow::anyhow!(
-                    "Missing attribute {}",
-                    Self::EVENT_ATTR_KEY_AMOUNT
-                ))
-            }
-        };
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
 
-        let denom = match denom {
-            Some(val) => val,
-            None => {
-                return Err(anyhow::anyhow!(
-                    "Missing attribute {}",
-                    Self::EVENT_ATTR_KEY_DENOM
-                ))
-            }
-        };
+        Ok(Self {
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            message_id: parse_attr(event, Self::EVENT_ATTR_KEY_MESSAGE_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_id: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            reason: get_attr(event, Self::EVENT_ATTR_KEY_REASON)?,
+        })
+    }
+}
+
+/// Funds the contract was holding, paid out to another chain, that didn't arrive. ICS20 gave them
+/// back to the contract, which put them back where they came from: exactly one of `held_for`,
+/// `treasury_chat_id` and `refunded_to` is set.
+#[cw_serde]
+pub struct PayoutReturnedEvent {
+    pub to_tg_id: Option<i64>,
+    pub to_tg_handle: Option<TgHandle>,
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Held again for this handle, when it was being released to its owner
+    pub held_for: Option<TgHandle>,
+    /// Back in this group's treasury, when it paid out a proposal
+    pub treasury_chat_id: Option<i64>,
+    /// Sent back to whoever put it in, for a held send, a gift or a giveaway
+    pub refunded_to: Option<Addr>,
+    pub reason: String,
+}
+
+impl PayoutReturnedEvent {
+    pub const EVENT_TYPE: &'static str = "payout-returned";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_HELD_FOR: &'static str = "held-for";
+    pub const EVENT_ATTR_KEY_TREASURY_CHAT_ID: &'static str = "treasury-chat-id";
+    pub const EVENT_ATTR_KEY_REFUNDED_TO: &'static str = "refunded-to";
+    pub const EVENT_ATTR_KEY_REASON: &'static str = "reason";
+}
+
+impl From<PayoutReturnedEvent> for cosmwasm_std::Event {
+    fn from(src: PayoutReturnedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PayoutReturnedEvent::EVENT_TYPE)
+            .add_attribute(
+                PayoutReturnedEvent::EVENT_ATTR_KEY_TO_ADDRESS,
+                src.to_address,
+            )
+            .add_attribute(PayoutReturnedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(PayoutReturnedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(PayoutReturnedEvent::EVENT_ATTR_KEY_REASON, src.reason);
+        let event = add_opt_attr(
+            event,
+            PayoutReturnedEvent::EVENT_ATTR_KEY_TO_TG_ID,
+            src.to_tg_id.map(|id| id.to_string()),
+        );
+        let event = add_opt_attr(
+            event,
+            PayoutReturnedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+            src.to_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            PayoutReturnedEvent::EVENT_ATTR_KEY_HELD_FOR,
+            src.held_for.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            PayoutReturnedEvent::EVENT_ATTR_KEY_TREASURY_CHAT_ID,
+            src.treasury_chat_id.map(|id| id.to_string()),
+        );
+        add_opt_attr(
+            event,
+            PayoutReturnedEvent::EVENT_ATTR_KEY_REFUNDED_TO,
+            src.refunded_to.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PayoutReturnedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
 
         Ok(Self {
-            from_tg_handle,
-            to_tg_handle,
-            from_address,
-            to_address,
-            amount,
-            denom,
+            to_tg_id: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            held_for: parse_opt_attr(event, Self::EVENT_ATTR_KEY_HELD_FOR)?,
+            treasury_chat_id: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TREASURY_CHAT_ID)?,
+            refunded_to: get_opt_attr(event, Self::EVENT_ATTR_KEY_REFUNDED_TO).map(Addr::unchecked),
+            reason: get_attr(event, Self::EVENT_ATTR_KEY_REASON)?,
+        })
+    }
+}
+
+/// One recipient's share of a split. Same attributes as [`SendPaymentEvent`], but its own type
+/// so a split is reported once through [`SplitPaymentEvent`] rather than once per recipient
+#[cw_serde]
+pub struct SplitPaymentLegEvent(pub SendPaymentEvent);
+
+impl SplitPaymentLegEvent {
+    pub const EVENT_TYPE: &'static str = "split-payment-leg";
+}
+
+impl From<SplitPaymentLegEvent> for cosmwasm_std::Event {
+    fn from(src: SplitPaymentLegEvent) -> Self {
+        let mut event = cosmwasm_std::Event::from(src.0);
+        event.ty = SplitPaymentLegEvent::EVENT_TYPE.to_string();
+        event
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for SplitPaymentLegEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let mut event = event.clone();
+        event.ty = SendPaymentEvent::EVENT_TYPE.to_string();
+        SendPaymentEvent::try_from(&event).map(Self)
+    }
+}
+
+#[cw_serde]
+pub struct SplitPaymentEvent {
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub denom: String,
+    pub total: Uint256,
+    /// Charged once on `total` and paid on top of it. None when there was no fee.
+    pub fee: Option<Uint256>,
+    /// In the order the recipients were given
+    pub legs: Vec<SplitLeg>,
+}
+
+#[cw_serde]
+pub struct SplitLeg {
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    /// Held by the contract until the recipient registers
+    pub pending: bool,
+}
+
+impl SplitPaymentEvent {
+    pub const EVENT_TYPE: &'static str = "split-payment";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_TOTAL: &'static str = "total";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+    /// Comma separated `handle=amount`
+    pub const EVENT_ATTR_KEY_LEGS: &'static str = "legs";
+    /// Comma separated handles of the held legs, left out when empty
+    pub const EVENT_ATTR_KEY_PENDING: &'static str = "pending";
+}
+
+impl From<SplitPaymentEvent> for cosmwasm_std::Event {
+    fn from(src: SplitPaymentEvent) -> Self {
+        let legs: Vec<String> = src
+            .legs
+            .iter()
+            .map(|leg| format!("{}={}", leg.to_tg_handle, leg.amount))
+            .collect();
+        let pending: Vec<String> = src
+            .legs
+            .iter()
+            .filter(|leg| leg.pending)
+            .map(|leg| leg.to_tg_handle.to_string())
+            .collect();
+
+        let event = cosmwasm_std::Event::new(SplitPaymentEvent::EVENT_TYPE)
+            .add_attribute(
+                SplitPaymentEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                SplitPaymentEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(SplitPaymentEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(SplitPaymentEvent::EVENT_ATTR_KEY_TOTAL, src.total)
+            .add_attribute(SplitPaymentEvent::EVENT_ATTR_KEY_LEGS, legs.join(","));
+        let event = add_opt_attr(
+            event,
+            SplitPaymentEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            SplitPaymentEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        );
+        add_opt_attr(
+            event,
+            SplitPaymentEvent::EVENT_ATTR_KEY_PENDING,
+            join_list(&pending),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for SplitPaymentEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let pending = split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_PENDING));
+        let legs = split_list(Some(get_attr(event, Self::EVENT_ATTR_KEY_LEGS)?))
+            .into_iter()
+            .map(|leg| {
+                let (handle, amount) = leg
+                    .split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("Invalid split leg {}", leg))?;
+                Ok(SplitLeg {
+                    to_tg_handle: handle
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Invalid split leg {}", leg))?,
+                    amount: amount
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("Invalid split leg {}", leg))?,
+                    pending: pending.iter().any(|pending| pending == handle),
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            total: parse_attr(event, Self::EVENT_ATTR_KEY_TOTAL)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+            legs,
         })
     }
 }
 
 #[cw_serde]
 pub struct ConnectEvent {
-    pub tg_handle: String,
+    /// None for a legacy account not yet linked to its user id
+    pub tg_user_id: Option<i64>,
+    pub tg_handle: Option<TgHandle>,
     pub address: Addr,
 }
 
 impl ConnectEvent {
     pub const EVENT_TYPE: &'static str = "connect";
+    pub const EVENT_ATTR_KEY_TG_USER_ID: &'static str = "tg-user-id";
     pub const EVENT_ATTR_KEY_TG_HANDLE: &'static str = "tg-handle";
     pub const EVENT_ATTR_KEY_ADDRESS: &'static str = "address";
 }
 
 impl From<ConnectEvent> for cosmwasm_std::Event {
     fn from(src: ConnectEvent) -> Self {
-        cosmwasm_std::Event::new(ConnectEvent::EVENT_TYPE)
-            .add_attribute(ConnectEvent::EVENT_ATTR_KEY_TG_HANDLE, src.tg_handle)
+        let event = cosmwasm_std::Event::new(ConnectEvent::EVENT_TYPE).add_attribute(
+            ConnectEvent::EVENT_ATTR_KEY_ADDRESS,
+            src.address.to_string(),
+        );
+        let event = add_opt_attr(
+            event,
+            ConnectEvent::EVENT_ATTR_KEY_TG_USER_ID,
+            src.tg_user_id.map(|id| id.to_string()),
+        );
+        add_opt_attr(
+            event,
+            ConnectEvent::EVENT_ATTR_KEY_TG_HANDLE,
+            src.tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ConnectEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            tg_user_id: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TG_USER_ID)?,
+            tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TG_HANDLE)?,
+            address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_ADDRESS)?),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct PendingPaymentCreatedEvent {
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+}
+
+impl PendingPaymentCreatedEvent {
+    pub const EVENT_TYPE: &'static str = "pending-payment-created";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+}
+
+impl From<PendingPaymentCreatedEvent> for cosmwasm_std::Event {
+    fn from(src: PendingPaymentCreatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PendingPaymentCreatedEvent::EVENT_TYPE)
             .add_attribute(
-                ConnectEvent::EVENT_ATTR_KEY_ADDRESS,
-                src.address.to_string(),
+                PendingPaymentCreatedEvent::EVENT_ATTR_KEY_ID,
+                src.id.to_string(),
+            )
+            .add_attribute(
+                PendingPaymentCreatedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                PendingPaymentCreatedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(
+                PendingPaymentCreatedEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(
+                PendingPaymentCreatedEvent::EVENT_ATTR_KEY_AMOUNT,
+                src.amount,
             )
+            .add_attribute(PendingPaymentCreatedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        add_opt_attr(
+            event,
+            PendingPaymentCreatedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        )
     }
 }
 
-impl TryFrom<&cosmwasm_std::Event> for ConnectEvent {
+impl TryFrom<&cosmwasm_std::Event> for PendingPaymentCreatedEvent {
     type Error = anyhow::Error;
 
     fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
-        if event.ty != Self::EVENT_TYPE && event.ty != format!("wasm-{}", Self::EVENT_TYPE) {
-            return Err(anyhow::anyhow!(
-                "Expected event type {}, found {}",
-                Self::EVENT_TYPE,
-                event.ty
-            ));
-        }
+        check_event_type(event, Self::EVENT_TYPE)?;
 
-        let mut tg_handle = None;
-        let mut address = None;
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+        })
+    }
+}
 
-        for attr in event.attributes.iter() {
-            match attr.key.as_str() {
-                Self::EVENT_ATTR_KEY_TG_HANDLE => tg_handle = Some(attr.value.to_string()),
-                Self::EVENT_ATTR_KEY_ADDRESS => {
-                    address = Some(Addr::unchecked(attr.value.to_string()))
-                }
-                _ => {}
-            }
-        }
+#[cw_serde]
+pub struct PendingPaymentRefundedEvent {
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    /// The original sender, who gets the funds back
+    pub refund_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+}
 
-        match (tg_handle, address) {
-            (Some(tg_handle), Some(address)) => Ok(Self { tg_handle, address }),
-            (Some(_), None) => Err(anyhow::anyhow!(
-                "Missing attribute {}",
-                Self::EVENT_ATTR_KEY_ADDRESS
-            )),
-            (None, Some(_)) => Err(anyhow::anyhow!(
-                "Missing attribute {}",
-                Self::EVENT_ATTR_KEY_TG_HANDLE
-            )),
-            _ => Err(anyhow::anyhow!("Missing required attributes")),
-        }
+impl PendingPaymentRefundedEvent {
+    pub const EVENT_TYPE: &'static str = "pending-payment-refunded";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_REFUND_ADDRESS: &'static str = "refund-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+}
+
+impl From<PendingPaymentRefundedEvent> for cosmwasm_std::Event {
+    fn from(src: PendingPaymentRefundedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PendingPaymentRefundedEvent::EVENT_TYPE)
+            .add_attribute(
+                PendingPaymentRefundedEvent::EVENT_ATTR_KEY_ID,
+                src.id.to_string(),
+            )
+            .add_attribute(
+                PendingPaymentRefundedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                PendingPaymentRefundedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(
+                PendingPaymentRefundedEvent::EVENT_ATTR_KEY_REFUND_ADDRESS,
+                src.refund_address,
+            )
+            .add_attribute(
+                PendingPaymentRefundedEvent::EVENT_ATTR_KEY_AMOUNT,
+                src.amount,
+            )
+            .add_attribute(PendingPaymentRefundedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        add_opt_attr(
+            event,
+            PendingPaymentRefundedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PendingPaymentRefundedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            refund_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_REFUND_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+        })
+    }
+}
+
+#[cw_serde]
+pub struct InvoiceCreatedEvent {
+    pub id: u64,
+    pub requester_tg_id: i64,
+    pub requester_tg_handle: Option<TgHandle>,
+    pub payer_tg_handle: TgHandle,
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    pub memo: Option<String>,
+}
+
+impl InvoiceCreatedEvent {
+    pub const EVENT_TYPE: &'static str = "invoice-created";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_REQUESTER_TG_ID: &'static str = "requester-tg-id";
+    pub const EVENT_ATTR_KEY_REQUESTER_TG_HANDLE: &'static str = "requester-tg-handle";
+    pub const EVENT_ATTR_KEY_PAYER_TG_HANDLE: &'static str = "payer-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_MEMO: &'static str = "memo";
+}
+
+impl From<InvoiceCreatedEvent> for cosmwasm_std::Event {
+    fn from(src: InvoiceCreatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(InvoiceCreatedEvent::EVENT_TYPE)
+            .add_attribute(InvoiceCreatedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                InvoiceCreatedEvent::EVENT_ATTR_KEY_REQUESTER_TG_ID,
+                src.requester_tg_id.to_string(),
+            )
+            .add_attribute(
+                InvoiceCreatedEvent::EVENT_ATTR_KEY_PAYER_TG_HANDLE,
+                src.payer_tg_handle,
+            )
+            .add_attribute(
+                InvoiceCreatedEvent::EVENT_ATTR_KEY_TO_ADDRESS,
+                src.to_address,
+            )
+            .add_attribute(InvoiceCreatedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(InvoiceCreatedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            InvoiceCreatedEvent::EVENT_ATTR_KEY_REQUESTER_TG_HANDLE,
+            src.requester_tg_handle.map(String::from),
+        );
+        add_opt_attr(event, InvoiceCreatedEvent::EVENT_ATTR_KEY_MEMO, src.memo)
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for InvoiceCreatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            requester_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_REQUESTER_TG_ID)?,
+            requester_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_REQUESTER_TG_HANDLE)?,
+            payer_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_PAYER_TG_HANDLE)?,
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            memo: get_opt_attr(event, Self::EVENT_ATTR_KEY_MEMO),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct InvoicePaidEvent {
+    pub id: u64,
+    pub requester_tg_id: i64,
+    pub requester_tg_handle: Option<TgHandle>,
+    pub payer_tg_id: i64,
+    pub payer_tg_handle: TgHandle,
+    pub from_address: Addr,
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Paid to the treasury on top of `amount`, in the same denom. None when there was no fee.
+    pub fee: Option<Uint256>,
+}
+
+impl InvoicePaidEvent {
+    pub const EVENT_TYPE: &'static str = "invoice-paid";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_REQUESTER_TG_ID: &'static str = "requester-tg-id";
+    pub const EVENT_ATTR_KEY_REQUESTER_TG_HANDLE: &'static str = "requester-tg-handle";
+    pub const EVENT_ATTR_KEY_PAYER_TG_ID: &'static str = "payer-tg-id";
+    pub const EVENT_ATTR_KEY_PAYER_TG_HANDLE: &'static str = "payer-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+}
+
+impl From<InvoicePaidEvent> for cosmwasm_std::Event {
+    fn from(src: InvoicePaidEvent) -> Self {
+        let event = cosmwasm_std::Event::new(InvoicePaidEvent::EVENT_TYPE)
+            .add_attribute(InvoicePaidEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                InvoicePaidEvent::EVENT_ATTR_KEY_REQUESTER_TG_ID,
+                src.requester_tg_id.to_string(),
+            )
+            .add_attribute(
+                InvoicePaidEvent::EVENT_ATTR_KEY_PAYER_TG_ID,
+                src.payer_tg_id.to_string(),
+            )
+            .add_attribute(
+                InvoicePaidEvent::EVENT_ATTR_KEY_PAYER_TG_HANDLE,
+                src.payer_tg_handle,
+            )
+            .add_attribute(
+                InvoicePaidEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(InvoicePaidEvent::EVENT_ATTR_KEY_TO_ADDRESS, src.to_address)
+            .add_attribute(InvoicePaidEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(InvoicePaidEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            InvoicePaidEvent::EVENT_ATTR_KEY_REQUESTER_TG_HANDLE,
+            src.requester_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            InvoicePaidEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        )
     }
 }
+
+impl TryFrom<&cosmwasm_std::Event> for InvoicePaidEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            requester_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_REQUESTER_TG_ID)?,
+            requester_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_REQUESTER_TG_HANDLE)?,
+            payer_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_PAYER_TG_ID)?,
+            payer_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_PAYER_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+        })
+    }
+}
+
+#[cw_serde]
+pub struct InvoiceDeclinedEvent {
+    pub id: u64,
+    pub requester_tg_id: i64,
+    pub requester_tg_handle: Option<TgHandle>,
+    pub payer_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+}
+
+impl InvoiceDeclinedEvent {
+    pub const EVENT_TYPE: &'static str = "invoice-declined";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_REQUESTER_TG_ID: &'static str = "requester-tg-id";
+    pub const EVENT_ATTR_KEY_REQUESTER_TG_HANDLE: &'static str = "requester-tg-handle";
+    pub const EVENT_ATTR_KEY_PAYER_TG_HANDLE: &'static str = "payer-tg-handle";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+}
+
+impl From<InvoiceDeclinedEvent> for cosmwasm_std::Event {
+    fn from(src: InvoiceDeclinedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(InvoiceDeclinedEvent::EVENT_TYPE)
+            .add_attribute(InvoiceDeclinedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                InvoiceDeclinedEvent::EVENT_ATTR_KEY_REQUESTER_TG_ID,
+                src.requester_tg_id.to_string(),
+            )
+            .add_attribute(
+                InvoiceDeclinedEvent::EVENT_ATTR_KEY_PAYER_TG_HANDLE,
+                src.payer_tg_handle,
+            )
+            .add_attribute(InvoiceDeclinedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(InvoiceDeclinedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        add_opt_attr(
+            event,
+            InvoiceDeclinedEvent::EVENT_ATTR_KEY_REQUESTER_TG_HANDLE,
+            src.requester_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for InvoiceDeclinedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            requester_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_REQUESTER_TG_ID)?,
+            requester_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_REQUESTER_TG_HANDLE)?,
+            payer_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_PAYER_TG_HANDLE)?,
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+        })
+    }
+}
+
+#[cw_serde]
+pub struct OwnershipTransferProposedEvent {
+    pub owner: Addr,
+    /// None when the owner cancelled a proposed transfer
+    pub pending_owner: Option<Addr>,
+}
+
+impl OwnershipTransferProposedEvent {
+    pub const EVENT_TYPE: &'static str = "ownership-transfer-proposed";
+    pub const EVENT_ATTR_KEY_OWNER: &'static str = "owner";
+    pub const EVENT_ATTR_KEY_PENDING_OWNER: &'static str = "pending-owner";
+}
+
+impl From<OwnershipTransferProposedEvent> for cosmwasm_std::Event {
+    fn from(src: OwnershipTransferProposedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(OwnershipTransferProposedEvent::EVENT_TYPE)
+            .add_attribute(
+                OwnershipTransferProposedEvent::EVENT_ATTR_KEY_OWNER,
+                src.owner.to_string(),
+            );
+        add_opt_attr(
+            event,
+            OwnershipTransferProposedEvent::EVENT_ATTR_KEY_PENDING_OWNER,
+            src.pending_owner.map(|addr| addr.to_string()),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for OwnershipTransferProposedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            owner: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_OWNER)?),
+            pending_owner: get_opt_attr(event, Self::EVENT_ATTR_KEY_PENDING_OWNER)
+                .map(Addr::unchecked),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct OwnershipTransferredEvent {
+    pub previous_owner: Addr,
+    pub new_owner: Addr,
+}
+
+impl OwnershipTransferredEvent {
+    pub const EVENT_TYPE: &'static str = "ownership-transferred";
+    pub const EVENT_ATTR_KEY_PREVIOUS_OWNER: &'static str = "previous-owner";
+    pub const EVENT_ATTR_KEY_NEW_OWNER: &'static str = "new-owner";
+}
+
+impl From<OwnershipTransferredEvent> for cosmwasm_std::Event {
+    fn from(src: OwnershipTransferredEvent) -> Self {
+        cosmwasm_std::Event::new(OwnershipTransferredEvent::EVENT_TYPE)
+            .add_attribute(
+                OwnershipTransferredEvent::EVENT_ATTR_KEY_PREVIOUS_OWNER,
+                src.previous_owner.to_string(),
+            )
+            .add_attribute(
+                OwnershipTransferredEvent::EVENT_ATTR_KEY_NEW_OWNER,
+                src.new_owner.to_string(),
+            )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for OwnershipTransferredEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            previous_owner: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_PREVIOUS_OWNER)?),
+            new_owner: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_NEW_OWNER)?),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct DenomsUpdatedEvent {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl DenomsUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "denoms-updated";
+    /// Comma separated, left out when empty
+    pub const EVENT_ATTR_KEY_ADDED: &'static str = "added";
+    /// Comma separated, left out when empty
+    pub const EVENT_ATTR_KEY_REMOVED: &'static str = "removed";
+}
+
+impl From<DenomsUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: DenomsUpdatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(DenomsUpdatedEvent::EVENT_TYPE);
+        let event = add_opt_attr(
+            event,
+            DenomsUpdatedEvent::EVENT_ATTR_KEY_ADDED,
+            join_list(&src.added),
+        );
+        add_opt_attr(
+            event,
+            DenomsUpdatedEvent::EVENT_ATTR_KEY_REMOVED,
+            join_list(&src.removed),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for DenomsUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            added: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_ADDED)),
+            removed: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_REMOVED)),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct AuthUpdatedEvent {
+    pub previous: Auth,
+    pub auth: Auth,
+}
+
+impl AuthUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "auth-updated";
+    /// `service-manager` or `admin`
+    pub const EVENT_ATTR_KEY_PREVIOUS_KIND: &'static str = "previous-kind";
+    pub const EVENT_ATTR_KEY_PREVIOUS_ADDRESS: &'static str = "previous-address";
+    /// `service-manager` or `admin`
+    pub const EVENT_ATTR_KEY_KIND: &'static str = "kind";
+    pub const EVENT_ATTR_KEY_ADDRESS: &'static str = "address";
+
+    pub const KIND_SERVICE_MANAGER: &'static str = "service-manager";
+    pub const KIND_ADMIN: &'static str = "admin";
+
+    fn split(auth: Auth) -> (&'static str, String) {
+        match auth {
+            Auth::ServiceManager(addr) => (Self::KIND_SERVICE_MANAGER, addr),
+            Auth::Admin(addr) => (Self::KIND_ADMIN, addr),
+        }
+    }
+
+    fn join(kind: &str, addr: String) -> anyhow::Result<Auth> {
+        match kind {
+            Self::KIND_SERVICE_MANAGER => Ok(Auth::ServiceManager(addr)),
+            Self::KIND_ADMIN => Ok(Auth::Admin(addr)),
+            _ => Err(anyhow::anyhow!("Invalid auth kind {}", kind)),
+        }
+    }
+}
+
+impl From<AuthUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: AuthUpdatedEvent) -> Self {
+        let (previous_kind, previous_addr) = AuthUpdatedEvent::split(src.previous);
+        let (kind, addr) = AuthUpdatedEvent::split(src.auth);
+        cosmwasm_std::Event::new(AuthUpdatedEvent::EVENT_TYPE)
+            .add_attribute(
+                AuthUpdatedEvent::EVENT_ATTR_KEY_PREVIOUS_KIND,
+                previous_kind,
+            )
+            .add_attribute(
+                AuthUpdatedEvent::EVENT_ATTR_KEY_PREVIOUS_ADDRESS,
+                previous_addr,
+            )
+            .add_attribute(AuthUpdatedEvent::EVENT_ATTR_KEY_KIND, kind)
+            .add_attribute(AuthUpdatedEvent::EVENT_ATTR_KEY_ADDRESS, addr)
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for AuthUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            previous: Self::join(
+                &get_attr(event, Self::EVENT_ATTR_KEY_PREVIOUS_KIND)?,
+                get_attr(event, Self::EVENT_ATTR_KEY_PREVIOUS_ADDRESS)?,
+            )?,
+            auth: Self::join(
+                &get_attr(event, Self::EVENT_ATTR_KEY_KIND)?,
+                get_attr(event, Self::EVENT_ATTR_KEY_ADDRESS)?,
+            )?,
+        })
+    }
+}
+
+/// A sender changed the cap on their own account. Without any of the caps, the limit was removed.
+#[cw_serde]
+pub struct SpendLimitUpdatedEvent {
+    pub address: Addr,
+    pub denom: String,
+    pub per_payment: Option<Uint256>,
+    pub per_window: Option<Uint256>,
+    pub window_seconds: Option<u64>,
+}
+
+impl SpendLimitUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "spend-limit-updated";
+    pub const EVENT_ATTR_KEY_ADDRESS: &'static str = "address";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_PER_PAYMENT: &'static str = "per-payment";
+    pub const EVENT_ATTR_KEY_PER_WINDOW: &'static str = "per-window";
+    pub const EVENT_ATTR_KEY_WINDOW_SECONDS: &'static str = "window-seconds";
+}
+
+impl From<SpendLimitUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: SpendLimitUpdatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(SpendLimitUpdatedEvent::EVENT_TYPE)
+            .add_attribute(SpendLimitUpdatedEvent::EVENT_ATTR_KEY_ADDRESS, src.address)
+            .add_attribute(SpendLimitUpdatedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            SpendLimitUpdatedEvent::EVENT_ATTR_KEY_PER_PAYMENT,
+            src.per_payment.map(|amount| amount.to_string()),
+        );
+        let event = add_opt_attr(
+            event,
+            SpendLimitUpdatedEvent::EVENT_ATTR_KEY_PER_WINDOW,
+            src.per_window.map(|amount| amount.to_string()),
+        );
+        add_opt_attr(
+            event,
+            SpendLimitUpdatedEvent::EVENT_ATTR_KEY_WINDOW_SECONDS,
+            src.window_seconds.map(|seconds| seconds.to_string()),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for SpendLimitUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_ADDRESS)?),
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            per_payment: parse_opt_attr(event, Self::EVENT_ATTR_KEY_PER_PAYMENT)?,
+            per_window: parse_opt_attr(event, Self::EVENT_ATTR_KEY_PER_WINDOW)?,
+            window_seconds: parse_opt_attr(event, Self::EVENT_ATTR_KEY_WINDOW_SECONDS)?,
+        })
+    }
+}
+
+/// The owner changed the fee charged on sends. None means sends are free now.
+#[cw_serde]
+pub struct FeeUpdatedEvent {
+    pub fee: Option<FeeConfig>,
+}
+
+impl FeeUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "fee-updated";
+    pub const EVENT_ATTR_KEY_BASIS_POINTS: &'static str = "basis-points";
+    /// Comma separated coins, e.g. `100untrn,50uatom`, left out when empty
+    pub const EVENT_ATTR_KEY_FLAT: &'static str = "flat";
+    pub const EVENT_ATTR_KEY_TREASURY: &'static str = "treasury";
+}
+
+impl From<FeeUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: FeeUpdatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(FeeUpdatedEvent::EVENT_TYPE);
+        match src.fee {
+            Some(fee) => {
+                let flat = fee
+                    .flat
+                    .iter()
+                    .map(|coin| coin.to_string())
+                    .collect::<Vec<_>>();
+                let event = event
+                    .add_attribute(
+                        FeeUpdatedEvent::EVENT_ATTR_KEY_BASIS_POINTS,
+                        fee.basis_points.to_string(),
+                    )
+                    .add_attribute(FeeUpdatedEvent::EVENT_ATTR_KEY_TREASURY, fee.treasury);
+                add_opt_attr(
+                    event,
+                    FeeUpdatedEvent::EVENT_ATTR_KEY_FLAT,
+                    join_list(&flat),
+                )
+            }
+            None => event,
+        }
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for FeeUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let Some(treasury) = get_opt_attr(event, Self::EVENT_ATTR_KEY_TREASURY) else {
+            return Ok(Self { fee: None });
+        };
+
+        Ok(Self {
+            fee: Some(FeeConfig {
+                basis_points: parse_attr(event, Self::EVENT_ATTR_KEY_BASIS_POINTS)?,
+                flat: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_FLAT))
+                    .iter()
+                    .map(|coin| coin.parse())
+                    .collect::<Result<_, _>>()?,
+                treasury,
+            }),
+        })
+    }
+}
+
+/// The owner paused or resumed the contract
+#[cw_serde]
+pub struct PauseUpdatedEvent {
+    pub paused: bool,
+}
+
+impl PauseUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "pause-updated";
+    pub const EVENT_ATTR_KEY_PAUSED: &'static str = "paused";
+}
+
+impl From<PauseUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: PauseUpdatedEvent) -> Self {
+        cosmwasm_std::Event::new(PauseUpdatedEvent::EVENT_TYPE).add_attribute(
+            PauseUpdatedEvent::EVENT_ATTR_KEY_PAUSED,
+            src.paused.to_string(),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PauseUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            paused: parse_attr(event, Self::EVENT_ATTR_KEY_PAUSED)?,
+        })
+    }
+}
+
+/// The owner added handles and addresses to the blocklist, or took them off it
+#[cw_serde]
+pub struct BlocklistUpdatedEvent {
+    /// False when they were unblocked
+    pub blocked: bool,
+    pub handles: Vec<TgHandle>,
+    pub addresses: Vec<Addr>,
+}
+
+impl BlocklistUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "blocklist-updated";
+    pub const EVENT_ATTR_KEY_BLOCKED: &'static str = "blocked";
+    /// Comma separated, left out when empty
+    pub const EVENT_ATTR_KEY_HANDLES: &'static str = "handles";
+    /// Comma separated, left out when empty
+    pub const EVENT_ATTR_KEY_ADDRESSES: &'static str = "addresses";
+}
+
+impl From<BlocklistUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: BlocklistUpdatedEvent) -> Self {
+        let handles = src
+            .handles
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let addresses = src
+            .addresses
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let event = cosmwasm_std::Event::new(BlocklistUpdatedEvent::EVENT_TYPE).add_attribute(
+            BlocklistUpdatedEvent::EVENT_ATTR_KEY_BLOCKED,
+            src.blocked.to_string(),
+        );
+        let event = add_opt_attr(
+            event,
+            BlocklistUpdatedEvent::EVENT_ATTR_KEY_HANDLES,
+            join_list(&handles),
+        );
+        add_opt_attr(
+            event,
+            BlocklistUpdatedEvent::EVENT_ATTR_KEY_ADDRESSES,
+            join_list(&addresses),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for BlocklistUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            blocked: parse_attr(event, Self::EVENT_ATTR_KEY_BLOCKED)?,
+            handles: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_HANDLES))
+                .iter()
+                .map(|handle| handle.parse())
+                .collect::<Result<_, _>>()?,
+            addresses: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_ADDRESSES))
+                .into_iter()
+                .map(Addr::unchecked)
+                .collect(),
+        })
+    }
+}
+
+/// The owner set or removed the route to another chain
+#[cw_serde]
+pub struct IbcRouteUpdatedEvent {
+    pub prefix: String,
+    /// None when it was removed
+    pub route: Option<IbcRoute>,
+}
+
+impl IbcRouteUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "ibc-route-updated";
+    pub const EVENT_ATTR_KEY_PREFIX: &'static str = "prefix";
+    pub const EVENT_ATTR_KEY_CHANNEL_ID: &'static str = "channel-id";
+    pub const EVENT_ATTR_KEY_CHAIN_ID: &'static str = "chain-id";
+    pub const EVENT_ATTR_KEY_TIMEOUT_SECONDS: &'static str = "timeout-seconds";
+}
+
+impl From<IbcRouteUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: IbcRouteUpdatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(IbcRouteUpdatedEvent::EVENT_TYPE)
+            .add_attribute(IbcRouteUpdatedEvent::EVENT_ATTR_KEY_PREFIX, src.prefix);
+        match src.route {
+            Some(route) => event
+                .add_attribute(
+                    IbcRouteUpdatedEvent::EVENT_ATTR_KEY_CHANNEL_ID,
+                    route.channel_id,
+                )
+                .add_attribute(
+                    IbcRouteUpdatedEvent::EVENT_ATTR_KEY_CHAIN_ID,
+                    route.chain_id,
+                )
+                .add_attribute(
+                    IbcRouteUpdatedEvent::EVENT_ATTR_KEY_TIMEOUT_SECONDS,
+                    route.timeout_seconds.to_string(),
+                ),
+            None => event,
+        }
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for IbcRouteUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let prefix = get_attr(event, Self::EVENT_ATTR_KEY_PREFIX)?;
+        let Some(channel_id) = get_opt_attr(event, Self::EVENT_ATTR_KEY_CHANNEL_ID) else {
+            return Ok(Self {
+                prefix,
+                route: None,
+            });
+        };
+
+        Ok(Self {
+            route: Some(IbcRoute {
+                prefix: prefix.clone(),
+                channel_id,
+                chain_id: get_attr(event, Self::EVENT_ATTR_KEY_CHAIN_ID)?,
+                timeout_seconds: parse_attr(event, Self::EVENT_ATTR_KEY_TIMEOUT_SECONDS)?,
+            }),
+            prefix,
+        })
+    }
+}
+
+#[cw_serde]
+pub struct DenomMetadataUpdatedEvent {
+    pub denom: String,
+    /// None when it was removed
+    pub metadata: Option<DenomMetadata>,
+}
+
+impl DenomMetadataUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "denom-metadata-updated";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_SYMBOL: &'static str = "symbol";
+    pub const EVENT_ATTR_KEY_EXPONENT: &'static str = "exponent";
+    /// Comma separated, left out when empty
+    pub const EVENT_ATTR_KEY_ALIASES: &'static str = "aliases";
+}
+
+impl From<DenomMetadataUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: DenomMetadataUpdatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(DenomMetadataUpdatedEvent::EVENT_TYPE)
+            .add_attribute(DenomMetadataUpdatedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        match src.metadata {
+            Some(metadata) => add_opt_attr(
+                event
+                    .add_attribute(
+                        DenomMetadataUpdatedEvent::EVENT_ATTR_KEY_SYMBOL,
+                        metadata.symbol,
+                    )
+                    .add_attribute(
+                        DenomMetadataUpdatedEvent::EVENT_ATTR_KEY_EXPONENT,
+                        metadata.exponent.to_string(),
+                    ),
+                DenomMetadataUpdatedEvent::EVENT_ATTR_KEY_ALIASES,
+                join_list(&metadata.aliases),
+            ),
+            None => event,
+        }
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for DenomMetadataUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let denom = get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?;
+        let Some(symbol) = get_opt_attr(event, Self::EVENT_ATTR_KEY_SYMBOL) else {
+            return Ok(Self {
+                denom,
+                metadata: None,
+            });
+        };
+
+        Ok(Self {
+            metadata: Some(DenomMetadata {
+                denom: denom.clone(),
+                symbol,
+                exponent: parse_attr(event, Self::EVENT_ATTR_KEY_EXPONENT)?,
+                aliases: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_ALIASES)),
+            }),
+            denom,
+        })
+    }
+}
+
+/// How one entry of a [`crate::payments::msg::WavsPayload::Batch`] went. A failed entry changed
+/// nothing, the others in the batch still went through.
+#[cw_serde]
+pub struct BatchEntryEvent {
+    /// Position in the batch
+    pub index: u32,
+    /// The Telegram message the entry came from
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// As reported by the chain, None if it went through
+    pub error: Option<String>,
+}
+
+impl BatchEntryEvent {
+    pub const EVENT_TYPE: &'static str = "batch-entry";
+    pub const EVENT_ATTR_KEY_INDEX: &'static str = "index";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_MESSAGE_ID: &'static str = "message-id";
+    pub const EVENT_ATTR_KEY_SUCCESS: &'static str = "success";
+    pub const EVENT_ATTR_KEY_ERROR: &'static str = "error";
+}
+
+impl From<BatchEntryEvent> for cosmwasm_std::Event {
+    fn from(src: BatchEntryEvent) -> Self {
+        let event = cosmwasm_std::Event::new(BatchEntryEvent::EVENT_TYPE)
+            .add_attribute(BatchEntryEvent::EVENT_ATTR_KEY_INDEX, src.index.to_string())
+            .add_attribute(
+                BatchEntryEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                BatchEntryEvent::EVENT_ATTR_KEY_MESSAGE_ID,
+                src.message_id.to_string(),
+            )
+            .add_attribute(
+                BatchEntryEvent::EVENT_ATTR_KEY_SUCCESS,
+                src.error.is_none().to_string(),
+            );
+        add_opt_attr(event, BatchEntryEvent::EVENT_ATTR_KEY_ERROR, src.error)
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for BatchEntryEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            index: parse_attr(event, Self::EVENT_ATTR_KEY_INDEX)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            message_id: parse_attr(event, Self::EVENT_ATTR_KEY_MESSAGE_ID)?,
+            error: get_opt_attr(event, Self::EVENT_ATTR_KEY_ERROR),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct PullClaimsUpdatedEvent {
+    pub enabled: bool,
+}
+
+impl PullClaimsUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "pull-claims-updated";
+    pub const EVENT_ATTR_KEY_ENABLED: &'static str = "enabled";
+}
+
+impl From<PullClaimsUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: PullClaimsUpdatedEvent) -> Self {
+        cosmwasm_std::Event::new(PullClaimsUpdatedEvent::EVENT_TYPE).add_attribute(
+            PullClaimsUpdatedEvent::EVENT_ATTR_KEY_ENABLED,
+            src.enabled.to_string(),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PullClaimsUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            enabled: parse_attr(event, Self::EVENT_ATTR_KEY_ENABLED)?,
+        })
+    }
+}
+
+/// A registered account took what was held for its handle
+#[cw_serde]
+pub struct PendingClaimedEvent {
+    pub tg_user_id: i64,
+    pub tg_handle: TgHandle,
+    /// Where it was paid, the registered address unless the claim named another one
+    pub address: Addr,
+    pub amount: Vec<Coin>,
+}
+
+impl PendingClaimedEvent {
+    pub const EVENT_TYPE: &'static str = "pending-claimed";
+    pub const EVENT_ATTR_KEY_TG_USER_ID: &'static str = "tg-user-id";
+    pub const EVENT_ATTR_KEY_TG_HANDLE: &'static str = "tg-handle";
+    pub const EVENT_ATTR_KEY_ADDRESS: &'static str = "address";
+    /// Comma separated coins, e.g. `100untrn,5uatom`
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+}
+
+impl From<PendingClaimedEvent> for cosmwasm_std::Event {
+    fn from(src: PendingClaimedEvent) -> Self {
+        let amount = src.amount.iter().map(Coin::to_string).collect::<Vec<_>>();
+        add_opt_attr(
+            cosmwasm_std::Event::new(PendingClaimedEvent::EVENT_TYPE)
+                .add_attribute(
+                    PendingClaimedEvent::EVENT_ATTR_KEY_TG_USER_ID,
+                    src.tg_user_id.to_string(),
+                )
+                .add_attribute(
+                    PendingClaimedEvent::EVENT_ATTR_KEY_TG_HANDLE,
+                    String::from(src.tg_handle),
+                )
+                .add_attribute(PendingClaimedEvent::EVENT_ATTR_KEY_ADDRESS, src.address),
+            PendingClaimedEvent::EVENT_ATTR_KEY_AMOUNT,
+            join_list(&amount),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PendingClaimedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            tg_user_id: parse_attr(event, Self::EVENT_ATTR_KEY_TG_USER_ID)?,
+            tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TG_HANDLE)?,
+            address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_ADDRESS)?),
+            amount: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_AMOUNT))
+                .iter()
+                .map(|coin| coin.parse())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// A send taken into escrow, the sender can cancel it until it releases
+#[cw_serde]
+pub struct PaymentHeldEvent {
+    /// Of the escrow, for cancelling it
+    pub id: u64,
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Held along with `amount`, in the same denom. None when there is no fee.
+    pub fee: Option<Uint256>,
+    pub releases: Expiration,
+}
+
+impl PaymentHeldEvent {
+    pub const EVENT_TYPE: &'static str = "payment-held";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_MESSAGE_ID: &'static str = "message-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+    /// Only one of these is set, depending on whether the window is in blocks or seconds
+    pub const EVENT_ATTR_KEY_RELEASE_HEIGHT: &'static str = "release-height";
+    pub const EVENT_ATTR_KEY_RELEASE_TIME: &'static str = "release-time";
+}
+
+impl From<PaymentHeldEvent> for cosmwasm_std::Event {
+    fn from(src: PaymentHeldEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PaymentHeldEvent::EVENT_TYPE)
+            .add_attribute(PaymentHeldEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                PaymentHeldEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                PaymentHeldEvent::EVENT_ATTR_KEY_MESSAGE_ID,
+                src.message_id.to_string(),
+            )
+            .add_attribute(
+                PaymentHeldEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                PaymentHeldEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(
+                PaymentHeldEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(PaymentHeldEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(PaymentHeldEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            PaymentHeldEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            PaymentHeldEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        );
+        match src.releases {
+            Expiration::AtHeight(height) => event.add_attribute(
+                PaymentHeldEvent::EVENT_ATTR_KEY_RELEASE_HEIGHT,
+                height.to_string(),
+            ),
+            Expiration::AtTime(time) => event.add_attribute(
+                PaymentHeldEvent::EVENT_ATTR_KEY_RELEASE_TIME,
+                time.seconds().to_string(),
+            ),
+            Expiration::Never {} => event,
+        }
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PaymentHeldEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let releases = match (
+            parse_opt_attr(event, Self::EVENT_ATTR_KEY_RELEASE_HEIGHT)?,
+            parse_opt_attr(event, Self::EVENT_ATTR_KEY_RELEASE_TIME)?,
+        ) {
+            (Some(height), _) => Expiration::AtHeight(height),
+            (None, Some(seconds)) => Expiration::AtTime(Timestamp::from_seconds(seconds)),
+            (None, None) => Expiration::Never {},
+        };
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            message_id: parse_attr(event, Self::EVENT_ATTR_KEY_MESSAGE_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+            releases,
+        })
+    }
+}
+
+/// A held send paid out once its window passed
+#[cw_serde]
+pub struct PaymentReleasedEvent {
+    /// Of the escrow
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    /// None when the recipient is not registered and the payment is held for them instead
+    pub to_tg_id: Option<i64>,
+    pub to_tg_handle: TgHandle,
+    pub from_address: Addr,
+    /// The contract itself while the recipient is unregistered
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Paid to the treasury, in the same denom. None when there was no fee.
+    pub fee: Option<Uint256>,
+    /// Chain id of the other chain, when `to_address` is on one
+    pub to_chain: Option<String>,
+}
+
+impl PaymentReleasedEvent {
+    pub const EVENT_TYPE: &'static str = "payment-released";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+    pub const EVENT_ATTR_KEY_TO_CHAIN: &'static str = "to-chain";
+}
+
+impl From<PaymentReleasedEvent> for cosmwasm_std::Event {
+    fn from(src: PaymentReleasedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PaymentReleasedEvent::EVENT_TYPE)
+            .add_attribute(PaymentReleasedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                PaymentReleasedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                PaymentReleasedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(
+                PaymentReleasedEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(
+                PaymentReleasedEvent::EVENT_ATTR_KEY_TO_ADDRESS,
+                src.to_address,
+            )
+            .add_attribute(PaymentReleasedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(PaymentReleasedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            PaymentReleasedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            PaymentReleasedEvent::EVENT_ATTR_KEY_TO_TG_ID,
+            src.to_tg_id.map(|id| id.to_string()),
+        );
+        let event = add_opt_attr(
+            event,
+            PaymentReleasedEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        );
+        add_opt_attr(
+            event,
+            PaymentReleasedEvent::EVENT_ATTR_KEY_TO_CHAIN,
+            src.to_chain,
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PaymentReleasedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_id: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+            to_chain: get_opt_attr(event, Self::EVENT_ATTR_KEY_TO_CHAIN),
+        })
+    }
+}
+
+/// A held send returned to the sender, with its fee
+#[cw_serde]
+pub struct PaymentCancelledEvent {
+    /// Of the escrow
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    /// Where the funds went back to
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    pub fee: Option<Uint256>,
+    /// Why it couldn't be released, None when the sender cancelled it
+    pub reason: Option<String>,
+}
+
+impl PaymentCancelledEvent {
+    pub const EVENT_TYPE: &'static str = "payment-cancelled";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+    pub const EVENT_ATTR_KEY_REASON: &'static str = "reason";
+}
+
+impl From<PaymentCancelledEvent> for cosmwasm_std::Event {
+    fn from(src: PaymentCancelledEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PaymentCancelledEvent::EVENT_TYPE)
+            .add_attribute(PaymentCancelledEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                PaymentCancelledEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                PaymentCancelledEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(
+                PaymentCancelledEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(PaymentCancelledEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(PaymentCancelledEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            PaymentCancelledEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            PaymentCancelledEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        );
+        add_opt_attr(
+            event,
+            PaymentCancelledEvent::EVENT_ATTR_KEY_REASON,
+            src.reason,
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PaymentCancelledEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+            reason: get_opt_attr(event, Self::EVENT_ATTR_KEY_REASON),
+        })
+    }
+}
+
+#[cw_serde]
+pub struct EscrowWindowUpdatedEvent {
+    /// None when sends go out right away
+    pub window: Option<Duration>,
+}
+
+impl EscrowWindowUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "escrow-window-updated";
+    /// Only one of these is set, depending on whether the window is in blocks or seconds
+    pub const EVENT_ATTR_KEY_WINDOW_HEIGHT: &'static str = "window-height";
+    pub const EVENT_ATTR_KEY_WINDOW_TIME: &'static str = "window-time";
+}
+
+impl From<EscrowWindowUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: EscrowWindowUpdatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(EscrowWindowUpdatedEvent::EVENT_TYPE);
+        match src.window {
+            Some(Duration::Height(blocks)) => event.add_attribute(
+                EscrowWindowUpdatedEvent::EVENT_ATTR_KEY_WINDOW_HEIGHT,
+                blocks.to_string(),
+            ),
+            Some(Duration::Time(seconds)) => event.add_attribute(
+                EscrowWindowUpdatedEvent::EVENT_ATTR_KEY_WINDOW_TIME,
+                seconds.to_string(),
+            ),
+            None => event,
+        }
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for EscrowWindowUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let window = match (
+            parse_opt_attr(event, Self::EVENT_ATTR_KEY_WINDOW_HEIGHT)?,
+            parse_opt_attr(event, Self::EVENT_ATTR_KEY_WINDOW_TIME)?,
+        ) {
+            (Some(blocks), _) => Some(Duration::Height(blocks)),
+            (None, Some(seconds)) => Some(Duration::Time(seconds)),
+            (None, None) => None,
+        };
+
+        Ok(Self { window })
+    }
+}
+
+#[cw_serde]
+pub struct PendingExpiryUpdatedEvent {
+    /// None when pending payments are held forever
+    pub expiry: Option<Duration>,
+}
+
+impl PendingExpiryUpdatedEvent {
+    pub const EVENT_TYPE: &'static str = "pending-expiry-updated";
+    /// Only one of these is set, depending on whether the expiry is in blocks or seconds
+    pub const EVENT_ATTR_KEY_EXPIRY_HEIGHT: &'static str = "expiry-height";
+    pub const EVENT_ATTR_KEY_EXPIRY_TIME: &'static str = "expiry-time";
+}
+
+impl From<PendingExpiryUpdatedEvent> for cosmwasm_std::Event {
+    fn from(src: PendingExpiryUpdatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(PendingExpiryUpdatedEvent::EVENT_TYPE);
+        match src.expiry {
+            Some(Duration::Height(blocks)) => event.add_attribute(
+                PendingExpiryUpdatedEvent::EVENT_ATTR_KEY_EXPIRY_HEIGHT,
+                blocks.to_string(),
+            ),
+            Some(Duration::Time(seconds)) => event.add_attribute(
+                PendingExpiryUpdatedEvent::EVENT_ATTR_KEY_EXPIRY_TIME,
+                seconds.to_string(),
+            ),
+            None => event,
+        }
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for PendingExpiryUpdatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        let expiry = match (
+            parse_opt_attr(event, Self::EVENT_ATTR_KEY_EXPIRY_HEIGHT)?,
+            parse_opt_attr(event, Self::EVENT_ATTR_KEY_EXPIRY_TIME)?,
+        ) {
+            (Some(blocks), _) => Some(Duration::Height(blocks)),
+            (None, Some(seconds)) => Some(Duration::Time(seconds)),
+            (None, None) => None,
+        };
+
+        Ok(Self { expiry })
+    }
+}
+
+/// A recurring payment set up, its first run is due right away
+#[cw_serde]
+pub struct ScheduleCreatedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    pub interval_seconds: u64,
+    pub next_due: Timestamp,
+    pub until: Option<Timestamp>,
+}
+
+impl ScheduleCreatedEvent {
+    pub const EVENT_TYPE: &'static str = "schedule-created";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_MESSAGE_ID: &'static str = "message-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_INTERVAL_SECONDS: &'static str = "interval-seconds";
+    /// Unix seconds, like `until`
+    pub const EVENT_ATTR_KEY_NEXT_DUE: &'static str = "next-due";
+    pub const EVENT_ATTR_KEY_UNTIL: &'static str = "until";
+}
+
+impl From<ScheduleCreatedEvent> for cosmwasm_std::Event {
+    fn from(src: ScheduleCreatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(ScheduleCreatedEvent::EVENT_TYPE)
+            .add_attribute(ScheduleCreatedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                ScheduleCreatedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                ScheduleCreatedEvent::EVENT_ATTR_KEY_MESSAGE_ID,
+                src.message_id.to_string(),
+            )
+            .add_attribute(
+                ScheduleCreatedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                ScheduleCreatedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(ScheduleCreatedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(ScheduleCreatedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                ScheduleCreatedEvent::EVENT_ATTR_KEY_INTERVAL_SECONDS,
+                src.interval_seconds.to_string(),
+            )
+            .add_attribute(
+                ScheduleCreatedEvent::EVENT_ATTR_KEY_NEXT_DUE,
+                src.next_due.seconds().to_string(),
+            );
+        let event = add_opt_attr(
+            event,
+            ScheduleCreatedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            ScheduleCreatedEvent::EVENT_ATTR_KEY_UNTIL,
+            src.until.map(|until| until.seconds().to_string()),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ScheduleCreatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            message_id: parse_attr(event, Self::EVENT_ATTR_KEY_MESSAGE_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            interval_seconds: parse_attr(event, Self::EVENT_ATTR_KEY_INTERVAL_SECONDS)?,
+            next_due: Timestamp::from_seconds(parse_attr(event, Self::EVENT_ATTR_KEY_NEXT_DUE)?),
+            until: parse_opt_attr(event, Self::EVENT_ATTR_KEY_UNTIL)?.map(Timestamp::from_seconds),
+        })
+    }
+}
+
+/// A recurring payment that won't run again, because the sender cancelled it or its last run
+/// was paid
+#[cw_serde]
+pub struct ScheduleEndedEvent {
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    /// False when it ran until its end date
+    pub cancelled: bool,
+}
+
+impl ScheduleEndedEvent {
+    pub const EVENT_TYPE: &'static str = "schedule-ended";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_CANCELLED: &'static str = "cancelled";
+}
+
+impl From<ScheduleEndedEvent> for cosmwasm_std::Event {
+    fn from(src: ScheduleEndedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(ScheduleEndedEvent::EVENT_TYPE)
+            .add_attribute(ScheduleEndedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                ScheduleEndedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                ScheduleEndedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(
+                ScheduleEndedEvent::EVENT_ATTR_KEY_CANCELLED,
+                src.cancelled.to_string(),
+            );
+        add_opt_attr(
+            event,
+            ScheduleEndedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ScheduleEndedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            cancelled: parse_attr(event, Self::EVENT_ATTR_KEY_CANCELLED)?,
+        })
+    }
+}
+
+/// A due run of a recurring payment that couldn't go out. Its period is skipped, the next one
+/// is tried as usual. Runs that do go out show up like any other send.
+#[cw_serde]
+pub struct ScheduleRunFailedEvent {
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    pub due: Timestamp,
+    pub reason: String,
+}
+
+impl ScheduleRunFailedEvent {
+    pub const EVENT_TYPE: &'static str = "schedule-run-failed";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    /// Unix seconds
+    pub const EVENT_ATTR_KEY_DUE: &'static str = "due";
+    pub const EVENT_ATTR_KEY_REASON: &'static str = "reason";
+}
+
+impl From<ScheduleRunFailedEvent> for cosmwasm_std::Event {
+    fn from(src: ScheduleRunFailedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(ScheduleRunFailedEvent::EVENT_TYPE)
+            .add_attribute(
+                ScheduleRunFailedEvent::EVENT_ATTR_KEY_ID,
+                src.id.to_string(),
+            )
+            .add_attribute(
+                ScheduleRunFailedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                ScheduleRunFailedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(ScheduleRunFailedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(ScheduleRunFailedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                ScheduleRunFailedEvent::EVENT_ATTR_KEY_DUE,
+                src.due.seconds().to_string(),
+            )
+            .add_attribute(ScheduleRunFailedEvent::EVENT_ATTR_KEY_REASON, src.reason);
+        add_opt_attr(
+            event,
+            ScheduleRunFailedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ScheduleRunFailedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            due: Timestamp::from_seconds(parse_attr(event, Self::EVENT_ATTR_KEY_DUE)?),
+            reason: get_attr(event, Self::EVENT_ATTR_KEY_REASON)?,
+        })
+    }
+}
+
+/// A group chat set up a shared treasury
+#[cw_serde]
+pub struct TreasuryCreatedEvent {
+    pub chat_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    /// The handles of the members who have one, there are `member_count` members in all
+    pub members: Vec<TgHandle>,
+    pub member_count: u32,
+    pub threshold: u32,
+}
+
+impl TreasuryCreatedEvent {
+    pub const EVENT_TYPE: &'static str = "treasury-created";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    /// Comma separated
+    pub const EVENT_ATTR_KEY_MEMBERS: &'static str = "members";
+    pub const EVENT_ATTR_KEY_MEMBER_COUNT: &'static str = "member-count";
+    pub const EVENT_ATTR_KEY_THRESHOLD: &'static str = "threshold";
+}
+
+impl From<TreasuryCreatedEvent> for cosmwasm_std::Event {
+    fn from(src: TreasuryCreatedEvent) -> Self {
+        let members = src
+            .members
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let event = cosmwasm_std::Event::new(TreasuryCreatedEvent::EVENT_TYPE)
+            .add_attribute(
+                TreasuryCreatedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                TreasuryCreatedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                TreasuryCreatedEvent::EVENT_ATTR_KEY_MEMBER_COUNT,
+                src.member_count.to_string(),
+            )
+            .add_attribute(
+                TreasuryCreatedEvent::EVENT_ATTR_KEY_THRESHOLD,
+                src.threshold.to_string(),
+            );
+        let event = add_opt_attr(
+            event,
+            TreasuryCreatedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            TreasuryCreatedEvent::EVENT_ATTR_KEY_MEMBERS,
+            join_list(&members),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for TreasuryCreatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            members: split_list(get_opt_attr(event, Self::EVENT_ATTR_KEY_MEMBERS))
+                .iter()
+                .map(|member| member.parse())
+                .collect::<Result<_, _>>()?,
+            member_count: parse_attr(event, Self::EVENT_ATTR_KEY_MEMBER_COUNT)?,
+            threshold: parse_attr(event, Self::EVENT_ATTR_KEY_THRESHOLD)?,
+        })
+    }
+}
+
+/// Funds moved from someone's account into a group chat's treasury
+#[cw_serde]
+pub struct TreasuryFundedEvent {
+    pub chat_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// What the treasury holds in `denom` now
+    pub balance: Uint256,
+}
+
+impl TreasuryFundedEvent {
+    pub const EVENT_TYPE: &'static str = "treasury-funded";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_BALANCE: &'static str = "balance";
+}
+
+impl From<TreasuryFundedEvent> for cosmwasm_std::Event {
+    fn from(src: TreasuryFundedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(TreasuryFundedEvent::EVENT_TYPE)
+            .add_attribute(
+                TreasuryFundedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                TreasuryFundedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                TreasuryFundedEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(TreasuryFundedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(TreasuryFundedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(TreasuryFundedEvent::EVENT_ATTR_KEY_BALANCE, src.balance);
+        add_opt_attr(
+            event,
+            TreasuryFundedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for TreasuryFundedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            balance: parse_attr(event, Self::EVENT_ATTR_KEY_BALANCE)?,
+        })
+    }
+}
+
+/// A member proposed a payout out of their group's treasury. The members vote on it from the
+/// prompt, the proposer's own vote already counts as an approval.
+#[cw_serde]
+pub struct ProposalCreatedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub proposer_tg_id: i64,
+    pub proposer_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    pub memo: Option<String>,
+    pub threshold: u32,
+}
+
+impl ProposalCreatedEvent {
+    pub const EVENT_TYPE: &'static str = "proposal-created";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_MESSAGE_ID: &'static str = "message-id";
+    pub const EVENT_ATTR_KEY_PROPOSER_TG_ID: &'static str = "proposer-tg-id";
+    pub const EVENT_ATTR_KEY_PROPOSER_TG_HANDLE: &'static str = "proposer-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_MEMO: &'static str = "memo";
+    pub const EVENT_ATTR_KEY_THRESHOLD: &'static str = "threshold";
+}
+
+impl From<ProposalCreatedEvent> for cosmwasm_std::Event {
+    fn from(src: ProposalCreatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(ProposalCreatedEvent::EVENT_TYPE)
+            .add_attribute(ProposalCreatedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                ProposalCreatedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                ProposalCreatedEvent::EVENT_ATTR_KEY_MESSAGE_ID,
+                src.message_id.to_string(),
+            )
+            .add_attribute(
+                ProposalCreatedEvent::EVENT_ATTR_KEY_PROPOSER_TG_ID,
+                src.proposer_tg_id.to_string(),
+            )
+            .add_attribute(
+                ProposalCreatedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(ProposalCreatedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(ProposalCreatedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                ProposalCreatedEvent::EVENT_ATTR_KEY_THRESHOLD,
+                src.threshold.to_string(),
+            );
+        let event = add_opt_attr(
+            event,
+            ProposalCreatedEvent::EVENT_ATTR_KEY_PROPOSER_TG_HANDLE,
+            src.proposer_tg_handle.map(String::from),
+        );
+        add_opt_attr(event, ProposalCreatedEvent::EVENT_ATTR_KEY_MEMO, src.memo)
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ProposalCreatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            message_id: parse_attr(event, Self::EVENT_ATTR_KEY_MESSAGE_ID)?,
+            proposer_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_PROPOSER_TG_ID)?,
+            proposer_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_PROPOSER_TG_HANDLE)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            memo: get_opt_attr(event, Self::EVENT_ATTR_KEY_MEMO),
+            threshold: parse_attr(event, Self::EVENT_ATTR_KEY_THRESHOLD)?,
+        })
+    }
+}
+
+/// A member voted on an open proposal. Followed by a [`ProposalExecutedEvent`] or a
+/// [`ProposalRejectedEvent`] if the vote decided it.
+#[cw_serde]
+pub struct ProposalVotedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub voter_tg_id: i64,
+    pub voter_tg_handle: Option<TgHandle>,
+    pub approve: bool,
+    /// Tallies including this vote
+    pub approvals: u32,
+    pub rejections: u32,
+    pub threshold: u32,
+}
+
+impl ProposalVotedEvent {
+    pub const EVENT_TYPE: &'static str = "proposal-voted";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_VOTER_TG_ID: &'static str = "voter-tg-id";
+    pub const EVENT_ATTR_KEY_VOTER_TG_HANDLE: &'static str = "voter-tg-handle";
+    pub const EVENT_ATTR_KEY_APPROVE: &'static str = "approve";
+    pub const EVENT_ATTR_KEY_APPROVALS: &'static str = "approvals";
+    pub const EVENT_ATTR_KEY_REJECTIONS: &'static str = "rejections";
+    pub const EVENT_ATTR_KEY_THRESHOLD: &'static str = "threshold";
+}
+
+impl From<ProposalVotedEvent> for cosmwasm_std::Event {
+    fn from(src: ProposalVotedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(ProposalVotedEvent::EVENT_TYPE)
+            .add_attribute(ProposalVotedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                ProposalVotedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                ProposalVotedEvent::EVENT_ATTR_KEY_VOTER_TG_ID,
+                src.voter_tg_id.to_string(),
+            )
+            .add_attribute(
+                ProposalVotedEvent::EVENT_ATTR_KEY_APPROVE,
+                src.approve.to_string(),
+            )
+            .add_attribute(
+                ProposalVotedEvent::EVENT_ATTR_KEY_APPROVALS,
+                src.approvals.to_string(),
+            )
+            .add_attribute(
+                ProposalVotedEvent::EVENT_ATTR_KEY_REJECTIONS,
+                src.rejections.to_string(),
+            )
+            .add_attribute(
+                ProposalVotedEvent::EVENT_ATTR_KEY_THRESHOLD,
+                src.threshold.to_string(),
+            );
+        add_opt_attr(
+            event,
+            ProposalVotedEvent::EVENT_ATTR_KEY_VOTER_TG_HANDLE,
+            src.voter_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ProposalVotedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            voter_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_VOTER_TG_ID)?,
+            voter_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_VOTER_TG_HANDLE)?,
+            approve: parse_attr(event, Self::EVENT_ATTR_KEY_APPROVE)?,
+            approvals: parse_attr(event, Self::EVENT_ATTR_KEY_APPROVALS)?,
+            rejections: parse_attr(event, Self::EVENT_ATTR_KEY_REJECTIONS)?,
+            threshold: parse_attr(event, Self::EVENT_ATTR_KEY_THRESHOLD)?,
+        })
+    }
+}
+
+/// A proposal reached its threshold and was paid out of the treasury
+#[cw_serde]
+pub struct ProposalExecutedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub to_tg_id: Option<i64>,
+    pub to_tg_handle: TgHandle,
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Set if the recipient is on another chain
+    pub to_chain: Option<String>,
+}
+
+impl ProposalExecutedEvent {
+    pub const EVENT_TYPE: &'static str = "proposal-executed";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_TO_CHAIN: &'static str = "to-chain";
+}
+
+impl From<ProposalExecutedEvent> for cosmwasm_std::Event {
+    fn from(src: ProposalExecutedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(ProposalExecutedEvent::EVENT_TYPE)
+            .add_attribute(ProposalExecutedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                ProposalExecutedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                ProposalExecutedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(
+                ProposalExecutedEvent::EVENT_ATTR_KEY_TO_ADDRESS,
+                src.to_address,
+            )
+            .add_attribute(ProposalExecutedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(ProposalExecutedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            ProposalExecutedEvent::EVENT_ATTR_KEY_TO_TG_ID,
+            src.to_tg_id.map(|id| id.to_string()),
+        );
+        add_opt_attr(
+            event,
+            ProposalExecutedEvent::EVENT_ATTR_KEY_TO_CHAIN,
+            src.to_chain,
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ProposalExecutedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            to_tg_id: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            to_chain: get_opt_attr(event, Self::EVENT_ATTR_KEY_TO_CHAIN),
+        })
+    }
+}
+
+/// Enough members rejected a proposal that it can no longer reach its threshold
+#[cw_serde]
+pub struct ProposalRejectedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+}
+
+impl ProposalRejectedEvent {
+    pub const EVENT_TYPE: &'static str = "proposal-rejected";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+}
+
+impl From<ProposalRejectedEvent> for cosmwasm_std::Event {
+    fn from(src: ProposalRejectedEvent) -> Self {
+        cosmwasm_std::Event::new(ProposalRejectedEvent::EVENT_TYPE)
+            .add_attribute(ProposalRejectedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                ProposalRejectedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                ProposalRejectedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+                src.to_tg_handle,
+            )
+            .add_attribute(ProposalRejectedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(ProposalRejectedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for ProposalRejectedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            to_tg_handle: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+        })
+    }
+}
+
+/// Funds locked under the hash of a secret, the sender gets a link with the secret in it
+#[cw_serde]
+pub struct GiftCreatedEvent {
+    pub id: u64,
+    /// Of the command that created it, which the secret is worked out from
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Paid to the treasury on top of `amount`, in the same denom. None when there was no fee.
+    pub fee: Option<Uint256>,
+    pub expires: Timestamp,
+}
+
+impl GiftCreatedEvent {
+    pub const EVENT_TYPE: &'static str = "gift-created";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_MESSAGE_ID: &'static str = "message-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+    /// Unix seconds
+    pub const EVENT_ATTR_KEY_EXPIRES: &'static str = "expires";
+}
+
+impl From<GiftCreatedEvent> for cosmwasm_std::Event {
+    fn from(src: GiftCreatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(GiftCreatedEvent::EVENT_TYPE)
+            .add_attribute(GiftCreatedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                GiftCreatedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                GiftCreatedEvent::EVENT_ATTR_KEY_MESSAGE_ID,
+                src.message_id.to_string(),
+            )
+            .add_attribute(
+                GiftCreatedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                GiftCreatedEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(GiftCreatedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(GiftCreatedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                GiftCreatedEvent::EVENT_ATTR_KEY_EXPIRES,
+                src.expires.seconds().to_string(),
+            );
+        let event = add_opt_attr(
+            event,
+            GiftCreatedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            GiftCreatedEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for GiftCreatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            message_id: parse_attr(event, Self::EVENT_ATTR_KEY_MESSAGE_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+            expires: Timestamp::from_seconds(parse_attr(event, Self::EVENT_ATTR_KEY_EXPIRES)?),
+        })
+    }
+}
+
+/// A gift claimed with its secret and paid to the claimer
+#[cw_serde]
+pub struct GiftClaimedEvent {
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_id: i64,
+    pub to_tg_handle: Option<TgHandle>,
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Set if the claimer is on another chain
+    pub to_chain: Option<String>,
+}
+
+impl GiftClaimedEvent {
+    pub const EVENT_TYPE: &'static str = "gift-claimed";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_TO_CHAIN: &'static str = "to-chain";
+}
+
+impl From<GiftClaimedEvent> for cosmwasm_std::Event {
+    fn from(src: GiftClaimedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(GiftClaimedEvent::EVENT_TYPE)
+            .add_attribute(GiftClaimedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                GiftClaimedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                GiftClaimedEvent::EVENT_ATTR_KEY_TO_TG_ID,
+                src.to_tg_id.to_string(),
+            )
+            .add_attribute(GiftClaimedEvent::EVENT_ATTR_KEY_TO_ADDRESS, src.to_address)
+            .add_attribute(GiftClaimedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(GiftClaimedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        let event = add_opt_attr(
+            event,
+            GiftClaimedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        let event = add_opt_attr(
+            event,
+            GiftClaimedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+            src.to_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            GiftClaimedEvent::EVENT_ATTR_KEY_TO_CHAIN,
+            src.to_chain,
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for GiftClaimedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            to_chain: get_opt_attr(event, Self::EVENT_ATTR_KEY_TO_CHAIN),
+        })
+    }
+}
+
+/// A gift opened by someone who isn't registered to receive yet, held for them until it expires
+#[cw_serde]
+pub struct GiftReservedEvent {
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_id: i64,
+    pub to_tg_handle: Option<TgHandle>,
+    pub amount: Uint256,
+    pub denom: String,
+    pub expires: Timestamp,
+}
+
+impl GiftReservedEvent {
+    pub const EVENT_TYPE: &'static str = "gift-reserved";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_EXPIRES: &'static str = "expires";
+}
+
+impl From<GiftReservedEvent> for cosmwasm_std::Event {
+    fn from(src: GiftReservedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(GiftReservedEvent::EVENT_TYPE)
+            .add_attribute(GiftReservedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                GiftReservedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                GiftReservedEvent::EVENT_ATTR_KEY_TO_TG_ID,
+                src.to_tg_id.to_string(),
+            )
+            .add_attribute(GiftReservedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(GiftReservedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                GiftReservedEvent::EVENT_ATTR_KEY_EXPIRES,
+                src.expires.seconds().to_string(),
+            );
+        let event = add_opt_attr(
+            event,
+            GiftReservedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            GiftReservedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+            src.to_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for GiftReservedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            to_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            expires: Timestamp::from_seconds(parse_attr(event, Self::EVENT_ATTR_KEY_EXPIRES)?),
+        })
+    }
+}
+
+/// A gift nobody claimed in time, returned to the sender
+#[cw_serde]
+pub struct GiftRefundedEvent {
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+}
+
+impl GiftRefundedEvent {
+    pub const EVENT_TYPE: &'static str = "gift-refunded";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+}
+
+impl From<GiftRefundedEvent> for cosmwasm_std::Event {
+    fn from(src: GiftRefundedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(GiftRefundedEvent::EVENT_TYPE)
+            .add_attribute(GiftRefundedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                GiftRefundedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                GiftRefundedEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(GiftRefundedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(GiftRefundedEvent::EVENT_ATTR_KEY_DENOM, src.denom);
+        add_opt_attr(
+            event,
+            GiftRefundedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for GiftRefundedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+        })
+    }
+}
+
+/// A pot put up in a group chat, posted there with a Claim button
+#[cw_serde]
+pub struct GiveawayCreatedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    pub count: u32,
+    pub split: GiveawaySplit,
+    /// Paid to the treasury on top of `amount`, in the same denom. None when there was no fee.
+    pub fee: Option<Uint256>,
+    pub expires: Timestamp,
+}
+
+impl GiveawayCreatedEvent {
+    pub const EVENT_TYPE: &'static str = "giveaway-created";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_MESSAGE_ID: &'static str = "message-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_COUNT: &'static str = "count";
+    /// `random` or `equal`
+    pub const EVENT_ATTR_KEY_SPLIT: &'static str = "split";
+    pub const EVENT_ATTR_KEY_FEE: &'static str = "fee";
+    /// Unix seconds
+    pub const EVENT_ATTR_KEY_EXPIRES: &'static str = "expires";
+}
+
+impl From<GiveawayCreatedEvent> for cosmwasm_std::Event {
+    fn from(src: GiveawayCreatedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(GiveawayCreatedEvent::EVENT_TYPE)
+            .add_attribute(GiveawayCreatedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                GiveawayCreatedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                GiveawayCreatedEvent::EVENT_ATTR_KEY_MESSAGE_ID,
+                src.message_id.to_string(),
+            )
+            .add_attribute(
+                GiveawayCreatedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                GiveawayCreatedEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(GiveawayCreatedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(GiveawayCreatedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                GiveawayCreatedEvent::EVENT_ATTR_KEY_COUNT,
+                src.count.to_string(),
+            )
+            .add_attribute(
+                GiveawayCreatedEvent::EVENT_ATTR_KEY_SPLIT,
+                src.split.to_string(),
+            )
+            .add_attribute(
+                GiveawayCreatedEvent::EVENT_ATTR_KEY_EXPIRES,
+                src.expires.seconds().to_string(),
+            );
+        let event = add_opt_attr(
+            event,
+            GiveawayCreatedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            GiveawayCreatedEvent::EVENT_ATTR_KEY_FEE,
+            src.fee.map(|fee| fee.to_string()),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for GiveawayCreatedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            message_id: parse_attr(event, Self::EVENT_ATTR_KEY_MESSAGE_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            count: parse_attr(event, Self::EVENT_ATTR_KEY_COUNT)?,
+            split: parse_attr(event, Self::EVENT_ATTR_KEY_SPLIT)?,
+            fee: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FEE)?,
+            expires: Timestamp::from_seconds(parse_attr(event, Self::EVENT_ATTR_KEY_EXPIRES)?),
+        })
+    }
+}
+
+/// A member's share of a giveaway, paid to their registered address
+#[cw_serde]
+pub struct GiveawayClaimedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub to_tg_id: i64,
+    pub to_tg_handle: Option<TgHandle>,
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// How many shares have been claimed, this one included
+    pub claims: u32,
+    pub count: u32,
+    /// What is left in the pot, the giveaway is over once this is zero
+    pub remaining: Uint256,
+    /// Set if the claimer is on another chain
+    pub to_chain: Option<String>,
+}
+
+impl GiveawayClaimedEvent {
+    pub const EVENT_TYPE: &'static str = "giveaway-claimed";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_TO_TG_ID: &'static str = "to-tg-id";
+    pub const EVENT_ATTR_KEY_TO_TG_HANDLE: &'static str = "to-tg-handle";
+    pub const EVENT_ATTR_KEY_TO_ADDRESS: &'static str = "to-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_CLAIMS: &'static str = "claims";
+    pub const EVENT_ATTR_KEY_COUNT: &'static str = "count";
+    pub const EVENT_ATTR_KEY_REMAINING: &'static str = "remaining";
+    pub const EVENT_ATTR_KEY_TO_CHAIN: &'static str = "to-chain";
+}
+
+impl From<GiveawayClaimedEvent> for cosmwasm_std::Event {
+    fn from(src: GiveawayClaimedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(GiveawayClaimedEvent::EVENT_TYPE)
+            .add_attribute(GiveawayClaimedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                GiveawayClaimedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                GiveawayClaimedEvent::EVENT_ATTR_KEY_TO_TG_ID,
+                src.to_tg_id.to_string(),
+            )
+            .add_attribute(
+                GiveawayClaimedEvent::EVENT_ATTR_KEY_TO_ADDRESS,
+                src.to_address,
+            )
+            .add_attribute(GiveawayClaimedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(GiveawayClaimedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                GiveawayClaimedEvent::EVENT_ATTR_KEY_CLAIMS,
+                src.claims.to_string(),
+            )
+            .add_attribute(
+                GiveawayClaimedEvent::EVENT_ATTR_KEY_COUNT,
+                src.count.to_string(),
+            )
+            .add_attribute(
+                GiveawayClaimedEvent::EVENT_ATTR_KEY_REMAINING,
+                src.remaining,
+            );
+        let event = add_opt_attr(
+            event,
+            GiveawayClaimedEvent::EVENT_ATTR_KEY_TO_TG_HANDLE,
+            src.to_tg_handle.map(String::from),
+        );
+        add_opt_attr(
+            event,
+            GiveawayClaimedEvent::EVENT_ATTR_KEY_TO_CHAIN,
+            src.to_chain,
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for GiveawayClaimedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            to_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_TO_TG_ID)?,
+            to_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_TO_TG_HANDLE)?,
+            to_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_TO_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            claims: parse_attr(event, Self::EVENT_ATTR_KEY_CLAIMS)?,
+            count: parse_attr(event, Self::EVENT_ATTR_KEY_COUNT)?,
+            remaining: parse_attr(event, Self::EVENT_ATTR_KEY_REMAINING)?,
+            to_chain: get_opt_attr(event, Self::EVENT_ATTR_KEY_TO_CHAIN),
+        })
+    }
+}
+
+/// What was left of a giveaway when it expired, returned to its creator
+#[cw_serde]
+pub struct GiveawayRefundedEvent {
+    pub id: u64,
+    pub chat_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    /// How many shares were claimed before it expired
+    pub claims: u32,
+}
+
+impl GiveawayRefundedEvent {
+    pub const EVENT_TYPE: &'static str = "giveaway-refunded";
+    pub const EVENT_ATTR_KEY_ID: &'static str = "id";
+    pub const EVENT_ATTR_KEY_CHAT_ID: &'static str = "chat-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_ID: &'static str = "from-tg-id";
+    pub const EVENT_ATTR_KEY_FROM_TG_HANDLE: &'static str = "from-tg-handle";
+    pub const EVENT_ATTR_KEY_FROM_ADDRESS: &'static str = "from-address";
+    pub const EVENT_ATTR_KEY_AMOUNT: &'static str = "amount";
+    pub const EVENT_ATTR_KEY_DENOM: &'static str = "denom";
+    pub const EVENT_ATTR_KEY_CLAIMS: &'static str = "claims";
+}
+
+impl From<GiveawayRefundedEvent> for cosmwasm_std::Event {
+    fn from(src: GiveawayRefundedEvent) -> Self {
+        let event = cosmwasm_std::Event::new(GiveawayRefundedEvent::EVENT_TYPE)
+            .add_attribute(GiveawayRefundedEvent::EVENT_ATTR_KEY_ID, src.id.to_string())
+            .add_attribute(
+                GiveawayRefundedEvent::EVENT_ATTR_KEY_CHAT_ID,
+                src.chat_id.to_string(),
+            )
+            .add_attribute(
+                GiveawayRefundedEvent::EVENT_ATTR_KEY_FROM_TG_ID,
+                src.from_tg_id.to_string(),
+            )
+            .add_attribute(
+                GiveawayRefundedEvent::EVENT_ATTR_KEY_FROM_ADDRESS,
+                src.from_address,
+            )
+            .add_attribute(GiveawayRefundedEvent::EVENT_ATTR_KEY_AMOUNT, src.amount)
+            .add_attribute(GiveawayRefundedEvent::EVENT_ATTR_KEY_DENOM, src.denom)
+            .add_attribute(
+                GiveawayRefundedEvent::EVENT_ATTR_KEY_CLAIMS,
+                src.claims.to_string(),
+            );
+        add_opt_attr(
+            event,
+            GiveawayRefundedEvent::EVENT_ATTR_KEY_FROM_TG_HANDLE,
+            src.from_tg_handle.map(String::from),
+        )
+    }
+}
+
+impl TryFrom<&cosmwasm_std::Event> for GiveawayRefundedEvent {
+    type Error = anyhow::Error;
+
+    fn try_from(event: &cosmwasm_std::Event) -> Result<Self, Self::Error> {
+        check_event_type(event, Self::EVENT_TYPE)?;
+
+        Ok(Self {
+            id: parse_attr(event, Self::EVENT_ATTR_KEY_ID)?,
+            chat_id: parse_attr(event, Self::EVENT_ATTR_KEY_CHAT_ID)?,
+            from_tg_id: parse_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_ID)?,
+            from_tg_handle: parse_opt_attr(event, Self::EVENT_ATTR_KEY_FROM_TG_HANDLE)?,
+            from_address: Addr::unchecked(get_attr(event, Self::EVENT_ATTR_KEY_FROM_ADDRESS)?),
+            amount: parse_attr(event, Self::EVENT_ATTR_KEY_AMOUNT)?,
+            denom: get_attr(event, Self::EVENT_ATTR_KEY_DENOM)?,
+            claims: parse_attr(event, Self::EVENT_ATTR_KEY_CLAIMS)?,
+        })
+    }
+}
+
+// Shared helpers for parsing events back out of a tx, accepts both the raw and the `wasm-` prefixed type
+fn check_event_type(event: &cosmwasm_std::Event, ty: &str) -> anyhow::Result<()> {
+    if event.ty != ty && event.ty != format!("wasm-{ty}") {
+        return Err(anyhow::anyhow!(
+            "Expected event type {}, found {}",
+            ty,
+            event.ty
+        ));
+    }
+    Ok(())
+}
+
+fn get_opt_attr(event: &cosmwasm_std::Event, key: &str) -> Option<String> {
+    event
+        .attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.to_string())
+}
+
+fn get_attr(event: &cosmwasm_std::Event, key: &str) -> anyhow::Result<String> {
+    get_opt_attr(event, key).ok_or_else(|| anyhow::anyhow!("Missing attribute {}", key))
+}
+
+fn parse_opt_attr<T>(event: &cosmwasm_std::Event, key: &str) -> anyhow::Result<Option<T>>
+where
+    T: std::str::FromStr,
+{
+    get_opt_attr(event, key)
+        .map(|val| {
+            val.parse::<T>()
+                .map_err(|_| anyhow::anyhow!("Invalid attribute {}: {}", key, val))
+        })
+        .transpose()
+}
+
+fn parse_attr<T>(event: &cosmwasm_std::Event, key: &str) -> anyhow::Result<T>
+where
+    T: std::str::FromStr,
+{
+    parse_opt_attr(event, key)?.ok_or_else(|| anyhow::anyhow!("Missing attribute {}", key))
+}
+
+// Optional values are left out of the event entirely, rather than written as an empty string
+fn add_opt_attr(
+    event: cosmwasm_std::Event,
+    key: &str,
+    value: Option<String>,
+) -> cosmwasm_std::Event {
+    match value {
+        Some(value) => event.add_attribute(key, value),
+        None => event,
+    }
+}
+
+// Lists are written comma separated, an empty list is left out like any other optional value
+fn join_list(values: &[String]) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(","))
+    }
+}
+
+fn split_list(value: Option<String>) -> Vec<String> {
+    value
+        .map(|value| value.split(',').map(String::from).collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,205 @@
+use std::str::FromStr;
+
+use cosmwasm_schema::cw_schema::Schemaifier;
+use cosmwasm_schema::schemars::{self, JsonSchema};
+use serde::{Deserialize, Serialize};
+
+/// A Telegram username in canonical form: lowercase, without the leading `@`.
+///
+/// Telegram usernames are case-insensitive, and users type them with or without the `@`,
+/// so every handle is normalized through this type before being used as a key.
+///
+/// Use [`TgHandle::new`] (or `FromStr`) to normalize user input. Deserialization is strict and
+/// only accepts the canonical form, so messages carrying anything else are rejected.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    JsonSchema,
+    Schemaifier,
+)]
+#[serde(try_from = "String", into = "String")]
+// Validation happens in `try_from`, the schema is just a string
+#[schemaifier(
+    crate = "::cosmwasm_schema::cw_schema",
+    type = ::cosmwasm_schema::cw_schema::NodeType::String,
+    mute_warnings
+)]
+pub struct TgHandle(String);
+
+impl TgHandle {
+    /// Telegram requires 5, collectible usernames can be 4
+    pub const MIN_LEN: usize = 4;
+    pub const MAX_LEN: usize = 32;
+
+    /// Normalizes user input ("@Alice", "alice", ...) and validates it
+    pub fn new(handle: &str) -> Result<Self, TgHandleError> {
+        let handle = handle.trim();
+        let handle = handle.strip_prefix('@').unwrap_or(handle);
+        Self::canonical(handle.to_ascii_lowercase())
+    }
+
+    /// Only accepts a handle that is already in canonical form
+    pub fn canonical(handle: impl Into<String>) -> Result<Self, TgHandleError> {
+        let handle = handle.into();
+
+        if handle.len() < Self::MIN_LEN || handle.len() > Self::MAX_LEN {
+            return Err(TgHandleError::Length(handle));
+        }
+        if !handle
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return Err(TgHandleError::Characters(handle));
+        }
+        if !handle.starts_with(|c: char| c.is_ascii_lowercase()) {
+            return Err(TgHandleError::Start(handle));
+        }
+        if handle.ends_with('_') || handle.contains("__") {
+            return Err(TgHandleError::Underscore(handle));
+        }
+
+        Ok(Self(handle))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// For display in chat, with the leading `@`
+    pub fn mention(&self) -> String {
+        format!("@{}", self.0)
+    }
+}
+
+impl FromStr for TgHandle {
+    type Err = TgHandleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for TgHandle {
+    type Error = TgHandleError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::canonical(value)
+    }
+}
+
+impl From<TgHandle> for String {
+    fn from(handle: TgHandle) -> Self {
+        handle.0
+    }
+}
+
+impl AsRef<str> for TgHandle {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for TgHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TgHandleError {
+    Length(String),
+    Characters(String),
+    Start(String),
+    Underscore(String),
+}
+
+impl std::fmt::Display for TgHandleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TgHandleError::Length(handle) => write!(
+                f,
+                "invalid Telegram handle {handle}: must be {}-{} characters",
+                TgHandle::MIN_LEN,
+                TgHandle::MAX_LEN
+            ),
+            TgHandleError::Characters(handle) => write!(
+                f,
+                "invalid Telegram handle {handle}: only a-z, 0-9 and underscores are allowed"
+            ),
+            TgHandleError::Start(handle) => write!(
+                f,
+                "invalid Telegram handle {handle}: must start with a letter"
+            ),
+            TgHandleError::Underscore(handle) => write!(
+                f,
+                "invalid Telegram handle {handle}: can't end with or repeat underscores"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TgHandleError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_input() {
+        let expected = TgHandle::canonical("alice_99").unwrap();
+        for input in [
+            "alice_99",
+            "@alice_99",
+            "Alice_99",
+            "@ALICE_99",
+            " @alice_99 ",
+        ] {
+            assert_eq!(TgHandle::new(input).unwrap(), expected, "{input}");
+        }
+        assert_eq!(expected.as_str(), "alice_99");
+        assert_eq!(expected.mention(), "@alice_99");
+    }
+
+    #[test]
+    fn enforces_telegram_rules() {
+        for input in [
+            "",
+            "@",
+            "abc",
+            "a".repeat(33).as_str(),
+            "9lives",
+            "_alice",
+            "alice_",
+            "ali__ce",
+            "alice-b",
+            "alice.b",
+            "@@alice",
+            "álice",
+        ] {
+            assert!(TgHandle::new(input).is_err(), "{input}");
+        }
+        assert!(TgHandle::new("dave").is_ok());
+        assert!(TgHandle::new(&"a".repeat(32)).is_ok());
+    }
+
+    #[test]
+    fn deserialization_is_strict() {
+        let handle: TgHandle = cosmwasm_std::from_json(r#""alice""#).unwrap();
+        assert_eq!(handle.as_str(), "alice");
+        assert_eq!(cosmwasm_std::to_json_string(&handle).unwrap(), r#""alice""#);
+
+        for input in [r#""@alice""#, r#""Alice""#, r#""ab""#] {
+            assert!(
+                cosmwasm_std::from_json::<TgHandle>(input).is_err(),
+                "{input}"
+            );
+        }
+    }
+}
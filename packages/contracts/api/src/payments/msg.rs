@@ -1,13 +1,25 @@
+use crate::payments::handle::TgHandle;
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint256;
+use cosmwasm_std::{Addr, Coin, HexBinary, Timestamp, Uint256};
+use cw_utils::{Duration, Expiration};
+use sha2::{Digest, Sha256};
+
 use wavs_types::contracts::cosmwasm::service_handler::{
     ServiceHandlerExecuteMessages, ServiceHandlerQueryMessages,
 };
 
 #[cw_serde]
 pub struct InstantiateMsg {
+    /// Native denoms, or CW20 contract addresses
     pub allowed_denoms: Vec<String>,
     pub auth: Auth,
+    /// How long payments to unregistered handles are held before they can be refunded.
+    /// None means they are held until the recipient registers.
+    pub pending_expiry: Option<Duration>,
+    /// Account allowed to change the contract configuration. Defaults to the instantiator.
+    pub owner: Option<String>,
+    /// Charged to senders on top of each send. None means sends are free.
+    pub fee: Option<FeeConfig>,
 }
 
 #[cw_serde]
@@ -31,16 +43,171 @@ pub enum QueryMsg {
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum CustomQueryMsg {
+    /// Resolves the handle to whichever Telegram user currently holds it
+    #[returns(ChainAddrResponse)]
+    AddrByTg { handle: TgHandle },
     #[returns(ChainAddrResponse)]
-    AddrByTg { handle: String },
+    AddrByTgId { user_id: i64 },
     #[returns(TgHandleResponse)]
     TgByAddr { account: String },
     #[returns(AdminResponse)]
     Admin {},
+    #[returns(OwnerResponse)]
+    Owner {},
+    /// Which of the two authorization modes is active, and for which address
+    #[returns(Auth)]
+    Auth {},
     #[returns(Vec<cosmwasm_std::Coin>)]
-    PendingPayments { handle: String },
+    PendingPayments { handle: TgHandle },
+    /// Individual pending payments for an unregistered handle, oldest first
+    #[returns(PendingPaymentsResponse)]
+    PendingPaymentsByTg {
+        handle: TgHandle,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(PendingExpiryResponse)]
+    PendingExpiry {},
     #[returns(Vec<String>)]
     AllowedDenoms {},
+    /// Payments sent or received by a Telegram handle, newest first
+    #[returns(PaymentHistoryResponse)]
+    PaymentsByTg {
+        handle: TgHandle,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Payments sent or received by a Telegram user, across handle changes, newest first
+    #[returns(PaymentHistoryResponse)]
+    PaymentsByTgId {
+        user_id: i64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Payments sent or received by a blockchain address, newest first
+    #[returns(PaymentHistoryResponse)]
+    PaymentsByAddr {
+        account: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Payments in a given denom, newest first
+    #[returns(PaymentHistoryResponse)]
+    PaymentsByDenom {
+        denom: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// None once the invoice has been paid or declined
+    #[returns(InvoiceResponse)]
+    Invoice { id: u64 },
+    /// Open invoices a handle has been asked to pay, oldest first
+    #[returns(InvoicesResponse)]
+    InvoicesByPayer {
+        handle: TgHandle,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The sender's cap in `denom`, and how much of it is left right now
+    #[returns(SpendLimitResponse)]
+    SpendLimit { account: String, denom: String },
+    #[returns(FeeResponse)]
+    Fee {},
+    /// The authz grant `account` gave this contract to send on its behalf, which native sends are
+    /// paid through. Registering to send doesn't check for one.
+    #[returns(SendGrantResponse)]
+    SendGrant { account: String },
+    /// What sending `amount` would cost the sender, so it can be shown before they send
+    #[returns(FeeQuoteResponse)]
+    FeeQuote { amount: Uint256, denom: String },
+    #[returns(PausedResponse)]
+    Paused {},
+    /// Whether either the handle or the address is on the blocklist
+    #[returns(BlockedResponse)]
+    Blocked {
+        handle: Option<TgHandle>,
+        account: Option<String>,
+    },
+    /// Accounts registered to receive, by Telegram user id. Accounts registered before user ids
+    /// were tracked have no id to page by, so they are only listed once a migration or the user's
+    /// next payment links them. `Stats` counts them either way.
+    #[returns(AccountsResponse)]
+    ListAccounts {
+        start_after: Option<i64>,
+        limit: Option<u32>,
+    },
+    /// Accounts registered to send, by address. Same caveat as `ListAccounts`.
+    #[returns(FundedAccountsResponse)]
+    ListFundedAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Everything held for handles, by handle. With pull claims on, this includes handles that
+    /// registered but haven't claimed yet, see `PendingClaim`.
+    #[returns(PendingBalancesResponse)]
+    ListPendingPayments {
+        start_after: Option<TgHandle>,
+        limit: Option<u32>,
+    },
+    #[returns(StatsResponse)]
+    Stats {},
+    /// Other chains members can register a receive address on, by bech32 prefix
+    #[returns(IbcRoutesResponse)]
+    IbcRoutes {},
+    /// How denoms are shown to users, and the symbols and aliases they can type instead
+    #[returns(DenomMetadataResponse)]
+    DenomMetadata {},
+    #[returns(PullClaimsResponse)]
+    PullClaims {},
+    /// What is held for a handle, and whether its registered account can claim it yet
+    #[returns(PendingClaimResponse)]
+    PendingClaim { handle: TgHandle },
+    /// How long sends are held before they go out, None if they go out right away
+    #[returns(EscrowWindowResponse)]
+    EscrowWindow {},
+    /// Sends still held in escrow, oldest first. Only the ones from `from_tg_id` if it is set.
+    #[returns(EscrowsResponse)]
+    Escrows {
+        from_tg_id: Option<i64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// How many held sends `ReleaseMatured` would pay out right now, at most as many as it takes
+    /// in one go
+    #[returns(MaturedEscrowsResponse)]
+    MaturedEscrows {},
+    #[returns(ScheduleResponse)]
+    Schedule { id: u64 },
+    /// Recurring payments, by id. Only the ones from `from_tg_id` if it is set.
+    #[returns(SchedulesResponse)]
+    Schedules {
+        from_tg_id: Option<i64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Recurring payments due right now, soonest first, at most as many as `RunSchedules` takes
+    /// in one go
+    #[returns(SchedulesResponse)]
+    DueSchedules { limit: Option<u32> },
+    /// The treasury of a group chat, with what it holds
+    #[returns(TreasuryResponse)]
+    Treasury { chat_id: i64 },
+    #[returns(ProposalResponse)]
+    Proposal { id: u64 },
+    /// Open payout proposals of a group chat's treasury, by id
+    #[returns(ProposalsResponse)]
+    Proposals {
+        chat_id: i64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    #[returns(GiftResponse)]
+    Gift { id: u64 },
+    /// The unclaimed gift locked under `hash`, see [`Gift::hash_secret`]
+    #[returns(GiftResponse)]
+    GiftByHash { hash: HexBinary },
+    #[returns(GiveawayResponse)]
+    Giveaway { id: u64 },
 }
 
 #[cw_serde]
@@ -57,37 +224,508 @@ pub enum CustomExecuteMsg {
     RegisterReceive(RegisterReceiveMsg),
     /// Must be called by WAVS operators
     SendPayment(SendPaymentMsg),
-    /// Called directly by the blockchain account authorizing payments
-    RegisterSend { tg_handle: String },
+    /// Must be called by WAVS operators
+    SplitPayment(SplitPaymentMsg),
+    /// Must be called by WAVS operators
+    Tip(TipMsg),
+    /// Must be called by WAVS operators
+    RequestPayment(RequestPaymentMsg),
+    /// Must be called by WAVS operators
+    PayInvoice(InvoiceActionMsg),
+    /// Must be called by WAVS operators
+    DeclineInvoice(InvoiceActionMsg),
+    /// Must be called by WAVS operators
+    CancelPayment(CancelPaymentMsg),
+    /// Must be called by WAVS operators
+    SchedulePayment(SchedulePaymentMsg),
+    /// Must be called by WAVS operators
+    Unschedule(UnscheduleMsg),
+    /// Must be called by WAVS operators. Pays the recurring payments that are due, each at most
+    /// once per period.
+    RunSchedules { runs: Vec<ScheduleRun> },
+    /// Must be called by WAVS operators
+    CreateTreasury(CreateTreasuryMsg),
+    /// Must be called by WAVS operators
+    FundTreasury(FundTreasuryMsg),
+    /// Must be called by WAVS operators
+    ProposePayout(ProposePayoutMsg),
+    /// Must be called by WAVS operators
+    Vote(VoteMsg),
+    /// Must be called by WAVS operators
+    CreateGift(CreateGiftMsg),
+    /// Must be called by WAVS operators
+    ClaimGift(ClaimGiftMsg),
+    /// Must be called by WAVS operators
+    CreateGiveaway(CreateGiveawayMsg),
+    /// Must be called by WAVS operators
+    ClaimGiveaway(ClaimGiveawayMsg),
+    /// Called directly by the blockchain account authorizing payments.
+    /// The handle is resolved to the Telegram user currently holding it.
+    RegisterSend { tg_handle: TgHandle },
+    /// Called directly by a registered sender's blockchain account, to cap what can be paid out
+    /// of it in `denom`. None removes the cap.
+    SetSpendLimit {
+        denom: String,
+        limit: Option<SpendLimit>,
+    },
+    /// Callable by anyone. Returns expired pending payments to their senders, oldest first.
+    RefundExpired { limit: Option<u32> },
+    /// Callable by anyone. Returns expired gifts to their senders, soonest expired first.
+    RefundExpiredGifts { limit: Option<u32> },
+    /// Callable by anyone. Returns what is left of expired giveaways to their creators, soonest
+    /// expired first.
+    RefundExpiredGiveaways { limit: Option<u32> },
+    /// Owner only. Proposes a new owner, who must accept before it takes effect.
+    /// Replaces any transfer already in progress.
+    TransferOwnership { new_owner: String },
+    /// Must be called by the proposed owner
+    AcceptOwnership {},
+    /// Owner only. Withdraws a proposed transfer.
+    CancelOwnershipTransfer {},
+    /// Owner only. Native denoms, or CW20 contract addresses.
+    AddDenoms { denoms: Vec<String> },
+    /// Owner only. Payments already held in these denoms can still be released or refunded.
+    RemoveDenoms { denoms: Vec<String> },
+    /// Owner only. Rotates the service manager or admin, or switches between the two modes.
+    SetAuth { auth: Auth },
+    /// Owner only. None stops charging a fee.
+    SetFee { fee: Option<FeeConfig> },
+    /// Owner only. How long payments to unregistered handles are held before they can be refunded,
+    /// None holds them forever. Payments already held keep the expiry they were made with.
+    SetPendingExpiry { expiry: Option<Duration> },
+    /// Owner only. While paused, nothing the operators submit goes through, so no one can register
+    /// or be paid. Refunds, queries and what senders call on their own accounts keep working.
+    SetPaused { paused: bool },
+    /// Owner only. Blocked handles and addresses can't register, send, request or be paid.
+    Block {
+        handles: Vec<TgHandle>,
+        addresses: Vec<String>,
+    },
+    /// Owner only
+    Unblock {
+        handles: Vec<TgHandle>,
+        addresses: Vec<String>,
+    },
+    /// Owner only. Lets members register receive addresses with this route's prefix, and pays
+    /// them over its channel. Replaces any route already set for the prefix.
+    SetIbcRoute { route: IbcRoute },
+    /// Owner only. Sends to addresses already registered with this prefix fail until it is set again.
+    RemoveIbcRoute { prefix: String },
+    /// Owner only. Replaces any metadata already set for the denom.
+    SetDenomMetadata { metadata: DenomMetadata },
+    /// Owner only. Amounts in this denom are shown and typed in base units again.
+    RemoveDenomMetadata { denom: String },
+    /// Only the contract itself, for each entry of a [`WavsPayload::Batch`], so a failing entry
+    /// reverts on its own
+    ProcessBatchEntry { payload: WavsPayload },
+    /// Only the contract itself, for each due run of [`CustomExecuteMsg::RunSchedules`], so a
+    /// failing payment doesn't hold up the others
+    ProcessScheduleRun { payment: SendPaymentMsg },
+    /// Owner only. While enabled, funds held for a handle stay in the contract after it registers,
+    /// until the registered address calls `ClaimPending`.
+    SetPullClaims { enabled: bool },
+    /// From the address registered to receive for `tg_handle`. Pays out everything held for it,
+    /// to `to` if set, which can be on any chain a receive address can be on.
+    ClaimPending {
+        tg_handle: TgHandle,
+        to: Option<String>,
+    },
+    /// Owner only. While set, sends are held by the contract for this long before they go out,
+    /// and the sender can cancel them until then. None sends them right away.
+    SetEscrowWindow { window: Option<Duration> },
+    /// Callable by anyone. Pays out held sends whose window has passed, oldest first.
+    ReleaseMatured { limit: Option<u32> },
 }
 
 #[cw_serde]
 pub struct RegisterReceiveMsg {
+    pub chat_id: i64,
     pub message_id: i64,
-    pub tg_handle: String,
+    /// Numeric Telegram user id, the stable identity of the account
+    pub tg_user_id: i64,
+    /// Current username, if the user has one. Kept as a mutable alias for the id.
+    pub tg_handle: Option<TgHandle>,
+    /// On this chain, or on another one there is an IBC route to
     pub chain_addr: String,
 }
 
 #[cw_serde]
 pub struct SendPaymentMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender
+    pub from_tg_id: i64,
+    /// Sender's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    /// Recipient handle, as typed in the chat
+    pub to_tg: TgHandle,
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address. CW20 amounts must fit in a Uint128.
+    pub denom: String,
+}
+
+/// A payment to the author of the message being replied to, who is known by user id
+#[cw_serde]
+pub struct TipMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender
+    pub from_tg_id: i64,
+    /// Sender's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    /// Numeric Telegram user id of the recipient
+    pub to_tg_id: i64,
+    /// Recipient's current username. Without one, the recipient must already be registered,
+    /// since payments can only be held for a handle.
+    pub to_tg: Option<TgHandle>,
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address
+    pub denom: String,
+}
+
+/// One payment divided between several recipients, all legs succeed or fail together
+#[cw_serde]
+pub struct SplitPaymentMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender
+    pub from_tg_id: i64,
+    /// Sender's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    /// Total across all recipients
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address
+    pub denom: String,
+    pub recipients: Vec<SplitRecipient>,
+}
+
+#[cw_serde]
+pub struct SplitRecipient {
+    pub to_tg: TgHandle,
+    /// Share of the total relative to the other recipients, 1 for an even split
+    pub weight: u32,
+}
+
+/// Asks another user for a payment, which they can pay or decline later
+#[cw_serde]
+pub struct RequestPaymentMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the requester, who must be registered to receive
+    pub from_tg_id: i64,
+    /// Requester's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    /// Who is asked to pay, as typed in the chat
+    pub payer_tg: TgHandle,
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address
+    pub denom: String,
+    pub memo: Option<String>,
+}
+
+/// The payer pressing Pay or Decline on an invoice prompt
+#[cw_serde]
+pub struct InvoiceActionMsg {
+    /// The prompt the button was on, so each prompt can only be answered once
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of whoever pressed the button
+    pub from_tg_id: i64,
+    /// Must be the handle the invoice was addressed to
+    pub from_tg: Option<TgHandle>,
+    pub invoice_id: u64,
+}
+
+/// The sender taking back a send that is still held in escrow
+#[cw_serde]
+pub struct CancelPaymentMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender, only they can cancel it
+    pub from_tg_id: i64,
+    /// The held send to cancel, the sender's latest one if None
+    pub escrow_id: Option<u64>,
+}
+
+/// A payment that goes out every `interval_seconds`, starting right away
+#[cw_serde]
+pub struct SchedulePaymentMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender
+    pub from_tg_id: i64,
+    /// Sender's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    /// Recipient handle, as typed in the chat. Resolved on every run.
+    pub to_tg: TgHandle,
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address
+    pub denom: String,
+    pub interval_seconds: u64,
+    /// No runs are due after this, None keeps it going until it is cancelled
+    pub until: Option<Timestamp>,
+}
+
+#[cw_serde]
+pub struct UnscheduleMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender, only they can cancel it
+    pub from_tg_id: i64,
+    pub schedule_id: u64,
+}
+
+/// Sets up a shared treasury for a group chat, paid out when enough of its members approve
+#[cw_serde]
+pub struct CreateTreasuryMsg {
+    /// The group chat, a treasury is keyed by it
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of whoever set it up, they are always a member
+    pub from_tg_id: i64,
+    pub from_tg: Option<TgHandle>,
+    /// The other members, each has to be known to the contract by their handle
+    pub members: Vec<TgHandle>,
+    /// How many members have to approve a payout
+    pub threshold: u32,
+}
+
+/// Moves funds from the sender's own account into the treasury of the group chat
+#[cw_serde]
+pub struct FundTreasuryMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender, who doesn't have to be a member
+    pub from_tg_id: i64,
+    /// Sender's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address
+    pub denom: String,
+}
+
+/// Proposes paying `to_tg` out of the treasury of the group chat. The proposer's own vote counts
+/// as an approval.
+#[cw_serde]
+pub struct ProposePayoutMsg {
+    pub chat_id: i64,
     pub message_id: i64,
-    pub from_tg: String,
-    pub to_tg: String,
+    /// Numeric Telegram user id of the proposer, who has to be a member
+    pub from_tg_id: i64,
+    pub from_tg: Option<TgHandle>,
+    /// Recipient handle, as typed in the chat. They have to be registered to receive.
+    pub to_tg: TgHandle,
     pub amount: Uint256,
+    /// Native denom, or CW20 contract address
     pub denom: String,
+    pub memo: Option<String>,
 }
 
+/// A member's vote on an open payout proposal
+#[cw_serde]
+pub struct VoteMsg {
+    /// The group chat of the treasury, votes from anywhere else are turned away
+    pub chat_id: i64,
+    /// Every member votes on the same prompt, so this isn't checked for replays. A member can
+    /// only vote once on each proposal anyway.
+    pub message_id: i64,
+    /// Numeric Telegram user id of the voter
+    pub from_tg_id: i64,
+    pub from_tg: Option<TgHandle>,
+    pub proposal_id: u64,
+    pub approve: bool,
+}
+
+/// Locks funds from the sender's own account under the hash of a secret, for whoever has the
+/// secret to claim. The secret itself only goes on chain when the gift is claimed.
+#[cw_serde]
+pub struct CreateGiftMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender
+    pub from_tg_id: i64,
+    /// Sender's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address
+    pub denom: String,
+    /// See [`Gift::hash_secret`]
+    pub hash: HexBinary,
+    /// How long it can be claimed for, a week if None
+    pub expires_in_seconds: Option<u64>,
+}
+
+/// Claims a gift with the secret it was locked under, paid to the claimer's registered address
+#[cw_serde]
+pub struct ClaimGiftMsg {
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the claimer, who has to be registered to receive
+    pub to_tg_id: i64,
+    pub to_tg: Option<TgHandle>,
+    pub secret: String,
+}
+
+/// Puts funds from the sender's own account up for grabs in a group chat, shared between the
+/// first `count` members to claim
+#[cw_serde]
+pub struct CreateGiveawayMsg {
+    /// The group chat it can be claimed from
+    pub chat_id: i64,
+    pub message_id: i64,
+    /// Numeric Telegram user id of the sender
+    pub from_tg_id: i64,
+    /// Sender's current username, refreshes their alias if it changed
+    pub from_tg: Option<TgHandle>,
+    /// The whole pot, at least one base unit per claim
+    pub amount: Uint256,
+    /// Native denom, or CW20 contract address
+    pub denom: String,
+    /// How many members can claim a share
+    pub count: u32,
+    pub split: GiveawaySplit,
+}
+
+/// How a giveaway's pot is shared out between its claims
+#[cw_serde]
+#[derive(Copy)]
+pub enum GiveawaySplit {
+    /// Each claim takes a random share, up to twice the average of what is left. The randomness
+    /// comes from the block the claim lands in, so every operator's submission agrees.
+    Random,
+    /// Each claim takes the same share, the last one also takes whatever doesn't divide evenly
+    Equal,
+}
+
+impl std::fmt::Display for GiveawaySplit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GiveawaySplit::Random => write!(f, "random"),
+            GiveawaySplit::Equal => write!(f, "equal"),
+        }
+    }
+}
+
+impl std::str::FromStr for GiveawaySplit {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "random" => Ok(GiveawaySplit::Random),
+            "equal" => Ok(GiveawaySplit::Equal),
+            _ => Err(anyhow::anyhow!("unknown split {s}, use random or equal")),
+        }
+    }
+}
+
+/// A member grabbing their share of a giveaway, paid to their registered address
+#[cw_serde]
+pub struct ClaimGiveawayMsg {
+    /// The group chat of the giveaway, claims from anywhere else are turned away
+    pub chat_id: i64,
+    /// Every member claims on the same prompt, so this isn't checked for replays. A member can
+    /// only claim once from each giveaway anyway.
+    pub message_id: i64,
+    /// Numeric Telegram user id of the claimer, who has to be registered to receive
+    pub to_tg_id: i64,
+    pub to_tg: Option<TgHandle>,
+    pub giveaway_id: u64,
+}
+
+/// A run of a recurring payment, for the period that was due at `due`. It only goes through if
+/// that period hasn't been paid yet, so operators submitting the same run is harmless.
+#[cw_serde]
+pub struct ScheduleRun {
+    pub id: u64,
+    pub due: Timestamp,
+}
+
+/// The most entries a [`WavsPayload::Batch`] can have. Keeps a batch well inside the gas a single
+/// envelope can get. Running out of gas can't be caught, so it would still revert every entry.
+pub const MAX_BATCH_SIZE: usize = 50;
+
 #[cw_serde]
 pub enum WavsPayload {
     Register(RegisterReceiveMsg),
     SendPayment(SendPaymentMsg),
+    SplitPayment(SplitPaymentMsg),
+    Tip(TipMsg),
+    RequestPayment(RequestPaymentMsg),
+    PayInvoice(InvoiceActionMsg),
+    DeclineInvoice(InvoiceActionMsg),
+    CancelPayment(CancelPaymentMsg),
+    SchedulePayment(SchedulePaymentMsg),
+    Unschedule(UnscheduleMsg),
+    CreateTreasury(CreateTreasuryMsg),
+    FundTreasury(FundTreasuryMsg),
+    ProposePayout(ProposePayoutMsg),
+    Vote(VoteMsg),
+    CreateGift(CreateGiftMsg),
+    ClaimGift(ClaimGiftMsg),
+    CreateGiveaway(CreateGiveawayMsg),
+    ClaimGiveaway(ClaimGiveawayMsg),
+    /// Sent by the operators on their own schedule rather than for a Telegram message, so it has
+    /// no message of its own and can't be batched. Replaying it is harmless.
+    ReleaseMatured {
+        limit: Option<u32>,
+    },
+    /// Like [`WavsPayload::ReleaseMatured`], for the recurring payments that are due
+    RunSchedules(Vec<ScheduleRun>),
+    /// Several commands in one envelope. Each goes through or fails on its own, see
+    /// [`crate::payments::event::BatchEntryEvent`]. Batches can't be nested.
+    Batch(Vec<WavsPayload>),
 }
 
 impl WavsPayload {
-    pub fn message_id(&self) -> i64 {
+    /// Telegram message ids are only unique within a chat. None for a batch, whose entries each
+    /// have their own, and for a release or a run, which isn't for any message.
+    pub fn chat_id(&self) -> Option<i64> {
         match self {
-            WavsPayload::Register(msg) => msg.message_id,
-            WavsPayload::SendPayment(msg) => msg.message_id,
+            WavsPayload::Register(msg) => Some(msg.chat_id),
+            WavsPayload::SendPayment(msg) => Some(msg.chat_id),
+            WavsPayload::SplitPayment(msg) => Some(msg.chat_id),
+            WavsPayload::Tip(msg) => Some(msg.chat_id),
+            WavsPayload::RequestPayment(msg) => Some(msg.chat_id),
+            WavsPayload::PayInvoice(msg) | WavsPayload::DeclineInvoice(msg) => Some(msg.chat_id),
+            WavsPayload::CancelPayment(msg) => Some(msg.chat_id),
+            WavsPayload::SchedulePayment(msg) => Some(msg.chat_id),
+            WavsPayload::Unschedule(msg) => Some(msg.chat_id),
+            WavsPayload::CreateTreasury(msg) => Some(msg.chat_id),
+            WavsPayload::FundTreasury(msg) => Some(msg.chat_id),
+            WavsPayload::ProposePayout(msg) => Some(msg.chat_id),
+            WavsPayload::Vote(msg) => Some(msg.chat_id),
+            WavsPayload::CreateGift(msg) => Some(msg.chat_id),
+            WavsPayload::ClaimGift(msg) => Some(msg.chat_id),
+            WavsPayload::CreateGiveaway(msg) => Some(msg.chat_id),
+            WavsPayload::ClaimGiveaway(msg) => Some(msg.chat_id),
+            WavsPayload::ReleaseMatured { .. }
+            | WavsPayload::RunSchedules(_)
+            | WavsPayload::Batch(_) => None,
+        }
+    }
+
+    pub fn message_id(&self) -> Option<i64> {
+        match self {
+            WavsPayload::Register(msg) => Some(msg.message_id),
+            WavsPayload::SendPayment(msg) => Some(msg.message_id),
+            WavsPayload::SplitPayment(msg) => Some(msg.message_id),
+            WavsPayload::Tip(msg) => Some(msg.message_id),
+            WavsPayload::RequestPayment(msg) => Some(msg.message_id),
+            WavsPayload::PayInvoice(msg) | WavsPayload::DeclineInvoice(msg) => Some(msg.message_id),
+            WavsPayload::CancelPayment(msg) => Some(msg.message_id),
+            WavsPayload::SchedulePayment(msg) => Some(msg.message_id),
+            WavsPayload::Unschedule(msg) => Some(msg.message_id),
+            WavsPayload::CreateTreasury(msg) => Some(msg.message_id),
+            WavsPayload::FundTreasury(msg) => Some(msg.message_id),
+            WavsPayload::ProposePayout(msg) => Some(msg.message_id),
+            WavsPayload::Vote(msg) => Some(msg.message_id),
+            WavsPayload::CreateGift(msg) => Some(msg.message_id),
+            WavsPayload::ClaimGift(msg) => Some(msg.message_id),
+            WavsPayload::CreateGiveaway(msg) => Some(msg.message_id),
+            WavsPayload::ClaimGiveaway(msg) => Some(msg.message_id),
+            WavsPayload::ReleaseMatured { .. }
+            | WavsPayload::RunSchedules(_)
+            | WavsPayload::Batch(_) => None,
         }
     }
 
@@ -102,7 +740,9 @@ impl WavsPayload {
 
 #[cw_serde]
 pub struct TgHandleResponse {
-    pub handle: Option<String>,
+    pub handle: Option<TgHandle>,
+    /// None if there is no account, or for a legacy account not yet linked to its user id
+    pub user_id: Option<i64>,
 }
 
 #[cw_serde]
@@ -116,4 +756,474 @@ pub struct AdminResponse {
 }
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct OwnerResponse {
+    pub owner: Addr,
+    /// Proposed by the owner, but not accepted yet
+    pub pending_owner: Option<Addr>,
+}
+
+#[cw_serde]
+pub struct PaymentHistoryResponse {
+    pub payments: Vec<PaymentRecord>,
+}
+
+/// One entry in the payment ledger
+#[cw_serde]
+pub struct PaymentRecord {
+    /// Monotonically increasing, use as `start_after` to page through history
+    pub id: u64,
+    pub kind: PaymentKind,
+    /// None when the contract itself released held funds
+    pub from_tg_id: Option<i64>,
+    /// Handle at the time of the payment, if the sender had one
+    pub from_tg_handle: Option<TgHandle>,
+    /// None when the recipient is not registered yet
+    pub to_tg_id: Option<i64>,
+    pub to_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub to_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    pub block_height: u64,
+    pub timestamp: Timestamp,
+}
+
+#[cw_serde]
+pub struct PendingPaymentsResponse {
+    pub payments: Vec<PendingPayment>,
+}
+
+/// A payment held by the contract until the recipient registers or it expires
+#[cw_serde]
+pub struct PendingPayment {
+    /// Same id as the `Pending` entry in the payment ledger
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    pub created_height: u64,
+    pub created_time: Timestamp,
+    pub expires: Expiration,
+}
+
+/// A send held by the contract until its window passes, the sender can cancel it until then
+#[cw_serde]
+pub struct Escrow {
+    /// Use as `start_after` to page through held sends, and to cancel one
+    pub id: u64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub from_address: Addr,
+    /// Resolved when it is released, so the recipient can still register in the meantime
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    /// Held along with `amount` and paid to the treasury on release, zero if there is no fee
+    pub fee: Uint256,
+    /// Where the fee goes, as configured when the send was made
+    pub treasury: Option<Addr>,
+    pub created_height: u64,
+    pub created_time: Timestamp,
+    pub releases: Expiration,
+}
+
+/// A recurring payment, see [`SchedulePaymentMsg`]
+#[cw_serde]
+pub struct Schedule {
+    /// Use as `start_after` to page through schedules, and to cancel one
+    pub id: u64,
+    /// Where it was set up, failed runs are reported there
+    pub chat_id: i64,
+    pub message_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    pub interval_seconds: u64,
+    /// When the next run is due. Moves a period ahead with every run, skipping any that were
+    /// missed rather than paying them twice.
+    pub next_due: Timestamp,
+    pub until: Option<Timestamp>,
+    pub created_time: Timestamp,
+}
+
+#[cw_serde]
+pub struct ScheduleResponse {
+    pub schedule: Option<Schedule>,
+}
+
+#[cw_serde]
+pub struct SchedulesResponse {
+    pub schedules: Vec<Schedule>,
+}
+
+/// A group chat's shared funds, paid out by vote of its members
+#[cw_serde]
+pub struct Treasury {
+    pub chat_id: i64,
+    /// Handles are as they were when the treasury was set up
+    pub members: Vec<TreasuryMember>,
+    /// How many members have to approve a payout
+    pub threshold: u32,
+    pub created_time: Timestamp,
+}
+
+impl Treasury {
+    pub fn is_member(&self, tg_id: i64) -> bool {
+        self.members.iter().any(|member| member.tg_id == tg_id)
+    }
+}
+
+#[cw_serde]
+pub struct TreasuryMember {
+    pub tg_id: i64,
+    pub tg_handle: Option<TgHandle>,
+}
+
+/// A payout out of a treasury, waiting on its members' votes
+#[cw_serde]
+pub struct Proposal {
+    /// Use as `start_after` to page through proposals, and to vote on one
+    pub id: u64,
+    /// The group chat of the treasury
+    pub chat_id: i64,
+    pub proposer_tg_id: i64,
+    pub proposer_tg_handle: Option<TgHandle>,
+    /// Resolved when it is paid out
+    pub to_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    pub memo: Option<String>,
+    /// Telegram user ids of the members who approved, starting with the proposer
+    pub approvals: Vec<i64>,
+    pub rejections: Vec<i64>,
+    pub created_time: Timestamp,
+}
+
+#[cw_serde]
+pub struct TreasuryResponse {
+    pub treasury: Option<Treasury>,
+    /// What it holds, one coin per denom
+    pub balances: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct ProposalResponse {
+    pub proposal: Option<Proposal>,
+}
+
+#[cw_serde]
+pub struct ProposalsResponse {
+    pub proposals: Vec<Proposal>,
+}
+
+/// Funds locked under the hash of a secret, until someone claims them with it or they expire
+#[cw_serde]
+pub struct Gift {
+    pub id: u64,
+    pub hash: HexBinary,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    /// Where it goes back to if it isn't claimed
+    pub from_address: Addr,
+    pub amount: Uint256,
+    pub denom: String,
+    pub created_time: Timestamp,
+    /// It can be claimed until then, and refunded after
+    pub expires: Timestamp,
+    /// Telegram user id of whoever opened the link before registering to receive. Only they can
+    /// claim it from then on, once they have registered.
+    pub claimer: Option<i64>,
+}
+
+impl Gift {
+    /// What a gift's secret is locked under, the SHA-256 of it
+    pub fn hash_secret(secret: &str) -> HexBinary {
+        Sha256::digest(secret.as_bytes()).to_vec().into()
+    }
+}
+
+#[cw_serde]
+pub struct GiftResponse {
+    pub gift: Option<Gift>,
+}
+
+/// A pot in a group chat, shared out a claim at a time until it or its claims run out
+#[cw_serde]
+pub struct Giveaway {
+    pub id: u64,
+    /// The group chat it can be claimed from
+    pub chat_id: i64,
+    pub from_tg_id: i64,
+    pub from_tg_handle: Option<TgHandle>,
+    /// Where what is left goes back to once it expires
+    pub from_address: Addr,
+    /// The whole pot, as it was put up
+    pub amount: Uint256,
+    /// What hasn't been claimed yet
+    pub remaining: Uint256,
+    pub denom: String,
+    /// How many members can claim a share
+    pub count: u32,
+    pub split: GiveawaySplit,
+    /// Telegram user ids of the members who claimed, in order
+    pub claimers: Vec<i64>,
+    pub created_time: Timestamp,
+    /// It can be claimed until then, and what is left refunded after
+    pub expires: Timestamp,
+}
+
+#[cw_serde]
+pub struct GiveawayResponse {
+    pub giveaway: Option<Giveaway>,
+}
+
+#[cw_serde]
+pub struct EscrowWindowResponse {
+    pub window: Option<Duration>,
+}
+
+#[cw_serde]
+pub struct EscrowsResponse {
+    pub escrows: Vec<Escrow>,
+}
+
+#[cw_serde]
+pub struct MaturedEscrowsResponse {
+    pub count: u32,
+}
+
+#[cw_serde]
+pub struct PendingExpiryResponse {
+    pub expiry: Option<Duration>,
+}
+
+#[cw_serde]
+pub enum PaymentKind {
+    /// Sent straight to the recipient's registered address
+    Direct,
+    /// Sent to the contract and held until the recipient registers
+    Pending,
+    /// Held funds paid out when the recipient registered
+    Released,
+    /// Held funds returned to the sender after expiring
+    Refunded,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct MigrateMsg {
+    /// Links accounts registered before user ids were tracked (keyed only by handle) to their user id.
+    /// Any legacy account not listed here is linked the next time its handle is seen with an id.
+    #[serde(default)]
+    pub legacy_accounts: Vec<LegacyAccountLink>,
+    /// Required when migrating a contract deployed before ownership was tracked, ignored otherwise
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Sets how long pending payments are held before they can be refunded, left as it is if None.
+    /// Payments already held keep the expiry they were made with.
+    #[serde(default)]
+    pub pending_expiry: Option<Duration>,
+}
+
+#[cw_serde]
+pub struct LegacyAccountLink {
+    pub tg_handle: TgHandle,
+    pub tg_user_id: i64,
+}
+
+#[cw_serde]
+pub struct InvoiceResponse {
+    pub invoice: Option<Invoice>,
+}
+
+#[cw_serde]
+pub struct InvoicesResponse {
+    pub invoices: Vec<Invoice>,
+}
+
+#[cw_serde]
+pub struct SpendLimitResponse {
+    pub limit: Option<SpendLimit>,
+    /// Sent within the current window
+    pub spent: Uint256,
+    /// The most the next payment can be, None if there is no cap
+    pub remaining: Option<Uint256>,
+}
+
+/// A payment request that is waiting on the payer
+#[cw_serde]
+pub struct Invoice {
+    /// Use as `start_after` to page through invoices
+    pub id: u64,
+    pub requester_tg_id: i64,
+    pub requester_tg_handle: Option<TgHandle>,
+    /// Where the payment goes, the requester's registered address
+    pub to_address: Addr,
+    pub payer_tg_handle: TgHandle,
+    pub amount: Uint256,
+    pub denom: String,
+    pub memo: Option<String>,
+    pub created_height: u64,
+    pub created_time: Timestamp,
+}
+
+/// A sender's own caps on what can be paid out of their account in one denom, so a compromised
+/// Telegram account can't drain the whole grant at once
+#[cw_serde]
+pub struct SpendLimit {
+    /// Most that can go out in a single payment
+    pub per_payment: Option<Uint256>,
+    /// Most that can go out within any `window_seconds`
+    pub per_window: Option<Uint256>,
+    /// Length of the rolling window, e.g. 86400 for a daily cap
+    pub window_seconds: u64,
+}
+
+/// What the operators charge for a send, paid by the sender on top of the amount
+#[cw_serde]
+pub struct FeeConfig {
+    /// Share of the amount, in hundredths of a percent
+    pub basis_points: u16,
+    /// Charged on every send in these denoms, on top of the share
+    pub flat: Vec<Coin>,
+    /// Where fees are paid to
+    pub treasury: String,
+}
+
+#[cw_serde]
+pub struct FeeResponse {
+    pub fee: Option<FeeConfig>,
+}
+
+#[cw_serde]
+pub struct FeeQuoteResponse {
+    /// What the recipient gets
+    pub amount: Uint256,
+    /// What the treasury gets, zero if there is no fee
+    pub fee: Uint256,
+    /// What leaves the sender's account
+    pub total: Uint256,
+}
+
+#[cw_serde]
+pub struct SendGrantResponse {
+    /// False if there is no grant, or it has expired
+    pub granted: bool,
+    /// None if the grant never expires
+    pub expiration: Option<Timestamp>,
+    /// What can still be sent under the grant, per denom. None if it isn't capped.
+    pub spend_limit: Option<Vec<Coin>>,
+}
+
+#[cw_serde]
+pub struct PausedResponse {
+    pub paused: bool,
+}
+
+#[cw_serde]
+pub struct PullClaimsResponse {
+    pub enabled: bool,
+}
+
+#[cw_serde]
+pub struct PendingClaimResponse {
+    /// The account registered to receive for the handle, None while it is unregistered
+    pub address: Option<String>,
+    /// Held for the handle, `address` can take it with `ClaimPending`
+    pub claimable: Vec<Coin>,
+    /// Held until the handle registers
+    pub unregistered: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct BlockedResponse {
+    pub blocked: bool,
+}
+
+#[cw_serde]
+pub struct AccountsResponse {
+    pub accounts: Vec<Account>,
+}
+
+#[cw_serde]
+pub struct Account {
+    pub tg_user_id: i64,
+    pub tg_handle: Option<TgHandle>,
+    pub address: Addr,
+}
+
+#[cw_serde]
+pub struct FundedAccountsResponse {
+    pub accounts: Vec<FundedAccount>,
+}
+
+#[cw_serde]
+pub struct FundedAccount {
+    pub address: Addr,
+    pub tg_user_id: i64,
+}
+
+#[cw_serde]
+pub struct PendingBalancesResponse {
+    pub balances: Vec<PendingBalance>,
+}
+
+/// Everything held for one handle, one coin per denom
+#[cw_serde]
+pub struct PendingBalance {
+    pub handle: TgHandle,
+    pub amount: Vec<Coin>,
+}
+
+/// How payments reach addresses on another chain
+#[cw_serde]
+pub struct IbcRoute {
+    /// Bech32 prefix of the other chain's addresses, e.g. "osmo"
+    pub prefix: String,
+    /// ICS20 transfer channel on this chain
+    pub channel_id: String,
+    /// Chain id of the other chain, shown to users
+    pub chain_id: String,
+    /// How long a transfer has to arrive before it is refunded
+    pub timeout_seconds: u64,
+}
+
+#[cw_serde]
+pub struct IbcRoutesResponse {
+    pub routes: Vec<IbcRoute>,
+}
+
+/// How a denom is shown to users, e.g. `untrn` as NTRN with 6 decimals
+#[cw_serde]
+pub struct DenomMetadata {
+    /// Native denom, or CW20 contract address, as the contract is paid in
+    pub denom: String,
+    /// Shown in place of the denom, e.g. "NTRN"
+    pub symbol: String,
+    /// Decimals between the base denom and the symbol
+    pub exponent: u32,
+    /// Other names users can type for it, matched case-insensitively like the symbol
+    pub aliases: Vec<String>,
+}
+
+#[cw_serde]
+pub struct DenomMetadataResponse {
+    pub denoms: Vec<DenomMetadata>,
+}
+
+#[cw_serde]
+pub struct StatsResponse {
+    /// Accounts registered to receive, including legacy ones not yet linked
+    pub registered: u64,
+    /// Accounts registered to send, including legacy ones not yet linked
+    pub funded: u64,
+    /// Everything users have sent, direct or held, per denom. Fees are not included.
+    pub volume: Vec<Coin>,
+    /// Currently held for unregistered handles, per denom
+    pub pending: Vec<Coin>,
+}
@@ -1,4 +1,7 @@
-use cosmwasm_std::{Addr, CheckedFromRatioError, DecimalRangeExceeded, OverflowError, StdError};
+use cosmwasm_std::{
+    Addr, CheckedFromRatioError, ConversionOverflowError, DecimalRangeExceeded, OverflowError,
+    StdError, Uint256,
+};
 use cw_utils::PaymentError;
 use thiserror::Error;
 
@@ -19,15 +22,93 @@ pub enum ContractError {
     #[error("{0}")]
     CheckedFromRatioError(#[from] CheckedFromRatioError),
 
+    #[error("{0}")]
+    ConversionOverflow(#[from] ConversionOverflowError),
+
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Payments are paused")]
+    Paused,
+
+    #[error("{0} is blocked")]
+    Blocked(String),
+
+    #[error("No ownership transfer in progress")]
+    NoPendingOwner,
+
+    #[error("Contract has no owner yet, one must be given in the migrate message")]
+    OwnerRequired,
+
+    #[error("Denom already allowed: {denom}")]
+    DenomAlreadyAllowed { denom: String },
+
     #[error("Trying to send 0 tokens")]
     ZeroSend,
 
+    #[error("A split needs between 1 and {max} recipients")]
+    SplitRecipients { max: usize },
+
+    #[error("{0} is in the split more than once")]
+    DuplicateSplitRecipient(String),
+
+    #[error("Split weights must be greater than 0")]
+    ZeroSplitWeight,
+
+    #[error("Telegram user {0} must register to receive before requesting payments")]
+    RequesterNotRegistered(i64),
+
+    #[error("Can't request a payment from yourself")]
+    SelfInvoice,
+
+    #[error("Recipient hasn't registered and has no username to hold the payment under")]
+    RecipientNotRegistered,
+
+    #[error("Can't tip yourself")]
+    SelfTip,
+
+    #[error("Spend limit reached, at most {remaining} {denom} can be sent right now")]
+    SpendLimitExceeded { remaining: Uint256, denom: String },
+
+    #[error("A spend limit needs a cap, and a window of 1 to {max_window_seconds} seconds")]
+    InvalidSpendLimit { max_window_seconds: u64 },
+
+    #[error("A fee can be at most {max_basis_points} basis points, with one non-zero flat amount per denom")]
+    InvalidFee { max_basis_points: u16 },
+
+    #[error("No IBC route to addresses starting with {0}")]
+    NoIbcRoute(String),
+
+    #[error("An IBC route needs a prefix, a channel, a chain id and a timeout of 1 to {max_timeout_seconds} seconds")]
+    InvalidIbcRoute { max_timeout_seconds: u64 },
+
+    #[error("Denom metadata needs a denom and a symbol and aliases without spaces or commas, and at most {max_exponent} decimals")]
+    InvalidDenomMetadata { max_exponent: u32 },
+
+    #[error("{0} already names another denom")]
+    DenomNameTaken(String),
+
+    #[error("CW20 tokens can't be sent to another chain")]
+    CrossChainCw20,
+
+    #[error("Recipients on another chain can only be paid with a send or a tip")]
+    CrossChainUnsupported,
+
+    #[error("Address {0} is not registered to send")]
+    AddrNotRegistered(Addr),
+
+    #[error("Memo is longer than {max} characters")]
+    MemoTooLong { max: usize },
+
+    #[error("Invoice {0} not found, it may already be paid or declined")]
+    InvoiceNotFound(u64),
+
     #[error("TG Handle {0} is already registered")]
     TgAlreadyRegistered(String),
 
+    #[error("Telegram user {0} is already registered")]
+    TgUserAlreadyRegistered(i64),
+
     #[error("Address {0} is already registered")]
     AddrAlreadyRegistered(Addr),
 
@@ -37,6 +118,112 @@ pub enum ContractError {
     #[error("Unknown reply id: {id}")]
     UnknownReplyId { id: u64 },
 
+    #[error("Nothing is held for {0}")]
+    NothingToClaim(String),
+
+    #[error("No held payment to cancel")]
+    NothingToCancel,
+
+    #[error("Payment {0} can't be cancelled anymore, its window has passed")]
+    EscrowMatured(u64),
+
+    #[error("The escrow window must be more than zero")]
+    InvalidEscrowWindow,
+
+    #[error("The pending payment expiry must be more than zero")]
+    InvalidPendingExpiry,
+
+    #[error("Payments can repeat at most every {min_seconds} seconds")]
+    ScheduleTooFrequent { min_seconds: u64 },
+
+    #[error("A recurring payment must run at least once before it ends")]
+    ScheduleEndsTooSoon,
+
+    #[error("At most {max} recurring payments per sender")]
+    TooManySchedules { max: usize },
+
+    #[error("Recurring payment {0} not found")]
+    ScheduleNotFound(u64),
+
+    #[error("At most {max} recurring payments can be run at once")]
+    TooManyScheduleRuns { max: usize },
+
+    #[error("Treasuries can only be set up in group chats")]
+    TreasuryNotGroupChat,
+
+    #[error("A treasury needs 1 to {max_members} members, and a threshold of 1 up to the number of members")]
+    InvalidTreasury { max_members: usize },
+
+    #[error("This group already has a treasury")]
+    TreasuryExists,
+
+    #[error("This group has no treasury")]
+    TreasuryNotFound,
+
+    #[error("{0} has to register before they can be a treasury member")]
+    MemberNotRegistered(String),
+
+    #[error("Only members of the treasury can do that")]
+    NotTreasuryMember,
+
+    #[error("{0} has to register to receive before a treasury can pay them")]
+    PayeeNotRegistered(String),
+
+    #[error("At most {max} open proposals per treasury")]
+    TooManyProposals { max: usize },
+
+    #[error("Proposal {0} not found, it may already be decided")]
+    ProposalNotFound(u64),
+
+    #[error("Already voted on proposal {0}")]
+    AlreadyVoted(u64),
+
+    #[error("The treasury only holds {available} {denom}")]
+    InsufficientTreasury { available: Uint256, denom: String },
+
+    #[error(
+        "A gift is locked under a 32 byte hash, and can be claimed for up to {max_seconds} seconds"
+    )]
+    InvalidGift { max_seconds: u64 },
+
+    #[error("There is already a gift locked under this hash")]
+    GiftExists,
+
+    #[error("No gift for this link, it may already be claimed or refunded")]
+    GiftNotFound,
+
+    #[error("Gift {0} has expired")]
+    GiftExpired(u64),
+
+    #[error("Giveaways can only be put up in group chats")]
+    GiveawayNotGroupChat,
+
+    #[error(
+        "A giveaway is shared between 1 to {max_count} members, with at least 1 base unit each"
+    )]
+    InvalidGiveaway { max_count: u32 },
+
+    #[error("Giveaway {0} not found, it may already be over")]
+    GiveawayNotFound(u64),
+
+    #[error("Giveaway {0} has expired")]
+    GiveawayExpired(u64),
+
+    #[error("Already claimed from giveaway {0}")]
+    AlreadyClaimed(u64),
+
+    #[error("Register to receive before claiming from a giveaway")]
+    ClaimerNotRegistered,
+
+    #[error("A batch needs 1 to {max} entries")]
+    InvalidBatch { max: usize },
+
+    #[error("Only a command from a single Telegram message can be processed here")]
+    UnexpectedPayload,
+
+    #[error("Telegram message {message_id} in chat {chat_id} was already processed")]
+    MessageAlreadyProcessed { chat_id: i64, message_id: i64 },
+
     #[error("ABI decode: {0}")]
     AbiDecode(String),
 }
@@ -1,11 +1,55 @@
 use crate::state::{
-    ADMIN, ALLOWED_DENOMS, FUNDED_ACCOUNTS, OPEN_ACCOUNTS, PENDING_PAYMENTS, SERVICE_MANAGER,
+    claim_legacy_account, expired_gifts, expired_giveaways, expired_pending_entries, fee_for,
+    load_auth, matured_escrows, next_escrow_id, next_gift_id, next_giveaway_id, next_invoice_id,
+    next_payment_id, next_proposal_id, next_schedule_id, prune_processed_messages, remaining_spend,
+    remove_escrow, remove_gift, remove_giveaway, remove_invoice, remove_payment,
+    remove_pending_entry, remove_proposal, remove_schedule, save_auth, save_escrow, save_gift,
+    save_giveaway, save_invoice, save_payment, save_pending_entry, save_processed_message,
+    save_proposal, save_schedule, set_tg_handle, spends_in_window, take_pending_entry,
+    update_account_counts, HeldFunds, HeldPayout, IbcTransfer, Spend, ADMIN, ALLOWED_DENOMS,
+    BLOCKED_ADDRS, BLOCKED_HANDLES, DENOM_METADATA, ESCROWS, ESCROWS_BY_SENDER, ESCROW_WINDOW, FEE,
+    FUNDED_ACCOUNTS, GIFTS, GIFTS_BY_HASH, GIVEAWAYS, IBC_ROUTES, IBC_TRANSFERS, INVOICES,
+    LEGACY_FUNDED_ACCOUNTS, LEGACY_OPEN_ACCOUNTS, OPEN_ACCOUNTS, OWNER, PAUSED, PAYMENTS,
+    PENDING_BY_TG, PENDING_ENTRIES, PENDING_EXPIRY, PENDING_OWNER, PENDING_PAYMENTS,
+    PROCESSED_MESSAGES, PROPOSALS, PROPOSALS_BY_CHAT, PULL_CLAIMS, SCHEDULES, SCHEDULES_BY_SENDER,
+    SERVICE_MANAGER, SPENDS, SPEND_LIMITS, TG_HANDLES, TG_USER_IDS, TREASURIES, TREASURY_BALANCES,
 };
-use cosmwasm_std::{ensure, AnyMsg, BankMsg, Coin, DepsMut, Env, MessageInfo, Response, Uint256};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    ensure, from_json, to_json_binary, to_json_string, Addr, AnyMsg, Api, BankMsg, Binary, Coin,
+    CosmosMsg, DepsMut, Env, Event, IbcBasicResponse, IbcCallbackRequest, IbcMsg,
+    IbcSourceCallbackMsg, IbcSrcCallback, MessageInfo, Order, Reply, Response, StdError, StdResult,
+    Storage, SubMsg, SubMsgResponse, SubMsgResult, Timestamp, Uint128, Uint256, WasmMsg,
+};
+use cw_utils::{Duration, Expiration};
 use layer_climb_proto::Any;
 use layer_climb_proto::{authz::MsgExec, bank::MsgSend, Coin as ProtoCoin, Message, Name};
-use tg_contract_api::payments::event::{ConnectEvent, RegistrationEvent, SendPaymentEvent};
-use tg_contract_api::payments::msg::{RegisterReceiveMsg, SendPaymentMsg, WavsPayload};
+use sha2::{Digest, Sha256};
+use tg_contract_api::cw20::Cw20ExecuteMsg;
+use tg_contract_api::payments::event::{
+    AuthUpdatedEvent, BatchEntryEvent, BlocklistUpdatedEvent, ConnectEvent,
+    DenomMetadataUpdatedEvent, DenomsUpdatedEvent, EscrowWindowUpdatedEvent, FeeUpdatedEvent,
+    GiftClaimedEvent, GiftCreatedEvent, GiftRefundedEvent, GiftReservedEvent, GiveawayClaimedEvent,
+    GiveawayCreatedEvent, GiveawayRefundedEvent, IbcRouteUpdatedEvent, InvoiceCreatedEvent,
+    InvoiceDeclinedEvent, InvoicePaidEvent, OwnershipTransferProposedEvent,
+    OwnershipTransferredEvent, PauseUpdatedEvent, PaymentCancelledEvent, PaymentFailedEvent,
+    PaymentHeldEvent, PaymentReleasedEvent, PayoutReturnedEvent, PendingClaimedEvent,
+    PendingExpiryUpdatedEvent, PendingPaymentCreatedEvent, PendingPaymentRefundedEvent,
+    ProposalCreatedEvent, ProposalExecutedEvent, ProposalRejectedEvent, ProposalVotedEvent,
+    PullClaimsUpdatedEvent, RegistrationEvent, ScheduleCreatedEvent, ScheduleEndedEvent,
+    ScheduleRunFailedEvent, SendPaymentEvent, SpendLimitUpdatedEvent, SplitLeg, SplitPaymentEvent,
+    SplitPaymentLegEvent, TreasuryCreatedEvent, TreasuryFundedEvent,
+};
+use tg_contract_api::payments::handle::TgHandle;
+use tg_contract_api::payments::msg::{
+    Auth, CancelPaymentMsg, ClaimGiftMsg, ClaimGiveawayMsg, CreateGiftMsg, CreateGiveawayMsg,
+    CreateTreasuryMsg, CustomExecuteMsg, DenomMetadata, Escrow, ExecuteMsg, FeeConfig,
+    FundTreasuryMsg, Gift, Giveaway, GiveawaySplit, IbcRoute, Invoice, InvoiceActionMsg,
+    PaymentKind, PaymentRecord, PendingPayment, Proposal, ProposePayoutMsg, RegisterReceiveMsg,
+    RequestPaymentMsg, Schedule, SchedulePaymentMsg, ScheduleRun, SendPaymentMsg, SpendLimit,
+    SplitPaymentMsg, SplitRecipient, TipMsg, Treasury, TreasuryMember, UnscheduleMsg, VoteMsg,
+    WavsPayload, MAX_BATCH_SIZE,
+};
 use wavs_types::contracts::cosmwasm::service_manager::ServiceManagerQueryMessages;
 use wavs_types::contracts::cosmwasm::{
     service_handler::{WavsEnvelope, WavsSignatureData},
@@ -18,40 +62,105 @@ pub fn register_send(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    tg_handle: String,
+    tg_handle: TgHandle,
 ) -> Result<Response, ContractError> {
     // Don't overwrite anything already registered
     let chain_addr = info.sender;
-    if FUNDED_ACCOUNTS.has(deps.storage, &chain_addr) {
+    if FUNDED_ACCOUNTS.has(deps.storage, &chain_addr)
+        || LEGACY_FUNDED_ACCOUNTS.has(deps.storage, &chain_addr)
+    {
         return Err(ContractError::AddrAlreadyRegistered(chain_addr));
     }
 
     // Ensure this address matches the previous receive registration
-    let registered_receive = OPEN_ACCOUNTS.load(deps.storage, &tg_handle)?;
+    let tg_user_id = TG_USER_IDS.may_load(deps.storage, tg_handle.as_str())?;
+    let registered_receive = match tg_user_id {
+        Some(tg_user_id) => OPEN_ACCOUNTS.load(deps.storage, tg_user_id)?,
+        None => LEGACY_OPEN_ACCOUNTS.load(deps.storage, tg_handle.as_str())?,
+    };
     ensure!(
         registered_receive == chain_addr,
         ContractError::Unauthorized
     ); // TODO: better error message
 
-    FUNDED_ACCOUNTS.save(deps.storage, &chain_addr, &tg_handle)?;
+    match tg_user_id {
+        Some(tg_user_id) => FUNDED_ACCOUNTS.save(deps.storage, &chain_addr, &tg_user_id)?,
+        // Linked to the user id along with the receive side, next time they show up
+        None => LEGACY_FUNDED_ACCOUNTS.save(deps.storage, &chain_addr, &tg_handle.to_string())?,
+    }
+    update_account_counts(deps.storage, |counts| counts.funded += 1)?;
 
     Ok(Response::new().add_event(ConnectEvent {
-        tg_handle: tg_handle.clone(),
-        address: chain_addr.clone(),
+        tg_user_id,
+        tg_handle: Some(tg_handle),
+        address: chain_addr,
     }))
 }
 
-pub fn register_receive(
+/// Keeps the spends we have to track, and so the gas of every capped payment, bounded
+pub const MAX_SPEND_WINDOW_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+pub fn set_spend_limit(
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
+    denom: String,
+    limit: Option<SpendLimit>,
+) -> Result<Response, ContractError> {
+    // Only the account itself, so a compromised Telegram account can't lift its own cap
+    let addr = info.sender;
+    ensure!(
+        FUNDED_ACCOUNTS.has(deps.storage, &addr) || LEGACY_FUNDED_ACCOUNTS.has(deps.storage, &addr),
+        ContractError::AddrNotRegistered(addr)
+    );
+
+    let event = match limit {
+        Some(limit) => {
+            ensure!(
+                (limit.per_payment.is_some() || limit.per_window.is_some())
+                    && (1..=MAX_SPEND_WINDOW_SECONDS).contains(&limit.window_seconds),
+                ContractError::InvalidSpendLimit {
+                    max_window_seconds: MAX_SPEND_WINDOW_SECONDS
+                }
+            );
+            SPEND_LIMITS.save(deps.storage, (&addr, &denom), &limit)?;
+            // Spends already in the window keep counting, so changing the cap can't reset it
+            SpendLimitUpdatedEvent {
+                address: addr,
+                denom,
+                per_payment: limit.per_payment,
+                per_window: limit.per_window,
+                window_seconds: Some(limit.window_seconds),
+            }
+        }
+        None => {
+            SPEND_LIMITS.remove(deps.storage, (&addr, &denom));
+            SPENDS.remove(deps.storage, (&addr, &denom));
+            SpendLimitUpdatedEvent {
+                address: addr,
+                denom,
+                per_payment: None,
+                per_window: None,
+                window_seconds: None,
+            }
+        }
+    };
+
+    Ok(Response::new().add_event(event))
+}
+
+pub fn register_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     msg: RegisterReceiveMsg,
 ) -> Result<Response, ContractError> {
     // TODO: better error messages
     let admin = ADMIN.load(deps.storage)?;
     ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
 
-    _register_receive(deps, msg.tg_handle, msg.chain_addr)
+    _register_receive(deps, env, msg)
 }
 
 pub fn send_payment(
@@ -63,8 +172,230 @@ pub fn send_payment(
     // TODO: better error messages
     let admin = ADMIN.load(deps.storage)?;
     ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _send_payment(deps, _env, msg)
+}
+
+pub fn cancel_payment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CancelPaymentMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+
+    // Not stopped by a pause, it only hands a held send back like the other refunds
+    _cancel_payment(deps, env, msg)
+}
+
+pub fn schedule_payment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: SchedulePaymentMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _schedule_payment(deps, env, msg)
+}
+
+pub fn unschedule(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: UnscheduleMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _unschedule(deps, msg)
+}
+
+pub fn run_schedules(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    runs: Vec<ScheduleRun>,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _run_schedules(deps, env, runs)
+}
+
+pub fn create_treasury(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreateTreasuryMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _create_treasury(deps, env, msg)
+}
+
+pub fn fund_treasury(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: FundTreasuryMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _fund_treasury(deps, env, msg)
+}
+
+pub fn propose_payout(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ProposePayoutMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _propose_payout(deps, env, msg)
+}
+
+pub fn vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: VoteMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _vote(deps, env, msg)
+}
+
+pub fn create_gift(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreateGiftMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _create_gift(deps, env, msg)
+}
+
+pub fn claim_gift(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ClaimGiftMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _claim_gift(deps, env, msg)
+}
+
+pub fn create_giveaway(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreateGiveawayMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _create_giveaway(deps, env, msg)
+}
+
+pub fn claim_giveaway(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ClaimGiveawayMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _claim_giveaway(deps, env, msg)
+}
+
+pub fn split_payment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: SplitPaymentMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _split_payment(deps, env, msg)
+}
+
+pub fn tip(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: TipMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _tip(deps, env, msg)
+}
+
+pub fn request_payment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: RequestPaymentMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _request_payment(deps, env, msg)
+}
+
+pub fn pay_invoice(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InvoiceActionMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
+
+    _pay_invoice(deps, env, msg)
+}
+
+pub fn decline_invoice(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InvoiceActionMsg,
+) -> Result<Response, ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    ensure!(info.sender == admin, ContractError::Unauthorized);
+    ensure_not_paused(deps.storage)?;
 
-    _send_payment(deps, _env, msg.from_tg, msg.to_tg, msg.amount, msg.denom)
+    _decline_invoice(deps, env, msg)
 }
 
 pub fn wavs_handle_envelope(
@@ -88,118 +419,3648 @@ pub fn wavs_handle_envelope(
         .decode()
         .map_err(|e| ContractError::AbiDecode(e.to_string()))?;
 
-    let payload = WavsPayload::decode(envelope.payload)?;
+    // Pausing is checked per command, each entry of a batch on its own, since `/undo` still goes
+    // through while paused
+    match WavsPayload::decode(envelope.payload)? {
+        WavsPayload::Batch(entries) => process_batch(&_env, entries),
+        WavsPayload::ReleaseMatured { limit } => release_matured(deps, _env, limit),
+        WavsPayload::RunSchedules(runs) => {
+            ensure_not_paused(deps.storage)?;
+            _run_schedules(deps, _env, runs)
+        }
+        payload => process_payload(deps, _env, payload),
+    }
+}
+
+/// The reply on one entry of a batch
+pub const BATCH_ENTRY_REPLY_ID: u64 = 3;
 
-    match payload {
-        WavsPayload::Register(msg) => _register_receive(deps, msg.tg_handle, msg.chain_addr),
-        WavsPayload::SendPayment(msg) => {
-            _send_payment(deps, _env, msg.from_tg, msg.to_tg, msg.amount, msg.denom)
+/// Handed back to [`batch_entry_reply`] along with the outcome of the entry
+#[cw_serde]
+struct BatchEntryReply {
+    index: u32,
+    chat_id: i64,
+    message_id: i64,
+}
+
+/// Each entry runs as a call back into the contract, so a failing one reverts only its own
+/// changes and is reported rather than taking the rest of the batch down with it
+fn process_batch(env: &Env, entries: Vec<WavsPayload>) -> Result<Response, ContractError> {
+    ensure!(
+        !entries.is_empty() && entries.len() <= MAX_BATCH_SIZE,
+        ContractError::InvalidBatch {
+            max: MAX_BATCH_SIZE
         }
+    );
+
+    let mut resp = Response::new();
+    for (index, payload) in entries.into_iter().enumerate() {
+        // A nested batch, a release or a run has no message of its own
+        let (Some(chat_id), Some(message_id)) = (payload.chat_id(), payload.message_id()) else {
+            return Err(ContractError::UnexpectedPayload);
+        };
+        let entry = WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_json_binary(&ExecuteMsg::Custom(CustomExecuteMsg::ProcessBatchEntry {
+                payload,
+            }))?,
+            funds: vec![],
+        };
+        let reply = to_json_binary(&BatchEntryReply {
+            index: index as u32,
+            chat_id,
+            message_id,
+        })?;
+        resp = resp
+            .add_submessage(SubMsg::reply_always(entry, BATCH_ENTRY_REPLY_ID).with_payload(reply));
+    }
+
+    Ok(resp)
+}
+
+pub fn process_batch_entry(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payload: WavsPayload,
+) -> Result<Response, ContractError> {
+    ensure!(
+        info.sender == env.contract.address,
+        ContractError::Unauthorized
+    );
+
+    process_payload(deps, env, payload)
+}
+
+pub fn batch_entry_reply(msg: Reply) -> Result<Response, ContractError> {
+    let BatchEntryReply {
+        index,
+        chat_id,
+        message_id,
+    } = from_json(&msg.payload)?;
+
+    Ok(Response::new().add_event(BatchEntryEvent {
+        index,
+        chat_id,
+        message_id,
+        error: msg.result.into_result().err(),
+    }))
+}
+
+/// A single command from Telegram, marked processed so it can't be replayed
+fn process_payload(
+    deps: DepsMut,
+    env: Env,
+    payload: WavsPayload,
+) -> Result<Response, ContractError> {
+    let (Some(chat_id), Some(message_id)) = (payload.chat_id(), payload.message_id()) else {
+        // Only a batch, a release or a run has no message of its own
+        return Err(ContractError::UnexpectedPayload);
+    };
+    // Rejected before it is marked processed, so it can go through once resumed. `/undo` only
+    // hands a held send back, so it keeps working like the other refunds.
+    if !matches!(payload, WavsPayload::CancelPayment(_)) {
+        ensure_not_paused(deps.storage)?;
+    }
+    // Every member votes or claims on the same prompt, and can only do it once there anyway
+    if !matches!(
+        payload,
+        WavsPayload::Vote(_) | WavsPayload::ClaimGiveaway(_)
+    ) {
+        record_message(deps.storage, &env, chat_id, message_id)?;
+    }
+
+    match payload {
+        WavsPayload::Register(msg) => _register_receive(deps, env, msg),
+        WavsPayload::SendPayment(msg) => _send_payment(deps, env, msg),
+        WavsPayload::SplitPayment(msg) => _split_payment(deps, env, msg),
+        WavsPayload::Tip(msg) => _tip(deps, env, msg),
+        WavsPayload::RequestPayment(msg) => _request_payment(deps, env, msg),
+        WavsPayload::PayInvoice(msg) => _pay_invoice(deps, env, msg),
+        WavsPayload::DeclineInvoice(msg) => _decline_invoice(deps, env, msg),
+        WavsPayload::CancelPayment(msg) => _cancel_payment(deps, env, msg),
+        WavsPayload::SchedulePayment(msg) => _schedule_payment(deps, env, msg),
+        WavsPayload::Unschedule(msg) => _unschedule(deps, msg),
+        WavsPayload::CreateTreasury(msg) => _create_treasury(deps, env, msg),
+        WavsPayload::FundTreasury(msg) => _fund_treasury(deps, env, msg),
+        WavsPayload::ProposePayout(msg) => _propose_payout(deps, env, msg),
+        WavsPayload::Vote(msg) => _vote(deps, env, msg),
+        WavsPayload::CreateGift(msg) => _create_gift(deps, env, msg),
+        WavsPayload::ClaimGift(msg) => _claim_gift(deps, env, msg),
+        WavsPayload::CreateGiveaway(msg) => _create_giveaway(deps, env, msg),
+        WavsPayload::ClaimGiveaway(msg) => _claim_giveaway(deps, env, msg),
+        WavsPayload::Batch(_)
+        | WavsPayload::ReleaseMatured { .. }
+        | WavsPayload::RunSchedules(_) => Err(ContractError::UnexpectedPayload),
     }
 }
 
+/// How long processed messages are remembered, a week. Telegram only keeps undelivered updates for
+/// 24 hours, so this leaves plenty of margin for an envelope to be resubmitted late.
+const REPLAY_WINDOW_SECONDS: u64 = 7 * 24 * 60 * 60;
+/// Bounds the extra gas each envelope spends on pruning
+const MAX_PRUNE_PER_MESSAGE: usize = 10;
+
+/// Rejects Telegram messages we have already acted on, so a resubmitted envelope can't pay twice
+fn record_message(
+    storage: &mut dyn Storage,
+    env: &Env,
+    chat_id: i64,
+    message_id: i64,
+) -> Result<(), ContractError> {
+    prune_processed_messages(
+        storage,
+        env.block.time.minus_seconds(REPLAY_WINDOW_SECONDS),
+        MAX_PRUNE_PER_MESSAGE,
+    )?;
+
+    if PROCESSED_MESSAGES.has(storage, (chat_id, message_id)) {
+        return Err(ContractError::MessageAlreadyProcessed {
+            chat_id,
+            message_id,
+        });
+    }
+    save_processed_message(storage, chat_id, message_id, env.block.time)?;
+
+    Ok(())
+}
+
 pub fn _register_receive(
     deps: DepsMut,
-    tg_handle: String,
-    chain_addr: String,
+    env: Env,
+    msg: RegisterReceiveMsg,
 ) -> Result<Response, ContractError> {
+    let RegisterReceiveMsg {
+        tg_user_id,
+        tg_handle,
+        chain_addr,
+        ..
+    } = msg;
+
     // Don't overwrite anything already registered
-    let chain_addr = deps.api.addr_validate(&chain_addr)?;
-    if OPEN_ACCOUNTS.has(deps.storage, &tg_handle) {
-        return Err(ContractError::TgAlreadyRegistered(tg_handle));
+    let chain_addr = receive_addr(deps.storage, deps.api, &chain_addr)?;
+    ensure_not_blocked(deps.storage, tg_handle.as_ref(), Some(&chain_addr))?;
+    if OPEN_ACCOUNTS.has(deps.storage, tg_user_id) {
+        return Err(ContractError::TgUserAlreadyRegistered(tg_user_id));
+    }
+    if let Some(tg_handle) = &tg_handle {
+        if LEGACY_OPEN_ACCOUNTS.has(deps.storage, tg_handle.as_str()) {
+            return Err(ContractError::TgAlreadyRegistered(tg_handle.to_string()));
+        }
     }
-    OPEN_ACCOUNTS.save(deps.storage, &tg_handle, &chain_addr)?;
+    OPEN_ACCOUNTS.save(deps.storage, tg_user_id, &chain_addr)?;
+    update_account_counts(deps.storage, |counts| counts.registered += 1)?;
+    set_tg_handle(deps.storage, tg_user_id, tg_handle.as_ref())?;
 
     let mut resp = Response::new();
 
-    // TODO: check if there are any pending payments for this tg_handle and send them
-    if let Some(pending) = PENDING_PAYMENTS.may_load(deps.storage, &tg_handle)? {
-        PENDING_PAYMENTS.remove(deps.storage, &tg_handle);
-        let msg = BankMsg::Send {
-            to_address: chain_addr.to_string(),
-            amount: pending.balance(),
-        };
-        resp = resp.add_message(msg);
+    if let Some(tg_handle) = &tg_handle {
+        let msgs = auto_release(
+            deps.storage,
+            deps.api,
+            &env,
+            tg_handle,
+            tg_user_id,
+            &chain_addr,
+        )?;
+        resp = resp.add_submessages(msgs);
     }
 
     Ok(resp.add_event(RegistrationEvent {
+        tg_user_id,
         tg_handle,
         address: chain_addr,
     }))
 }
 
-pub fn _send_payment(
-    deps: DepsMut,
-    env: Env,
-    from_tg: String,
-    to_tg: String,
-    amount: Uint256,
-    denom: String,
-) -> Result<Response, ContractError> {
-    // Check it is an allowed denom
-    let allowed_denoms = ALLOWED_DENOMS.load(deps.storage)?;
-    ensure!(
-        allowed_denoms.contains(&denom),
-        ContractError::TokenNotWhitelisted { token: denom }
-    );
-    // Ensure amount > 0
-    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
-    let amount = Coin { amount, denom };
-
-    // Ensure address this account is sending from
-    // FIXME: better error messages, not NotFound
-    // need to reverse lookup this to ensure it is proper
-    let from_addr = OPEN_ACCOUNTS.load(deps.storage, &from_tg)?;
-    let check_from = FUNDED_ACCOUNTS.load(deps.storage, &from_addr)?;
-    ensure!(check_from == from_tg, ContractError::Unauthorized);
+/// Pays out what is held for a handle its user just registered or took, unless the owner turned
+/// on pull claims, in which case it stays here until [`claim_pending`]. An address on another
+/// chain can't sign a claim, so it is always paid straight away.
+fn auto_release(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    tg_handle: &TgHandle,
+    tg_user_id: i64,
+    to_addr: &Addr,
+) -> Result<Vec<SubMsg>, ContractError> {
+    if PULL_CLAIMS.may_load(storage)?.unwrap_or_default()
+        && ibc_route(storage, api, to_addr)?.is_none()
+    {
+        return Ok(vec![]);
+    }
 
-    // Figure out where to send it to
-    let to_addr = match OPEN_ACCOUNTS.may_load(deps.storage, &to_tg)? {
-        Some(addr) => addr,
-        None => {
-            // Record the pending payment
-            let mut pending = PENDING_PAYMENTS
-                .may_load(deps.storage, &to_tg)?
-                .unwrap_or_default();
-            pending.add_payment(amount.clone());
-            PENDING_PAYMENTS.save(deps.storage, &to_tg, &pending)?;
+    release_pending(storage, api, env, tg_handle, tg_user_id, to_addr)
+}
 
-            // Send to this contract
-            env.contract.address.clone()
-        }
+/// Pays out everything held for `tg_handle` to the user that now owns it
+fn release_pending(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    tg_handle: &TgHandle,
+    tg_user_id: i64,
+    to_addr: &Addr,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let Some(pending) = PENDING_PAYMENTS.may_load(storage, tg_handle.as_str())? else {
+        return Ok(vec![]);
     };
+    PENDING_PAYMENTS.remove(storage, tg_handle.as_str());
 
-    // Custom bank MsgSend from the original sender, not the contract
-    let msg_send = MsgSend {
-        from_address: from_addr.to_string(),
-        to_address: to_addr.to_string(),
-        amount: vec![ProtoCoin {
-            amount: amount.amount.to_string(),
-            denom: amount.denom.clone(),
-        }],
-    };
-    // Wrapped in an authz MsgExec so it uses our allowances to send
-    let msg_exec = MsgExec {
-        grantee: env.contract.address.to_string(),
-        msgs: vec![Any {
-            type_url: MsgSend::type_url(),
-            value: msg_send.encode_to_vec().into(),
-        }],
-    };
-    // Converted into opaque protobut AnyMsg for wasmd to handle
-    let any_msg = AnyMsg {
-        type_url: MsgExec::type_url(),
-        value: msg_exec.encode_to_vec().into(),
-    };
+    // Everything is paid out at once, so the individual entries can no longer be refunded
+    let entry_ids = PENDING_BY_TG
+        .prefix(tg_handle.as_str())
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut entries = vec![];
+    for id in entry_ids {
+        let entry = PENDING_ENTRIES.load(storage, id)?;
+        remove_pending_entry(storage, &entry);
+        entries.push(entry);
+    }
+
+    let amount = pending.balance();
+    let mut payouts = vec![];
+    for coin in &amount {
+        let record = PaymentRecord {
+            id: next_payment_id(storage)?,
+            kind: PaymentKind::Released,
+            from_tg_id: None,
+            from_tg_handle: None,
+            to_tg_id: Some(tg_user_id),
+            to_tg_handle: Some(tg_handle.clone()),
+            from_address: env.contract.address.clone(),
+            to_address: to_addr.clone(),
+            amount: coin.amount,
+            denom: coin.denom.clone(),
+            block_height: env.block.height,
+            timestamp: env.block.time,
+        };
+        save_payment(storage, &record)?;
+        payouts.push(HeldPayout {
+            payment_id: record.id,
+            to_tg_id: Some(tg_user_id),
+            to_tg_handle: Some(tg_handle.clone()),
+            to_address: to_addr.clone(),
+            amount: coin.clone(),
+            returns_to: HeldFunds::Pending {
+                tg_handle: tg_handle.clone(),
+                entries: entries
+                    .iter()
+                    .filter(|entry| entry.denom == coin.denom)
+                    .cloned()
+                    .collect(),
+            },
+        });
+    }
+
+    match ibc_route(storage, api, to_addr)? {
+        Some(route) => send_held_funds_ibc(api, env, &route, payouts),
+        None => Ok(send_held_funds(api, to_addr, amount)?
+            .into_iter()
+            .map(SubMsg::new)
+            .collect()),
+    }
+}
+
+/// Validates a receive address, which can be on this chain or on one we have an IBC route to
+fn receive_addr(storage: &dyn Storage, api: &dyn Api, addr: &str) -> Result<Addr, ContractError> {
+    let local_err = match api.addr_validate(addr) {
+        Ok(addr) => return Ok(addr),
+        Err(err) => err,
+    };
+    let Ok((hrp, _)) = bech32::decode(addr) else {
+        return Err(local_err.into());
+    };
+    let prefix = hrp.to_lowercase();
+    ensure!(
+        IBC_ROUTES.has(storage, &prefix),
+        ContractError::NoIbcRoute(prefix)
+    );
+
+    Ok(Addr::unchecked(addr.to_ascii_lowercase()))
+}
+
+/// The route to the chain a receive address is on, or None if it is on this one
+fn ibc_route(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    addr: &Addr,
+) -> Result<Option<IbcRoute>, ContractError> {
+    if api.addr_validate(addr.as_str()).is_ok() {
+        return Ok(None);
+    }
+    // Checked when it was registered, but the route may have been removed since
+    let prefix = bech32::decode(addr.as_str())
+        .map(|(hrp, _)| hrp.to_lowercase())
+        .map_err(|_| ContractError::NoIbcRoute(addr.to_string()))?;
+    match IBC_ROUTES.may_load(storage, &prefix)? {
+        Some(route) => Ok(Some(route)),
+        None => Err(ContractError::NoIbcRoute(prefix)),
+    }
+}
+
+/// A denom that is a valid address is the CW20 contract for that token, anything else is native
+fn cw20_contract(api: &dyn Api, denom: &str) -> Option<Addr> {
+    api.addr_validate(denom).ok()
+}
+
+/// Pays out funds the contract is holding, native coins in one bank send and a transfer per CW20
+fn send_held_funds(
+    api: &dyn Api,
+    to_addr: &Addr,
+    funds: Vec<Coin>,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut msgs = vec![];
+    let mut native = vec![];
+
+    for coin in funds {
+        match cw20_contract(api, &coin.denom) {
+            Some(token) => msgs.push(
+                WasmMsg::Execute {
+                    contract_addr: token.into(),
+                    msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: to_addr.to_string(),
+                        amount: Uint128::try_from(coin.amount)?,
+                    })?,
+                    funds: vec![],
+                }
+                .into(),
+            ),
+            None => native.push(coin),
+        }
+    }
+
+    if !native.is_empty() {
+        msgs.insert(
+            0,
+            BankMsg::Send {
+                to_address: to_addr.to_string(),
+                amount: native,
+            }
+            .into(),
+        );
+    }
+
+    Ok(msgs)
+}
+
+/// Pays out held funds to another chain, a transfer per coin. Each is tracked like a send, so if
+/// it fails and ICS20 returns it to the contract, [`ibc_source_callback`] puts it back where it
+/// was held.
+fn send_held_funds_ibc(
+    api: &dyn Api,
+    env: &Env,
+    route: &IbcRoute,
+    payouts: Vec<HeldPayout>,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let callback = IbcCallbackRequest::source(IbcSrcCallback {
+        address: env.contract.address.clone(),
+        gas_limit: None,
+    });
+    payouts
+        .into_iter()
+        .map(|payout| {
+            ensure!(
+                cw20_contract(api, &payout.amount.denom).is_none(),
+                ContractError::CrossChainCw20
+            );
+            let transfer = IbcMsg::Transfer {
+                channel_id: route.channel_id.clone(),
+                to_address: payout.to_address.to_string(),
+                amount: payout.amount.clone(),
+                timeout: env.block.time.plus_seconds(route.timeout_seconds).into(),
+                memo: Some(to_json_string(&callback)?),
+            };
+            let reply = HeldTransferReply {
+                channel_id: route.channel_id.clone(),
+                payout,
+            };
+            // Only on success, a transfer that can't be submitted reverts the payout with it
+            Ok(
+                SubMsg::reply_on_success(transfer, HELD_IBC_TRANSFER_REPLY_ID)
+                    .with_payload(to_json_binary(&reply)?),
+            )
+        })
+        .collect()
+}
+
+/// Pays one held coin out to `payout.to_address`, over `route` if it is on another chain
+fn pay_out_held(
+    api: &dyn Api,
+    env: &Env,
+    route: Option<&IbcRoute>,
+    payout: HeldPayout,
+) -> Result<Vec<SubMsg>, ContractError> {
+    match route {
+        Some(route) => send_held_funds_ibc(api, env, route, vec![payout]),
+        None => Ok(
+            send_held_funds(api, &payout.to_address, vec![payout.amount])?
+                .into_iter()
+                .map(SubMsg::new)
+                .collect(),
+        ),
+    }
+}
+
+pub fn _send_payment(
+    deps: DepsMut,
+    env: Env,
+    msg: SendPaymentMsg,
+) -> Result<Response, ContractError> {
+    let SendPaymentMsg {
+        chat_id,
+        message_id,
+        from_tg_id,
+        from_tg,
+        to_tg,
+        amount,
+        denom,
+    } = msg;
+
+    check_denom(deps.storage, &denom)?;
+    // Ensure amount > 0
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    let amount = Coin { amount, denom };
+
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    let (fee, fee_transfer) = fee_leg(deps.storage, deps.api, &env, &sender.address, &amount)?;
+    charge_spend_limit(
+        deps.storage,
+        &env,
+        &sender.address,
+        &Coin {
+            amount: amount.amount.checked_add(fee)?,
+            denom: amount.denom.clone(),
+        },
+    )?;
+    if let Some(window) = ESCROW_WINDOW.may_load(deps.storage)? {
+        // Who holds the handle is only looked up on release, but a blocked one is turned away now
+        ensure_not_blocked(deps.storage, Some(&to_tg), None)?;
+        // The fee goes where it was configured to when the send was made
+        let treasury = match FEE.may_load(deps.storage)? {
+            Some(config) if !fee.is_zero() => Some(Addr::unchecked(config.treasury)),
+            _ => None,
+        };
+        let escrow = Escrow {
+            id: next_escrow_id(deps.storage)?,
+            from_tg_id,
+            from_tg_handle: sender.tg_handle,
+            from_address: sender.address,
+            to_tg_handle: to_tg,
+            amount: amount.amount,
+            denom: amount.denom,
+            fee,
+            treasury,
+            created_height: env.block.height,
+            created_time: env.block.time,
+            releases: window.after(&env.block),
+        };
+        let held = hold_payment(deps.storage, deps.api, &env, chat_id, message_id, escrow)?;
+        return Ok(Response::new()
+            .add_submessages(released)
+            .add_submessage(held));
+    }
+    let leg = pay(
+        deps.storage,
+        deps.api,
+        &env,
+        &sender,
+        Recipient::Handle(&to_tg),
+        &amount,
+    )?;
+
+    let sent = SendPaymentEvent {
+        from_tg_id,
+        from_tg_handle: sender.tg_handle,
+        to_tg_id: leg.to_tg_id,
+        to_tg_handle: Some(to_tg),
+        from_address: sender.address,
+        to_address: leg.to_addr.clone(),
+        amount: amount.amount,
+        denom: amount.denom,
+        fee: (!fee.is_zero()).then_some(fee),
+        ibc_channel: leg.ibc.as_ref().map(|route| route.channel_id.clone()),
+        to_chain: leg.ibc.as_ref().map(|route| route.chain_id.clone()),
+    };
 
     Ok(Response::new()
-        .add_message(any_msg)
-        .add_event(SendPaymentEvent {
-            from_tg_handle: from_tg.clone(),
-            to_tg_handle: to_tg.clone(),
-            from_address: from_addr,
-            to_address: to_addr,
+        .add_submessages(released)
+        .add_submessage(guarded_send(chat_id, message_id, leg, sent, fee_transfer)?))
+}
+
+/// The reply on a send being pulled into the contract to sit out the escrow window
+pub const ESCROW_REPLY_ID: u64 = 4;
+
+/// Handed back to [`escrow_reply`] along with the outcome of the transfer
+#[cw_serde]
+struct EscrowReply {
+    chat_id: i64,
+    message_id: i64,
+    escrow_id: u64,
+}
+
+/// Pulls a send and its fee into the contract until [`release_matured`] pays it out
+fn hold_payment(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    chat_id: i64,
+    message_id: i64,
+    escrow: Escrow,
+) -> Result<SubMsg, ContractError> {
+    save_escrow(storage, &escrow)?;
+
+    let transfer = transfer_msg(
+        api,
+        env,
+        &escrow.from_address,
+        &env.contract.address,
+        &escrow_held(&escrow)?,
+    )?;
+    let payload = to_json_binary(&EscrowReply {
+        chat_id,
+        message_id,
+        escrow_id: escrow.id,
+    })?;
+
+    Ok(SubMsg::reply_always(transfer, ESCROW_REPLY_ID).with_payload(payload))
+}
+
+pub fn escrow_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let EscrowReply {
+        chat_id,
+        message_id,
+        escrow_id,
+    } = from_json(&msg.payload)?;
+    let escrow = ESCROWS.load(deps.storage, escrow_id)?;
+
+    match msg.result {
+        SubMsgResult::Ok(_) => Ok(Response::new().add_event(PaymentHeldEvent {
+            id: escrow.id,
+            chat_id,
+            message_id,
+            from_tg_id: escrow.from_tg_id,
+            from_tg_handle: escrow.from_tg_handle,
+            to_tg_handle: escrow.to_tg_handle,
+            from_address: escrow.from_address,
+            amount: escrow.amount,
+            denom: escrow.denom,
+            fee: (!escrow.fee.is_zero()).then_some(escrow.fee),
+            releases: escrow.releases,
+        })),
+        SubMsgResult::Err(reason) => {
+            // Nothing moved, so there is nothing to hold
+            remove_escrow(deps.storage, &escrow);
+            refund_spend_limit(
+                deps.storage,
+                escrow.created_time,
+                &escrow.from_address,
+                &escrow_held(&escrow)?,
+            )?;
+
+            Ok(Response::new().add_event(PaymentFailedEvent {
+                chat_id,
+                message_id,
+                from_tg_id: escrow.from_tg_id,
+                from_tg_handle: escrow.from_tg_handle,
+                to_tg_id: None,
+                to_tg_handle: Some(escrow.to_tg_handle),
+                amount: escrow.amount,
+                denom: escrow.denom,
+                reason,
+            }))
+        }
+    }
+}
+
+/// What the contract holds for an escrow, the amount and its fee
+fn escrow_held(escrow: &Escrow) -> StdResult<Coin> {
+    Ok(Coin {
+        amount: escrow.amount.checked_add(escrow.fee)?,
+        denom: escrow.denom.clone(),
+    })
+}
+
+const DEFAULT_RELEASE_LIMIT: u32 = 10;
+pub const MAX_RELEASE_LIMIT: u32 = 30;
+
+/// Pays out held sends whose window has passed, oldest first. Anyone can call it, and the
+/// commander submits it from its cron trigger whenever some are due. One that can no longer go
+/// where it was sent, e.g. because the recipient was blocked in the meantime, goes back to the
+/// sender instead.
+pub fn release_matured(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    ensure_not_paused(deps.storage)?;
+    let limit = limit
+        .unwrap_or(DEFAULT_RELEASE_LIMIT)
+        .min(MAX_RELEASE_LIMIT) as usize;
+
+    let mut resp = Response::new();
+    for escrow in matured_escrows(deps.storage, &env.block, limit)? {
+        remove_escrow(deps.storage, &escrow);
+        let target = payout_target(
+            deps.storage,
+            deps.api,
+            Recipient::Handle(&escrow.to_tg_handle),
+            &escrow.denom,
+        );
+        let part = match target {
+            Ok(target) => release_escrow(deps.storage, deps.api, &env, escrow, target)?,
+            Err(err) => return_escrow(deps.storage, deps.api, escrow, Some(err.to_string()))?,
+        };
+        resp = resp.add_submessages(part.messages).add_events(part.events);
+    }
+
+    Ok(resp)
+}
+
+/// Where funds the contract pays out of its own go now
+struct PayoutTarget {
+    to_tg_id: Option<i64>,
+    /// None if the recipient hasn't registered
+    to_addr: Option<Addr>,
+    /// Set if `to_addr` is on another chain
+    ibc: Option<IbcRoute>,
+}
+
+/// Looks up who `to` is now and whether `denom` can reach them. Nothing changes for a handle, so
+/// if it fails the funds can still go back where they came from.
+fn payout_target(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    to: Recipient,
+    denom: &str,
+) -> Result<PayoutTarget, ContractError> {
+    let ResolvedRecipient {
+        tg_id: to_tg_id,
+        tg_handle: to_tg,
+        addr: to_addr,
+    } = resolve_recipient(storage, to)?;
+    ensure_not_blocked(storage, to_tg, to_addr.as_ref())?;
+    let ibc = match &to_addr {
+        Some(addr) => ibc_route(storage, api, addr)?,
+        None => None,
+    };
+    if ibc.is_some() {
+        ensure!(
+            cw20_contract(api, denom).is_none(),
+            ContractError::CrossChainCw20
+        );
+    }
+
+    Ok(PayoutTarget {
+        to_tg_id,
+        to_addr,
+        ibc,
+    })
+}
+
+/// Pays a held send out to its recipient, or holds it for their handle if they haven't registered,
+/// and its fee to the treasury
+fn release_escrow(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    escrow: Escrow,
+    PayoutTarget {
+        to_tg_id,
+        to_addr,
+        ibc,
+    }: PayoutTarget,
+) -> Result<Response, ContractError> {
+    let id = next_payment_id(storage)?;
+    let amount = Coin {
+        amount: escrow.amount,
+        denom: escrow.denom.clone(),
+    };
+
+    let mut resp = Response::new();
+    let mut pending_event = None;
+    let (to_addr, kind) = match to_addr {
+        Some(addr) => {
+            let payout = HeldPayout {
+                payment_id: id,
+                to_tg_id,
+                to_tg_handle: Some(escrow.to_tg_handle.clone()),
+                to_address: addr.clone(),
+                amount: amount.clone(),
+                returns_to: HeldFunds::Refund(escrow.from_address.clone()),
+            };
+            resp = resp.add_submessages(pay_out_held(api, env, ibc.as_ref(), payout)?);
+            (addr, PaymentKind::Direct)
+        }
+        None => {
+            let sender = Sender {
+                tg_id: escrow.from_tg_id,
+                tg_handle: escrow.from_tg_handle.clone(),
+                address: escrow.from_address.clone(),
+            };
+            pending_event = Some(hold_for_handle(
+                storage,
+                env,
+                id,
+                &sender,
+                &escrow.to_tg_handle,
+                &amount,
+            )?);
+            (env.contract.address.clone(), PaymentKind::Pending)
+        }
+    };
+
+    let record = PaymentRecord {
+        id,
+        kind,
+        from_tg_id: Some(escrow.from_tg_id),
+        from_tg_handle: escrow.from_tg_handle.clone(),
+        to_tg_id,
+        to_tg_handle: Some(escrow.to_tg_handle.clone()),
+        from_address: escrow.from_address.clone(),
+        to_address: to_addr.clone(),
+        amount: escrow.amount,
+        denom: escrow.denom.clone(),
+        block_height: env.block.height,
+        timestamp: env.block.time,
+    };
+    save_payment(storage, &record)?;
+
+    if let Some(treasury) = &escrow.treasury {
+        let fee = Coin {
+            amount: escrow.fee,
+            denom: escrow.denom.clone(),
+        };
+        resp = resp.add_messages(send_held_funds(api, treasury, vec![fee])?);
+    }
+
+    resp = resp.add_event(PaymentReleasedEvent {
+        id: escrow.id,
+        from_tg_id: escrow.from_tg_id,
+        from_tg_handle: escrow.from_tg_handle,
+        to_tg_id,
+        to_tg_handle: escrow.to_tg_handle,
+        from_address: escrow.from_address,
+        to_address: to_addr,
+        amount: escrow.amount,
+        denom: escrow.denom,
+        fee: (!escrow.fee.is_zero()).then_some(escrow.fee),
+        to_chain: ibc.map(|route| route.chain_id),
+    });
+    if let Some(event) = pending_event {
+        resp = resp.add_event(event);
+    }
+
+    Ok(resp)
+}
+
+/// Sends a held send and its fee back to the sender, and takes it back out of their spend window
+fn return_escrow(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    escrow: Escrow,
+    reason: Option<String>,
+) -> Result<Response, ContractError> {
+    let held = escrow_held(&escrow)?;
+    refund_spend_limit(storage, escrow.created_time, &escrow.from_address, &held)?;
+
+    Ok(Response::new()
+        .add_messages(send_held_funds(api, &escrow.from_address, vec![held])?)
+        .add_event(PaymentCancelledEvent {
+            id: escrow.id,
+            from_tg_id: escrow.from_tg_id,
+            from_tg_handle: escrow.from_tg_handle,
+            to_tg_handle: escrow.to_tg_handle,
+            from_address: escrow.from_address,
+            amount: escrow.amount,
+            denom: escrow.denom,
+            fee: (!escrow.fee.is_zero()).then_some(escrow.fee),
+            reason,
+        }))
+}
+
+/// `/undo` from Telegram, which hands a held send back to its sender while its window is open
+pub fn _cancel_payment(
+    deps: DepsMut,
+    env: Env,
+    msg: CancelPaymentMsg,
+) -> Result<Response, ContractError> {
+    let CancelPaymentMsg {
+        from_tg_id,
+        escrow_id,
+        ..
+    } = msg;
+
+    let escrow_id = match escrow_id {
+        Some(id) => Some(id),
+        None => ESCROWS_BY_SENDER
+            .prefix(from_tg_id)
+            .keys(deps.storage, None, None, Order::Descending)
+            .next()
+            .transpose()?,
+    };
+    let escrow = match escrow_id {
+        Some(id) => ESCROWS.may_load(deps.storage, id)?,
+        None => None,
+    }
+    .ok_or(ContractError::NothingToCancel)?;
+    ensure!(escrow.from_tg_id == from_tg_id, ContractError::Unauthorized);
+    ensure!(
+        !escrow.releases.is_expired(&env.block),
+        ContractError::EscrowMatured(escrow.id)
+    );
+
+    remove_escrow(deps.storage, &escrow);
+    return_escrow(deps.storage, deps.api, escrow, None)
+}
+
+/// Runs can't come more often than this, so a typo can't pay out every block
+pub const MIN_SCHEDULE_INTERVAL_SECONDS: u64 = 60 * 60;
+/// Bounds what the operators have to run for any one sender
+pub const MAX_SCHEDULES_PER_SENDER: usize = 20;
+
+/// `/schedule` from Telegram. The first run is due right away, the recipient is only resolved
+/// when each run goes out.
+pub fn _schedule_payment(
+    deps: DepsMut,
+    env: Env,
+    msg: SchedulePaymentMsg,
+) -> Result<Response, ContractError> {
+    let SchedulePaymentMsg {
+        chat_id,
+        message_id,
+        from_tg_id,
+        from_tg,
+        to_tg,
+        amount,
+        denom,
+        interval_seconds,
+        until,
+    } = msg;
+
+    check_denom(deps.storage, &denom)?;
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    ensure!(
+        interval_seconds >= MIN_SCHEDULE_INTERVAL_SECONDS,
+        ContractError::ScheduleTooFrequent {
+            min_seconds: MIN_SCHEDULE_INTERVAL_SECONDS
+        }
+    );
+    if let Some(until) = until {
+        ensure!(until >= env.block.time, ContractError::ScheduleEndsTooSoon);
+    }
+
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    ensure_not_blocked(deps.storage, Some(&to_tg), None)?;
+    let count = SCHEDULES_BY_SENDER
+        .prefix(from_tg_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(MAX_SCHEDULES_PER_SENDER)
+        .count();
+    ensure!(
+        count < MAX_SCHEDULES_PER_SENDER,
+        ContractError::TooManySchedules {
+            max: MAX_SCHEDULES_PER_SENDER
+        }
+    );
+
+    // Whole seconds, like the index by due time and the events
+    let now = Timestamp::from_seconds(env.block.time.seconds());
+    let schedule = Schedule {
+        id: next_schedule_id(deps.storage)?,
+        chat_id,
+        message_id,
+        from_tg_id,
+        from_tg_handle: sender.tg_handle,
+        to_tg_handle: to_tg,
+        amount,
+        denom,
+        interval_seconds,
+        next_due: now,
+        until,
+        created_time: env.block.time,
+    };
+    save_schedule(deps.storage, None, &schedule)?;
+
+    Ok(Response::new()
+        .add_submessages(released)
+        .add_event(ScheduleCreatedEvent {
+            id: schedule.id,
+            chat_id,
+            message_id,
+            from_tg_id,
+            from_tg_handle: schedule.from_tg_handle,
+            to_tg_handle: schedule.to_tg_handle,
+            amount: schedule.amount,
+            denom: schedule.denom,
+            interval_seconds,
+            next_due: schedule.next_due,
+            until,
+        }))
+}
+
+/// `/unschedule` from Telegram, only the sender can stop their own recurring payment
+pub fn _unschedule(deps: DepsMut, msg: UnscheduleMsg) -> Result<Response, ContractError> {
+    let UnscheduleMsg {
+        from_tg_id,
+        schedule_id,
+        ..
+    } = msg;
+
+    let schedule = SCHEDULES
+        .may_load(deps.storage, schedule_id)?
+        .ok_or(ContractError::ScheduleNotFound(schedule_id))?;
+    ensure!(
+        schedule.from_tg_id == from_tg_id,
+        ContractError::Unauthorized
+    );
+    remove_schedule(deps.storage, &schedule);
+
+    Ok(Response::new().add_event(ScheduleEndedEvent {
+        id: schedule.id,
+        from_tg_id,
+        from_tg_handle: schedule.from_tg_handle,
+        to_tg_handle: schedule.to_tg_handle,
+        cancelled: true,
+    }))
+}
+
+/// Keeps the runs in one envelope well inside its gas, like [`MAX_BATCH_SIZE`]
+pub const MAX_SCHEDULE_RUNS: u32 = 30;
+
+/// The reply on a run of a recurring payment that failed
+pub const SCHEDULE_RUN_REPLY_ID: u64 = 5;
+
+/// Handed back to [`schedule_run_reply`], the schedule as it was when the run was due
+#[cw_serde]
+struct ScheduleRunReply {
+    schedule: Schedule,
+}
+
+/// Pays the recurring payments that are due. A run only goes through if it is for the period
+/// the schedule is waiting on and that period has come, and the schedule moves on to its next
+/// period before the payment goes out, so the same run submitted by several operators, or
+/// replayed, pays once. Anything else is skipped rather than failing the rest.
+///
+/// Each payment goes out as a call back into the contract like a batch entry, so one that fails
+/// reverts only itself and its period is skipped rather than retried on every tick.
+pub fn _run_schedules(
+    deps: DepsMut,
+    env: Env,
+    runs: Vec<ScheduleRun>,
+) -> Result<Response, ContractError> {
+    ensure!(
+        runs.len() <= MAX_SCHEDULE_RUNS as usize,
+        ContractError::TooManyScheduleRuns {
+            max: MAX_SCHEDULE_RUNS as usize
+        }
+    );
+
+    let mut resp = Response::new();
+    for ScheduleRun { id, due } in runs {
+        let Some(schedule) = SCHEDULES.may_load(deps.storage, id)? else {
+            continue;
+        };
+        if schedule.next_due != due || due > env.block.time {
+            continue;
+        }
+
+        // The first period still ahead, any that were missed are skipped rather than paid late
+        let periods = (env.block.time.seconds() - due.seconds()) / schedule.interval_seconds + 1;
+        let next_due = due.plus_seconds(periods * schedule.interval_seconds);
+        match schedule.until {
+            Some(until) if next_due > until => {
+                remove_schedule(deps.storage, &schedule);
+                resp = resp.add_event(ScheduleEndedEvent {
+                    id,
+                    from_tg_id: schedule.from_tg_id,
+                    from_tg_handle: schedule.from_tg_handle.clone(),
+                    to_tg_handle: schedule.to_tg_handle.clone(),
+                    cancelled: false,
+                });
+            }
+            _ => save_schedule(
+                deps.storage,
+                Some(&schedule),
+                &Schedule {
+                    next_due,
+                    ..schedule.clone()
+                },
+            )?,
+        }
+
+        let payment = SendPaymentMsg {
+            chat_id: schedule.chat_id,
+            message_id: schedule.message_id,
+            from_tg_id: schedule.from_tg_id,
+            // The alias they have now, so the run doesn't put back one they have since dropped
+            from_tg: TG_HANDLES.may_load(deps.storage, schedule.from_tg_id)?,
+            to_tg: schedule.to_tg_handle.clone(),
+            amount: schedule.amount,
+            denom: schedule.denom.clone(),
+        };
+        let run = WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_json_binary(&ExecuteMsg::Custom(CustomExecuteMsg::ProcessScheduleRun {
+                payment,
+            }))?,
+            funds: vec![],
+        };
+        let reply = to_json_binary(&ScheduleRunReply { schedule })?;
+        resp = resp
+            .add_submessage(SubMsg::reply_on_error(run, SCHEDULE_RUN_REPLY_ID).with_payload(reply));
+    }
+
+    Ok(resp)
+}
+
+/// A run goes out like any other send, so it is held first while there is an escrow window
+pub fn process_schedule_run(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    payment: SendPaymentMsg,
+) -> Result<Response, ContractError> {
+    ensure!(
+        info.sender == env.contract.address,
+        ContractError::Unauthorized
+    );
+
+    _send_payment(deps, env, payment)
+}
+
+pub fn schedule_run_reply(msg: Reply) -> Result<Response, ContractError> {
+    let ScheduleRunReply { schedule } = from_json(&msg.payload)?;
+
+    Ok(Response::new().add_event(ScheduleRunFailedEvent {
+        id: schedule.id,
+        from_tg_id: schedule.from_tg_id,
+        from_tg_handle: schedule.from_tg_handle,
+        to_tg_handle: schedule.to_tg_handle,
+        amount: schedule.amount,
+        denom: schedule.denom,
+        due: schedule.next_due,
+        reason: msg.result.into_result().err().unwrap_or_default(),
+    }))
+}
+
+/// Bounds what a treasury stores, and the members a vote is checked against
+pub const MAX_TREASURY_MEMBERS: usize = 50;
+/// Bounds what the members of any one treasury have to vote on
+pub const MAX_OPEN_PROPOSALS: usize = 20;
+
+/// `/treasury create` from a group chat. Whoever sets it up is a member, the others have to be
+/// known to the contract by their handle, so the treasury keeps the user rather than whoever
+/// holds the handle later.
+pub fn _create_treasury(
+    deps: DepsMut,
+    env: Env,
+    msg: CreateTreasuryMsg,
+) -> Result<Response, ContractError> {
+    let CreateTreasuryMsg {
+        chat_id,
+        from_tg_id,
+        from_tg,
+        members,
+        threshold,
+        ..
+    } = msg;
+
+    // Chats with a single user have positive ids, groups and channels negative ones
+    ensure!(chat_id < 0, ContractError::TreasuryNotGroupChat);
+    ensure!(
+        !TREASURIES.has(deps.storage, chat_id),
+        ContractError::TreasuryExists
+    );
+
+    let mut treasury_members = vec![TreasuryMember {
+        tg_id: from_tg_id,
+        tg_handle: from_tg.clone(),
+    }];
+    for handle in members {
+        let tg_id = TG_USER_IDS
+            .may_load(deps.storage, handle.as_str())?
+            .ok_or_else(|| ContractError::MemberNotRegistered(handle.mention()))?;
+        if treasury_members.iter().all(|member| member.tg_id != tg_id) {
+            treasury_members.push(TreasuryMember {
+                tg_id,
+                tg_handle: Some(handle),
+            });
+        }
+    }
+    ensure!(
+        treasury_members.len() <= MAX_TREASURY_MEMBERS
+            && threshold >= 1
+            && threshold as usize <= treasury_members.len(),
+        ContractError::InvalidTreasury {
+            max_members: MAX_TREASURY_MEMBERS
+        }
+    );
+
+    let treasury = Treasury {
+        chat_id,
+        members: treasury_members,
+        threshold,
+        created_time: env.block.time,
+    };
+    TREASURIES.save(deps.storage, chat_id, &treasury)?;
+
+    Ok(Response::new().add_event(TreasuryCreatedEvent {
+        chat_id,
+        from_tg_id,
+        from_tg_handle: from_tg,
+        members: treasury
+            .members
+            .iter()
+            .filter_map(|member| member.tg_handle.clone())
+            .collect(),
+        member_count: treasury.members.len() as u32,
+        threshold,
+    }))
+}
+
+/// `/treasury fund` from a group chat. The funds come out of the sender's own account like a
+/// send, and count against their spend limit, but there is no fee since nobody is paid yet. The
+/// transfer isn't guarded, so if it fails nothing is added.
+pub fn _fund_treasury(
+    deps: DepsMut,
+    env: Env,
+    msg: FundTreasuryMsg,
+) -> Result<Response, ContractError> {
+    let FundTreasuryMsg {
+        chat_id,
+        from_tg_id,
+        from_tg,
+        amount,
+        denom,
+        ..
+    } = msg;
+
+    ensure!(
+        TREASURIES.has(deps.storage, chat_id),
+        ContractError::TreasuryNotFound
+    );
+    check_denom(deps.storage, &denom)?;
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    let amount = Coin { amount, denom };
+
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    charge_spend_limit(deps.storage, &env, &sender.address, &amount)?;
+    let balance = TREASURY_BALANCES
+        .may_load(deps.storage, (chat_id, &amount.denom))?
+        .unwrap_or_default()
+        .checked_add(amount.amount)?;
+    TREASURY_BALANCES.save(deps.storage, (chat_id, &amount.denom), &balance)?;
+
+    let transfer = transfer_msg(
+        deps.api,
+        &env,
+        &sender.address,
+        &env.contract.address,
+        &amount,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(released)
+        .add_message(transfer)
+        .add_event(TreasuryFundedEvent {
+            chat_id,
+            from_tg_id,
+            from_tg_handle: sender.tg_handle,
+            from_address: sender.address,
             amount: amount.amount,
-            denom: amount.denom.clone(),
+            denom: amount.denom,
+            balance,
         }))
 }
+
+/// `/propose` from a group chat, only its treasury's members can propose a payout. The
+/// proposer's vote counts as an approval, so a threshold of one pays out right away.
+pub fn _propose_payout(
+    deps: DepsMut,
+    env: Env,
+    msg: ProposePayoutMsg,
+) -> Result<Response, ContractError> {
+    let ProposePayoutMsg {
+        chat_id,
+        message_id,
+        from_tg_id,
+        from_tg,
+        to_tg,
+        amount,
+        denom,
+        memo,
+    } = msg;
+
+    let treasury = TREASURIES
+        .may_load(deps.storage, chat_id)?
+        .ok_or(ContractError::TreasuryNotFound)?;
+    ensure!(
+        treasury.is_member(from_tg_id),
+        ContractError::NotTreasuryMember
+    );
+    check_denom(deps.storage, &denom)?;
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    if let Some(memo) = &memo {
+        ensure!(
+            memo.chars().count() <= MAX_MEMO_LEN,
+            ContractError::MemoTooLong { max: MAX_MEMO_LEN }
+        );
+    }
+    // Checked again on payout, but turned away now rather than after everyone has voted
+    let target = payout_target(deps.storage, deps.api, Recipient::Handle(&to_tg), &denom)?;
+    ensure!(
+        target.to_addr.is_some(),
+        ContractError::PayeeNotRegistered(to_tg.mention())
+    );
+    let open = PROPOSALS_BY_CHAT
+        .prefix(chat_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(MAX_OPEN_PROPOSALS)
+        .count();
+    ensure!(
+        open < MAX_OPEN_PROPOSALS,
+        ContractError::TooManyProposals {
+            max: MAX_OPEN_PROPOSALS
+        }
+    );
+
+    let proposal = Proposal {
+        id: next_proposal_id(deps.storage)?,
+        chat_id,
+        proposer_tg_id: from_tg_id,
+        proposer_tg_handle: from_tg,
+        to_tg_handle: to_tg,
+        amount,
+        denom,
+        memo,
+        approvals: vec![from_tg_id],
+        rejections: vec![],
+        created_time: env.block.time,
+    };
+    let resp = Response::new().add_event(ProposalCreatedEvent {
+        id: proposal.id,
+        chat_id,
+        message_id,
+        proposer_tg_id: from_tg_id,
+        proposer_tg_handle: proposal.proposer_tg_handle.clone(),
+        to_tg_handle: proposal.to_tg_handle.clone(),
+        amount,
+        denom: proposal.denom.clone(),
+        memo: proposal.memo.clone(),
+        threshold: treasury.threshold,
+    });
+
+    decide_proposal(deps.storage, deps.api, &env, &treasury, proposal, resp)
+}
+
+/// `/vote` or a button on a proposal's prompt. Each member votes once, from the treasury's own
+/// group.
+pub fn _vote(deps: DepsMut, env: Env, msg: VoteMsg) -> Result<Response, ContractError> {
+    let VoteMsg {
+        chat_id,
+        from_tg_id,
+        from_tg,
+        proposal_id,
+        approve,
+        ..
+    } = msg;
+
+    let mut proposal = PROPOSALS
+        .may_load(deps.storage, proposal_id)?
+        .filter(|proposal| proposal.chat_id == chat_id)
+        .ok_or(ContractError::ProposalNotFound(proposal_id))?;
+    let treasury = TREASURIES.load(deps.storage, chat_id)?;
+    ensure!(
+        treasury.is_member(from_tg_id),
+        ContractError::NotTreasuryMember
+    );
+    ensure!(
+        !proposal.approvals.contains(&from_tg_id) && !proposal.rejections.contains(&from_tg_id),
+        ContractError::AlreadyVoted(proposal_id)
+    );
+
+    match approve {
+        true => proposal.approvals.push(from_tg_id),
+        false => proposal.rejections.push(from_tg_id),
+    }
+    let resp = Response::new().add_event(ProposalVotedEvent {
+        id: proposal_id,
+        chat_id,
+        voter_tg_id: from_tg_id,
+        voter_tg_handle: from_tg,
+        approve,
+        approvals: proposal.approvals.len() as u32,
+        rejections: proposal.rejections.len() as u32,
+        threshold: treasury.threshold,
+    });
+
+    decide_proposal(deps.storage, deps.api, &env, &treasury, proposal, resp)
+}
+
+/// Pays a proposal out once enough members approved it, or drops it once too many rejected it to
+/// ever get there. Otherwise it stays open for the rest to vote on.
+fn decide_proposal(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    treasury: &Treasury,
+    proposal: Proposal,
+    resp: Response,
+) -> Result<Response, ContractError> {
+    let threshold = treasury.threshold as usize;
+    let undecided = treasury.members.len() - proposal.approvals.len() - proposal.rejections.len();
+
+    if proposal.approvals.len() >= threshold {
+        remove_proposal(storage, &proposal);
+        let payout = pay_proposal(storage, api, env, proposal)?;
+        Ok(resp
+            .add_submessages(payout.messages)
+            .add_events(payout.events))
+    } else if proposal.approvals.len() + undecided < threshold {
+        remove_proposal(storage, &proposal);
+        Ok(resp.add_event(ProposalRejectedEvent {
+            id: proposal.id,
+            chat_id: proposal.chat_id,
+            to_tg_handle: proposal.to_tg_handle,
+            amount: proposal.amount,
+            denom: proposal.denom,
+        }))
+    } else {
+        save_proposal(storage, &proposal)?;
+        Ok(resp)
+    }
+}
+
+/// Pays an approved proposal out of its treasury, to whoever holds the handle now. Fails if the
+/// treasury doesn't hold enough, so the deciding vote can be cast again once it is funded.
+fn pay_proposal(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    proposal: Proposal,
+) -> Result<Response, ContractError> {
+    let PayoutTarget {
+        to_tg_id,
+        to_addr,
+        ibc,
+    } = payout_target(
+        storage,
+        api,
+        Recipient::Handle(&proposal.to_tg_handle),
+        &proposal.denom,
+    )?;
+    let to_addr = to_addr
+        .ok_or_else(|| ContractError::PayeeNotRegistered(proposal.to_tg_handle.mention()))?;
+
+    let key = (proposal.chat_id, proposal.denom.as_str());
+    let available = TREASURY_BALANCES
+        .may_load(storage, key)?
+        .unwrap_or_default();
+    let balance = available.checked_sub(proposal.amount).map_err(|_| {
+        ContractError::InsufficientTreasury {
+            available,
+            denom: proposal.denom.clone(),
+        }
+    })?;
+    if balance.is_zero() {
+        TREASURY_BALANCES.remove(storage, key);
+    } else {
+        TREASURY_BALANCES.save(storage, key, &balance)?;
+    }
+
+    let record = PaymentRecord {
+        id: next_payment_id(storage)?,
+        kind: PaymentKind::Direct,
+        from_tg_id: None,
+        from_tg_handle: None,
+        to_tg_id,
+        to_tg_handle: Some(proposal.to_tg_handle.clone()),
+        from_address: env.contract.address.clone(),
+        to_address: to_addr.clone(),
+        amount: proposal.amount,
+        denom: proposal.denom.clone(),
+        block_height: env.block.height,
+        timestamp: env.block.time,
+    };
+    save_payment(storage, &record)?;
+    let msgs = pay_out_held(
+        api,
+        env,
+        ibc.as_ref(),
+        HeldPayout {
+            payment_id: record.id,
+            to_tg_id,
+            to_tg_handle: Some(proposal.to_tg_handle.clone()),
+            to_address: to_addr.clone(),
+            amount: Coin {
+                amount: proposal.amount,
+                denom: proposal.denom.clone(),
+            },
+            returns_to: HeldFunds::Treasury {
+                chat_id: proposal.chat_id,
+            },
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_event(ProposalExecutedEvent {
+            id: proposal.id,
+            chat_id: proposal.chat_id,
+            to_tg_id,
+            to_tg_handle: proposal.to_tg_handle,
+            to_address: to_addr,
+            amount: proposal.amount,
+            denom: proposal.denom,
+            to_chain: ibc.map(|route| route.chain_id),
+        }))
+}
+
+/// How long a gift can be claimed for, unless the sender says otherwise
+pub const DEFAULT_GIFT_EXPIRY_SECONDS: u64 = 7 * 24 * 60 * 60;
+pub const MAX_GIFT_EXPIRY_SECONDS: u64 = 90 * 24 * 60 * 60;
+
+/// `/gift`, the funds and the fee leave the sender's account right away, and the funds wait in the
+/// contract for whoever has the secret. Only its hash is known here, the bot works the secret out
+/// again to hand the sender a link.
+pub fn _create_gift(
+    deps: DepsMut,
+    env: Env,
+    msg: CreateGiftMsg,
+) -> Result<Response, ContractError> {
+    let CreateGiftMsg {
+        chat_id,
+        message_id,
+        from_tg_id,
+        from_tg,
+        amount,
+        denom,
+        hash,
+        expires_in_seconds,
+    } = msg;
+
+    let expires_in = expires_in_seconds.unwrap_or(DEFAULT_GIFT_EXPIRY_SECONDS);
+    ensure!(
+        hash.len() == 32 && expires_in > 0 && expires_in <= MAX_GIFT_EXPIRY_SECONDS,
+        ContractError::InvalidGift {
+            max_seconds: MAX_GIFT_EXPIRY_SECONDS
+        }
+    );
+    ensure!(
+        !GIFTS_BY_HASH.has(deps.storage, hash.as_slice()),
+        ContractError::GiftExists
+    );
+    check_denom(deps.storage, &denom)?;
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    let amount = Coin { amount, denom };
+
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    let (fee, fee_transfer) = fee_leg(deps.storage, deps.api, &env, &sender.address, &amount)?;
+    charge_spend_limit(
+        deps.storage,
+        &env,
+        &sender.address,
+        &Coin {
+            amount: amount.amount.checked_add(fee)?,
+            denom: amount.denom.clone(),
+        },
+    )?;
+
+    let gift = Gift {
+        id: next_gift_id(deps.storage)?,
+        hash,
+        from_tg_id,
+        from_tg_handle: sender.tg_handle.clone(),
+        from_address: sender.address.clone(),
+        amount: amount.amount,
+        denom: amount.denom.clone(),
+        created_time: env.block.time,
+        expires: env.block.time.plus_seconds(expires_in),
+        claimer: None,
+    };
+    save_gift(deps.storage, &gift)?;
+
+    let transfer = transfer_msg(
+        deps.api,
+        &env,
+        &sender.address,
+        &env.contract.address,
+        &amount,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(released)
+        .add_message(transfer)
+        .add_messages(fee_transfer)
+        .add_event(GiftCreatedEvent {
+            id: gift.id,
+            chat_id,
+            message_id,
+            from_tg_id,
+            from_tg_handle: gift.from_tg_handle,
+            from_address: gift.from_address,
+            amount: gift.amount,
+            denom: gift.denom,
+            fee: (!fee.is_zero()).then_some(fee),
+            expires: gift.expires,
+        }))
+}
+
+/// `/start claim_<secret>`, from the gift link. The secret is on chain once this goes through, so
+/// rather than failing for someone who hasn't registered to receive yet, the gift is kept for
+/// them until they have.
+pub fn _claim_gift(deps: DepsMut, env: Env, msg: ClaimGiftMsg) -> Result<Response, ContractError> {
+    let ClaimGiftMsg {
+        to_tg_id,
+        to_tg,
+        secret,
+        ..
+    } = msg;
+
+    let mut gift = GIFTS_BY_HASH
+        .may_load(deps.storage, Gift::hash_secret(&secret).as_slice())?
+        .map(|id| GIFTS.load(deps.storage, id))
+        .transpose()?
+        .filter(|gift| gift.claimer.is_none_or(|claimer| claimer == to_tg_id))
+        .ok_or(ContractError::GiftNotFound)?;
+    ensure!(
+        env.block.time < gift.expires,
+        ContractError::GiftExpired(gift.id)
+    );
+
+    let PayoutTarget { to_addr, ibc, .. } = payout_target(
+        deps.storage,
+        deps.api,
+        Recipient::User {
+            tg_id: to_tg_id,
+            tg_handle: to_tg.as_ref(),
+        },
+        &gift.denom,
+    )?;
+    let Some(to_addr) = to_addr else {
+        gift.claimer = Some(to_tg_id);
+        save_gift(deps.storage, &gift)?;
+        return Ok(Response::new().add_event(GiftReservedEvent {
+            id: gift.id,
+            from_tg_id: gift.from_tg_id,
+            from_tg_handle: gift.from_tg_handle,
+            to_tg_id,
+            to_tg_handle: to_tg,
+            amount: gift.amount,
+            denom: gift.denom,
+            expires: gift.expires,
+        }));
+    };
+    remove_gift(deps.storage, &gift);
+
+    let record = PaymentRecord {
+        id: next_payment_id(deps.storage)?,
+        kind: PaymentKind::Direct,
+        from_tg_id: Some(gift.from_tg_id),
+        from_tg_handle: gift.from_tg_handle.clone(),
+        to_tg_id: Some(to_tg_id),
+        to_tg_handle: to_tg.clone(),
+        from_address: gift.from_address.clone(),
+        to_address: to_addr.clone(),
+        amount: gift.amount,
+        denom: gift.denom.clone(),
+        block_height: env.block.height,
+        timestamp: env.block.time,
+    };
+    save_payment(deps.storage, &record)?;
+    let msgs = pay_out_held(
+        deps.api,
+        &env,
+        ibc.as_ref(),
+        HeldPayout {
+            payment_id: record.id,
+            to_tg_id: Some(to_tg_id),
+            to_tg_handle: to_tg.clone(),
+            to_address: to_addr.clone(),
+            amount: Coin {
+                amount: gift.amount,
+                denom: gift.denom.clone(),
+            },
+            returns_to: HeldFunds::Refund(gift.from_address.clone()),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_event(GiftClaimedEvent {
+            id: gift.id,
+            from_tg_id: gift.from_tg_id,
+            from_tg_handle: gift.from_tg_handle,
+            to_tg_id,
+            to_tg_handle: to_tg,
+            to_address: to_addr,
+            amount: gift.amount,
+            denom: gift.denom,
+            to_chain: ibc.map(|route| route.chain_id),
+        }))
+}
+
+/// How long a giveaway can be claimed from, what is left can be refunded after
+pub const GIVEAWAY_EXPIRY_SECONDS: u64 = 24 * 60 * 60;
+pub const MAX_GIVEAWAY_COUNT: u32 = 100;
+
+/// `/giveaway`, the pot and the fee leave the sender's account right away, and the pot waits in
+/// the contract for the group's members to claim
+pub fn _create_giveaway(
+    deps: DepsMut,
+    env: Env,
+    msg: CreateGiveawayMsg,
+) -> Result<Response, ContractError> {
+    let CreateGiveawayMsg {
+        chat_id,
+        message_id,
+        from_tg_id,
+        from_tg,
+        amount,
+        denom,
+        count,
+        split,
+    } = msg;
+
+    ensure!(chat_id < 0, ContractError::GiveawayNotGroupChat);
+    ensure!(
+        count > 0 && count <= MAX_GIVEAWAY_COUNT && amount >= Uint256::from(count),
+        ContractError::InvalidGiveaway {
+            max_count: MAX_GIVEAWAY_COUNT
+        }
+    );
+    check_denom(deps.storage, &denom)?;
+    let amount = Coin { amount, denom };
+
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    let (fee, fee_transfer) = fee_leg(deps.storage, deps.api, &env, &sender.address, &amount)?;
+    charge_spend_limit(
+        deps.storage,
+        &env,
+        &sender.address,
+        &Coin {
+            amount: amount.amount.checked_add(fee)?,
+            denom: amount.denom.clone(),
+        },
+    )?;
+
+    let giveaway = Giveaway {
+        id: next_giveaway_id(deps.storage)?,
+        chat_id,
+        from_tg_id,
+        from_tg_handle: sender.tg_handle.clone(),
+        from_address: sender.address.clone(),
+        amount: amount.amount,
+        remaining: amount.amount,
+        denom: amount.denom.clone(),
+        count,
+        split,
+        claimers: vec![],
+        created_time: env.block.time,
+        expires: env.block.time.plus_seconds(GIVEAWAY_EXPIRY_SECONDS),
+    };
+    save_giveaway(deps.storage, &giveaway)?;
+
+    let transfer = transfer_msg(
+        deps.api,
+        &env,
+        &sender.address,
+        &env.contract.address,
+        &amount,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(released)
+        .add_message(transfer)
+        .add_messages(fee_transfer)
+        .add_event(GiveawayCreatedEvent {
+            id: giveaway.id,
+            chat_id,
+            message_id,
+            from_tg_id,
+            from_tg_handle: giveaway.from_tg_handle,
+            from_address: giveaway.from_address,
+            amount: giveaway.amount,
+            denom: giveaway.denom,
+            count,
+            split,
+            fee: (!fee.is_zero()).then_some(fee),
+            expires: giveaway.expires,
+        }))
+}
+
+/// A member pressing Claim on a giveaway, paid their share once. The giveaway is over once every
+/// share has been claimed, which is also when the pot runs out.
+pub fn _claim_giveaway(
+    deps: DepsMut,
+    env: Env,
+    msg: ClaimGiveawayMsg,
+) -> Result<Response, ContractError> {
+    let ClaimGiveawayMsg {
+        chat_id,
+        to_tg_id,
+        to_tg,
+        giveaway_id,
+        ..
+    } = msg;
+
+    let mut giveaway = GIVEAWAYS
+        .may_load(deps.storage, giveaway_id)?
+        .filter(|giveaway| giveaway.chat_id == chat_id)
+        .ok_or(ContractError::GiveawayNotFound(giveaway_id))?;
+    ensure!(
+        env.block.time < giveaway.expires,
+        ContractError::GiveawayExpired(giveaway_id)
+    );
+    ensure!(
+        !giveaway.claimers.contains(&to_tg_id),
+        ContractError::AlreadyClaimed(giveaway_id)
+    );
+
+    let PayoutTarget { to_addr, ibc, .. } = payout_target(
+        deps.storage,
+        deps.api,
+        Recipient::User {
+            tg_id: to_tg_id,
+            tg_handle: to_tg.as_ref(),
+        },
+        &giveaway.denom,
+    )?;
+    let to_addr = to_addr.ok_or(ContractError::ClaimerNotRegistered)?;
+
+    let share = giveaway_share(&env, &giveaway, to_tg_id);
+    giveaway.remaining -= share;
+    giveaway.claimers.push(to_tg_id);
+    let claims = giveaway.claimers.len() as u32;
+    if claims == giveaway.count {
+        remove_giveaway(deps.storage, &giveaway);
+    } else {
+        save_giveaway(deps.storage, &giveaway)?;
+    }
+
+    let record = PaymentRecord {
+        id: next_payment_id(deps.storage)?,
+        kind: PaymentKind::Direct,
+        from_tg_id: Some(giveaway.from_tg_id),
+        from_tg_handle: giveaway.from_tg_handle.clone(),
+        to_tg_id: Some(to_tg_id),
+        to_tg_handle: to_tg.clone(),
+        from_address: giveaway.from_address.clone(),
+        to_address: to_addr.clone(),
+        amount: share,
+        denom: giveaway.denom.clone(),
+        block_height: env.block.height,
+        timestamp: env.block.time,
+    };
+    save_payment(deps.storage, &record)?;
+    // Back to whoever put up the giveaway, which may be over by the time it fails
+    let msgs = pay_out_held(
+        deps.api,
+        &env,
+        ibc.as_ref(),
+        HeldPayout {
+            payment_id: record.id,
+            to_tg_id: Some(to_tg_id),
+            to_tg_handle: to_tg.clone(),
+            to_address: to_addr.clone(),
+            amount: Coin {
+                amount: share,
+                denom: giveaway.denom.clone(),
+            },
+            returns_to: HeldFunds::Refund(giveaway.from_address.clone()),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_event(GiveawayClaimedEvent {
+            id: giveaway.id,
+            chat_id,
+            to_tg_id,
+            to_tg_handle: to_tg,
+            to_address: to_addr,
+            amount: share,
+            denom: giveaway.denom,
+            claims,
+            count: giveaway.count,
+            remaining: giveaway.remaining,
+            to_chain: ibc.map(|route| route.chain_id),
+        }))
+}
+
+/// The next claim's share of a giveaway. The last claim takes whatever is left. A random share is
+/// 1 up to twice the average of what is left, but always leaves at least 1 for each claim after
+/// it. The randomness is the hash of the block and the claim, so every operator's submission of
+/// the same claim in the same block gets the same share, and nobody can pick theirs.
+fn giveaway_share(env: &Env, giveaway: &Giveaway, to_tg_id: i64) -> Uint256 {
+    let claims_left = Uint256::from(giveaway.count - giveaway.claimers.len() as u32);
+    if claims_left == Uint256::one() {
+        return giveaway.remaining;
+    }
+
+    match giveaway.split {
+        GiveawaySplit::Equal => giveaway.amount / Uint256::from(giveaway.count),
+        GiveawaySplit::Random => {
+            let max = (giveaway.remaining * Uint256::from(2u8) / claims_left)
+                .min(giveaway.remaining - (claims_left - Uint256::one()));
+            let seed = Sha256::new()
+                .chain_update(env.block.chain_id.as_bytes())
+                .chain_update(env.block.height.to_be_bytes())
+                .chain_update(env.block.time.nanos().to_be_bytes())
+                .chain_update(giveaway.id.to_be_bytes())
+                .chain_update(to_tg_id.to_be_bytes())
+                .finalize();
+            let seed = Uint256::from_be_bytes(seed.into());
+
+            Uint256::one() + seed % max
+        }
+    }
+}
+
+pub fn _tip(deps: DepsMut, env: Env, msg: TipMsg) -> Result<Response, ContractError> {
+    let TipMsg {
+        chat_id,
+        message_id,
+        from_tg_id,
+        from_tg,
+        to_tg_id,
+        to_tg,
+        amount,
+        denom,
+    } = msg;
+
+    check_denom(deps.storage, &denom)?;
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    ensure!(to_tg_id != from_tg_id, ContractError::SelfTip);
+    let amount = Coin { amount, denom };
+
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    let (fee, fee_transfer) = fee_leg(deps.storage, deps.api, &env, &sender.address, &amount)?;
+    charge_spend_limit(
+        deps.storage,
+        &env,
+        &sender.address,
+        &Coin {
+            amount: amount.amount.checked_add(fee)?,
+            denom: amount.denom.clone(),
+        },
+    )?;
+    let leg = pay(
+        deps.storage,
+        deps.api,
+        &env,
+        &sender,
+        Recipient::User {
+            tg_id: to_tg_id,
+            tg_handle: to_tg.as_ref(),
+        },
+        &amount,
+    )?;
+
+    let sent = SendPaymentEvent {
+        from_tg_id,
+        from_tg_handle: sender.tg_handle,
+        to_tg_id: leg.to_tg_id,
+        to_tg_handle: to_tg,
+        from_address: sender.address,
+        to_address: leg.to_addr.clone(),
+        amount: amount.amount,
+        denom: amount.denom,
+        fee: (!fee.is_zero()).then_some(fee),
+        ibc_channel: leg.ibc.as_ref().map(|route| route.channel_id.clone()),
+        to_chain: leg.ibc.as_ref().map(|route| route.chain_id.clone()),
+    };
+
+    Ok(Response::new()
+        .add_submessages(released)
+        .add_submessage(guarded_send(chat_id, message_id, leg, sent, fee_transfer)?))
+}
+
+/// The reply on the transfer of a single send
+pub const SEND_PAYMENT_REPLY_ID: u64 = 1;
+
+/// The reply on a send leaving for another chain, once it has been pulled into the contract
+pub const IBC_TRANSFER_REPLY_ID: u64 = 2;
+
+/// The reply on a payout of held funds to another chain, once it has been submitted
+pub const HELD_IBC_TRANSFER_REPLY_ID: u64 = 6;
+
+/// Handed back to [`send_payment_reply`] and [`ibc_transfer_reply`] along with the outcome of the
+/// transfer
+#[cw_serde]
+struct SendPaymentReply {
+    chat_id: i64,
+    message_id: i64,
+    payment_id: u64,
+    sent: SendPaymentEvent,
+    pending: Option<PendingPaymentCreatedEvent>,
+    fee_transfer: Option<CosmosMsg>,
+}
+
+/// Dispatches the transfer so a failure is reported to the sender rather than reverting the
+/// whole message. The events only go out from the reply, once we know it went through.
+fn guarded_send(
+    chat_id: i64,
+    message_id: i64,
+    leg: Leg,
+    sent: SendPaymentEvent,
+    fee_transfer: Option<CosmosMsg>,
+) -> StdResult<SubMsg> {
+    let payload = to_json_binary(&SendPaymentReply {
+        chat_id,
+        message_id,
+        payment_id: leg.id,
+        sent,
+        pending: leg.pending_event,
+        fee_transfer,
+    })?;
+
+    Ok(SubMsg::reply_always(leg.transfer, SEND_PAYMENT_REPLY_ID).with_payload(payload))
+}
+
+pub fn send_payment_reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let reply: SendPaymentReply = from_json(&msg.payload)?;
+
+    match msg.result {
+        SubMsgResult::Ok(_) => {
+            // Pulled into the contract so far, the events wait until it has left for the other chain
+            if let Some(route) = ibc_route(deps.storage, deps.api, &reply.sent.to_address)? {
+                return Ok(Response::new().add_submessage(ibc_transfer(&env, &route, &reply)?));
+            }
+
+            // Only charged once the payment went through. If the fee can't be paid, the whole
+            // send reverts with it.
+            let mut resp = Response::new()
+                .add_messages(reply.fee_transfer)
+                .add_event(reply.sent);
+            if let Some(event) = reply.pending {
+                resp = resp.add_event(event);
+            }
+            Ok(resp)
+        }
+        SubMsgResult::Err(reason) => {
+            // Nothing moved, so take the payment back out of the ledger and the pending balances
+            let SendPaymentReply {
+                chat_id,
+                message_id,
+                payment_id,
+                sent,
+                ..
+            } = reply;
+            undo_send(
+                deps.storage,
+                payment_id,
+                &sent.from_address,
+                &Coin {
+                    amount: sent.amount.checked_add(sent.fee.unwrap_or_default())?,
+                    denom: sent.denom.clone(),
+                },
+            )?;
+
+            Ok(Response::new().add_event(payment_failed(chat_id, message_id, sent, reason)))
+        }
+    }
+}
+
+/// Sends a payment the contract pulled in on to the other chain. Packet callbacks only go to
+/// whoever sent the packet, which is why it goes out from the contract and not the sender.
+fn ibc_transfer(env: &Env, route: &IbcRoute, reply: &SendPaymentReply) -> StdResult<SubMsg> {
+    let callback = IbcCallbackRequest::source(IbcSrcCallback {
+        address: env.contract.address.clone(),
+        gas_limit: None,
+    });
+    let transfer = IbcMsg::Transfer {
+        channel_id: route.channel_id.clone(),
+        to_address: reply.sent.to_address.to_string(),
+        amount: Coin {
+            amount: reply.sent.amount,
+            denom: reply.sent.denom.clone(),
+        },
+        timeout: env.block.time.plus_seconds(route.timeout_seconds).into(),
+        memo: Some(to_json_string(&callback)?),
+    };
+
+    Ok(SubMsg::reply_always(transfer, IBC_TRANSFER_REPLY_ID).with_payload(to_json_binary(reply)?))
+}
+
+/// Handed back to [`held_ibc_transfer_reply`] once the payout has been submitted
+#[cw_serde]
+struct HeldTransferReply {
+    channel_id: String,
+    payout: HeldPayout,
+}
+
+const MSG_TRANSFER_RESPONSE_TYPE_URL: &str = "/ibc.applications.transfer.v1.MsgTransferResponse";
+
+/// The packet sequence of a submitted transfer, out of its `MsgTransferResponse`
+fn sent_sequence(resp: &SubMsgResponse) -> StdResult<u64> {
+    resp.msg_responses
+        .iter()
+        .find(|resp| resp.type_url == MSG_TRANSFER_RESPONSE_TYPE_URL)
+        .and_then(|resp| transfer_sequence(&resp.value))
+        .ok_or_else(|| StdError::msg("missing MsgTransferResponse"))
+}
+
+pub fn ibc_transfer_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let SendPaymentReply {
+        chat_id,
+        message_id,
+        payment_id,
+        sent,
+        fee_transfer,
+        ..
+    } = from_json(&msg.payload)?;
+
+    match msg.result {
+        SubMsgResult::Ok(resp) => {
+            // Remembered by packet, so the callback can tell which payment it is about
+            let sequence = sent_sequence(&resp)?;
+            // Always set on a send to another chain
+            let channel_id = sent.ibc_channel.clone().unwrap_or_default();
+            IBC_TRANSFERS.save(
+                deps.storage,
+                (&channel_id, sequence),
+                &IbcTransfer::Send {
+                    chat_id,
+                    message_id,
+                    payment_id,
+                    sent: sent.clone(),
+                },
+            )?;
+
+            Ok(Response::new().add_messages(fee_transfer).add_event(sent))
+        }
+        SubMsgResult::Err(reason) => {
+            // The fee was never charged, and the payment goes straight back to the sender
+            let amount = Coin {
+                amount: sent.amount,
+                denom: sent.denom.clone(),
+            };
+            undo_send(
+                deps.storage,
+                payment_id,
+                &sent.from_address,
+                &Coin {
+                    amount: sent.amount.checked_add(sent.fee.unwrap_or_default())?,
+                    denom: sent.denom.clone(),
+                },
+            )?;
+
+            Ok(Response::new()
+                .add_message(BankMsg::Send {
+                    to_address: sent.from_address.to_string(),
+                    amount: vec![amount],
+                })
+                .add_event(payment_failed(chat_id, message_id, sent, reason)))
+        }
+    }
+}
+
+/// Remembers a payout of held funds by packet, so the callback can put it back if it fails
+pub fn held_ibc_transfer_reply(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let HeldTransferReply { channel_id, payout } = from_json(&msg.payload)?;
+    // Only replied to on success
+    let SubMsgResult::Ok(resp) = msg.result else {
+        return Ok(Response::new());
+    };
+    let sequence = sent_sequence(&resp)?;
+    IBC_TRANSFERS.save(
+        deps.storage,
+        (&channel_id, sequence),
+        &IbcTransfer::Held(payout),
+    )?;
+
+    Ok(Response::new())
+}
+
+/// The sequence out of an encoded `MsgTransferResponse`, its only field
+fn transfer_sequence(bytes: &[u8]) -> Option<u64> {
+    // Field 1, varint
+    let (&tag, mut rest) = bytes.split_first()?;
+    if tag != 0x08 {
+        return None;
+    }
+    let mut sequence = 0u64;
+    for shift in (0..64).step_by(7) {
+        let (&byte, tail) = rest.split_first()?;
+        rest = tail;
+        sequence |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Some(sequence);
+        }
+    }
+    None
+}
+
+/// The ICS20 acknowledgement, which only tells us whether the other chain took the funds
+#[cw_serde]
+enum Ics20Ack {
+    Result(Binary),
+    Error(String),
+}
+
+pub fn ibc_source_callback(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcSourceCallbackMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let (packet, failure) = match msg {
+        IbcSourceCallbackMsg::Acknowledgement(ack) => {
+            // Anything but an explicit error counts as delivered, so we never pay back funds
+            // the other chain kept
+            let failure = match from_json(&ack.acknowledgement.data) {
+                Ok(Ics20Ack::Error(error)) => Some(error),
+                _ => None,
+            };
+            (ack.original_packet, failure)
+        }
+        IbcSourceCallbackMsg::Timeout(timeout) => (
+            timeout.packet,
+            Some("timed out before it reached the other chain".to_string()),
+        ),
+    };
+
+    // Transfers that left before payouts of held funds were tracked aren't in here
+    let key = (packet.src.channel_id.as_str(), packet.sequence);
+    let Some(transfer) = IBC_TRANSFERS.may_load(deps.storage, key)? else {
+        return Ok(IbcBasicResponse::new());
+    };
+    IBC_TRANSFERS.remove(deps.storage, key);
+    let Some(reason) = failure else {
+        return Ok(IbcBasicResponse::new());
+    };
+
+    // ICS20 returned the funds to the contract, as the packet sender. The fee was paid when it
+    // left and stays with the treasury.
+    let (chat_id, message_id, payment_id, sent) = match transfer {
+        IbcTransfer::Send {
+            chat_id,
+            message_id,
+            payment_id,
+            sent,
+        } => (chat_id, message_id, payment_id, sent),
+        IbcTransfer::Held(payout) => {
+            return return_held_payout(deps.storage, deps.api, payout, reason)
+        }
+    };
+    let amount = Coin {
+        amount: sent.amount,
+        denom: sent.denom.clone(),
+    };
+    undo_send(deps.storage, payment_id, &sent.from_address, &amount)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_message(BankMsg::Send {
+            to_address: sent.from_address.to_string(),
+            amount: vec![amount],
+        })
+        .add_event(Event::from(payment_failed(
+            chat_id, message_id, sent, reason,
+        ))))
+}
+
+/// Puts held funds that never arrived back where they were held, and takes the payout back out of
+/// the ledger
+fn return_held_payout(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    payout: HeldPayout,
+    reason: String,
+) -> Result<IbcBasicResponse, ContractError> {
+    if let Some(record) = PAYMENTS.may_load(storage, payout.payment_id)? {
+        remove_payment(storage, &record)?;
+    }
+
+    let mut resp = IbcBasicResponse::new();
+    let mut returned = PayoutReturnedEvent {
+        to_tg_id: payout.to_tg_id,
+        to_tg_handle: payout.to_tg_handle,
+        to_address: payout.to_address,
+        amount: payout.amount.amount,
+        denom: payout.amount.denom.clone(),
+        held_for: None,
+        treasury_chat_id: None,
+        refunded_to: None,
+        reason,
+    };
+    match payout.returns_to {
+        HeldFunds::Pending { tg_handle, entries } => {
+            let mut pending = PENDING_PAYMENTS
+                .may_load(storage, tg_handle.as_str())?
+                .unwrap_or_default();
+            pending.add_payment(payout.amount);
+            PENDING_PAYMENTS.save(storage, tg_handle.as_str(), &pending)?;
+            // Refundable again once they expire, like before they were released
+            for entry in &entries {
+                save_pending_entry(storage, entry)?;
+            }
+            returned.held_for = Some(tg_handle);
+        }
+        HeldFunds::Treasury { chat_id } => {
+            let key = (chat_id, payout.amount.denom.as_str());
+            let balance = TREASURY_BALANCES
+                .may_load(storage, key)?
+                .unwrap_or_default()
+                .checked_add(payout.amount.amount)?;
+            TREASURY_BALANCES.save(storage, key, &balance)?;
+            returned.treasury_chat_id = Some(chat_id);
+        }
+        HeldFunds::Refund(addr) => {
+            resp = resp.add_messages(send_held_funds(api, &addr, vec![payout.amount])?);
+            returned.refunded_to = Some(addr);
+        }
+    }
+
+    Ok(resp.add_event(Event::from(returned)))
+}
+
+/// Takes a send that never arrived back out of the ledger, the pending balances and the spend
+/// window of the sender
+fn undo_send(
+    storage: &mut dyn Storage,
+    payment_id: u64,
+    from_addr: &Addr,
+    spent: &Coin,
+) -> StdResult<()> {
+    let record = PAYMENTS.load(storage, payment_id)?;
+    remove_payment(storage, &record)?;
+    if let Some(entry) = PENDING_ENTRIES.may_load(storage, payment_id)? {
+        take_pending_entry(storage, &entry)?;
+    }
+    refund_spend_limit(storage, record.timestamp, from_addr, spent)
+}
+
+fn payment_failed(
+    chat_id: i64,
+    message_id: i64,
+    sent: SendPaymentEvent,
+    reason: String,
+) -> PaymentFailedEvent {
+    PaymentFailedEvent {
+        chat_id,
+        message_id,
+        from_tg_id: sent.from_tg_id,
+        from_tg_handle: sent.from_tg_handle,
+        to_tg_id: sent.to_tg_id,
+        to_tg_handle: sent.to_tg_handle,
+        amount: sent.amount,
+        denom: sent.denom,
+        reason,
+    }
+}
+
+/// Keeps the events and the group message readable, and the gas bounded
+pub const MAX_SPLIT_RECIPIENTS: usize = 20;
+
+pub fn _split_payment(
+    deps: DepsMut,
+    env: Env,
+    msg: SplitPaymentMsg,
+) -> Result<Response, ContractError> {
+    let SplitPaymentMsg {
+        from_tg_id,
+        from_tg,
+        amount,
+        denom,
+        recipients,
+        ..
+    } = msg;
+
+    check_denom(deps.storage, &denom)?;
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    let shares = split_shares(amount, &recipients)?;
+
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    // Charged and capped as one payment, however many ways it is split
+    let (fee, fee_transfer) = fee_leg(
+        deps.storage,
+        deps.api,
+        &env,
+        &sender.address,
+        &Coin {
+            amount,
+            denom: denom.clone(),
+        },
+    )?;
+    charge_spend_limit(
+        deps.storage,
+        &env,
+        &sender.address,
+        &Coin {
+            amount: amount.checked_add(fee)?,
+            denom: denom.clone(),
+        },
+    )?;
+
+    // Every leg goes out in the same response, so if any one fails they all do
+    let mut resp = Response::new().add_submessages(released);
+    let mut legs = vec![];
+    for (recipient, share) in recipients.into_iter().zip(shares) {
+        let share = Coin {
+            amount: share,
+            denom: denom.clone(),
+        };
+        let leg = pay(
+            deps.storage,
+            deps.api,
+            &env,
+            &sender,
+            Recipient::Handle(&recipient.to_tg),
+            &share,
+        )?;
+        // Every leg goes out in one response, which leaves nowhere to handle a transfer failing
+        ensure!(leg.ibc.is_none(), ContractError::CrossChainUnsupported);
+
+        legs.push(SplitLeg {
+            to_tg_handle: recipient.to_tg.clone(),
+            amount: share.amount,
+            pending: leg.pending_event.is_some(),
+        });
+        resp = resp
+            .add_message(leg.transfer)
+            .add_event(SplitPaymentLegEvent(SendPaymentEvent {
+                from_tg_id,
+                from_tg_handle: sender.tg_handle.clone(),
+                to_tg_id: leg.to_tg_id,
+                to_tg_handle: Some(recipient.to_tg),
+                from_address: sender.address.clone(),
+                to_address: leg.to_addr,
+                amount: share.amount,
+                denom: share.denom,
+                fee: None,
+                ibc_channel: None,
+                to_chain: None,
+            }));
+        if let Some(event) = leg.pending_event {
+            resp = resp.add_event(event);
+        }
+    }
+
+    Ok(resp
+        .add_messages(fee_transfer)
+        .add_event(SplitPaymentEvent {
+            from_tg_id,
+            from_tg_handle: sender.tg_handle,
+            from_address: sender.address,
+            denom,
+            total: amount,
+            fee: (!fee.is_zero()).then_some(fee),
+            legs,
+        }))
+}
+
+/// Long enough for a short note, short enough to keep events and chat messages tidy
+pub const MAX_MEMO_LEN: usize = 140;
+
+pub fn _request_payment(
+    deps: DepsMut,
+    env: Env,
+    msg: RequestPaymentMsg,
+) -> Result<Response, ContractError> {
+    let RequestPaymentMsg {
+        from_tg_id,
+        from_tg,
+        payer_tg,
+        amount,
+        denom,
+        memo,
+        ..
+    } = msg;
+
+    check_denom(deps.storage, &denom)?;
+    ensure!(amount > Uint256::zero(), ContractError::ZeroSend);
+    if let Some(memo) = &memo {
+        ensure!(
+            memo.chars().count() <= MAX_MEMO_LEN,
+            ContractError::MemoTooLong { max: MAX_MEMO_LEN }
+        );
+    }
+    ensure!(
+        from_tg.as_ref() != Some(&payer_tg),
+        ContractError::SelfInvoice
+    );
+
+    // The requester only needs to be able to receive, the payer is checked when they pay
+    if let Some(from_tg) = &from_tg {
+        claim_legacy_account(deps.storage, from_tg_id, from_tg)?;
+    }
+    let to_address = OPEN_ACCOUNTS
+        .may_load(deps.storage, from_tg_id)?
+        .ok_or(ContractError::RequesterNotRegistered(from_tg_id))?;
+    ensure_not_blocked(deps.storage, from_tg.as_ref(), Some(&to_address))?;
+    // Invoices are paid by the payer, straight to this address
+    ensure!(
+        ibc_route(deps.storage, deps.api, &to_address)?.is_none(),
+        ContractError::CrossChainUnsupported
+    );
+
+    set_tg_handle(deps.storage, from_tg_id, from_tg.as_ref())?;
+    let released = match &from_tg {
+        Some(from_tg) => auto_release(
+            deps.storage,
+            deps.api,
+            &env,
+            from_tg,
+            from_tg_id,
+            &to_address,
+        )?,
+        None => vec![],
+    };
+
+    let invoice = Invoice {
+        id: next_invoice_id(deps.storage)?,
+        requester_tg_id: from_tg_id,
+        requester_tg_handle: from_tg,
+        to_address,
+        payer_tg_handle: payer_tg,
+        amount,
+        denom,
+        memo,
+        created_height: env.block.height,
+        created_time: env.block.time,
+    };
+    save_invoice(deps.storage, &invoice)?;
+
+    Ok(Response::new()
+        .add_submessages(released)
+        .add_event(InvoiceCreatedEvent {
+            id: invoice.id,
+            requester_tg_id: invoice.requester_tg_id,
+            requester_tg_handle: invoice.requester_tg_handle,
+            payer_tg_handle: invoice.payer_tg_handle,
+            to_address: invoice.to_address,
+            amount: invoice.amount,
+            denom: invoice.denom,
+            memo: invoice.memo,
+        }))
+}
+
+/// Loads an open invoice, as long as it is addressed to whoever is acting on it
+fn load_invoice_for(
+    storage: &dyn Storage,
+    invoice_id: u64,
+    from_tg: Option<&TgHandle>,
+) -> Result<Invoice, ContractError> {
+    let invoice = INVOICES
+        .may_load(storage, invoice_id)?
+        .ok_or(ContractError::InvoiceNotFound(invoice_id))?;
+    ensure!(
+        from_tg == Some(&invoice.payer_tg_handle),
+        ContractError::Unauthorized
+    );
+    Ok(invoice)
+}
+
+pub fn _pay_invoice(
+    deps: DepsMut,
+    env: Env,
+    msg: InvoiceActionMsg,
+) -> Result<Response, ContractError> {
+    let InvoiceActionMsg {
+        from_tg_id,
+        from_tg,
+        invoice_id,
+        ..
+    } = msg;
+
+    let invoice = load_invoice_for(deps.storage, invoice_id, from_tg.as_ref())?;
+    // The denom may have been removed, or the requester blocked, since the invoice was created
+    check_denom(deps.storage, &invoice.denom)?;
+    ensure_not_blocked(
+        deps.storage,
+        invoice.requester_tg_handle.as_ref(),
+        Some(&invoice.to_address),
+    )?;
+    remove_invoice(deps.storage, &invoice);
+
+    // Settled exactly like a send, from the payer's own account
+    let (sender, released) = load_sender(deps.storage, deps.api, &env, from_tg_id, from_tg)?;
+    let amount = Coin {
+        amount: invoice.amount,
+        denom: invoice.denom.clone(),
+    };
+    let (fee, fee_transfer) = fee_leg(deps.storage, deps.api, &env, &sender.address, &amount)?;
+    charge_spend_limit(
+        deps.storage,
+        &env,
+        &sender.address,
+        &Coin {
+            amount: amount.amount.checked_add(fee)?,
+            denom: amount.denom.clone(),
+        },
+    )?;
+    let transfer = transfer_msg(
+        deps.api,
+        &env,
+        &sender.address,
+        &invoice.to_address,
+        &amount,
+    )?;
+
+    let record = PaymentRecord {
+        id: next_payment_id(deps.storage)?,
+        kind: PaymentKind::Direct,
+        from_tg_id: Some(from_tg_id),
+        from_tg_handle: sender.tg_handle.clone(),
+        to_tg_id: Some(invoice.requester_tg_id),
+        to_tg_handle: invoice.requester_tg_handle.clone(),
+        from_address: sender.address.clone(),
+        to_address: invoice.to_address.clone(),
+        amount: invoice.amount,
+        denom: invoice.denom.clone(),
+        block_height: env.block.height,
+        timestamp: env.block.time,
+    };
+    save_payment(deps.storage, &record)?;
+
+    Ok(Response::new()
+        .add_submessages(released)
+        .add_message(transfer)
+        .add_messages(fee_transfer)
+        .add_event(InvoicePaidEvent {
+            id: invoice.id,
+            requester_tg_id: invoice.requester_tg_id,
+            requester_tg_handle: invoice.requester_tg_handle,
+            payer_tg_id: from_tg_id,
+            payer_tg_handle: invoice.payer_tg_handle,
+            from_address: sender.address,
+            to_address: invoice.to_address,
+            amount: invoice.amount,
+            denom: invoice.denom,
+            fee: (!fee.is_zero()).then_some(fee),
+        }))
+}
+
+pub fn _decline_invoice(
+    deps: DepsMut,
+    _env: Env,
+    msg: InvoiceActionMsg,
+) -> Result<Response, ContractError> {
+    let invoice = load_invoice_for(deps.storage, msg.invoice_id, msg.from_tg.as_ref())?;
+    remove_invoice(deps.storage, &invoice);
+
+    Ok(Response::new().add_event(InvoiceDeclinedEvent {
+        id: invoice.id,
+        requester_tg_id: invoice.requester_tg_id,
+        requester_tg_handle: invoice.requester_tg_handle,
+        payer_tg_handle: invoice.payer_tg_handle,
+        amount: invoice.amount,
+        denom: invoice.denom,
+    }))
+}
+
+/// Divides `total` by weight, rounding down. The few units left over go one each to the
+/// first recipients, so the shares always add up to exactly `total`
+fn split_shares(
+    total: Uint256,
+    recipients: &[SplitRecipient],
+) -> Result<Vec<Uint256>, ContractError> {
+    ensure!(
+        !recipients.is_empty() && recipients.len() <= MAX_SPLIT_RECIPIENTS,
+        ContractError::SplitRecipients {
+            max: MAX_SPLIT_RECIPIENTS
+        }
+    );
+    for (i, recipient) in recipients.iter().enumerate() {
+        ensure!(recipient.weight > 0, ContractError::ZeroSplitWeight);
+        ensure!(
+            recipients[..i]
+                .iter()
+                .all(|other| other.to_tg != recipient.to_tg),
+            ContractError::DuplicateSplitRecipient(recipient.to_tg.to_string())
+        );
+    }
+
+    let total_weight: u64 = recipients.iter().map(|r| u64::from(r.weight)).sum();
+    let mut shares: Vec<Uint256> = recipients
+        .iter()
+        .map(|r| total.multiply_ratio(r.weight, total_weight))
+        .collect();
+
+    // Less than one unit per recipient is left over
+    let mut remainder = total - shares.iter().fold(Uint256::zero(), |sum, s| sum + s);
+    for share in shares.iter_mut() {
+        if remainder.is_zero() {
+            break;
+        }
+        *share += Uint256::one();
+        remainder -= Uint256::one();
+    }
+    // Too small a total to give everyone something
+    ensure!(
+        shares.iter().all(|share| !share.is_zero()),
+        ContractError::ZeroSend
+    );
+
+    Ok(shares)
+}
+
+fn ensure_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+    ensure!(
+        !PAUSED.may_load(storage)?.unwrap_or_default(),
+        ContractError::Paused
+    );
+    Ok(())
+}
+
+/// Rejects anyone on the blocklist, by whichever of their handle and address we know
+fn ensure_not_blocked(
+    storage: &dyn Storage,
+    handle: Option<&TgHandle>,
+    addr: Option<&Addr>,
+) -> Result<(), ContractError> {
+    if let Some(handle) = handle {
+        ensure!(
+            !BLOCKED_HANDLES.has(storage, handle.as_str()),
+            ContractError::Blocked(handle.mention())
+        );
+    }
+    if let Some(addr) = addr {
+        ensure!(
+            !BLOCKED_ADDRS.has(storage, addr),
+            ContractError::Blocked(addr.to_string())
+        );
+    }
+    Ok(())
+}
+
+fn check_denom(storage: &dyn Storage, denom: &str) -> Result<(), ContractError> {
+    let allowed_denoms = ALLOWED_DENOMS.load(storage)?;
+    ensure!(
+        allowed_denoms.iter().any(|allowed| allowed == denom),
+        ContractError::TokenNotWhitelisted {
+            token: denom.to_string()
+        }
+    );
+    Ok(())
+}
+
+/// A registered sender, checked against both sides of their registration
+struct Sender {
+    tg_id: i64,
+    tg_handle: Option<TgHandle>,
+    address: Addr,
+}
+
+/// Loads the sender, along with the messages paying out anything held for a handle they just took
+fn load_sender(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    from_tg_id: i64,
+    from_tg: Option<TgHandle>,
+) -> Result<(Sender, Vec<SubMsg>), ContractError> {
+    // Accounts registered before user ids were tracked are linked the first time the sender shows up
+    if let Some(from_tg) = &from_tg {
+        claim_legacy_account(storage, from_tg_id, from_tg)?;
+    }
+
+    // Ensure address this account is sending from
+    // FIXME: better error messages, not NotFound
+    // need to reverse lookup this to ensure it is proper
+    let from_addr = OPEN_ACCOUNTS.load(storage, from_tg_id)?;
+    let check_from = FUNDED_ACCOUNTS.load(storage, &from_addr)?;
+    ensure!(check_from == from_tg_id, ContractError::Unauthorized);
+    ensure_not_blocked(storage, from_tg.as_ref(), Some(&from_addr))?;
+
+    // Keep the sender's alias current, and pay out anything held for a handle they just took
+    set_tg_handle(storage, from_tg_id, from_tg.as_ref())?;
+    let released = match &from_tg {
+        Some(from_tg) => auto_release(storage, api, env, from_tg, from_tg_id, &from_addr)?,
+        None => vec![],
+    };
+
+    Ok((
+        Sender {
+            tg_id: from_tg_id,
+            tg_handle: from_tg,
+            address: from_addr,
+        },
+        released,
+    ))
+}
+
+/// Who a payment is for, as named in the chat
+enum Recipient<'a> {
+    /// Whoever holds the handle right now
+    Handle(&'a TgHandle),
+    /// A user Telegram identified for us, e.g. the author of a replied-to message
+    User {
+        tg_id: i64,
+        tg_handle: Option<&'a TgHandle>,
+    },
+}
+
+/// One payment to a recipient, sent straight to them or held by the contract if they haven't registered
+struct Leg {
+    /// Of the ledger record
+    id: u64,
+    to_tg_id: Option<i64>,
+    to_addr: Addr,
+    transfer: CosmosMsg,
+    pending_event: Option<PendingPaymentCreatedEvent>,
+    /// Set if `to_addr` is on another chain, the transfer only pulls the funds into the contract
+    ibc: Option<IbcRoute>,
+}
+
+fn pay(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    sender: &Sender,
+    to: Recipient,
+    amount: &Coin,
+) -> Result<Leg, ContractError> {
+    let id = next_payment_id(storage)?;
+    let mut pending_event = None;
+
+    // Figure out where to send it to
+    let ResolvedRecipient {
+        tg_id: to_tg_id,
+        tg_handle: to_tg,
+        addr: to_addr,
+    } = resolve_recipient(storage, to)?;
+    ensure_not_blocked(storage, to_tg, to_addr.as_ref())?;
+    let ibc = match &to_addr {
+        Some(addr) => ibc_route(storage, api, addr)?,
+        None => None,
+    };
+    if ibc.is_some() {
+        ensure!(
+            cw20_contract(api, &amount.denom).is_none(),
+            ContractError::CrossChainCw20
+        );
+    }
+    let (to_addr, kind) = match to_addr {
+        Some(addr) => (addr, PaymentKind::Direct),
+        None => {
+            // Pending payments are held under a handle, so there's nowhere to keep this one
+            let to_tg = to_tg.ok_or(ContractError::RecipientNotRegistered)?;
+            pending_event = Some(hold_for_handle(storage, env, id, sender, to_tg, amount)?);
+
+            // Send to this contract
+            (env.contract.address.clone(), PaymentKind::Pending)
+        }
+    };
+
+    let record = PaymentRecord {
+        id,
+        kind,
+        from_tg_id: Some(sender.tg_id),
+        from_tg_handle: sender.tg_handle.clone(),
+        to_tg_id,
+        to_tg_handle: to_tg.cloned(),
+        from_address: sender.address.clone(),
+        to_address: to_addr.clone(),
+        amount: amount.amount,
+        denom: amount.denom.clone(),
+        block_height: env.block.height,
+        timestamp: env.block.time,
+    };
+    save_payment(storage, &record)?;
+
+    // Sent on from the contract once it is in, see [`send_payment_reply`]
+    let transfer_to = match ibc {
+        Some(_) => &env.contract.address,
+        None => &to_addr,
+    };
+    let transfer = transfer_msg(api, env, &sender.address, transfer_to, amount)?;
+
+    Ok(Leg {
+        id,
+        to_tg_id,
+        to_addr,
+        transfer,
+        pending_event,
+        ibc,
+    })
+}
+
+/// Who a [`Recipient`] turned out to be, as far as we know them
+struct ResolvedRecipient<'a> {
+    tg_id: Option<i64>,
+    tg_handle: Option<&'a TgHandle>,
+    /// None if they haven't registered to receive
+    addr: Option<Addr>,
+}
+
+/// The user a payment to `to` goes to and their registered address, if they have one
+fn resolve_recipient<'a>(
+    storage: &mut dyn Storage,
+    to: Recipient<'a>,
+) -> Result<ResolvedRecipient<'a>, ContractError> {
+    match to {
+        Recipient::Handle(to_tg) => {
+            let to_tg_id = TG_USER_IDS.may_load(storage, to_tg.as_str())?;
+            let to_addr = match to_tg_id {
+                Some(to_tg_id) => OPEN_ACCOUNTS.may_load(storage, to_tg_id)?,
+                None => LEGACY_OPEN_ACCOUNTS.may_load(storage, to_tg.as_str())?,
+            };
+            Ok(ResolvedRecipient {
+                tg_id: to_tg_id,
+                tg_handle: Some(to_tg),
+                addr: to_addr,
+            })
+        }
+        Recipient::User { tg_id, tg_handle } => {
+            // Telegram vouches for who holds the handle, so it's safe to link up a legacy account
+            if let Some(tg_handle) = tg_handle {
+                claim_legacy_account(storage, tg_id, tg_handle)?;
+            }
+            match OPEN_ACCOUNTS.may_load(storage, tg_id)? {
+                Some(to_addr) => {
+                    set_tg_handle(storage, tg_id, tg_handle)?;
+                    Ok(ResolvedRecipient {
+                        tg_id: Some(tg_id),
+                        tg_handle,
+                        addr: Some(to_addr),
+                    })
+                }
+                None => Ok(ResolvedRecipient {
+                    tg_id: None,
+                    tg_handle,
+                    addr: None,
+                }),
+            }
+        }
+    }
+}
+
+/// Adds a payment the contract is taking in to what is held for `to_tg`, with ledger id `id`
+fn hold_for_handle(
+    storage: &mut dyn Storage,
+    env: &Env,
+    id: u64,
+    sender: &Sender,
+    to_tg: &TgHandle,
+    amount: &Coin,
+) -> StdResult<PendingPaymentCreatedEvent> {
+    // Record the pending payment
+    let mut pending = PENDING_PAYMENTS
+        .may_load(storage, to_tg.as_str())?
+        .unwrap_or_default();
+    pending.add_payment(amount.clone());
+    PENDING_PAYMENTS.save(storage, to_tg.as_str(), &pending)?;
+
+    // And remember who it came from, so it can be refunded if never claimed
+    let expires = match PENDING_EXPIRY.may_load(storage)? {
+        Some(expiry) => expiry.after(&env.block),
+        None => Expiration::Never {},
+    };
+    save_pending_entry(
+        storage,
+        &PendingPayment {
+            id,
+            from_tg_id: sender.tg_id,
+            from_tg_handle: sender.tg_handle.clone(),
+            from_address: sender.address.clone(),
+            to_tg_handle: to_tg.clone(),
+            amount: amount.amount,
+            denom: amount.denom.clone(),
+            created_height: env.block.height,
+            created_time: env.block.time,
+            expires,
+        },
+    )?;
+
+    Ok(PendingPaymentCreatedEvent {
+        id,
+        from_tg_id: sender.tg_id,
+        from_tg_handle: sender.tg_handle.clone(),
+        to_tg_handle: to_tg.clone(),
+        from_address: sender.address.clone(),
+        amount: amount.amount,
+        denom: amount.denom.clone(),
+    })
+}
+
+/// What the sender pays the treasury on top of `amount`, and the transfer for it if it isn't zero
+fn fee_leg(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    from_addr: &Addr,
+    amount: &Coin,
+) -> Result<(Uint256, Option<CosmosMsg>), ContractError> {
+    let Some(fee) = FEE.may_load(storage)? else {
+        return Ok((Uint256::zero(), None));
+    };
+    let fee_amount = fee_for(&fee, amount)?;
+    if fee_amount.is_zero() {
+        return Ok((fee_amount, None));
+    }
+
+    // Validated before it was saved
+    let treasury = Addr::unchecked(fee.treasury);
+    let transfer = transfer_msg(
+        api,
+        env,
+        from_addr,
+        &treasury,
+        &Coin {
+            amount: fee_amount,
+            denom: amount.denom.clone(),
+        },
+    )?;
+
+    Ok((fee_amount, Some(transfer)))
+}
+
+/// Holds the payment to the caps the sender set on their account, and counts it towards the window
+fn charge_spend_limit(
+    storage: &mut dyn Storage,
+    env: &Env,
+    addr: &Addr,
+    amount: &Coin,
+) -> Result<(), ContractError> {
+    let Some(limit) = SPEND_LIMITS.may_load(storage, (addr, &amount.denom))? else {
+        return Ok(());
+    };
+
+    let mut spends = spends_in_window(storage, addr, &amount.denom, &limit, env.block.time)?;
+    if let Some(remaining) = remaining_spend(&limit, &spends) {
+        ensure!(
+            amount.amount <= remaining,
+            ContractError::SpendLimitExceeded {
+                remaining,
+                denom: amount.denom.clone(),
+            }
+        );
+    }
+
+    if limit.per_window.is_some() {
+        spends.push(Spend {
+            time: env.block.time,
+            amount: amount.amount,
+        });
+        SPENDS.save(storage, (addr, &amount.denom), &spends)?;
+    }
+
+    Ok(())
+}
+
+/// Undoes [`charge_spend_limit`] for a payment made at `time` whose transfer failed
+fn refund_spend_limit(
+    storage: &mut dyn Storage,
+    time: Timestamp,
+    addr: &Addr,
+    amount: &Coin,
+) -> StdResult<()> {
+    let Some(mut spends) = SPENDS.may_load(storage, (addr, &amount.denom))? else {
+        return Ok(());
+    };
+    if let Some(i) = spends
+        .iter()
+        .rposition(|spend| spend.time == time && spend.amount == amount.amount)
+    {
+        spends.remove(i);
+        SPENDS.save(storage, (addr, &amount.denom), &spends)?;
+    }
+
+    Ok(())
+}
+
+/// Moves funds out of the sender's own account, through the authz grant or CW20 allowance they gave us
+fn transfer_msg(
+    api: &dyn Api,
+    env: &Env,
+    from_addr: &Addr,
+    to_addr: &Addr,
+    amount: &Coin,
+) -> Result<CosmosMsg, ContractError> {
+    let msg: CosmosMsg = match cw20_contract(api, &amount.denom) {
+        // CW20s are pulled through the allowance the sender gave this contract
+        Some(token) => WasmMsg::Execute {
+            contract_addr: token.into(),
+            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: from_addr.to_string(),
+                recipient: to_addr.to_string(),
+                amount: Uint128::try_from(amount.amount)?,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+        None => {
+            // Custom bank MsgSend from the original sender, not the contract
+            let msg_send = MsgSend {
+                from_address: from_addr.to_string(),
+                to_address: to_addr.to_string(),
+                amount: vec![ProtoCoin {
+                    amount: amount.amount.to_string(),
+                    denom: amount.denom.clone(),
+                }],
+            };
+            // Wrapped in an authz MsgExec so it uses our allowances to send
+            let msg_exec = MsgExec {
+                grantee: env.contract.address.to_string(),
+                msgs: vec![Any {
+                    type_url: MsgSend::type_url(),
+                    value: msg_send.encode_to_vec().into(),
+                }],
+            };
+            // Converted into opaque protobut AnyMsg for wasmd to handle
+            AnyMsg {
+                type_url: MsgExec::type_url(),
+                value: msg_exec.encode_to_vec().into(),
+            }
+            .into()
+        }
+    };
+
+    Ok(msg)
+}
+
+const DEFAULT_REFUND_LIMIT: u32 = 10;
+const MAX_REFUND_LIMIT: u32 = 30;
+
+pub fn refund_expired(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_REFUND_LIMIT).min(MAX_REFUND_LIMIT) as usize;
+
+    let expired = expired_pending_entries(deps.storage, &env.block, limit)?;
+
+    let mut resp = Response::new();
+
+    for entry in expired {
+        take_pending_entry(deps.storage, &entry)?;
+        let coin = Coin {
+            amount: entry.amount,
+            denom: entry.denom.clone(),
+        };
+
+        let record = PaymentRecord {
+            id: next_payment_id(deps.storage)?,
+            kind: PaymentKind::Refunded,
+            from_tg_id: None,
+            from_tg_handle: None,
+            to_tg_id: Some(entry.from_tg_id),
+            to_tg_handle: entry.from_tg_handle.clone(),
+            from_address: env.contract.address.clone(),
+            to_address: entry.from_address.clone(),
+            amount: entry.amount,
+            denom: entry.denom.clone(),
+            block_height: env.block.height,
+            timestamp: env.block.time,
+        };
+        save_payment(deps.storage, &record)?;
+
+        resp = resp
+            .add_messages(send_held_funds(deps.api, &entry.from_address, vec![coin])?)
+            .add_event(PendingPaymentRefundedEvent {
+                id: entry.id,
+                from_tg_id: entry.from_tg_id,
+                from_tg_handle: entry.from_tg_handle,
+                to_tg_handle: entry.to_tg_handle,
+                refund_address: entry.from_address,
+                amount: entry.amount,
+                denom: entry.denom,
+            });
+    }
+
+    Ok(resp)
+}
+
+/// What is left of giveaways once they expire goes back to the address it came from, like gifts.
+/// The fee isn't returned.
+pub fn refund_expired_giveaways(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_REFUND_LIMIT).min(MAX_REFUND_LIMIT) as usize;
+    let expired = expired_giveaways(deps.storage, env.block.time, limit)?;
+
+    let mut resp = Response::new();
+    for giveaway in expired {
+        remove_giveaway(deps.storage, &giveaway);
+        let coin = Coin {
+            amount: giveaway.remaining,
+            denom: giveaway.denom.clone(),
+        };
+
+        let record = PaymentRecord {
+            id: next_payment_id(deps.storage)?,
+            kind: PaymentKind::Refunded,
+            from_tg_id: None,
+            from_tg_handle: None,
+            to_tg_id: Some(giveaway.from_tg_id),
+            to_tg_handle: giveaway.from_tg_handle.clone(),
+            from_address: env.contract.address.clone(),
+            to_address: giveaway.from_address.clone(),
+            amount: giveaway.remaining,
+            denom: giveaway.denom.clone(),
+            block_height: env.block.height,
+            timestamp: env.block.time,
+        };
+        save_payment(deps.storage, &record)?;
+
+        resp = resp
+            .add_messages(send_held_funds(
+                deps.api,
+                &giveaway.from_address,
+                vec![coin],
+            )?)
+            .add_event(GiveawayRefundedEvent {
+                id: giveaway.id,
+                chat_id: giveaway.chat_id,
+                from_tg_id: giveaway.from_tg_id,
+                from_tg_handle: giveaway.from_tg_handle,
+                from_address: giveaway.from_address,
+                amount: giveaway.remaining,
+                denom: giveaway.denom,
+                claims: giveaway.claimers.len() as u32,
+            });
+    }
+
+    Ok(resp)
+}
+
+/// Gifts nobody claimed in time go back to the address they came from, even if the sender has
+/// registered another since. The fee isn't returned.
+pub fn refund_expired_gifts(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_REFUND_LIMIT).min(MAX_REFUND_LIMIT) as usize;
+    let expired = expired_gifts(deps.storage, env.block.time, limit)?;
+
+    let mut resp = Response::new();
+    for gift in expired {
+        remove_gift(deps.storage, &gift);
+        let coin = Coin {
+            amount: gift.amount,
+            denom: gift.denom.clone(),
+        };
+
+        let record = PaymentRecord {
+            id: next_payment_id(deps.storage)?,
+            kind: PaymentKind::Refunded,
+            from_tg_id: None,
+            from_tg_handle: None,
+            to_tg_id: Some(gift.from_tg_id),
+            to_tg_handle: gift.from_tg_handle.clone(),
+            from_address: env.contract.address.clone(),
+            to_address: gift.from_address.clone(),
+            amount: gift.amount,
+            denom: gift.denom.clone(),
+            block_height: env.block.height,
+            timestamp: env.block.time,
+        };
+        save_payment(deps.storage, &record)?;
+
+        resp = resp
+            .add_messages(send_held_funds(deps.api, &gift.from_address, vec![coin])?)
+            .add_event(GiftRefundedEvent {
+                id: gift.id,
+                from_tg_id: gift.from_tg_id,
+                from_tg_handle: gift.from_tg_handle,
+                from_address: gift.from_address,
+                amount: gift.amount,
+                denom: gift.denom,
+            });
+    }
+
+    Ok(resp)
+}
+
+/// Pays out what is held for a handle, from the address its owner registered, either to that
+/// address or to `to`. This works whether or not pull claims are on, so nothing is stranded when
+/// they are turned off, and like refunds it keeps working while paused.
+pub fn claim_pending(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    tg_handle: TgHandle,
+    to: Option<String>,
+) -> Result<Response, ContractError> {
+    let tg_user_id = TG_USER_IDS
+        .may_load(deps.storage, tg_handle.as_str())?
+        .ok_or(ContractError::Unauthorized)?;
+    let registered = OPEN_ACCOUNTS
+        .may_load(deps.storage, tg_user_id)?
+        .ok_or(ContractError::Unauthorized)?;
+    ensure!(info.sender == registered, ContractError::Unauthorized);
+
+    let to_addr = match to {
+        Some(to) => receive_addr(deps.storage, deps.api, &to)?,
+        None => registered,
+    };
+    ensure_not_blocked(deps.storage, Some(&tg_handle), Some(&to_addr))?;
+
+    let amount = PENDING_PAYMENTS
+        .may_load(deps.storage, tg_handle.as_str())?
+        .map(|pending| pending.balance())
+        .unwrap_or_default();
+    ensure!(
+        !amount.is_empty(),
+        ContractError::NothingToClaim(tg_handle.to_string())
+    );
+
+    let msgs = release_pending(
+        deps.storage,
+        deps.api,
+        &env,
+        &tg_handle,
+        tg_user_id,
+        &to_addr,
+    )?;
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_event(PendingClaimedEvent {
+            tg_user_id,
+            tg_handle,
+            address: to_addr,
+            amount,
+        }))
+}
+
+fn ensure_owner(storage: &dyn Storage, sender: &Addr) -> Result<Addr, ContractError> {
+    let owner = OWNER.load(storage)?;
+    ensure!(*sender == owner, ContractError::Unauthorized);
+    Ok(owner)
+}
+
+pub fn transfer_ownership(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    new_owner: String,
+) -> Result<Response, ContractError> {
+    let owner = ensure_owner(deps.storage, &info.sender)?;
+    let pending_owner = deps.api.addr_validate(&new_owner)?;
+    PENDING_OWNER.save(deps.storage, &pending_owner)?;
+
+    Ok(Response::new().add_event(OwnershipTransferProposedEvent {
+        owner,
+        pending_owner: Some(pending_owner),
+    }))
+}
+
+pub fn accept_ownership(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingOwner)?;
+    ensure!(info.sender == pending_owner, ContractError::Unauthorized);
+
+    let previous_owner = OWNER.load(deps.storage)?;
+    OWNER.save(deps.storage, &pending_owner)?;
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_event(OwnershipTransferredEvent {
+        previous_owner,
+        new_owner: pending_owner,
+    }))
+}
+
+pub fn cancel_ownership_transfer(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let owner = ensure_owner(deps.storage, &info.sender)?;
+    ensure!(
+        PENDING_OWNER.exists(deps.storage),
+        ContractError::NoPendingOwner
+    );
+    PENDING_OWNER.remove(deps.storage);
+
+    Ok(Response::new().add_event(OwnershipTransferProposedEvent {
+        owner,
+        pending_owner: None,
+    }))
+}
+
+pub fn add_denoms(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denoms: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+
+    let mut allowed = ALLOWED_DENOMS.load(deps.storage)?;
+    for denom in &denoms {
+        ensure!(
+            !allowed.contains(denom),
+            ContractError::DenomAlreadyAllowed {
+                denom: denom.clone()
+            }
+        );
+        allowed.push(denom.clone());
+    }
+    ALLOWED_DENOMS.save(deps.storage, &allowed)?;
+
+    Ok(Response::new().add_event(DenomsUpdatedEvent {
+        added: denoms,
+        removed: vec![],
+    }))
+}
+
+pub fn remove_denoms(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denoms: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+
+    let mut allowed = ALLOWED_DENOMS.load(deps.storage)?;
+    for denom in &denoms {
+        let index = allowed.iter().position(|d| d == denom).ok_or_else(|| {
+            ContractError::TokenNotWhitelisted {
+                token: denom.clone(),
+            }
+        })?;
+        allowed.remove(index);
+    }
+    ALLOWED_DENOMS.save(deps.storage, &allowed)?;
+
+    Ok(Response::new().add_event(DenomsUpdatedEvent {
+        added: vec![],
+        removed: denoms,
+    }))
+}
+
+pub fn set_auth(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    auth: Auth,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+
+    let previous = load_auth(deps.storage)?;
+    save_auth(deps.storage, deps.api, &auth)?;
+
+    Ok(Response::new().add_event(AuthUpdatedEvent { previous, auth }))
+}
+
+/// Keeps a misconfigured fee from taking most of a payment
+pub const MAX_FEE_BASIS_POINTS: u16 = 1000;
+
+/// Validates the fee, with the treasury in its canonical form so it can be saved as is
+pub fn check_fee(api: &dyn Api, fee: FeeConfig) -> Result<FeeConfig, ContractError> {
+    let invalid = || ContractError::InvalidFee {
+        max_basis_points: MAX_FEE_BASIS_POINTS,
+    };
+    ensure!(fee.basis_points <= MAX_FEE_BASIS_POINTS, invalid());
+    for (i, coin) in fee.flat.iter().enumerate() {
+        ensure!(
+            !coin.amount.is_zero() && fee.flat[..i].iter().all(|other| other.denom != coin.denom),
+            invalid()
+        );
+    }
+
+    Ok(FeeConfig {
+        treasury: api.addr_validate(&fee.treasury)?.into(),
+        ..fee
+    })
+}
+
+pub fn set_fee(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    fee: Option<FeeConfig>,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+
+    let fee = fee.map(|fee| check_fee(deps.api, fee)).transpose()?;
+    match &fee {
+        Some(fee) => FEE.save(deps.storage, fee)?,
+        None => FEE.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_event(FeeUpdatedEvent { fee }))
+}
+
+pub fn set_paused(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    PAUSED.save(deps.storage, &paused)?;
+
+    Ok(Response::new().add_event(PauseUpdatedEvent { paused }))
+}
+
+pub fn set_pull_claims(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    PULL_CLAIMS.save(deps.storage, &enabled)?;
+
+    Ok(Response::new().add_event(PullClaimsUpdatedEvent { enabled }))
+}
+
+pub fn set_pending_expiry(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    expiry: Option<Duration>,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    save_pending_expiry(deps.storage, expiry)?;
+
+    Ok(Response::new().add_event(PendingExpiryUpdatedEvent { expiry }))
+}
+
+/// Payments already held keep the expiry they were made with
+pub fn save_pending_expiry(
+    storage: &mut dyn Storage,
+    expiry: Option<Duration>,
+) -> Result<(), ContractError> {
+    match expiry {
+        Some(Duration::Height(0) | Duration::Time(0)) => {
+            return Err(ContractError::InvalidPendingExpiry)
+        }
+        Some(expiry) => PENDING_EXPIRY.save(storage, &expiry)?,
+        None => PENDING_EXPIRY.remove(storage),
+    }
+
+    Ok(())
+}
+
+pub fn set_escrow_window(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    window: Option<Duration>,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    // Sends already held keep the window they were made with
+    match window {
+        Some(Duration::Height(0) | Duration::Time(0)) => {
+            return Err(ContractError::InvalidEscrowWindow)
+        }
+        Some(window) => ESCROW_WINDOW.save(deps.storage, &window)?,
+        None => ESCROW_WINDOW.remove(deps.storage),
+    }
+
+    Ok(Response::new().add_event(EscrowWindowUpdatedEvent { window }))
+}
+
+pub fn update_blocklist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    blocked: bool,
+    handles: Vec<TgHandle>,
+    addresses: Vec<String>,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+
+    let addresses = addresses
+        .iter()
+        .map(|addr| deps.api.addr_validate(addr))
+        .collect::<StdResult<Vec<_>>>()?;
+    for handle in &handles {
+        if blocked {
+            BLOCKED_HANDLES.save(deps.storage, handle.as_str(), &())?;
+        } else {
+            BLOCKED_HANDLES.remove(deps.storage, handle.as_str());
+        }
+    }
+    for addr in &addresses {
+        if blocked {
+            BLOCKED_ADDRS.save(deps.storage, addr, &())?;
+        } else {
+            BLOCKED_ADDRS.remove(deps.storage, addr);
+        }
+    }
+
+    Ok(Response::new().add_event(BlocklistUpdatedEvent {
+        blocked,
+        handles,
+        addresses,
+    }))
+}
+
+/// Long enough to ride out a relayer outage, short enough that a stuck send is refunded within the week
+pub const MAX_IBC_TIMEOUT_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+pub fn set_ibc_route(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    route: IbcRoute,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    // Prefixes are matched against lowercased addresses
+    ensure!(
+        !route.prefix.is_empty()
+            && route.prefix == route.prefix.to_ascii_lowercase()
+            && !route.channel_id.is_empty()
+            && !route.chain_id.is_empty()
+            && (1..=MAX_IBC_TIMEOUT_SECONDS).contains(&route.timeout_seconds),
+        ContractError::InvalidIbcRoute {
+            max_timeout_seconds: MAX_IBC_TIMEOUT_SECONDS
+        }
+    );
+    IBC_ROUTES.save(deps.storage, &route.prefix, &route)?;
+
+    Ok(Response::new().add_event(IbcRouteUpdatedEvent {
+        prefix: route.prefix.clone(),
+        route: Some(route),
+    }))
+}
+
+pub fn remove_ibc_route(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    prefix: String,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    IBC_ROUTES.remove(deps.storage, &prefix);
+
+    Ok(Response::new().add_event(IbcRouteUpdatedEvent {
+        prefix,
+        route: None,
+    }))
+}
+
+/// Past this a whole token no longer fits comfortably in the amounts users type
+pub const MAX_DENOM_EXPONENT: u32 = 18;
+
+pub fn set_denom_metadata(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    metadata: DenomMetadata,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    // Names end up as a single word in bot commands and in comma separated event attributes
+    let valid_name =
+        |name: &String| !name.is_empty() && !name.contains(|c: char| c.is_whitespace() || c == ',');
+    ensure!(
+        valid_name(&metadata.denom)
+            && valid_name(&metadata.symbol)
+            && metadata.aliases.iter().all(valid_name)
+            && metadata.exponent <= MAX_DENOM_EXPONENT,
+        ContractError::InvalidDenomMetadata {
+            max_exponent: MAX_DENOM_EXPONENT
+        }
+    );
+
+    // Symbols and aliases are typed case-insensitively, so no name may point at two denoms
+    for item in DENOM_METADATA.range(deps.storage, None, None, Order::Ascending) {
+        let (denom, other) = item?;
+        if denom == metadata.denom {
+            continue;
+        }
+        let taken: Vec<&String> = std::iter::once(&other.denom)
+            .chain(std::iter::once(&other.symbol))
+            .chain(&other.aliases)
+            .collect();
+        let names = std::iter::once(&metadata.denom)
+            .chain(std::iter::once(&metadata.symbol))
+            .chain(&metadata.aliases);
+        for name in names {
+            ensure!(
+                !taken.iter().any(|t| t.eq_ignore_ascii_case(name)),
+                ContractError::DenomNameTaken(name.clone())
+            );
+        }
+    }
+    DENOM_METADATA.save(deps.storage, &metadata.denom, &metadata)?;
+
+    Ok(Response::new().add_event(DenomMetadataUpdatedEvent {
+        denom: metadata.denom.clone(),
+        metadata: Some(metadata),
+    }))
+}
+
+pub fn remove_denom_metadata(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    denom: String,
+) -> Result<Response, ContractError> {
+    ensure_owner(deps.storage, &info.sender)?;
+    DENOM_METADATA.remove(deps.storage, &denom);
+
+    Ok(Response::new().add_event(DenomMetadataUpdatedEvent {
+        denom,
+        metadata: None,
+    }))
+}
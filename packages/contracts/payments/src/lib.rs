@@ -1,7 +1,8 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+    to_json_binary, Binary, Deps, DepsMut, Env, IbcBasicResponse, IbcSourceCallbackMsg,
+    MessageInfo, Reply, Response, StdResult,
 };
 use cw2::set_contract_version;
 use tg_contract_api::payments::msg::{
@@ -12,7 +13,10 @@ use wavs_types::contracts::cosmwasm::service_handler::{
 };
 
 use crate::error::ContractError;
-use crate::state::{ADMIN, ALLOWED_DENOMS, SERVICE_MANAGER};
+use crate::state::{
+    backfill_stats, canonicalize_legacy_handles, claim_legacy_account, index_escrow_releases,
+    index_pending_expiries, save_auth, AccountCounts, ACCOUNT_COUNTS, ALLOWED_DENOMS, FEE, OWNER,
+};
 
 mod error;
 mod execute;
@@ -29,28 +33,46 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     let mut resp = Response::new().add_attribute("method", "instantiate");
 
+    let owner = match msg.owner {
+        Some(owner) => deps.api.addr_validate(&owner)?,
+        None => info.sender,
+    };
+    OWNER.save(deps.storage, &owner)?;
+    resp = resp.add_attribute("owner", owner);
+
     // Set admin or service manager for later validation
+    save_auth(deps.storage, deps.api, &msg.auth)?;
     match msg.auth {
         Auth::ServiceManager(service_manager) => {
-            let service_manager_addr = deps.api.addr_validate(&service_manager)?;
-            SERVICE_MANAGER.save(deps.storage, &service_manager_addr)?;
             resp = resp.add_attribute("service_manager", service_manager);
         }
         Auth::Admin(admin) => {
-            let admin_addr = deps.api.addr_validate(&admin)?;
-            ADMIN.save(deps.storage, &admin_addr)?;
             resp = resp.add_attribute("admin", admin);
         }
     }
 
     ALLOWED_DENOMS.save(deps.storage, &msg.allowed_denoms)?;
+    ACCOUNT_COUNTS.save(deps.storage, &AccountCounts::default())?;
+
+    if let Some(expiry) = msg.pending_expiry {
+        execute::save_pending_expiry(deps.storage, Some(expiry))?;
+        resp = resp.add_attribute("pending_expiry", expiry.to_string());
+    }
+
+    if let Some(fee) = msg.fee {
+        let fee = execute::check_fee(deps.api, fee)?;
+        resp = resp
+            .add_attribute("fee_basis_points", fee.basis_points.to_string())
+            .add_attribute("fee_treasury", &fee.treasury);
+        FEE.save(deps.storage, &fee)?;
+    }
 
     Ok(resp)
 }
@@ -70,7 +92,93 @@ pub fn execute(
             CustomExecuteMsg::RegisterSend { tg_handle } => {
                 execute::register_send(deps, env, info, tg_handle)
             }
+            CustomExecuteMsg::SetSpendLimit { denom, limit } => {
+                execute::set_spend_limit(deps, env, info, denom, limit)
+            }
             CustomExecuteMsg::SendPayment(msg) => execute::send_payment(deps, env, info, msg),
+            CustomExecuteMsg::SplitPayment(msg) => execute::split_payment(deps, env, info, msg),
+            CustomExecuteMsg::Tip(msg) => execute::tip(deps, env, info, msg),
+            CustomExecuteMsg::RequestPayment(msg) => execute::request_payment(deps, env, info, msg),
+            CustomExecuteMsg::PayInvoice(msg) => execute::pay_invoice(deps, env, info, msg),
+            CustomExecuteMsg::DeclineInvoice(msg) => execute::decline_invoice(deps, env, info, msg),
+            CustomExecuteMsg::RefundExpired { limit } => {
+                execute::refund_expired(deps, env, info, limit)
+            }
+            CustomExecuteMsg::RefundExpiredGifts { limit } => {
+                execute::refund_expired_gifts(deps, env, info, limit)
+            }
+            CustomExecuteMsg::RefundExpiredGiveaways { limit } => {
+                execute::refund_expired_giveaways(deps, env, info, limit)
+            }
+            CustomExecuteMsg::TransferOwnership { new_owner } => {
+                execute::transfer_ownership(deps, env, info, new_owner)
+            }
+            CustomExecuteMsg::AcceptOwnership {} => execute::accept_ownership(deps, env, info),
+            CustomExecuteMsg::CancelOwnershipTransfer {} => {
+                execute::cancel_ownership_transfer(deps, env, info)
+            }
+            CustomExecuteMsg::AddDenoms { denoms } => execute::add_denoms(deps, env, info, denoms),
+            CustomExecuteMsg::RemoveDenoms { denoms } => {
+                execute::remove_denoms(deps, env, info, denoms)
+            }
+            CustomExecuteMsg::SetAuth { auth } => execute::set_auth(deps, env, info, auth),
+            CustomExecuteMsg::SetFee { fee } => execute::set_fee(deps, env, info, fee),
+            CustomExecuteMsg::SetPendingExpiry { expiry } => {
+                execute::set_pending_expiry(deps, env, info, expiry)
+            }
+            CustomExecuteMsg::SetPaused { paused } => execute::set_paused(deps, env, info, paused),
+            CustomExecuteMsg::Block { handles, addresses } => {
+                execute::update_blocklist(deps, env, info, true, handles, addresses)
+            }
+            CustomExecuteMsg::Unblock { handles, addresses } => {
+                execute::update_blocklist(deps, env, info, false, handles, addresses)
+            }
+            CustomExecuteMsg::SetIbcRoute { route } => {
+                execute::set_ibc_route(deps, env, info, route)
+            }
+            CustomExecuteMsg::RemoveIbcRoute { prefix } => {
+                execute::remove_ibc_route(deps, env, info, prefix)
+            }
+            CustomExecuteMsg::SetDenomMetadata { metadata } => {
+                execute::set_denom_metadata(deps, env, info, metadata)
+            }
+            CustomExecuteMsg::RemoveDenomMetadata { denom } => {
+                execute::remove_denom_metadata(deps, env, info, denom)
+            }
+            CustomExecuteMsg::ProcessBatchEntry { payload } => {
+                execute::process_batch_entry(deps, env, info, payload)
+            }
+            CustomExecuteMsg::SetPullClaims { enabled } => {
+                execute::set_pull_claims(deps, env, info, enabled)
+            }
+            CustomExecuteMsg::ClaimPending { tg_handle, to } => {
+                execute::claim_pending(deps, env, info, tg_handle, to)
+            }
+            CustomExecuteMsg::CancelPayment(msg) => execute::cancel_payment(deps, env, info, msg),
+            CustomExecuteMsg::SetEscrowWindow { window } => {
+                execute::set_escrow_window(deps, env, info, window)
+            }
+            CustomExecuteMsg::ReleaseMatured { limit } => {
+                execute::release_matured(deps, env, limit)
+            }
+            CustomExecuteMsg::SchedulePayment(msg) => {
+                execute::schedule_payment(deps, env, info, msg)
+            }
+            CustomExecuteMsg::Unschedule(msg) => execute::unschedule(deps, env, info, msg),
+            CustomExecuteMsg::RunSchedules { runs } => {
+                execute::run_schedules(deps, env, info, runs)
+            }
+            CustomExecuteMsg::ProcessScheduleRun { payment } => {
+                execute::process_schedule_run(deps, env, info, payment)
+            }
+            CustomExecuteMsg::CreateTreasury(msg) => execute::create_treasury(deps, env, info, msg),
+            CustomExecuteMsg::FundTreasury(msg) => execute::fund_treasury(deps, env, info, msg),
+            CustomExecuteMsg::ProposePayout(msg) => execute::propose_payout(deps, env, info, msg),
+            CustomExecuteMsg::Vote(msg) => execute::vote(deps, env, info, msg),
+            CustomExecuteMsg::CreateGift(msg) => execute::create_gift(deps, env, info, msg),
+            CustomExecuteMsg::ClaimGift(msg) => execute::claim_gift(deps, env, info, msg),
+            CustomExecuteMsg::CreateGiveaway(msg) => execute::create_giveaway(deps, env, info, msg),
+            CustomExecuteMsg::ClaimGiveaway(msg) => execute::claim_giveaway(deps, env, info, msg),
         },
         ExecuteMsg::Wavs(msg) => match msg {
             ServiceHandlerExecuteMessages::WavsHandleSignedEnvelope {
@@ -82,20 +190,129 @@ pub fn execute(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Custom(msg) => match msg {
             CustomQueryMsg::AddrByTg { handle } => {
                 to_json_binary(&query::addr_by_tg(deps, handle)?)
             }
+            CustomQueryMsg::AddrByTgId { user_id } => {
+                to_json_binary(&query::addr_by_tg_id(deps, user_id)?)
+            }
             CustomQueryMsg::TgByAddr { account } => {
                 to_json_binary(&query::tg_by_addr(deps, account)?)
             }
             CustomQueryMsg::Admin {} => to_json_binary(&query::admin(deps)?),
+            CustomQueryMsg::Owner {} => to_json_binary(&query::owner(deps)?),
+            CustomQueryMsg::Auth {} => to_json_binary(&query::auth(deps)?),
             CustomQueryMsg::PendingPayments { handle } => {
                 to_json_binary(&query::pending_payments(deps, handle)?)
             }
+            CustomQueryMsg::PendingPaymentsByTg {
+                handle,
+                start_after,
+                limit,
+            } => to_json_binary(&query::pending_payments_by_tg(
+                deps,
+                handle,
+                start_after,
+                limit,
+            )?),
+            CustomQueryMsg::PendingExpiry {} => to_json_binary(&query::pending_expiry(deps)?),
             CustomQueryMsg::AllowedDenoms {} => to_json_binary(&query::allowed_denoms(deps)?),
+            CustomQueryMsg::PaymentsByTg {
+                handle,
+                start_after,
+                limit,
+            } => to_json_binary(&query::payments_by_tg(deps, handle, start_after, limit)?),
+            CustomQueryMsg::PaymentsByTgId {
+                user_id,
+                start_after,
+                limit,
+            } => to_json_binary(&query::payments_by_tg_id(
+                deps,
+                user_id,
+                start_after,
+                limit,
+            )?),
+            CustomQueryMsg::PaymentsByAddr {
+                account,
+                start_after,
+                limit,
+            } => to_json_binary(&query::payments_by_addr(deps, account, start_after, limit)?),
+            CustomQueryMsg::PaymentsByDenom {
+                denom,
+                start_after,
+                limit,
+            } => to_json_binary(&query::payments_by_denom(deps, denom, start_after, limit)?),
+            CustomQueryMsg::Invoice { id } => to_json_binary(&query::invoice(deps, id)?),
+            CustomQueryMsg::InvoicesByPayer {
+                handle,
+                start_after,
+                limit,
+            } => to_json_binary(&query::invoices_by_payer(deps, handle, start_after, limit)?),
+            CustomQueryMsg::SpendLimit { account, denom } => {
+                to_json_binary(&query::spend_limit(deps, env, account, denom)?)
+            }
+            CustomQueryMsg::SendGrant { account } => {
+                to_json_binary(&query::send_grant(deps, env, account)?)
+            }
+            CustomQueryMsg::Fee {} => to_json_binary(&query::fee(deps)?),
+            CustomQueryMsg::FeeQuote { amount, denom } => {
+                to_json_binary(&query::fee_quote(deps, amount, denom)?)
+            }
+            CustomQueryMsg::Paused {} => to_json_binary(&query::paused(deps)?),
+            CustomQueryMsg::Blocked { handle, account } => {
+                to_json_binary(&query::blocked(deps, handle, account)?)
+            }
+            CustomQueryMsg::ListAccounts { start_after, limit } => {
+                to_json_binary(&query::list_accounts(deps, start_after, limit)?)
+            }
+            CustomQueryMsg::ListFundedAccounts { start_after, limit } => {
+                to_json_binary(&query::list_funded_accounts(deps, start_after, limit)?)
+            }
+            CustomQueryMsg::ListPendingPayments { start_after, limit } => {
+                to_json_binary(&query::list_pending_payments(deps, start_after, limit)?)
+            }
+            CustomQueryMsg::Stats {} => to_json_binary(&query::stats(deps)?),
+            CustomQueryMsg::IbcRoutes {} => to_json_binary(&query::ibc_routes(deps)?),
+            CustomQueryMsg::DenomMetadata {} => to_json_binary(&query::denom_metadata(deps)?),
+            CustomQueryMsg::PullClaims {} => to_json_binary(&query::pull_claims(deps)?),
+            CustomQueryMsg::EscrowWindow {} => to_json_binary(&query::escrow_window(deps)?),
+            CustomQueryMsg::Escrows {
+                from_tg_id,
+                start_after,
+                limit,
+            } => to_json_binary(&query::escrows(deps, from_tg_id, start_after, limit)?),
+            CustomQueryMsg::MaturedEscrows {} => {
+                to_json_binary(&query::matured_escrows_count(deps, env)?)
+            }
+            CustomQueryMsg::Schedule { id } => to_json_binary(&query::schedule(deps, id)?),
+            CustomQueryMsg::Schedules {
+                from_tg_id,
+                start_after,
+                limit,
+            } => to_json_binary(&query::schedules(deps, from_tg_id, start_after, limit)?),
+            CustomQueryMsg::DueSchedules { limit } => {
+                to_json_binary(&query::due_schedules(deps, env, limit)?)
+            }
+            CustomQueryMsg::Treasury { chat_id } => {
+                to_json_binary(&query::treasury(deps, chat_id)?)
+            }
+            CustomQueryMsg::Proposal { id } => to_json_binary(&query::proposal(deps, id)?),
+            CustomQueryMsg::Proposals {
+                chat_id,
+                start_after,
+                limit,
+            } => to_json_binary(&query::proposals(deps, chat_id, start_after, limit)?),
+            CustomQueryMsg::PendingClaim { handle } => {
+                to_json_binary(&query::pending_claim(deps, handle)?)
+            }
+            CustomQueryMsg::Gift { id } => to_json_binary(&query::gift(deps, id)?),
+            CustomQueryMsg::GiftByHash { hash } => {
+                to_json_binary(&query::gift_by_hash(deps, hash)?)
+            }
+            CustomQueryMsg::Giveaway { id } => to_json_binary(&query::giveaway(deps, id)?),
         },
         QueryMsg::Wavs(msg) => match msg {
             ServiceHandlerQueryMessages::WavsServiceManager {} => {
@@ -106,14 +323,62 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
+        execute::SEND_PAYMENT_REPLY_ID => execute::send_payment_reply(deps, env, msg),
+        execute::IBC_TRANSFER_REPLY_ID => execute::ibc_transfer_reply(deps, msg),
+        execute::HELD_IBC_TRANSFER_REPLY_ID => execute::held_ibc_transfer_reply(deps, msg),
+        execute::BATCH_ENTRY_REPLY_ID => execute::batch_entry_reply(msg),
+        execute::ESCROW_REPLY_ID => execute::escrow_reply(deps, msg),
+        execute::SCHEDULE_RUN_REPLY_ID => execute::schedule_run_reply(msg),
         _ => Err(ContractError::UnknownReplyId { id: msg.id }),
     }
 }
 
+/// Acks and timeouts for the transfers we sent to other chains
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn ibc_source_callback(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcSourceCallbackMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    execute::ibc_source_callback(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::default())
+
+    // Older versions had no owner, so the first migration after the upgrade has to name one
+    if !OWNER.exists(deps.storage) {
+        let owner = msg.owner.ok_or(ContractError::OwnerRequired)?;
+        OWNER.save(deps.storage, &deps.api.addr_validate(&owner)?)?;
+    }
+
+    // Older versions stored handles exactly as typed, bring them in line before linking anything
+    let canonicalized = canonicalize_legacy_handles(deps.storage)?;
+
+    index_pending_expiries(deps.storage)?;
+    index_escrow_releases(deps.storage)?;
+    if let Some(expiry) = msg.pending_expiry {
+        execute::save_pending_expiry(deps.storage, Some(expiry))?;
+    }
+
+    // Older versions didn't keep the Stats totals, so they are worked out once from what is stored
+    if !ACCOUNT_COUNTS.exists(deps.storage) {
+        backfill_stats(deps.storage)?;
+    }
+
+    // Handle-keyed accounts can't be moved without knowing the user id, so the caller supplies it.
+    // Anything left over is linked lazily when the user next sends a payment.
+    let mut linked = 0;
+    for link in msg.legacy_accounts {
+        if claim_legacy_account(deps.storage, link.tg_user_id, &link.tg_handle)? {
+            linked += 1;
+        }
+    }
+
+    Ok(Response::default()
+        .add_attribute("canonicalized_handles", canonicalized.to_string())
+        .add_attribute("linked_legacy_accounts", linked.to_string()))
 }
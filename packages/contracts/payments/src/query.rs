@@ -1,20 +1,74 @@
+use crate::execute::{MAX_RELEASE_LIMIT, MAX_SCHEDULE_RUNS};
 use crate::state::{
-    ADMIN, ALLOWED_DENOMS, FUNDED_ACCOUNTS, OPEN_ACCOUNTS, PENDING_PAYMENTS, SERVICE_MANAGER,
+    fee_for, load_auth, matured_escrows, remaining_spend, spends_in_window, ACCOUNT_COUNTS, ADMIN,
+    ALLOWED_DENOMS, BLOCKED_ADDRS, BLOCKED_HANDLES, DENOM_METADATA, ESCROWS, ESCROWS_BY_SENDER,
+    ESCROW_WINDOW, FEE, FUNDED_ACCOUNTS, GIFTS, GIFTS_BY_HASH, GIVEAWAYS, IBC_ROUTES, INVOICES,
+    INVOICES_BY_PAYER, LEGACY_FUNDED_ACCOUNTS, LEGACY_OPEN_ACCOUNTS, OPEN_ACCOUNTS, OWNER, PAUSED,
+    PAYMENTS, PAYMENTS_BY_ADDR, PAYMENTS_BY_DENOM, PAYMENTS_BY_TG, PAYMENTS_BY_TG_ID,
+    PENDING_BY_TG, PENDING_ENTRIES, PENDING_EXPIRY, PENDING_OWNER, PENDING_PAYMENTS,
+    PENDING_TOTALS, PROPOSALS, PROPOSALS_BY_CHAT, PULL_CLAIMS, SCHEDULES, SCHEDULES_BY_SENDER,
+    SERVICE_MANAGER, SPEND_LIMITS, TG_HANDLES, TG_USER_IDS, TREASURIES, TREASURY_BALANCES, VOLUME,
 };
-use cosmwasm_std::{Coin, Deps, StdResult};
-use tg_contract_api::payments::msg::{AdminResponse, ChainAddrResponse, TgHandleResponse};
+use cosmwasm_std::{
+    Coin, Deps, Env, HexBinary, Order, StdError, StdResult, Storage, Timestamp, Uint256,
+};
+use cw_storage_plus::{Bound, Map};
+use layer_climb_proto::{
+    authz::{QueryGrantsRequest, QueryGrantsResponse},
+    bank::{MsgSend, SendAuthorization},
+    Coin as ProtoCoin, Message, Name,
+};
+use tg_contract_api::payments::handle::TgHandle;
+use tg_contract_api::payments::msg::{
+    Account, AccountsResponse, AdminResponse, Auth, BlockedResponse, ChainAddrResponse,
+    DenomMetadataResponse, EscrowWindowResponse, EscrowsResponse, FeeQuoteResponse, FeeResponse,
+    FundedAccount, FundedAccountsResponse, GiftResponse, GiveawayResponse, IbcRoutesResponse,
+    InvoiceResponse, InvoicesResponse, MaturedEscrowsResponse, OwnerResponse, PausedResponse,
+    PaymentHistoryResponse, PendingBalance, PendingBalancesResponse, PendingClaimResponse,
+    PendingExpiryResponse, PendingPaymentsResponse, ProposalResponse, ProposalsResponse,
+    PullClaimsResponse, ScheduleResponse, SchedulesResponse, SendGrantResponse, SpendLimitResponse,
+    StatsResponse, TgHandleResponse, TreasuryResponse,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+const AUTHZ_GRANTS_PATH: &str = "/cosmos.authz.v1beta1.Query/Grants";
+/// What `x/authz` says when there is no grant, `ErrNoAuthorizationFound`
+const AUTHZ_NOT_FOUND: &str = "authorization not found";
+
+pub fn addr_by_tg(deps: Deps, handle: TgHandle) -> StdResult<ChainAddrResponse> {
+    let addr = match TG_USER_IDS.may_load(deps.storage, handle.as_str())? {
+        Some(user_id) => OPEN_ACCOUNTS.may_load(deps.storage, user_id)?,
+        None => LEGACY_OPEN_ACCOUNTS.may_load(deps.storage, handle.as_str())?,
+    };
+    Ok(ChainAddrResponse {
+        addr: addr.map(Into::into),
+    })
+}
 
-pub fn addr_by_tg(deps: Deps, handle: String) -> StdResult<ChainAddrResponse> {
+pub fn addr_by_tg_id(deps: Deps, user_id: i64) -> StdResult<ChainAddrResponse> {
     let addr = OPEN_ACCOUNTS
-        .may_load(deps.storage, &handle)?
+        .may_load(deps.storage, user_id)?
         .map(Into::into);
     Ok(ChainAddrResponse { addr })
 }
 
 pub fn tg_by_addr(deps: Deps, account: String) -> StdResult<TgHandleResponse> {
     let addr = deps.api.addr_validate(&account)?;
-    let handle = FUNDED_ACCOUNTS.may_load(deps.storage, &addr)?;
-    Ok(TgHandleResponse { handle })
+    match FUNDED_ACCOUNTS.may_load(deps.storage, &addr)? {
+        Some(user_id) => Ok(TgHandleResponse {
+            handle: TG_HANDLES.may_load(deps.storage, user_id)?,
+            user_id: Some(user_id),
+        }),
+        None => Ok(TgHandleResponse {
+            // Only reported once it is in canonical form, see canonicalize_legacy_handles
+            handle: LEGACY_FUNDED_ACCOUNTS
+                .may_load(deps.storage, &addr)?
+                .and_then(|handle| TgHandle::canonical(handle).ok()),
+            user_id: None,
+        }),
+    }
 }
 
 pub fn allowed_denoms(deps: Deps) -> StdResult<Vec<String>> {
@@ -31,8 +85,558 @@ pub fn admin(deps: Deps) -> StdResult<AdminResponse> {
     Ok(AdminResponse { admin })
 }
 
-pub fn pending_payments(deps: Deps, handle: String) -> StdResult<Vec<Coin>> {
-    let loaded = PENDING_PAYMENTS.may_load(deps.storage, &handle)?;
+pub fn owner(deps: Deps) -> StdResult<OwnerResponse> {
+    Ok(OwnerResponse {
+        owner: OWNER.load(deps.storage)?,
+        pending_owner: PENDING_OWNER.may_load(deps.storage)?,
+    })
+}
+
+pub fn auth(deps: Deps) -> StdResult<Auth> {
+    load_auth(deps.storage)
+}
+
+pub fn pending_payments(deps: Deps, handle: TgHandle) -> StdResult<Vec<Coin>> {
+    let loaded = PENDING_PAYMENTS.may_load(deps.storage, handle.as_str())?;
     let payments = loaded.map(|p| p.balance()).unwrap_or_default();
     Ok(payments)
 }
+
+pub fn pending_payments_by_tg(
+    deps: Deps,
+    handle: TgHandle,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PendingPaymentsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let payments = PENDING_BY_TG
+        .prefix(handle.as_str())
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|id| PENDING_ENTRIES.load(deps.storage, id?))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PendingPaymentsResponse { payments })
+}
+
+pub fn invoice(deps: Deps, id: u64) -> StdResult<InvoiceResponse> {
+    Ok(InvoiceResponse {
+        invoice: INVOICES.may_load(deps.storage, id)?,
+    })
+}
+
+pub fn invoices_by_payer(
+    deps: Deps,
+    handle: TgHandle,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<InvoicesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let invoices = INVOICES_BY_PAYER
+        .prefix(handle.as_str())
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|id| INVOICES.load(deps.storage, id?))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(InvoicesResponse { invoices })
+}
+
+pub fn spend_limit(
+    deps: Deps,
+    env: Env,
+    account: String,
+    denom: String,
+) -> StdResult<SpendLimitResponse> {
+    let addr = deps.api.addr_validate(&account)?;
+    let Some(limit) = SPEND_LIMITS.may_load(deps.storage, (&addr, &denom))? else {
+        return Ok(SpendLimitResponse {
+            limit: None,
+            spent: Uint256::zero(),
+            remaining: None,
+        });
+    };
+
+    let spends = spends_in_window(deps.storage, &addr, &denom, &limit, env.block.time)?;
+    Ok(SpendLimitResponse {
+        spent: spends.iter().map(|spend| spend.amount).sum(),
+        remaining: remaining_spend(&limit, &spends),
+        limit: Some(limit),
+    })
+}
+
+pub fn send_grant(deps: Deps, env: Env, account: String) -> StdResult<SendGrantResponse> {
+    let granter = deps.api.addr_validate(&account)?;
+    let request = QueryGrantsRequest {
+        granter: granter.to_string(),
+        grantee: env.contract.address.to_string(),
+        msg_type_url: MsgSend::type_url(),
+        pagination: None,
+    };
+
+    // The authz module answers a missing grant with an error rather than an empty list, anything
+    // else going wrong is passed on rather than reported as no grant
+    let grant = match deps.querier.query_grpc(
+        AUTHZ_GRANTS_PATH.to_string(),
+        request.encode_to_vec().into(),
+    ) {
+        Ok(resp) => QueryGrantsResponse::decode(resp.as_slice())
+            .map_err(StdError::msg)?
+            .grants
+            .into_iter()
+            .next(),
+        Err(err) if err.to_string().contains(AUTHZ_NOT_FOUND) => None,
+        Err(err) => return Err(err),
+    };
+    let Some(grant) = grant else {
+        return Ok(SendGrantResponse {
+            granted: false,
+            expiration: None,
+            spend_limit: None,
+        });
+    };
+
+    let expiration = grant
+        .expiration
+        .map(|time| Timestamp::from_seconds(time.seconds as u64).plus_nanos(time.nanos as u64));
+    let spend_limit = match grant.authorization {
+        Some(auth) if auth.type_url == SendAuthorization::type_url() => Some(
+            SendAuthorization::decode(auth.value.as_slice())
+                .map_err(StdError::msg)?
+                .spend_limit
+                .into_iter()
+                .map(proto_coin)
+                .collect::<StdResult<Vec<_>>>()?,
+        ),
+        // A generic authorization for MsgSend, nothing is capped
+        _ => None,
+    };
+
+    Ok(SendGrantResponse {
+        granted: expiration.is_none_or(|expiration| expiration > env.block.time),
+        expiration,
+        spend_limit,
+    })
+}
+
+fn proto_coin(coin: ProtoCoin) -> StdResult<Coin> {
+    Ok(Coin {
+        amount: coin.amount.parse()?,
+        denom: coin.denom,
+    })
+}
+
+pub fn fee(deps: Deps) -> StdResult<FeeResponse> {
+    let fee = FEE.may_load(deps.storage)?;
+    Ok(FeeResponse { fee })
+}
+
+pub fn fee_quote(deps: Deps, amount: Uint256, denom: String) -> StdResult<FeeQuoteResponse> {
+    let fee = match FEE.may_load(deps.storage)? {
+        Some(config) => fee_for(&config, &Coin { amount, denom })?,
+        None => Uint256::zero(),
+    };
+    Ok(FeeQuoteResponse {
+        amount,
+        fee,
+        total: amount.checked_add(fee)?,
+    })
+}
+
+pub fn paused(deps: Deps) -> StdResult<PausedResponse> {
+    Ok(PausedResponse {
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn blocked(
+    deps: Deps,
+    handle: Option<TgHandle>,
+    account: Option<String>,
+) -> StdResult<BlockedResponse> {
+    let handle_blocked = match handle {
+        Some(handle) => BLOCKED_HANDLES.has(deps.storage, handle.as_str()),
+        None => false,
+    };
+    let account_blocked = match account {
+        Some(account) => BLOCKED_ADDRS.has(deps.storage, &deps.api.addr_validate(&account)?),
+        None => false,
+    };
+    Ok(BlockedResponse {
+        blocked: handle_blocked || account_blocked,
+    })
+}
+
+pub fn list_accounts(
+    deps: Deps,
+    start_after: Option<i64>,
+    limit: Option<u32>,
+) -> StdResult<AccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // Legacy accounts are keyed by handle, with no user id to page by, so they are left out until
+    // claim_legacy_account links them. Stats still counts them, being accounts all the same
+    let accounts = OPEN_ACCOUNTS
+        .range(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (tg_user_id, address) = item?;
+            Ok(Account {
+                tg_user_id,
+                tg_handle: TG_HANDLES.may_load(deps.storage, tg_user_id)?,
+                address,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(AccountsResponse { accounts })
+}
+
+pub fn list_funded_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FundedAccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let accounts = FUNDED_ACCOUNTS
+        .range(
+            deps.storage,
+            start_after.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            let (address, tg_user_id) = item?;
+            Ok(FundedAccount {
+                address,
+                tg_user_id,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(FundedAccountsResponse { accounts })
+}
+
+pub fn list_pending_payments(
+    deps: Deps,
+    start_after: Option<TgHandle>,
+    limit: Option<u32>,
+) -> StdResult<PendingBalancesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let balances = PENDING_PAYMENTS
+        .range(
+            deps.storage,
+            start_after
+                .as_ref()
+                .map(|handle| Bound::exclusive(handle.as_str())),
+            None,
+            Order::Ascending,
+        )
+        // Only left in another form if migration couldn't make a valid handle of it, see
+        // canonicalize_legacy_handles. Skipped before paging so a page is never cut short
+        .filter_map(|item| match item {
+            Ok((handle, pending)) => TgHandle::canonical(handle).ok().map(|handle| {
+                Ok(PendingBalance {
+                    handle,
+                    amount: pending.balance(),
+                })
+            }),
+            Err(err) => Some(Err(err)),
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PendingBalancesResponse { balances })
+}
+
+pub fn stats(deps: Deps) -> StdResult<StatsResponse> {
+    let counts = ACCOUNT_COUNTS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(StatsResponse {
+        registered: counts.registered,
+        funded: counts.funded,
+        volume: load_totals(deps.storage, &VOLUME)?,
+        pending: load_totals(deps.storage, &PENDING_TOTALS)?,
+    })
+}
+
+pub fn ibc_routes(deps: Deps) -> StdResult<IbcRoutesResponse> {
+    // Set by the owner one at a time, so there are never many
+    let routes = IBC_ROUTES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, route)| route))
+        .collect::<StdResult<_>>()?;
+    Ok(IbcRoutesResponse { routes })
+}
+
+pub fn denom_metadata(deps: Deps) -> StdResult<DenomMetadataResponse> {
+    // Set by the owner one at a time, like the IBC routes
+    let denoms = DENOM_METADATA
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, metadata)| metadata))
+        .collect::<StdResult<_>>()?;
+    Ok(DenomMetadataResponse { denoms })
+}
+
+pub fn pull_claims(deps: Deps) -> StdResult<PullClaimsResponse> {
+    Ok(PullClaimsResponse {
+        enabled: PULL_CLAIMS.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn pending_claim(deps: Deps, handle: TgHandle) -> StdResult<PendingClaimResponse> {
+    let held = PENDING_PAYMENTS
+        .may_load(deps.storage, handle.as_str())?
+        .map(|pending| pending.balance())
+        .unwrap_or_default();
+
+    // Whoever holds the handle now can claim it, once they have registered to receive
+    let address = match TG_USER_IDS.may_load(deps.storage, handle.as_str())? {
+        Some(tg_user_id) => OPEN_ACCOUNTS.may_load(deps.storage, tg_user_id)?,
+        None => None,
+    };
+
+    Ok(match address {
+        Some(address) => PendingClaimResponse {
+            address: Some(address.to_string()),
+            claimable: held,
+            unregistered: vec![],
+        },
+        None => PendingClaimResponse {
+            address: None,
+            claimable: vec![],
+            unregistered: held,
+        },
+    })
+}
+
+pub fn escrow_window(deps: Deps) -> StdResult<EscrowWindowResponse> {
+    Ok(EscrowWindowResponse {
+        window: ESCROW_WINDOW.may_load(deps.storage)?,
+    })
+}
+
+pub fn escrows(
+    deps: Deps,
+    from_tg_id: Option<i64>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<EscrowsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let escrows = match from_tg_id {
+        Some(from_tg_id) => ESCROWS_BY_SENDER
+            .prefix(from_tg_id)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|id| ESCROWS.load(deps.storage, id?))
+            .collect::<StdResult<Vec<_>>>()?,
+        None => ESCROWS
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, escrow)| escrow))
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+    Ok(EscrowsResponse { escrows })
+}
+
+pub fn matured_escrows_count(deps: Deps, env: Env) -> StdResult<MaturedEscrowsResponse> {
+    let matured = matured_escrows(deps.storage, &env.block, MAX_RELEASE_LIMIT as usize)?;
+    Ok(MaturedEscrowsResponse {
+        count: matured.len() as u32,
+    })
+}
+
+pub fn schedule(deps: Deps, id: u64) -> StdResult<ScheduleResponse> {
+    Ok(ScheduleResponse {
+        schedule: SCHEDULES.may_load(deps.storage, id)?,
+    })
+}
+
+pub fn schedules(
+    deps: Deps,
+    from_tg_id: Option<i64>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<SchedulesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let schedules = match from_tg_id {
+        Some(from_tg_id) => SCHEDULES_BY_SENDER
+            .prefix(from_tg_id)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|id| SCHEDULES.load(deps.storage, id?))
+            .collect::<StdResult<Vec<_>>>()?,
+        None => SCHEDULES
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, schedule)| schedule))
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+    Ok(SchedulesResponse { schedules })
+}
+
+pub fn due_schedules(deps: Deps, env: Env, limit: Option<u32>) -> StdResult<SchedulesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_SCHEDULE_RUNS) as usize;
+    Ok(SchedulesResponse {
+        schedules: crate::state::due_schedules(deps.storage, env.block.time, limit)?,
+    })
+}
+
+pub fn treasury(deps: Deps, chat_id: i64) -> StdResult<TreasuryResponse> {
+    let balances = TREASURY_BALANCES
+        .prefix(chat_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(TreasuryResponse {
+        treasury: TREASURIES.may_load(deps.storage, chat_id)?,
+        balances,
+    })
+}
+
+pub fn proposal(deps: Deps, id: u64) -> StdResult<ProposalResponse> {
+    Ok(ProposalResponse {
+        proposal: PROPOSALS.may_load(deps.storage, id)?,
+    })
+}
+
+pub fn gift(deps: Deps, id: u64) -> StdResult<GiftResponse> {
+    Ok(GiftResponse {
+        gift: GIFTS.may_load(deps.storage, id)?,
+    })
+}
+
+pub fn gift_by_hash(deps: Deps, hash: HexBinary) -> StdResult<GiftResponse> {
+    let gift = match GIFTS_BY_HASH.may_load(deps.storage, hash.as_slice())? {
+        Some(id) => GIFTS.may_load(deps.storage, id)?,
+        None => None,
+    };
+    Ok(GiftResponse { gift })
+}
+
+pub fn giveaway(deps: Deps, id: u64) -> StdResult<GiveawayResponse> {
+    Ok(GiveawayResponse {
+        giveaway: GIVEAWAYS.may_load(deps.storage, id)?,
+    })
+}
+
+pub fn proposals(
+    deps: Deps,
+    chat_id: i64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let proposals = PROPOSALS_BY_CHAT
+        .prefix(chat_id)
+        .keys(
+            deps.storage,
+            start_after.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|id| PROPOSALS.load(deps.storage, id?))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(ProposalsResponse { proposals })
+}
+
+fn load_totals(storage: &dyn Storage, totals: &Map<&str, Uint256>) -> StdResult<Vec<Coin>> {
+    totals
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect()
+}
+
+pub fn pending_expiry(deps: Deps) -> StdResult<PendingExpiryResponse> {
+    let expiry = PENDING_EXPIRY.may_load(deps.storage)?;
+    Ok(PendingExpiryResponse { expiry })
+}
+
+pub fn payments_by_tg(
+    deps: Deps,
+    handle: TgHandle,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PaymentHistoryResponse> {
+    let ids = PAYMENTS_BY_TG.prefix(handle.as_str()).keys(
+        deps.storage,
+        None,
+        start_after.map(Bound::exclusive),
+        Order::Descending,
+    );
+    load_payments(deps, ids, limit)
+}
+
+pub fn payments_by_tg_id(
+    deps: Deps,
+    user_id: i64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PaymentHistoryResponse> {
+    let ids = PAYMENTS_BY_TG_ID.prefix(user_id).keys(
+        deps.storage,
+        None,
+        start_after.map(Bound::exclusive),
+        Order::Descending,
+    );
+    load_payments(deps, ids, limit)
+}
+
+pub fn payments_by_addr(
+    deps: Deps,
+    account: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PaymentHistoryResponse> {
+    let addr = deps.api.addr_validate(&account)?;
+    let ids = PAYMENTS_BY_ADDR.prefix(&addr).keys(
+        deps.storage,
+        None,
+        start_after.map(Bound::exclusive),
+        Order::Descending,
+    );
+    load_payments(deps, ids, limit)
+}
+
+pub fn payments_by_denom(
+    deps: Deps,
+    denom: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<PaymentHistoryResponse> {
+    let ids = PAYMENTS_BY_DENOM.prefix(&denom).keys(
+        deps.storage,
+        None,
+        start_after.map(Bound::exclusive),
+        Order::Descending,
+    );
+    load_payments(deps, ids, limit)
+}
+
+fn load_payments(
+    deps: Deps,
+    ids: impl Iterator<Item = StdResult<u64>>,
+    limit: Option<u32>,
+) -> StdResult<PaymentHistoryResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let payments = ids
+        .take(limit)
+        .map(|id| PAYMENTS.load(deps.storage, id?))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PaymentHistoryResponse { payments })
+}
@@ -1,6 +1,15 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Coin};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Api, BlockInfo, Coin, Order, StdResult, Storage, Timestamp, Uint256};
+use cw_storage_plus::{Bound, Item, Map};
+use cw_utils::{Duration, Expiration};
+use tg_contract_api::payments::{
+    event::SendPaymentEvent,
+    handle::TgHandle,
+    msg::{
+        Auth, DenomMetadata, Escrow, FeeConfig, Gift, Giveaway, IbcRoute, Invoice, PaymentKind,
+        PaymentRecord, PendingPayment, Proposal, Schedule, SpendLimit, Treasury,
+    },
+};
 
 #[cw_serde]
 #[derive(Default)]
@@ -30,28 +39,894 @@ impl PendingPayments {
         }
     }
 
+    /// Only safe way to remove. Drops the denom entirely once it reaches zero.
+    pub fn remove_payment(&mut self, payment: &Coin) {
+        if let Some(i) = self.payments.iter().position(|p| p.denom == payment.denom) {
+            self.payments[i].amount = self.payments[i].amount.saturating_sub(payment.amount);
+            if self.payments[i].amount.is_zero() {
+                self.payments.remove(i);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.payments.is_empty()
+    }
+
     /// Makes payments readable but not writable
     pub fn balance(self) -> Vec<Coin> {
         self.payments
     }
 }
 
-/// Maps a telegram handle to a blockchain address
-pub const OPEN_ACCOUNTS: Map<&str, Addr> = Map::new("open_accounts");
-/// Maps a blockchain address to a telegram handle
-pub const FUNDED_ACCOUNTS: Map<&Addr, String> = Map::new("funded_accounts");
+/// Maps a telegram user id to a blockchain address
+pub const OPEN_ACCOUNTS: Map<i64, Addr> = Map::new("open_accounts_by_id");
+/// Maps a blockchain address to a telegram user id
+pub const FUNDED_ACCOUNTS: Map<&Addr, i64> = Map::new("funded_accounts_by_id");
+
+/// Current username of a registered telegram user. Usernames can change, the id can't.
+pub const TG_HANDLES: Map<i64, TgHandle> = Map::new("tg_handles");
+/// Reverse of TG_HANDLES, used to find the recipient of a payment addressed by username
+pub const TG_USER_IDS: Map<&str, i64> = Map::new("tg_user_ids");
+
+/// Registrations from before user ids were tracked, keyed by handle.
+/// Moved into OPEN_ACCOUNTS the first time we see the handle together with its user id.
+/// Keys are brought into canonical form by `canonicalize_legacy_handles` on migration.
+pub const LEGACY_OPEN_ACCOUNTS: Map<&str, Addr> = Map::new("open_accounts");
+/// Same as LEGACY_OPEN_ACCOUNTS, for FUNDED_ACCOUNTS
+pub const LEGACY_FUNDED_ACCOUNTS: Map<&Addr, String> = Map::new("funded_accounts");
 
-/// Maps an unregistered telegram handle to a list of pending payments, only one
+/// Maps an unregistered (canonical) telegram handle to a list of pending payments, only one
 pub const PENDING_PAYMENTS: Map<&str, PendingPayments> = Map::new("pending_payments");
 
+/// Each payment making up PENDING_PAYMENTS, keyed by its ledger id
+pub const PENDING_ENTRIES: Map<u64, PendingPayment> = Map::new("pending_entries");
+/// Index into PENDING_ENTRIES by recipient telegram handle
+pub const PENDING_BY_TG: Map<(&str, u64), ()> = Map::new("pending_by_tg");
+/// Index into PENDING_ENTRIES by when they expire, in unix nanoseconds or block height depending on
+/// the expiry they were made with. Entries that never expire aren't indexed.
+pub const PENDING_BY_EXPIRY_TIME: Map<(u64, u64), ()> = Map::new("pending_by_expiry_time");
+pub const PENDING_BY_EXPIRY_HEIGHT: Map<(u64, u64), ()> = Map::new("pending_by_expiry_height");
+/// How long pending payments are held before they can be refunded, unset means forever
+pub const PENDING_EXPIRY: Item<Duration> = Item::new("pending_expiry");
+
 /// Which denoms we will accept for payments
 pub const ALLOWED_DENOMS: Item<Vec<String>> = Item::new("allowed_denoms");
 
+/// Charged to senders on top of each send, unset means sends are free.
+/// The treasury is validated before it is saved.
+pub const FEE: Item<FeeConfig> = Item::new("fee");
+
+/// Other chains receive addresses can be on, by bech32 prefix
+pub const IBC_ROUTES: Map<&str, IbcRoute> = Map::new("ibc_routes");
+/// How denoms are shown to users, by base denom
+pub const DENOM_METADATA: Map<&str, DenomMetadata> = Map::new("denom_metadata");
+/// Sends and payouts of held funds on their way to another chain, by source channel and packet
+/// sequence, until the acknowledgement or timeout comes back
+pub const IBC_TRANSFERS: Map<(&str, u64), IbcTransfer> = Map::new("ibc_transfers");
+
+/// Set by the owner to stop everything the operators submit, unset means not paused
+pub const PAUSED: Item<bool> = Item::new("paused");
+/// Set by the owner to hold funds after their handle registers, until the account claims them.
+/// Unset means they are paid out on registration.
+pub const PULL_CLAIMS: Item<bool> = Item::new("pull_claims");
+/// Canonical handles that can't register, send, request or be paid
+pub const BLOCKED_HANDLES: Map<&str, ()> = Map::new("blocked_handles");
+/// Same as BLOCKED_HANDLES, for addresses
+pub const BLOCKED_ADDRS: Map<&Addr, ()> = Map::new("blocked_addrs");
+
 /// Only set if we take ServiceHandler interface
 pub const SERVICE_MANAGER: Item<Addr> = Item::new("service_manager");
 /// Only set in the test approach
 pub const ADMIN: Item<Addr> = Item::new("admin");
 
+/// Can change the configuration below, set on instantiate (or on migrate for older deployments)
+pub const OWNER: Item<Addr> = Item::new("owner");
+/// Proposed by the owner, takes over once it accepts
+pub const PENDING_OWNER: Item<Addr> = Item::new("pending_owner");
+
+/// Telegram (chat_id, message_id) pairs already handled through WAVS, with when they were seen
+pub const PROCESSED_MESSAGES: Map<(i64, i64), Timestamp> = Map::new("processed_messages");
+/// Index into PROCESSED_MESSAGES by the time (in seconds) they were seen, used for pruning
+pub const PROCESSED_BY_TIME: Map<(u64, i64, i64), ()> = Map::new("processed_by_time");
+
+/// Last id handed out to the payment ledger
+pub const PAYMENT_SEQ: Item<u64> = Item::new("payment_seq");
+/// Ledger of every transfer the contract has made, keyed by id
+pub const PAYMENTS: Map<u64, PaymentRecord> = Map::new("payments");
+/// Index into PAYMENTS by telegram handle (sender and recipient)
+pub const PAYMENTS_BY_TG: Map<(&str, u64), ()> = Map::new("payments_by_tg");
+/// Index into PAYMENTS by telegram user id (sender and recipient)
+pub const PAYMENTS_BY_TG_ID: Map<(i64, u64), ()> = Map::new("payments_by_tg_id");
+/// Index into PAYMENTS by address (sender and recipient)
+pub const PAYMENTS_BY_ADDR: Map<(&Addr, u64), ()> = Map::new("payments_by_addr");
+/// Index into PAYMENTS by denom
+pub const PAYMENTS_BY_DENOM: Map<(&str, u64), ()> = Map::new("payments_by_denom");
+
+/// Last id handed out to an invoice
+pub const INVOICE_SEQ: Item<u64> = Item::new("invoice_seq");
+/// Open invoices, removed once paid or declined
+pub const INVOICES: Map<u64, Invoice> = Map::new("invoices");
+/// Index into INVOICES by the handle asked to pay
+pub const INVOICES_BY_PAYER: Map<(&str, u64), ()> = Map::new("invoices_by_payer");
+
+/// Set by the owner to hold sends for this long before they go out, unset means they go out
+/// right away
+pub const ESCROW_WINDOW: Item<Duration> = Item::new("escrow_window");
+/// Last id handed out to an escrow
+pub const ESCROW_SEQ: Item<u64> = Item::new("escrow_seq");
+/// Sends held until their window passes, removed once released or cancelled
+pub const ESCROWS: Map<u64, Escrow> = Map::new("escrows");
+/// Index into ESCROWS by the sender's telegram user id
+pub const ESCROWS_BY_SENDER: Map<(i64, u64), ()> = Map::new("escrows_by_sender");
+/// Index into ESCROWS by when they release, in unix nanoseconds or block height depending on the
+/// window they were held with
+pub const ESCROWS_BY_RELEASE_TIME: Map<(u64, u64), ()> = Map::new("escrows_by_release_time");
+pub const ESCROWS_BY_RELEASE_HEIGHT: Map<(u64, u64), ()> = Map::new("escrows_by_release_height");
+
+/// Last id handed out to a recurring payment
+pub const SCHEDULE_SEQ: Item<u64> = Item::new("schedule_seq");
+/// Recurring payments, removed once cancelled or past their end date
+pub const SCHEDULES: Map<u64, Schedule> = Map::new("schedules");
+/// Index into SCHEDULES by the sender's telegram user id
+pub const SCHEDULES_BY_SENDER: Map<(i64, u64), ()> = Map::new("schedules_by_sender");
+/// Index into SCHEDULES by when the next run is due, in unix seconds
+pub const SCHEDULES_BY_DUE: Map<(u64, u64), ()> = Map::new("schedules_by_due");
+
+/// Shared funds of group chats, by chat id
+pub const TREASURIES: Map<i64, Treasury> = Map::new("treasuries");
+/// What each treasury holds, by chat id and denom
+pub const TREASURY_BALANCES: Map<(i64, &str), Uint256> = Map::new("treasury_balances");
+/// Last id handed out to a payout proposal
+pub const PROPOSAL_SEQ: Item<u64> = Item::new("proposal_seq");
+/// Open payout proposals, removed once paid out or rejected
+pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+/// Index into PROPOSALS by the chat id of the treasury
+pub const PROPOSALS_BY_CHAT: Map<(i64, u64), ()> = Map::new("proposals_by_chat");
+
+/// Last id handed out to a gift
+pub const GIFT_SEQ: Item<u64> = Item::new("gift_seq");
+/// Unclaimed gifts, removed once claimed or refunded
+pub const GIFTS: Map<u64, Gift> = Map::new("gifts");
+/// Index into GIFTS by the hash of the secret, which is unique among unclaimed gifts
+pub const GIFTS_BY_HASH: Map<&[u8], u64> = Map::new("gifts_by_hash");
+/// Index into GIFTS by when it expires, in unix seconds
+pub const GIFTS_BY_EXPIRY: Map<(u64, u64), ()> = Map::new("gifts_by_expiry");
+
+/// Last id handed out to a giveaway
+pub const GIVEAWAY_SEQ: Item<u64> = Item::new("giveaway_seq");
+/// Giveaways still being claimed, removed once they run out or are refunded
+pub const GIVEAWAYS: Map<u64, Giveaway> = Map::new("giveaways");
+/// Index into GIVEAWAYS by when it expires, in unix seconds
+pub const GIVEAWAYS_BY_EXPIRY: Map<(u64, u64), ()> = Map::new("giveaways_by_expiry");
+
+/// Caps senders put on their own accounts, by address and denom
+pub const SPEND_LIMITS: Map<(&Addr, &str), SpendLimit> = Map::new("spend_limits");
+/// What went out under a window cap, oldest first. Only tracked while there is one.
+pub const SPENDS: Map<(&Addr, &str), Vec<Spend>> = Map::new("spends");
+
+/// Running totals behind the Stats query, so it doesn't have to walk the registry
+pub const ACCOUNT_COUNTS: Item<AccountCounts> = Item::new("account_counts");
+/// Everything users have sent, direct or held, by denom
+pub const VOLUME: Map<&str, Uint256> = Map::new("volume");
+/// The sum of PENDING_PAYMENTS, by denom
+pub const PENDING_TOTALS: Map<&str, Uint256> = Map::new("pending_totals");
+
+/// Legacy accounts are counted too, so linking one to its user id doesn't change anything
+#[cw_serde]
+#[derive(Default)]
+pub struct AccountCounts {
+    pub registered: u64,
+    pub funded: u64,
+}
+
+/// What we need to report a cross-chain transfer that fails after it left, and give the funds back
+#[cw_serde]
+#[serde(untagged)]
+#[schemaifier(mute_warnings)]
+pub enum IbcTransfer {
+    /// A send pulled from the sender's account. Untagged, so sends stored before payouts were
+    /// tracked still load.
+    Send {
+        chat_id: i64,
+        message_id: i64,
+        /// Of the ledger record
+        payment_id: u64,
+        sent: SendPaymentEvent,
+    },
+    Held(HeldPayout),
+}
+
+/// Funds the contract was holding, paid out to another chain
+#[cw_serde]
+pub struct HeldPayout {
+    /// Of the ledger record
+    pub payment_id: u64,
+    pub to_tg_id: Option<i64>,
+    pub to_tg_handle: Option<TgHandle>,
+    pub to_address: Addr,
+    pub amount: Coin,
+    pub returns_to: HeldFunds,
+}
+
+/// Where held funds go back to if they don't arrive
+#[cw_serde]
+pub enum HeldFunds {
+    /// Held again for the handle, as the entries they were released from
+    Pending {
+        tg_handle: TgHandle,
+        entries: Vec<PendingPayment>,
+    },
+    /// Back into the group's treasury
+    Treasury { chat_id: i64 },
+    /// Sent back to whoever put them in
+    Refund(Addr),
+}
+
+#[cw_serde]
+pub struct Spend {
+    pub time: Timestamp,
+    pub amount: Uint256,
+}
+
+pub fn next_payment_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = PAYMENT_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    PAYMENT_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+/// Stores the record and writes all the secondary indexes for it
+pub fn save_payment(storage: &mut dyn Storage, record: &PaymentRecord) -> StdResult<()> {
+    PAYMENTS.save(storage, record.id, record)?;
+
+    for handle in [&record.from_tg_handle, &record.to_tg_handle]
+        .into_iter()
+        .flatten()
+    {
+        PAYMENTS_BY_TG.save(storage, (handle.as_str(), record.id), &())?;
+    }
+    for user_id in [record.from_tg_id, record.to_tg_id].into_iter().flatten() {
+        PAYMENTS_BY_TG_ID.save(storage, (user_id, record.id), &())?;
+    }
+
+    PAYMENTS_BY_ADDR.save(storage, (&record.from_address, record.id), &())?;
+    PAYMENTS_BY_ADDR.save(storage, (&record.to_address, record.id), &())?;
+
+    PAYMENTS_BY_DENOM.save(storage, (&record.denom, record.id), &())?;
+
+    track_totals(storage, record, false)
+}
+
+/// Undoes [`save_payment`], for a payment that never went through
+pub fn remove_payment(storage: &mut dyn Storage, record: &PaymentRecord) -> StdResult<()> {
+    PAYMENTS.remove(storage, record.id);
+
+    for handle in [&record.from_tg_handle, &record.to_tg_handle]
+        .into_iter()
+        .flatten()
+    {
+        PAYMENTS_BY_TG.remove(storage, (handle.as_str(), record.id));
+    }
+    for user_id in [record.from_tg_id, record.to_tg_id].into_iter().flatten() {
+        PAYMENTS_BY_TG_ID.remove(storage, (user_id, record.id));
+    }
+
+    PAYMENTS_BY_ADDR.remove(storage, (&record.from_address, record.id));
+    PAYMENTS_BY_ADDR.remove(storage, (&record.to_address, record.id));
+
+    PAYMENTS_BY_DENOM.remove(storage, (&record.denom, record.id));
+
+    track_totals(storage, record, true)
+}
+
+/// Keeps VOLUME and PENDING_TOTALS in step with the ledger. Held funds count towards the volume
+/// when they are sent, and not again when they are released or refunded.
+fn track_totals(storage: &mut dyn Storage, record: &PaymentRecord, undo: bool) -> StdResult<()> {
+    let (volume, pending) = match record.kind {
+        PaymentKind::Direct => (true, false),
+        PaymentKind::Pending => (true, true),
+        PaymentKind::Released | PaymentKind::Refunded => (false, true),
+    };
+    if volume {
+        adjust_total(storage, &VOLUME, &record.denom, record.amount, !undo)?;
+    }
+    if pending {
+        // Released and refunded funds are leaving the contract
+        let held = record.kind == PaymentKind::Pending;
+        adjust_total(
+            storage,
+            &PENDING_TOTALS,
+            &record.denom,
+            record.amount,
+            held != undo,
+        )?;
+    }
+    Ok(())
+}
+
+fn adjust_total(
+    storage: &mut dyn Storage,
+    totals: &Map<&str, Uint256>,
+    denom: &str,
+    amount: Uint256,
+    add: bool,
+) -> StdResult<()> {
+    let total = totals.may_load(storage, denom)?.unwrap_or_default();
+    let total = if add {
+        total.checked_add(amount)?
+    } else {
+        total.saturating_sub(amount)
+    };
+    if total.is_zero() {
+        totals.remove(storage, denom);
+    } else {
+        totals.save(storage, denom, &total)?;
+    }
+    Ok(())
+}
+
+pub fn update_account_counts(
+    storage: &mut dyn Storage,
+    update: impl FnOnce(&mut AccountCounts),
+) -> StdResult<()> {
+    let mut counts = ACCOUNT_COUNTS.may_load(storage)?.unwrap_or_default();
+    update(&mut counts);
+    ACCOUNT_COUNTS.save(storage, &counts)
+}
+
+/// Works out the Stats totals from scratch, for contracts deployed before they were tracked
+pub fn backfill_stats(storage: &mut dyn Storage) -> StdResult<()> {
+    let counts = AccountCounts {
+        registered: (OPEN_ACCOUNTS
+            .keys_raw(storage, None, None, Order::Ascending)
+            .count()
+            + LEGACY_OPEN_ACCOUNTS
+                .keys_raw(storage, None, None, Order::Ascending)
+                .count()) as u64,
+        funded: (FUNDED_ACCOUNTS
+            .keys_raw(storage, None, None, Order::Ascending)
+            .count()
+            + LEGACY_FUNDED_ACCOUNTS
+                .keys_raw(storage, None, None, Order::Ascending)
+                .count()) as u64,
+    };
+    ACCOUNT_COUNTS.save(storage, &counts)?;
+
+    // Payments held before the ledger existed have no record, so pending comes from the balances
+    let records = PAYMENTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (_, record) in records {
+        if matches!(record.kind, PaymentKind::Direct | PaymentKind::Pending) {
+            adjust_total(storage, &VOLUME, &record.denom, record.amount, true)?;
+        }
+    }
+    let held = PENDING_PAYMENTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (_, pending) in held {
+        for coin in pending.balance() {
+            adjust_total(storage, &PENDING_TOTALS, &coin.denom, coin.amount, true)?;
+        }
+    }
+
+    Ok(())
+}
+
+pub fn next_invoice_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = INVOICE_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    INVOICE_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+pub fn save_invoice(storage: &mut dyn Storage, invoice: &Invoice) -> StdResult<()> {
+    INVOICES.save(storage, invoice.id, invoice)?;
+    INVOICES_BY_PAYER.save(storage, (invoice.payer_tg_handle.as_str(), invoice.id), &())
+}
+
+pub fn remove_invoice(storage: &mut dyn Storage, invoice: &Invoice) {
+    INVOICES.remove(storage, invoice.id);
+    INVOICES_BY_PAYER.remove(storage, (invoice.payer_tg_handle.as_str(), invoice.id));
+}
+
+pub fn next_escrow_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = ESCROW_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    ESCROW_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+pub fn save_escrow(storage: &mut dyn Storage, escrow: &Escrow) -> StdResult<()> {
+    ESCROWS.save(storage, escrow.id, escrow)?;
+    ESCROWS_BY_SENDER.save(storage, (escrow.from_tg_id, escrow.id), &())?;
+    index_escrow_release(storage, escrow)
+}
+
+pub fn remove_escrow(storage: &mut dyn Storage, escrow: &Escrow) {
+    ESCROWS.remove(storage, escrow.id);
+    ESCROWS_BY_SENDER.remove(storage, (escrow.from_tg_id, escrow.id));
+    match escrow.releases {
+        Expiration::AtTime(time) => {
+            ESCROWS_BY_RELEASE_TIME.remove(storage, (time.nanos(), escrow.id))
+        }
+        Expiration::AtHeight(height) => {
+            ESCROWS_BY_RELEASE_HEIGHT.remove(storage, (height, escrow.id))
+        }
+        Expiration::Never {} => {}
+    }
+}
+
+fn index_escrow_release(storage: &mut dyn Storage, escrow: &Escrow) -> StdResult<()> {
+    match escrow.releases {
+        Expiration::AtTime(time) => {
+            ESCROWS_BY_RELEASE_TIME.save(storage, (time.nanos(), escrow.id), &())
+        }
+        Expiration::AtHeight(height) => {
+            ESCROWS_BY_RELEASE_HEIGHT.save(storage, (height, escrow.id), &())
+        }
+        Expiration::Never {} => Ok(()),
+    }
+}
+
+/// Older versions didn't index held sends by when they release. Indexing them again is harmless,
+/// so this runs on every migration.
+pub fn index_escrow_releases(storage: &mut dyn Storage) -> StdResult<()> {
+    let escrows = ESCROWS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, escrow)| escrow))
+        .collect::<StdResult<Vec<_>>>()?;
+    for escrow in &escrows {
+        index_escrow_release(storage, escrow)?;
+    }
+
+    Ok(())
+}
+
+/// Up to `limit` held sends whose window has passed, soonest released first, those held by time
+/// before those held by height. A send made after the owner shortened the window isn't held up
+/// behind older ones.
+pub fn matured_escrows(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    limit: usize,
+) -> StdResult<Vec<Escrow>> {
+    // Released at its own second or height, like any expiration
+    let by_time = ESCROWS_BY_RELEASE_TIME.keys(
+        storage,
+        None,
+        Some(Bound::inclusive((block.time.nanos(), u64::MAX))),
+        Order::Ascending,
+    );
+    let by_height = ESCROWS_BY_RELEASE_HEIGHT.keys(
+        storage,
+        None,
+        Some(Bound::inclusive((block.height, u64::MAX))),
+        Order::Ascending,
+    );
+
+    by_time
+        .chain(by_height)
+        .take(limit)
+        .map(|key| key.and_then(|(_, id)| ESCROWS.load(storage, id)))
+        .collect()
+}
+
+pub fn next_schedule_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = SCHEDULE_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    SCHEDULE_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+/// Reindexes by the new due time, `previous` is the schedule as it was stored, if it was
+pub fn save_schedule(
+    storage: &mut dyn Storage,
+    previous: Option<&Schedule>,
+    schedule: &Schedule,
+) -> StdResult<()> {
+    if let Some(previous) = previous {
+        SCHEDULES_BY_DUE.remove(storage, (previous.next_due.seconds(), previous.id));
+    }
+    SCHEDULES.save(storage, schedule.id, schedule)?;
+    SCHEDULES_BY_SENDER.save(storage, (schedule.from_tg_id, schedule.id), &())?;
+    SCHEDULES_BY_DUE.save(storage, (schedule.next_due.seconds(), schedule.id), &())
+}
+
+pub fn remove_schedule(storage: &mut dyn Storage, schedule: &Schedule) {
+    SCHEDULES.remove(storage, schedule.id);
+    SCHEDULES_BY_SENDER.remove(storage, (schedule.from_tg_id, schedule.id));
+    SCHEDULES_BY_DUE.remove(storage, (schedule.next_due.seconds(), schedule.id));
+}
+
+/// Up to `limit` recurring payments due at `now`, soonest first
+pub fn due_schedules(
+    storage: &dyn Storage,
+    now: Timestamp,
+    limit: usize,
+) -> StdResult<Vec<Schedule>> {
+    SCHEDULES_BY_DUE
+        .keys(
+            storage,
+            None,
+            Some(Bound::inclusive((now.seconds(), u64::MAX))),
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|key| key.and_then(|(_, id)| SCHEDULES.load(storage, id)))
+        .collect()
+}
+
+pub fn next_proposal_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = PROPOSAL_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    PROPOSAL_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+pub fn save_proposal(storage: &mut dyn Storage, proposal: &Proposal) -> StdResult<()> {
+    PROPOSALS.save(storage, proposal.id, proposal)?;
+    PROPOSALS_BY_CHAT.save(storage, (proposal.chat_id, proposal.id), &())
+}
+
+pub fn remove_proposal(storage: &mut dyn Storage, proposal: &Proposal) {
+    PROPOSALS.remove(storage, proposal.id);
+    PROPOSALS_BY_CHAT.remove(storage, (proposal.chat_id, proposal.id));
+}
+
+pub fn next_gift_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = GIFT_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    GIFT_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+pub fn save_gift(storage: &mut dyn Storage, gift: &Gift) -> StdResult<()> {
+    GIFTS.save(storage, gift.id, gift)?;
+    GIFTS_BY_HASH.save(storage, gift.hash.as_slice(), &gift.id)?;
+    GIFTS_BY_EXPIRY.save(storage, (gift.expires.seconds(), gift.id), &())
+}
+
+pub fn remove_gift(storage: &mut dyn Storage, gift: &Gift) {
+    GIFTS.remove(storage, gift.id);
+    GIFTS_BY_HASH.remove(storage, gift.hash.as_slice());
+    GIFTS_BY_EXPIRY.remove(storage, (gift.expires.seconds(), gift.id));
+}
+
+/// Up to `limit` gifts expired at `now`, soonest expired first
+pub fn expired_gifts(storage: &dyn Storage, now: Timestamp, limit: usize) -> StdResult<Vec<Gift>> {
+    GIFTS_BY_EXPIRY
+        .keys(
+            storage,
+            None,
+            Some(Bound::inclusive((now.seconds(), u64::MAX))),
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|key| key.and_then(|(_, id)| GIFTS.load(storage, id)))
+        .collect()
+}
+
+pub fn next_giveaway_id(storage: &mut dyn Storage) -> StdResult<u64> {
+    let id = GIVEAWAY_SEQ.may_load(storage)?.unwrap_or_default() + 1;
+    GIVEAWAY_SEQ.save(storage, &id)?;
+    Ok(id)
+}
+
+pub fn save_giveaway(storage: &mut dyn Storage, giveaway: &Giveaway) -> StdResult<()> {
+    GIVEAWAYS.save(storage, giveaway.id, giveaway)?;
+    GIVEAWAYS_BY_EXPIRY.save(storage, (giveaway.expires.seconds(), giveaway.id), &())
+}
+
+pub fn remove_giveaway(storage: &mut dyn Storage, giveaway: &Giveaway) {
+    GIVEAWAYS.remove(storage, giveaway.id);
+    GIVEAWAYS_BY_EXPIRY.remove(storage, (giveaway.expires.seconds(), giveaway.id));
+}
+
+/// Up to `limit` giveaways expired at `now`, soonest expired first
+pub fn expired_giveaways(
+    storage: &dyn Storage,
+    now: Timestamp,
+    limit: usize,
+) -> StdResult<Vec<Giveaway>> {
+    GIVEAWAYS_BY_EXPIRY
+        .keys(
+            storage,
+            None,
+            Some(Bound::inclusive((now.seconds(), u64::MAX))),
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|key| key.and_then(|(_, id)| GIVEAWAYS.load(storage, id)))
+        .collect()
+}
+
+pub fn save_pending_entry(storage: &mut dyn Storage, entry: &PendingPayment) -> StdResult<()> {
+    PENDING_ENTRIES.save(storage, entry.id, entry)?;
+    PENDING_BY_TG.save(storage, (entry.to_tg_handle.as_str(), entry.id), &())?;
+    index_pending_expiry(storage, entry)
+}
+
+pub fn remove_pending_entry(storage: &mut dyn Storage, entry: &PendingPayment) {
+    PENDING_ENTRIES.remove(storage, entry.id);
+    PENDING_BY_TG.remove(storage, (entry.to_tg_handle.as_str(), entry.id));
+    match entry.expires {
+        Expiration::AtTime(time) => {
+            PENDING_BY_EXPIRY_TIME.remove(storage, (time.nanos(), entry.id))
+        }
+        Expiration::AtHeight(height) => {
+            PENDING_BY_EXPIRY_HEIGHT.remove(storage, (height, entry.id))
+        }
+        Expiration::Never {} => {}
+    }
+}
+
+fn index_pending_expiry(storage: &mut dyn Storage, entry: &PendingPayment) -> StdResult<()> {
+    match entry.expires {
+        Expiration::AtTime(time) => {
+            PENDING_BY_EXPIRY_TIME.save(storage, (time.nanos(), entry.id), &())
+        }
+        Expiration::AtHeight(height) => {
+            PENDING_BY_EXPIRY_HEIGHT.save(storage, (height, entry.id), &())
+        }
+        Expiration::Never {} => Ok(()),
+    }
+}
+
+/// Older versions didn't index pending payments by expiry. Indexing them again is harmless, so
+/// this runs on every migration.
+pub fn index_pending_expiries(storage: &mut dyn Storage) -> StdResult<()> {
+    let entries = PENDING_ENTRIES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect::<StdResult<Vec<_>>>()?;
+    for entry in &entries {
+        index_pending_expiry(storage, entry)?;
+    }
+
+    Ok(())
+}
+
+/// Up to `limit` pending payments that have expired, soonest expired first, those expiring by
+/// time before those expiring by height
+pub fn expired_pending_entries(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    limit: usize,
+) -> StdResult<Vec<PendingPayment>> {
+    // An expiration is reached at its own second or height, not only after it
+    let by_time = PENDING_BY_EXPIRY_TIME.keys(
+        storage,
+        None,
+        Some(Bound::inclusive((block.time.nanos(), u64::MAX))),
+        Order::Ascending,
+    );
+    let by_height = PENDING_BY_EXPIRY_HEIGHT.keys(
+        storage,
+        None,
+        Some(Bound::inclusive((block.height, u64::MAX))),
+        Order::Ascending,
+    );
+
+    by_time
+        .chain(by_height)
+        .take(limit)
+        .map(|key| key.and_then(|(_, id)| PENDING_ENTRIES.load(storage, id)))
+        .collect()
+}
+
+/// Removes the entry and takes its amount back out of what is held for the handle
+pub fn take_pending_entry(storage: &mut dyn Storage, entry: &PendingPayment) -> StdResult<()> {
+    remove_pending_entry(storage, entry);
+
+    let mut pending = PENDING_PAYMENTS
+        .may_load(storage, entry.to_tg_handle.as_str())?
+        .unwrap_or_default();
+    pending.remove_payment(&Coin {
+        amount: entry.amount,
+        denom: entry.denom.clone(),
+    });
+    if pending.is_empty() {
+        PENDING_PAYMENTS.remove(storage, entry.to_tg_handle.as_str());
+    } else {
+        PENDING_PAYMENTS.save(storage, entry.to_tg_handle.as_str(), &pending)?;
+    }
+
+    Ok(())
+}
+
+/// Spends that still count against the window at `now`, older ones are dropped
+pub fn spends_in_window(
+    storage: &dyn Storage,
+    addr: &Addr,
+    denom: &str,
+    limit: &SpendLimit,
+    now: Timestamp,
+) -> StdResult<Vec<Spend>> {
+    let mut spends = SPENDS.may_load(storage, (addr, denom))?.unwrap_or_default();
+    spends.retain(|spend| spend.time.plus_seconds(limit.window_seconds) > now);
+    Ok(spends)
+}
+
+/// The most the next payment can be under `limit`, None if nothing is capped
+pub fn remaining_spend(limit: &SpendLimit, spends: &[Spend]) -> Option<Uint256> {
+    let spent: Uint256 = spends.iter().map(|spend| spend.amount).sum();
+    let in_window = limit.per_window.map(|cap| cap.saturating_sub(spent));
+    match (limit.per_payment, in_window) {
+        (Some(per_payment), Some(in_window)) => Some(per_payment.min(in_window)),
+        (per_payment, in_window) => per_payment.or(in_window),
+    }
+}
+
+/// What the treasury gets for a send of `amount`, zero if nothing is charged in its denom
+pub fn fee_for(fee: &FeeConfig, amount: &Coin) -> StdResult<Uint256> {
+    let share = amount.amount.multiply_ratio(fee.basis_points, 10_000u32);
+    let flat = fee
+        .flat
+        .iter()
+        .find(|coin| coin.denom == amount.denom)
+        .map(|coin| coin.amount)
+        .unwrap_or_default();
+    Ok(share.checked_add(flat)?)
+}
+
+/// Points the user's alias at `handle`, or clears it if they no longer have a username.
+/// If another user held this handle before, they must have given it up, so their alias is dropped.
+pub fn set_tg_handle(
+    storage: &mut dyn Storage,
+    user_id: i64,
+    handle: Option<&TgHandle>,
+) -> StdResult<()> {
+    let old = TG_HANDLES.may_load(storage, user_id)?;
+    if old.as_ref() == handle {
+        return Ok(());
+    }
+
+    if let Some(old) = old {
+        TG_USER_IDS.remove(storage, old.as_str());
+    }
+
+    match handle {
+        Some(handle) => {
+            if let Some(prev_owner) = TG_USER_IDS.may_load(storage, handle.as_str())? {
+                TG_HANDLES.remove(storage, prev_owner);
+            }
+            TG_USER_IDS.save(storage, handle.as_str(), &user_id)?;
+            TG_HANDLES.save(storage, user_id, handle)?;
+        }
+        None => TG_HANDLES.remove(storage, user_id),
+    }
+
+    Ok(())
+}
+
+/// Moves a handle-keyed registration over to the user id, if there is one and the id is still free.
+/// Returns true if anything was migrated.
+pub fn claim_legacy_account(
+    storage: &mut dyn Storage,
+    user_id: i64,
+    handle: &TgHandle,
+) -> StdResult<bool> {
+    let Some(addr) = LEGACY_OPEN_ACCOUNTS.may_load(storage, handle.as_str())? else {
+        return Ok(false);
+    };
+    if OPEN_ACCOUNTS.has(storage, user_id) {
+        return Ok(false);
+    }
+
+    LEGACY_OPEN_ACCOUNTS.remove(storage, handle.as_str());
+    OPEN_ACCOUNTS.save(storage, user_id, &addr)?;
+
+    if LEGACY_FUNDED_ACCOUNTS.may_load(storage, &addr)?.as_deref() == Some(handle.as_str()) {
+        LEGACY_FUNDED_ACCOUNTS.remove(storage, &addr);
+        FUNDED_ACCOUNTS.save(storage, &addr, &user_id)?;
+    }
+
+    set_tg_handle(storage, user_id, Some(handle))?;
+
+    Ok(true)
+}
+
+/// Rewrites handles stored before they were normalized ("@Alice" and "alice" were different keys).
+/// Pending payments for the same canonical handle are merged. A legacy account whose handle is
+/// invalid, or collides with one already in canonical form, is left as is.
+/// Returns how many keys were rewritten.
+pub fn canonicalize_legacy_handles(storage: &mut dyn Storage) -> StdResult<u32> {
+    let mut rewritten = 0;
+
+    let accounts = LEGACY_OPEN_ACCOUNTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (raw, addr) in accounts {
+        let Ok(handle) = TgHandle::new(&raw) else {
+            continue;
+        };
+        if handle.as_str() == raw || LEGACY_OPEN_ACCOUNTS.has(storage, handle.as_str()) {
+            continue;
+        }
+        LEGACY_OPEN_ACCOUNTS.remove(storage, &raw);
+        LEGACY_OPEN_ACCOUNTS.save(storage, handle.as_str(), &addr)?;
+        if LEGACY_FUNDED_ACCOUNTS.may_load(storage, &addr)?.as_deref() == Some(raw.as_str()) {
+            LEGACY_FUNDED_ACCOUNTS.save(storage, &addr, &handle.to_string())?;
+        }
+        rewritten += 1;
+    }
+
+    let pending = PENDING_PAYMENTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (raw, payments) in pending {
+        let Ok(handle) = TgHandle::new(&raw) else {
+            continue;
+        };
+        if handle.as_str() == raw {
+            continue;
+        }
+        let mut merged = PENDING_PAYMENTS
+            .may_load(storage, handle.as_str())?
+            .unwrap_or_default();
+        for coin in payments.balance() {
+            merged.add_payment(coin);
+        }
+        PENDING_PAYMENTS.remove(storage, &raw);
+        PENDING_PAYMENTS.save(storage, handle.as_str(), &merged)?;
+        rewritten += 1;
+    }
+
+    Ok(rewritten)
+}
+
+/// Remembers the message was handled at `now`
+pub fn save_processed_message(
+    storage: &mut dyn Storage,
+    chat_id: i64,
+    message_id: i64,
+    now: Timestamp,
+) -> StdResult<()> {
+    PROCESSED_MESSAGES.save(storage, (chat_id, message_id), &now)?;
+    PROCESSED_BY_TIME.save(storage, (now.seconds(), chat_id, message_id), &())
+}
+
+/// Forgets up to `limit` messages that were seen before `cutoff`, oldest first
+pub fn prune_processed_messages(
+    storage: &mut dyn Storage,
+    cutoff: Timestamp,
+    limit: usize,
+) -> StdResult<()> {
+    let stale = PROCESSED_BY_TIME
+        .keys(storage, None, None, Order::Ascending)
+        .take_while(|key| match key {
+            Ok((seconds, _, _)) => *seconds < cutoff.seconds(),
+            Err(_) => true,
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    for (seconds, chat_id, message_id) in stale {
+        PROCESSED_BY_TIME.remove(storage, (seconds, chat_id, message_id));
+        PROCESSED_MESSAGES.remove(storage, (chat_id, message_id));
+    }
+
+    Ok(())
+}
+
+/// Exactly one of SERVICE_MANAGER and ADMIN is set, switching modes clears the other
+pub fn save_auth(storage: &mut dyn Storage, api: &dyn Api, auth: &Auth) -> StdResult<()> {
+    match auth {
+        Auth::ServiceManager(service_manager) => {
+            SERVICE_MANAGER.save(storage, &api.addr_validate(service_manager)?)?;
+            ADMIN.remove(storage);
+        }
+        Auth::Admin(admin) => {
+            ADMIN.save(storage, &api.addr_validate(admin)?)?;
+            SERVICE_MANAGER.remove(storage);
+        }
+    }
+    Ok(())
+}
+
+pub fn load_auth(storage: &dyn Storage) -> StdResult<Auth> {
+    match SERVICE_MANAGER.may_load(storage)? {
+        Some(service_manager) => Ok(Auth::ServiceManager(service_manager.into())),
+        None => Ok(Auth::Admin(ADMIN.load(storage)?.into())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,4 +993,289 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_prune_processed_messages() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+        save_processed_message(&mut storage, 1, 10, Timestamp::from_seconds(100)).unwrap();
+        save_processed_message(&mut storage, 1, 11, Timestamp::from_seconds(200)).unwrap();
+        save_processed_message(&mut storage, 2, 10, Timestamp::from_seconds(300)).unwrap();
+
+        // limit is respected, oldest goes first
+        prune_processed_messages(&mut storage, Timestamp::from_seconds(250), 1).unwrap();
+        assert!(!PROCESSED_MESSAGES.has(&storage, (1, 10)));
+        assert!(PROCESSED_MESSAGES.has(&storage, (1, 11)));
+
+        prune_processed_messages(&mut storage, Timestamp::from_seconds(250), 10).unwrap();
+        assert!(!PROCESSED_MESSAGES.has(&storage, (1, 11)));
+        assert!(PROCESSED_MESSAGES.has(&storage, (2, 10)));
+        assert_eq!(
+            PROCESSED_BY_TIME
+                .keys(&storage, None, None, Order::Ascending)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_set_tg_handle() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+
+        let alice = TgHandle::new("alice").unwrap();
+        let alice2 = TgHandle::new("alice2").unwrap();
+
+        set_tg_handle(&mut storage, 1, Some(&alice)).unwrap();
+        assert_eq!(TG_USER_IDS.load(&storage, "alice").unwrap(), 1);
+
+        // rename frees up the old handle
+        set_tg_handle(&mut storage, 1, Some(&alice2)).unwrap();
+        assert!(!TG_USER_IDS.has(&storage, "alice"));
+        assert_eq!(TG_HANDLES.load(&storage, 1).unwrap(), alice2);
+
+        // someone else picks up the freed handle, then takes the new one too
+        set_tg_handle(&mut storage, 2, Some(&alice)).unwrap();
+        set_tg_handle(&mut storage, 2, Some(&alice2)).unwrap();
+        assert!(!TG_HANDLES.has(&storage, 1));
+        assert!(!TG_USER_IDS.has(&storage, "alice"));
+        assert_eq!(TG_USER_IDS.load(&storage, "alice2").unwrap(), 2);
+
+        // dropping the username clears the alias
+        set_tg_handle(&mut storage, 2, None).unwrap();
+        assert!(!TG_HANDLES.has(&storage, 2));
+        assert!(!TG_USER_IDS.has(&storage, "alice2"));
+    }
+
+    #[test]
+    fn test_claim_legacy_account() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+        let addr = Addr::unchecked("cosmos1alice");
+        LEGACY_OPEN_ACCOUNTS
+            .save(&mut storage, "alice", &addr)
+            .unwrap();
+        LEGACY_FUNDED_ACCOUNTS
+            .save(&mut storage, &addr, &"alice".to_string())
+            .unwrap();
+
+        let alice = TgHandle::new("alice").unwrap();
+        let bobby = TgHandle::new("bobby").unwrap();
+
+        assert!(!claim_legacy_account(&mut storage, 1, &bobby).unwrap());
+        assert!(claim_legacy_account(&mut storage, 1, &alice).unwrap());
+        assert!(!claim_legacy_account(&mut storage, 1, &alice).unwrap());
+
+        assert_eq!(OPEN_ACCOUNTS.load(&storage, 1).unwrap(), addr);
+        assert_eq!(FUNDED_ACCOUNTS.load(&storage, &addr).unwrap(), 1);
+        assert_eq!(TG_USER_IDS.load(&storage, "alice").unwrap(), 1);
+        assert!(!LEGACY_OPEN_ACCOUNTS.has(&storage, "alice"));
+        assert!(!LEGACY_FUNDED_ACCOUNTS.has(&storage, &addr));
+    }
+
+    #[test]
+    fn test_canonicalize_legacy_handles() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+        let alice = Addr::unchecked("cosmos1alice");
+        let bob = Addr::unchecked("cosmos1bob");
+        LEGACY_OPEN_ACCOUNTS
+            .save(&mut storage, "@Alice", &alice)
+            .unwrap();
+        LEGACY_FUNDED_ACCOUNTS
+            .save(&mut storage, &alice, &"@Alice".to_string())
+            .unwrap();
+        // invalid handles are left alone
+        LEGACY_OPEN_ACCOUNTS
+            .save(&mut storage, "@ab", &bob)
+            .unwrap();
+
+        // the same recipient written two ways
+        for (raw, amount) in [("@Carol", 100u128), ("carol", 50u128)] {
+            let mut pending = PendingPayments::default();
+            pending.add_payment(Coin {
+                amount: Uint256::from(amount),
+                denom: "uusd".to_string(),
+            });
+            PENDING_PAYMENTS.save(&mut storage, raw, &pending).unwrap();
+        }
+
+        assert_eq!(canonicalize_legacy_handles(&mut storage).unwrap(), 2);
+        assert_eq!(canonicalize_legacy_handles(&mut storage).unwrap(), 0);
+
+        assert_eq!(LEGACY_OPEN_ACCOUNTS.load(&storage, "alice").unwrap(), alice);
+        assert!(!LEGACY_OPEN_ACCOUNTS.has(&storage, "@Alice"));
+        assert_eq!(
+            LEGACY_FUNDED_ACCOUNTS.load(&storage, &alice).unwrap(),
+            "alice"
+        );
+        assert_eq!(LEGACY_OPEN_ACCOUNTS.load(&storage, "@ab").unwrap(), bob);
+
+        assert!(!PENDING_PAYMENTS.has(&storage, "@Carol"));
+        assert_eq!(
+            PENDING_PAYMENTS.load(&storage, "carol").unwrap().balance(),
+            vec![Coin {
+                amount: Uint256::from(150u128),
+                denom: "uusd".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_backfill_stats() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+        let alice = Addr::unchecked("cosmos1alice");
+        let bob = Addr::unchecked("cosmos1bob");
+        OPEN_ACCOUNTS.save(&mut storage, 1, &alice).unwrap();
+        FUNDED_ACCOUNTS.save(&mut storage, &alice, &1).unwrap();
+        LEGACY_OPEN_ACCOUNTS
+            .save(&mut storage, "bobby", &bob)
+            .unwrap();
+
+        // held before the ledger existed, so there is no record of it
+        let mut pending = PendingPayments::default();
+        pending.add_payment(Coin {
+            amount: Uint256::from(40u128),
+            denom: "uusd".to_string(),
+        });
+        PENDING_PAYMENTS
+            .save(&mut storage, "carol", &pending)
+            .unwrap();
+
+        for (id, kind, amount) in [
+            (1, PaymentKind::Direct, 100u128),
+            (2, PaymentKind::Pending, 30),
+            (3, PaymentKind::Refunded, 30),
+        ] {
+            let record = PaymentRecord {
+                id,
+                kind,
+                from_tg_id: None,
+                from_tg_handle: None,
+                to_tg_id: None,
+                to_tg_handle: None,
+                from_address: alice.clone(),
+                to_address: bob.clone(),
+                amount: Uint256::from(amount),
+                denom: "uusd".to_string(),
+                block_height: 1,
+                timestamp: Timestamp::from_seconds(1),
+            };
+            PAYMENTS.save(&mut storage, id, &record).unwrap();
+        }
+
+        backfill_stats(&mut storage).unwrap();
+
+        let counts = ACCOUNT_COUNTS.load(&storage).unwrap();
+        assert_eq!(counts.registered, 2);
+        assert_eq!(counts.funded, 1);
+        assert_eq!(
+            VOLUME.load(&storage, "uusd").unwrap(),
+            Uint256::from(130u128)
+        );
+        assert_eq!(
+            PENDING_TOTALS.load(&storage, "uusd").unwrap(),
+            Uint256::from(40u128)
+        );
+    }
+
+    #[test]
+    fn test_remove_payment() {
+        let mut pending = PendingPayments::default();
+        pending.add_payment(Coin {
+            amount: Uint256::from(100u128),
+            denom: "uusd".to_string(),
+        });
+        pending.add_payment(Coin {
+            amount: Uint256::from(200u128),
+            denom: "ntrn".to_string(),
+        });
+        pending.remove_payment(&Coin {
+            amount: Uint256::from(50u128),
+            denom: "uusd".to_string(),
+        });
+        pending.remove_payment(&Coin {
+            amount: Uint256::from(200u128),
+            denom: "ntrn".to_string(),
+        });
+        assert!(!pending.is_empty());
+        assert_eq!(
+            pending.balance(),
+            vec![Coin {
+                amount: Uint256::from(50u128),
+                denom: "uusd".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_remaining_spend() {
+        let spends = vec![
+            Spend {
+                time: Timestamp::from_seconds(10),
+                amount: Uint256::from(150u128),
+            },
+            Spend {
+                time: Timestamp::from_seconds(20),
+                amount: Uint256::from(80u128),
+            },
+        ];
+        let limit = SpendLimit {
+            per_payment: Some(Uint256::from(100u128)),
+            per_window: Some(Uint256::from(250u128)),
+            window_seconds: 60,
+        };
+
+        // Whichever cap is tighter
+        assert_eq!(
+            remaining_spend(&limit, &spends),
+            Some(Uint256::from(20u128))
+        );
+        assert_eq!(remaining_spend(&limit, &[]), Some(Uint256::from(100u128)));
+        assert_eq!(
+            remaining_spend(
+                &SpendLimit {
+                    per_payment: None,
+                    ..limit.clone()
+                },
+                &spends
+            ),
+            Some(Uint256::from(20u128))
+        );
+        // Spending past a lowered cap leaves nothing, rather than underflowing
+        assert_eq!(
+            remaining_spend(
+                &SpendLimit {
+                    per_window: Some(Uint256::from(100u128)),
+                    ..limit
+                },
+                &spends
+            ),
+            Some(Uint256::zero())
+        );
+    }
+
+    #[test]
+    fn test_fee_for() {
+        let fee = FeeConfig {
+            basis_points: 150,
+            flat: vec![Coin {
+                amount: Uint256::from(5u128),
+                denom: "untrn".to_string(),
+            }],
+            treasury: "treasury".to_string(),
+        };
+        let coin = |amount: u128, denom: &str| Coin {
+            amount: Uint256::from(amount),
+            denom: denom.to_string(),
+        };
+
+        // 1.5% plus the flat amount for the denom
+        assert_eq!(
+            fee_for(&fee, &coin(1000, "untrn")).unwrap(),
+            Uint256::from(20u128)
+        );
+        // Only the share where there is no flat amount, rounded down
+        assert_eq!(
+            fee_for(&fee, &coin(1000, "uatom")).unwrap(),
+            Uint256::from(15u128)
+        );
+        assert_eq!(fee_for(&fee, &coin(50, "uatom")).unwrap(), Uint256::zero());
+    }
 }
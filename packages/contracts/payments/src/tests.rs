@@ -1,10 +1,22 @@
 use cosmwasm_std::{
+    coin, from_json,
     testing::{mock_dependencies, mock_env},
-    MessageInfo,
+    BankMsg, Binary, CosmosMsg, IbcEndpoint, IbcMsg, IbcPacket, IbcSourceCallbackMsg,
+    IbcTimeoutCallbackMsg, MessageInfo, MsgResponse, Reply, SubMsgResponse, SubMsgResult,
+};
+use cw_utils::Duration;
+use tg_contract_api::payments::event::{PaymentFailedEvent, PayoutReturnedEvent, SendPaymentEvent};
+use tg_contract_api::payments::msg::{
+    Auth, CustomExecuteMsg, CustomQueryMsg, ExecuteMsg, IbcRoute, InstantiateMsg, MigrateMsg,
+    PendingBalancesResponse, QueryMsg, RegisterReceiveMsg, SendPaymentMsg, WavsPayload,
 };
-use tg_contract_api::payments::msg::{Auth, InstantiateMsg};
 
-use crate::instantiate;
+use crate::error::ContractError;
+use crate::state::{
+    PendingPayments, IBC_TRANSFERS, OWNER, PAYMENTS, PENDING_ENTRIES, PENDING_EXPIRY,
+    PENDING_PAYMENTS,
+};
+use crate::{execute, ibc_source_callback, instantiate, migrate, query, reply};
 
 #[test]
 fn test_instantiate_unit() {
@@ -15,17 +27,518 @@ fn test_instantiate_unit() {
     let admin = deps.api.addr_make("admin");
 
     let info = MessageInfo {
-        sender,
+        sender: sender.clone(),
         funds: vec![],
     };
 
     let msg = InstantiateMsg {
         allowed_denoms: vec!["untrn".to_string(), "uatom".to_string()],
         auth: Auth::Admin(admin.to_string()),
+        pending_expiry: None,
+        owner: None,
+        fee: None,
     };
 
     let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-    assert_eq!(res.attributes.len(), 2);
+    assert_eq!(res.attributes.len(), 3);
     assert_eq!(res.attributes[0].key, "method");
     assert_eq!(res.attributes[0].value, "instantiate");
+    assert_eq!(res.attributes[1].key, "owner");
+    assert_eq!(res.attributes[1].value, sender.to_string());
+}
+
+#[test]
+fn test_migrate_sets_missing_owner() {
+    let mut deps = mock_dependencies();
+    let owner = deps.api.addr_make("owner");
+
+    // Deployments from before ownership was tracked have no owner to keep
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg::default()).unwrap_err();
+    assert!(matches!(err, ContractError::OwnerRequired));
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some(owner.to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(OWNER.load(&deps.storage).unwrap(), owner);
+
+    // Once set, only a transfer changes it
+    let other = deps.api.addr_make("other");
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some(other.to_string()),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(OWNER.load(&deps.storage).unwrap(), owner);
+}
+
+#[test]
+fn test_migrate_sets_pending_expiry() {
+    let mut deps = mock_dependencies();
+    let owner = deps.api.addr_make("owner");
+    let migrate_msg = |pending_expiry| MigrateMsg {
+        owner: Some(owner.to_string()),
+        pending_expiry,
+        ..Default::default()
+    };
+
+    migrate(deps.as_mut(), mock_env(), migrate_msg(None)).unwrap();
+    assert_eq!(PENDING_EXPIRY.may_load(&deps.storage).unwrap(), None);
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        migrate_msg(Some(Duration::Time(3600))),
+    )
+    .unwrap();
+    assert_eq!(
+        PENDING_EXPIRY.load(&deps.storage).unwrap(),
+        Duration::Time(3600)
+    );
+
+    // Leaving it out keeps what is set
+    migrate(deps.as_mut(), mock_env(), migrate_msg(None)).unwrap();
+    assert_eq!(
+        PENDING_EXPIRY.load(&deps.storage).unwrap(),
+        Duration::Time(3600)
+    );
+
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        migrate_msg(Some(Duration::Height(0))),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::InvalidPendingExpiry));
+}
+
+#[test]
+fn test_list_pending_payments_pages_past_invalid_handles() {
+    let mut deps = mock_dependencies();
+    let mut pending = PendingPayments::default();
+    pending.add_payment(coin(100, "untrn"));
+    // Sorts ahead of the valid handles, and migration couldn't make a handle of it
+    for handle in ["@ab", "alice", "bobby"] {
+        PENDING_PAYMENTS
+            .save(&mut deps.storage, handle, &pending)
+            .unwrap();
+    }
+
+    let page: PendingBalancesResponse = from_json(
+        query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Custom(CustomQueryMsg::ListPendingPayments {
+                start_after: None,
+                limit: Some(1),
+            }),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(page.balances.len(), 1);
+    assert_eq!(page.balances[0].handle.as_str(), "alice");
+}
+
+#[test]
+fn test_send_grant_passes_on_query_errors() {
+    let deps = mock_dependencies();
+    let alice = deps.api.addr_make("alice");
+
+    // The mock chain has no authz module, which is not the same as having no grant
+    query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Custom(CustomQueryMsg::SendGrant {
+            account: alice.to_string(),
+        }),
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_batch_entry_rejects_payloads_without_a_message() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let contract = env.contract.address.clone();
+
+    let err = execute(
+        deps.as_mut(),
+        env,
+        MessageInfo {
+            sender: contract,
+            funds: vec![],
+        },
+        ExecuteMsg::Custom(CustomExecuteMsg::ProcessBatchEntry {
+            payload: WavsPayload::ReleaseMatured { limit: None },
+        }),
+    )
+    .unwrap_err();
+    assert!(matches!(err, ContractError::UnexpectedPayload));
+}
+
+#[test]
+fn test_ibc_send_refunded_on_timeout() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let owner = deps.api.addr_make("owner");
+    let admin = deps.api.addr_make("admin");
+    let alice = deps.api.addr_make("alice");
+    let dave = "osmo1v84qsqlcs56j8dmh6s22eccnpn2d87fdrkd3r6";
+    let info = |sender: &cosmwasm_std::Addr| MessageInfo {
+        sender: sender.clone(),
+        funds: vec![],
+    };
+    let exec = |msg| ExecuteMsg::Custom(msg);
+
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info(&owner),
+        InstantiateMsg {
+            allowed_denoms: vec!["untrn".to_string()],
+            auth: Auth::Admin(admin.to_string()),
+            pending_expiry: None,
+            owner: None,
+            fee: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&owner),
+        exec(CustomExecuteMsg::SetIbcRoute {
+            route: IbcRoute {
+                prefix: "osmo".to_string(),
+                channel_id: "channel-0".to_string(),
+                chain_id: "osmosis-1".to_string(),
+                timeout_seconds: 600,
+            },
+        }),
+    )
+    .unwrap();
+    for (tg_user_id, chain_addr) in [(1, alice.to_string()), (2, dave.to_string())] {
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info(&admin),
+            exec(CustomExecuteMsg::RegisterReceive(RegisterReceiveMsg {
+                chat_id: -100,
+                message_id: tg_user_id,
+                tg_user_id,
+                tg_handle: Some(format!("@user{tg_user_id}").parse().unwrap()),
+                chain_addr,
+            })),
+        )
+        .unwrap();
+    }
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&alice),
+        exec(CustomExecuteMsg::RegisterSend {
+            tg_handle: "@user1".parse().unwrap(),
+        }),
+    )
+    .unwrap();
+
+    // Pulled into the contract first
+    let resp = execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&admin),
+        exec(CustomExecuteMsg::SendPayment(SendPaymentMsg {
+            chat_id: -100,
+            message_id: 42,
+            from_tg_id: 1,
+            from_tg: Some("@user1".parse().unwrap()),
+            to_tg: "@user2".parse().unwrap(),
+            amount: 1000u128.into(),
+            denom: "untrn".to_string(),
+        })),
+    )
+    .unwrap();
+    let pull = resp.messages.last().unwrap();
+    #[allow(deprecated)]
+    let pulled = SubMsgResponse {
+        events: vec![],
+        data: None,
+        msg_responses: vec![],
+    };
+    let resp = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: pull.id,
+            payload: pull.payload.clone(),
+            gas_used: 0,
+            result: SubMsgResult::Ok(pulled),
+        },
+    )
+    .unwrap();
+
+    // Then sent on over the route, with the contract asking to hear back
+    let transfer = &resp.messages[0];
+    let CosmosMsg::Ibc(IbcMsg::Transfer {
+        channel_id,
+        to_address,
+        memo,
+        ..
+    }) = &transfer.msg
+    else {
+        panic!("expected an ibc transfer, got {:?}", transfer.msg);
+    };
+    assert_eq!(channel_id, "channel-0");
+    assert_eq!(to_address, dave);
+    assert!(memo.as_ref().unwrap().contains("src_callback"));
+    #[allow(deprecated)]
+    let sent = SubMsgResponse {
+        events: vec![],
+        data: None,
+        msg_responses: vec![MsgResponse {
+            type_url: "/ibc.applications.transfer.v1.MsgTransferResponse".to_string(),
+            // Sequence 300, as a varint
+            value: Binary::from(vec![0x08, 0xac, 0x02]),
+        }],
+    };
+    let resp = reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: transfer.id,
+            payload: transfer.payload.clone(),
+            gas_used: 0,
+            result: SubMsgResult::Ok(sent),
+        },
+    )
+    .unwrap();
+    let sent = SendPaymentEvent::try_from(&resp.events[0]).unwrap();
+    assert_eq!(sent.ibc_channel.as_deref(), Some("channel-0"));
+    assert_eq!(sent.to_chain.as_deref(), Some("osmosis-1"));
+    assert!(IBC_TRANSFERS.has(&deps.storage, ("channel-0", 300)));
+
+    // ICS20 hands the funds back to the contract, which passes them on to the sender
+    let packet = IbcPacket::new(
+        Binary::default(),
+        IbcEndpoint {
+            port_id: "transfer".to_string(),
+            channel_id: "channel-0".to_string(),
+        },
+        IbcEndpoint {
+            port_id: "transfer".to_string(),
+            channel_id: "channel-7".to_string(),
+        },
+        300,
+        env.block.time.plus_seconds(600).into(),
+    );
+    let resp = ibc_source_callback(
+        deps.as_mut(),
+        env.clone(),
+        IbcSourceCallbackMsg::Timeout(IbcTimeoutCallbackMsg::new(packet, owner.clone())),
+    )
+    .unwrap();
+    assert_eq!(
+        resp.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: alice.to_string(),
+            amount: vec![cosmwasm_std::coin(1000, "untrn")],
+        })
+    );
+    let failed = PaymentFailedEvent::try_from(&resp.events[0]).unwrap();
+    assert_eq!(failed.message_id, 42);
+    assert!(failed.reason.contains("timed out"));
+    assert!(!IBC_TRANSFERS.has(&deps.storage, ("channel-0", 300)));
+    assert!(PAYMENTS.is_empty(&deps.storage));
+}
+
+#[test]
+fn test_ibc_payout_held_again_on_timeout() {
+    let mut deps = mock_dependencies();
+    let env = mock_env();
+    let owner = deps.api.addr_make("owner");
+    let admin = deps.api.addr_make("admin");
+    let alice = deps.api.addr_make("alice");
+    let dave = "osmo1v84qsqlcs56j8dmh6s22eccnpn2d87fdrkd3r6";
+    let info = |sender: &cosmwasm_std::Addr| MessageInfo {
+        sender: sender.clone(),
+        funds: vec![],
+    };
+    let exec = |msg| ExecuteMsg::Custom(msg);
+    let register = |tg_user_id: i64, chain_addr: String| {
+        exec(CustomExecuteMsg::RegisterReceive(RegisterReceiveMsg {
+            chat_id: -100,
+            message_id: tg_user_id,
+            tg_user_id,
+            tg_handle: Some(format!("@user{tg_user_id}").parse().unwrap()),
+            chain_addr,
+        }))
+    };
+
+    instantiate(
+        deps.as_mut(),
+        env.clone(),
+        info(&owner),
+        InstantiateMsg {
+            allowed_denoms: vec!["untrn".to_string()],
+            auth: Auth::Admin(admin.to_string()),
+            pending_expiry: None,
+            owner: None,
+            fee: None,
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&owner),
+        exec(CustomExecuteMsg::SetIbcRoute {
+            route: IbcRoute {
+                prefix: "osmo".to_string(),
+                channel_id: "channel-0".to_string(),
+                chain_id: "osmosis-1".to_string(),
+                timeout_seconds: 600,
+            },
+        }),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&admin),
+        register(1, alice.to_string()),
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&alice),
+        exec(CustomExecuteMsg::RegisterSend {
+            tg_handle: "@user1".parse().unwrap(),
+        }),
+    )
+    .unwrap();
+
+    // Held for @user2, who hasn't registered yet
+    let resp = execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&admin),
+        exec(CustomExecuteMsg::SendPayment(SendPaymentMsg {
+            chat_id: -100,
+            message_id: 42,
+            from_tg_id: 1,
+            from_tg: Some("@user1".parse().unwrap()),
+            to_tg: "@user2".parse().unwrap(),
+            amount: 1000u128.into(),
+            denom: "untrn".to_string(),
+        })),
+    )
+    .unwrap();
+    let pull = resp.messages.last().unwrap();
+    #[allow(deprecated)]
+    let pulled = SubMsgResponse {
+        events: vec![],
+        data: None,
+        msg_responses: vec![],
+    };
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: pull.id,
+            payload: pull.payload.clone(),
+            gas_used: 0,
+            result: SubMsgResult::Ok(pulled),
+        },
+    )
+    .unwrap();
+
+    // Registering an address on another chain releases it over the route
+    let resp = execute(
+        deps.as_mut(),
+        env.clone(),
+        info(&admin),
+        register(2, dave.to_string()),
+    )
+    .unwrap();
+    let transfer = resp
+        .messages
+        .iter()
+        .find(|msg| matches!(msg.msg, CosmosMsg::Ibc(IbcMsg::Transfer { .. })))
+        .unwrap();
+    assert!(!PENDING_PAYMENTS.has(&deps.storage, "user2"));
+    #[allow(deprecated)]
+    let sent = SubMsgResponse {
+        events: vec![],
+        data: None,
+        msg_responses: vec![MsgResponse {
+            type_url: "/ibc.applications.transfer.v1.MsgTransferResponse".to_string(),
+            // Sequence 300, as a varint
+            value: Binary::from(vec![0x08, 0xac, 0x02]),
+        }],
+    };
+    reply(
+        deps.as_mut(),
+        env.clone(),
+        Reply {
+            id: transfer.id,
+            payload: transfer.payload.clone(),
+            gas_used: 0,
+            result: SubMsgResult::Ok(sent),
+        },
+    )
+    .unwrap();
+    assert!(IBC_TRANSFERS.has(&deps.storage, ("channel-0", 300)));
+
+    // It never arrives, so it is held for the handle again
+    let packet = IbcPacket::new(
+        Binary::default(),
+        IbcEndpoint {
+            port_id: "transfer".to_string(),
+            channel_id: "channel-0".to_string(),
+        },
+        IbcEndpoint {
+            port_id: "transfer".to_string(),
+            channel_id: "channel-7".to_string(),
+        },
+        300,
+        env.block.time.plus_seconds(600).into(),
+    );
+    let resp = ibc_source_callback(
+        deps.as_mut(),
+        env.clone(),
+        IbcSourceCallbackMsg::Timeout(IbcTimeoutCallbackMsg::new(packet, owner.clone())),
+    )
+    .unwrap();
+    assert!(resp.messages.is_empty());
+    let returned = PayoutReturnedEvent::try_from(&resp.events[0]).unwrap();
+    assert_eq!(returned.to_tg_id, Some(2));
+    assert_eq!(returned.held_for, Some("@user2".parse().unwrap()));
+    assert!(returned.reason.contains("timed out"));
+    assert_eq!(
+        PENDING_PAYMENTS
+            .load(&deps.storage, "user2")
+            .unwrap()
+            .balance(),
+        vec![coin(1000, "untrn")]
+    );
+    assert!(PENDING_ENTRIES.has(&deps.storage, 1));
+    assert!(!IBC_TRANSFERS.has(&deps.storage, ("channel-0", 300)));
+    // Only the original pending payment is left in the ledger
+    assert_eq!(
+        PAYMENTS
+            .keys(&deps.storage, None, None, cosmwasm_std::Order::Ascending)
+            .count(),
+        1
+    );
 }
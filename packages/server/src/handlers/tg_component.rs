@@ -3,6 +3,8 @@ use axum::extract::{Json, State};
 use axum::response::IntoResponse;
 #[cfg(debug_assertions)]
 use tg_components_shared::ReportEventRequest;
+#[cfg(debug_assertions)]
+use tg_contract_api::payments::handle::TgHandle;
 
 #[cfg(debug_assertions)]
 #[axum::debug_handler]
@@ -10,8 +12,22 @@ pub async fn handle_tg_component(
     State(state): State<HttpState>,
     Json(req): Json<ReportEventRequest>,
 ) -> impl IntoResponse {
+    use cw_utils::Expiration;
     use tg_components_shared::ReportEvent;
-    use tg_contract_api::payments::event::{ConnectEvent, RegistrationEvent, SendPaymentEvent};
+    use tg_contract_api::payments::event::{
+        BlocklistUpdatedEvent, ConnectEvent, GiftClaimedEvent, GiftCreatedEvent, GiftRefundedEvent,
+        GiftReservedEvent, GiveawayClaimedEvent, GiveawayCreatedEvent, GiveawayRefundedEvent,
+        InvoiceCreatedEvent, InvoiceDeclinedEvent, InvoicePaidEvent, PauseUpdatedEvent,
+        PaymentCancelledEvent, PaymentFailedEvent, PaymentHeldEvent, PaymentReleasedEvent,
+        PayoutReturnedEvent, ProposalCreatedEvent, ProposalExecutedEvent, ProposalRejectedEvent,
+        ProposalVotedEvent, RegistrationEvent, ScheduleCreatedEvent, ScheduleEndedEvent,
+        ScheduleRunFailedEvent, SendPaymentEvent, SplitPaymentEvent, TreasuryCreatedEvent,
+        TreasuryFundedEvent,
+    };
+    use tg_utils::telegram::api::bot::TelegramWavsCommandPrefix;
+    use tg_utils::telegram::api::bot::{
+        display_interval, GiveawayButton, InvoiceButton, ProposalButton,
+    };
 
     use crate::error::AnyError;
 
@@ -29,33 +45,642 @@ pub async fn handle_tg_component(
         return axum::http::StatusCode::OK.into_response();
     }
 
+    let denoms = state.denom_registry().await;
+
+    // Invoices are answered from the group, with the buttons on the prompt itself, and so are
+    // proposals that need more than the proposer's own approval, and giveaways
+    let buttons: Vec<(&str, String)> = match &req.event {
+        ReportEvent::InvoiceCreated(InvoiceCreatedEvent { id, .. }) => {
+            [InvoiceButton::Pay, InvoiceButton::Decline]
+                .into_iter()
+                .map(|button| (button.label(), button.callback_data(*id)))
+                .collect()
+        }
+        ReportEvent::ProposalCreated(ProposalCreatedEvent { id, threshold, .. })
+            if *threshold > 1 =>
+        {
+            [ProposalButton::Approve, ProposalButton::Reject]
+                .into_iter()
+                .map(|button| (button.label(), button.callback_data(*id)))
+                .collect()
+        }
+        ReportEvent::GiveawayCreated(GiveawayCreatedEvent { id, .. }) => {
+            vec![(
+                GiveawayButton::Claim.label(),
+                GiveawayButton::Claim.callback_data(*id),
+            )]
+        }
+        _ => vec![],
+    };
+
+    // A failed payment is only the sender's business, so it goes to them rather than the group.
+    // So do gifts, the link is a secret and the claimer already got a reply to opening it.
+    let direct_to = match &req.event {
+        ReportEvent::PaymentFailed(PaymentFailedEvent { from_tg_id, .. })
+        | ReportEvent::ScheduleRunFailed(ScheduleRunFailedEvent { from_tg_id, .. })
+        | ReportEvent::GiftCreated(GiftCreatedEvent { from_tg_id, .. })
+        | ReportEvent::GiftClaimed(GiftClaimedEvent { from_tg_id, .. })
+        | ReportEvent::GiftRefunded(GiftRefundedEvent { from_tg_id, .. }) => Some(*from_tg_id),
+        ReportEvent::GiftReserved(GiftReservedEvent { to_tg_id, .. }) => Some(*to_tg_id),
+        ReportEvent::PayoutReturned(PayoutReturnedEvent { to_tg_id, .. }) => *to_tg_id,
+        _ => None,
+    };
+
     let text = match req.event {
-        ReportEvent::Connect(ConnectEvent { tg_handle, address }) => {
+        ReportEvent::Connect(ConnectEvent {
+            tg_user_id,
+            tg_handle,
+            address,
+        }) => {
             format!(
-                "User connected!\nTelegram: @{}\nAddress: {}",
-                tg_handle, address
+                "User connected!\nTelegram: {}\nAddress: {}",
+                display_user(tg_user_id, tg_handle.as_ref()),
+                address
             )
         }
-        ReportEvent::Registration(RegistrationEvent { tg_handle, address }) => {
+        ReportEvent::Registration(RegistrationEvent {
+            tg_user_id,
+            tg_handle,
+            address,
+        }) => {
             format!(
-                "New user registered!\nTelegram: @{}\nAddress: {}",
-                tg_handle, address
+                "New user registered!\nTelegram: {}\nAddress: {}",
+                display_user(Some(tg_user_id), tg_handle.as_ref()),
+                address
             )
         }
 
         ReportEvent::SendPayment(SendPaymentEvent {
+            from_tg_id,
+            from_tg_handle,
+            to_tg_id,
+            to_tg_handle,
+            from_address,
+            to_address,
+            amount,
+            denom,
+            fee,
+            to_chain,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            let to = display_user(to_tg_id, to_tg_handle.as_ref());
+            let to_chain = to_chain
+                .map(|chain| format!(" on {chain}"))
+                .unwrap_or_default();
+            let fee = fee
+                .map(|fee| format!("\nFee: {}", denoms.display(fee, &denom)))
+                .unwrap_or_default();
+            format!(
+                "Payment sent!\nFrom: {from} ({from_address})\nTo: {to} ({to_address}{to_chain})\nAmount: {}{fee}",
+                denoms.display(amount, &denom)
+            )
+        }
+
+        // One message for the whole split, rather than one per recipient
+        ReportEvent::SplitPayment(SplitPaymentEvent {
+            from_tg_id,
+            from_tg_handle,
+            from_address,
+            denom,
+            total,
+            fee,
+            legs,
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            let legs = legs
+                .iter()
+                .map(|leg| {
+                    let held = if leg.pending {
+                        " (held until they register)"
+                    } else {
+                        ""
+                    };
+                    format!(
+                        "{}: {}{held}",
+                        leg.to_tg_handle.mention(),
+                        denoms.display(leg.amount, &denom)
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            let fee = fee
+                .map(|fee| format!("\nFee: {}", denoms.display(fee, &denom)))
+                .unwrap_or_default();
+            format!(
+                "Payment split!\nFrom: {from} ({from_address})\nTotal: {}{fee}\n{legs}",
+                denoms.display(total, &denom)
+            )
+        }
+
+        ReportEvent::InvoiceCreated(InvoiceCreatedEvent {
+            id,
+            requester_tg_id,
+            requester_tg_handle,
+            payer_tg_handle,
+            amount,
+            denom,
+            memo,
+            ..
+        }) => {
+            let from = display_user(Some(requester_tg_id), requester_tg_handle.as_ref());
+            let memo = memo
+                .map(|memo| format!("\nFor: {memo}"))
+                .unwrap_or_default();
+            format!(
+                "Payment requested! (invoice #{id})\nFrom: {from}\nTo: {}\nAmount: {}{memo}",
+                payer_tg_handle.mention(),
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::InvoicePaid(InvoicePaidEvent {
+            id,
+            requester_tg_id,
+            requester_tg_handle,
+            payer_tg_handle,
+            amount,
+            denom,
+            fee,
+            ..
+        }) => {
+            let to = display_user(Some(requester_tg_id), requester_tg_handle.as_ref());
+            let fee = fee
+                .map(|fee| format!("\nFee: {}", denoms.display(fee, &denom)))
+                .unwrap_or_default();
+            format!(
+                "Invoice #{id} paid!\n{} paid {to} {}{fee}",
+                payer_tg_handle.mention(),
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::InvoiceDeclined(InvoiceDeclinedEvent {
+            id,
+            requester_tg_id,
+            requester_tg_handle,
+            payer_tg_handle,
+            amount,
+            denom,
+        }) => {
+            let to = display_user(Some(requester_tg_id), requester_tg_handle.as_ref());
+            format!(
+                "Invoice #{id} declined\n{} won't be paying {to} {}",
+                payer_tg_handle.mention(),
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::PaymentFailed(PaymentFailedEvent {
+            to_tg_id,
+            to_tg_handle,
+            amount,
+            denom,
+            reason,
+            ..
+        }) => {
+            let to = display_user(to_tg_id, to_tg_handle.as_ref());
+            format!(
+                "Your payment of {} to {to} didn't go through, nothing was sent.\nReason: {reason}",
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::PauseUpdated(PauseUpdatedEvent { paused: true }) => {
+            "Payments are paused, nothing can be sent or registered until they resume".to_string()
+        }
+        ReportEvent::PauseUpdated(PauseUpdatedEvent { paused: false }) => {
+            "Payments have resumed".to_string()
+        }
+
+        ReportEvent::BlocklistUpdated(BlocklistUpdatedEvent {
+            blocked,
+            handles,
+            addresses,
+        }) => {
+            let action = if blocked { "Blocked" } else { "Unblocked" };
+            let targets = handles
+                .iter()
+                .map(TgHandle::mention)
+                .chain(addresses.iter().map(ToString::to_string))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{action}:\n{targets}")
+        }
+
+        ReportEvent::PaymentHeld(PaymentHeldEvent {
+            id,
+            from_tg_id,
+            from_tg_handle,
+            to_tg_handle,
+            from_address,
+            amount,
+            denom,
+            fee,
+            releases,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            let fee = fee
+                .map(|fee| format!("\nFee: {}", denoms.display(fee, &denom)))
+                .unwrap_or_default();
+            let releases = match releases {
+                Expiration::AtHeight(height) => format!("block {height}"),
+                Expiration::AtTime(time) => format!("{} (unix time)", time.seconds()),
+                Expiration::Never {} => "never".to_string(),
+            };
+            format!(
+                "Payment held! (payment #{id})\nFrom: {from} ({from_address})\nTo: {}\nAmount: {}{fee}\nGoes out at {releases}, until then the sender can cancel it with {} {id}",
+                to_tg_handle.mention(),
+                denoms.display(amount, &denom),
+                TelegramWavsCommandPrefix::Undo
+            )
+        }
+
+        ReportEvent::PaymentReleased(PaymentReleasedEvent {
+            id,
+            from_tg_id,
+            from_tg_handle,
+            to_tg_id,
+            to_tg_handle,
+            from_address,
+            to_address,
+            amount,
+            denom,
+            fee,
+            to_chain,
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            let to = display_user(to_tg_id, Some(&to_tg_handle));
+            let to = match (to_tg_id, to_chain) {
+                (None, _) => format!("{to} (held until they register)"),
+                (Some(_), Some(chain)) => format!("{to} ({to_address} on {chain})"),
+                (Some(_), None) => format!("{to} ({to_address})"),
+            };
+            let fee = fee
+                .map(|fee| format!("\nFee: {}", denoms.display(fee, &denom)))
+                .unwrap_or_default();
+            format!(
+                "Payment #{id} released!\nFrom: {from} ({from_address})\nTo: {to}\nAmount: {}{fee}",
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::PaymentCancelled(PaymentCancelledEvent {
+            id,
+            from_tg_id,
+            from_tg_handle,
+            to_tg_handle,
+            amount,
+            denom,
+            reason,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            let amount = denoms.display(amount, &denom);
+            match reason {
+                None => format!(
+                    "Payment #{id} cancelled\n{from} took back {amount} meant for {}",
+                    to_tg_handle.mention()
+                ),
+                Some(reason) => format!(
+                    "Payment #{id} couldn't be released, {amount} went back to {from}\nReason: {reason}"
+                ),
+            }
+        }
+
+        ReportEvent::ScheduleCreated(ScheduleCreatedEvent {
+            id,
+            from_tg_id,
+            from_tg_handle,
+            to_tg_handle,
+            amount,
+            denom,
+            interval_seconds,
+            until,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            let until = until
+                .map(|until| format!(" until {} (unix time)", until.seconds()))
+                .unwrap_or_default();
+            format!(
+                "Recurring payment set up! (schedule #{id})\nFrom: {from}\nTo: {}\nAmount: {} every {}{until}\nThe first one goes out now, the sender can stop it with {} {id}",
+                to_tg_handle.mention(),
+                denoms.display(amount, &denom),
+                display_interval(interval_seconds),
+                TelegramWavsCommandPrefix::Unschedule
+            )
+        }
+
+        ReportEvent::ScheduleEnded(ScheduleEndedEvent {
+            id,
+            from_tg_id,
             from_tg_handle,
             to_tg_handle,
+            cancelled,
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            match cancelled {
+                true => format!(
+                    "Recurring payment #{id} stopped\n{from} no longer pays {}",
+                    to_tg_handle.mention()
+                ),
+                false => format!(
+                    "Recurring payment #{id} from {from} to {} is done, its last payment went out",
+                    to_tg_handle.mention()
+                ),
+            }
+        }
+
+        ReportEvent::ScheduleRunFailed(ScheduleRunFailedEvent {
+            id,
+            to_tg_handle,
+            amount,
+            denom,
+            due,
+            reason,
+            ..
+        }) => format!(
+            "Your recurring payment #{id} of {} to {} due at {} (unix time) didn't go out, it will be tried again next period\nReason: {reason}",
+            denoms.display(amount, &denom),
+            to_tg_handle.mention(),
+            due.seconds()
+        ),
+
+        ReportEvent::TreasuryCreated(TreasuryCreatedEvent {
+            from_tg_id,
+            from_tg_handle,
+            members,
+            member_count,
+            threshold,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            let members = members
+                .iter()
+                .map(TgHandle::mention)
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "Treasury set up by {from}!\nMembers ({member_count}): {members}\nPayouts need {threshold} approvals\nFund it with {} fund <amount> <denom>, and propose payouts with {}",
+                TelegramWavsCommandPrefix::Treasury,
+                TelegramWavsCommandPrefix::Propose
+            )
+        }
+
+        ReportEvent::TreasuryFunded(TreasuryFundedEvent {
+            from_tg_id,
+            from_tg_handle,
+            amount,
+            denom,
+            balance,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            format!(
+                "Treasury funded!\n{from} added {}, it now holds {}",
+                denoms.display(amount, &denom),
+                denoms.display(balance, &denom)
+            )
+        }
+
+        ReportEvent::ProposalCreated(ProposalCreatedEvent {
+            id,
+            proposer_tg_id,
+            proposer_tg_handle,
+            to_tg_handle,
+            amount,
+            denom,
+            memo,
+            threshold,
+            ..
+        }) => {
+            let from = display_user(Some(proposer_tg_id), proposer_tg_handle.as_ref());
+            let memo = memo
+                .map(|memo| format!("\nFor: {memo}"))
+                .unwrap_or_default();
+            format!(
+                "Payout proposed! (proposal #{id})\nBy: {from}\nTo: {}\nAmount: {}{memo}\nApprovals: 1 of {threshold}",
+                to_tg_handle.mention(),
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::ProposalVoted(ProposalVotedEvent {
+            id,
+            voter_tg_id,
+            voter_tg_handle,
+            approve,
+            approvals,
+            rejections,
+            threshold,
+            ..
+        }) => {
+            let voter = display_user(Some(voter_tg_id), voter_tg_handle.as_ref());
+            format!(
+                "{voter} {} proposal #{id}\nApprovals: {approvals} of {threshold}, rejections: {rejections}",
+                if approve { "approved" } else { "rejected" }
+            )
+        }
+
+        ReportEvent::ProposalExecuted(ProposalExecutedEvent {
+            id,
+            to_tg_handle,
+            to_address,
+            amount,
+            denom,
+            to_chain,
+            ..
+        }) => {
+            let chain = to_chain
+                .map(|chain| format!(" on {chain}"))
+                .unwrap_or_default();
+            format!(
+                "Proposal #{id} approved!\nThe treasury paid {} {} ({to_address}{chain})",
+                to_tg_handle.mention(),
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::ProposalRejected(ProposalRejectedEvent {
+            id,
+            to_tg_handle,
+            amount,
+            denom,
+            ..
+        }) => format!(
+            "Proposal #{id} rejected\nThe treasury won't be paying {} {}",
+            to_tg_handle.mention(),
+            denoms.display(amount, &denom)
+        ),
+
+        ReportEvent::GiftCreated(GiftCreatedEvent {
+            id,
+            chat_id,
+            message_id,
+            amount,
+            denom,
+            fee,
+            expires,
+            ..
+        }) => {
+            let link = match state.tg_bot().gift_link(chat_id, message_id).await {
+                Ok(link) => link,
+                Err(e) => {
+                    tracing::error!("Failed to work out the link for gift #{id}: {:?}", e);
+                    return AnyError::from(e).into_response();
+                }
+            };
+            let fee = fee
+                .map(|fee| format!("\nFee: {}", denoms.display(fee, &denom)))
+                .unwrap_or_default();
+            format!(
+                "Your gift is ready! (gift #{id})\nAmount: {}{fee}\nWhoever opens this link first can claim it, so only share it with who it's for:\n{link}\nIf it isn't claimed by {} (unix time), it comes back to you",
+                denoms.display(amount, &denom),
+                expires.seconds()
+            )
+        }
+
+        ReportEvent::GiftClaimed(GiftClaimedEvent {
+            id,
+            to_tg_id,
+            to_tg_handle,
+            to_address,
+            amount,
+            denom,
+            to_chain,
+            ..
+        }) => {
+            let to = display_user(Some(to_tg_id), to_tg_handle.as_ref());
+            let chain = to_chain
+                .map(|chain| format!(" on {chain}"))
+                .unwrap_or_default();
+            format!(
+                "Your gift #{id} was claimed!\n{to} ({to_address}{chain}) received {}",
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::GiftRefunded(GiftRefundedEvent {
+            id,
             from_address,
+            amount,
+            denom,
+            ..
+        }) => format!(
+            "Nobody claimed your gift #{id} in time, {} went back to {from_address}",
+            denoms.display(amount, &denom)
+        ),
+        ReportEvent::GiftReserved(GiftReservedEvent {
+            id,
+            from_tg_id,
+            from_tg_handle,
+            amount,
+            denom,
+            expires,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            format!(
+                "Gift #{id} of {} from {from} is reserved for you. Register to receive, then open the link again before {} (unix time) to be paid",
+                denoms.display(amount, &denom),
+                expires.seconds()
+            )
+        }
+
+        ReportEvent::GiveawayCreated(GiveawayCreatedEvent {
+            id,
+            from_tg_id,
+            from_tg_handle,
+            amount,
+            denom,
+            count,
+            split,
+            expires,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            format!(
+                "Giveaway! (giveaway #{id})\n{from} is giving away {} to the first {count} to claim, in {split} shares\nRegister to receive first, then press Claim before {} (unix time)",
+                denoms.display(amount, &denom),
+                expires.seconds()
+            )
+        }
+
+        ReportEvent::GiveawayClaimed(GiveawayClaimedEvent {
+            id,
+            to_tg_id,
+            to_tg_handle,
+            amount,
+            denom,
+            claims,
+            count,
+            remaining,
+            ..
+        }) => {
+            let to = display_user(Some(to_tg_id), to_tg_handle.as_ref());
+            match claims == count {
+                true => format!(
+                    "{to} claimed {} from giveaway #{id}\nThat was the last of {count}, the giveaway is over",
+                    denoms.display(amount, &denom)
+                ),
+                false => format!(
+                    "{to} claimed {} from giveaway #{id}\nClaims: {claims} of {count}, {} left",
+                    denoms.display(amount, &denom),
+                    denoms.display(remaining, &denom)
+                ),
+            }
+        }
+
+        ReportEvent::GiveawayRefunded(GiveawayRefundedEvent {
+            id,
+            from_tg_id,
+            from_tg_handle,
+            amount,
+            denom,
+            claims,
+            ..
+        }) => {
+            let from = display_user(Some(from_tg_id), from_tg_handle.as_ref());
+            format!(
+                "Giveaway #{id} expired after {claims} claims, the {} left went back to {from}",
+                denoms.display(amount, &denom)
+            )
+        }
+
+        ReportEvent::PayoutReturned(PayoutReturnedEvent {
+            to_tg_id,
+            to_tg_handle,
             to_address,
             amount,
             denom,
+            held_for,
+            treasury_chat_id,
+            refunded_to,
+            reason,
         }) => {
-            format!("Payment sent!\nFrom: @{from_tg_handle} ({from_address})\nTo: @{to_tg_handle} ({to_address})\nAmount: {amount} {denom}")
+            let to = display_user(to_tg_id, to_tg_handle.as_ref());
+            let returned = match (held_for, treasury_chat_id, refunded_to) {
+                (Some(handle), _, _) => format!("it is held for {} again", handle.mention()),
+                (_, Some(_), _) => "it went back to the group's treasury".to_string(),
+                (_, _, Some(addr)) => format!("it went back to {addr}"),
+                _ => "it went back to the contract".to_string(),
+            };
+            format!(
+                "The payout of {} to {to} at {to_address} didn't arrive, {returned}.\nReason: {reason}",
+                denoms.display(amount, &denom)
+            )
         }
     };
 
-    match state.tg_bot().send_message_to_group(&text).await {
+    let sent = match direct_to {
+        Some(user_id) => state.tg_bot().send_message_to_user(user_id, &text).await,
+        None if buttons.is_empty() => state.tg_bot().send_message_to_group(&text).await,
+        None => state.tg_bot().send_buttons_to_group(&text, &buttons).await,
+    };
+
+    match sent {
         Ok(_) => axum::http::StatusCode::OK.into_response(),
         Err(e) => {
             tracing::error!("Failed to send telegram message: {:?}", e);
@@ -63,3 +688,13 @@ pub async fn handle_tg_component(
         }
     }
 }
+
+/// Prefer the username, fall back to the numeric id for users without one
+#[cfg(debug_assertions)]
+fn display_user(user_id: Option<i64>, handle: Option<&TgHandle>) -> String {
+    match (handle, user_id) {
+        (Some(handle), _) => handle.mention(),
+        (None, Some(user_id)) => format!("user {user_id}"),
+        (None, None) => "unknown user".to_string(),
+    }
+}
@@ -1,21 +1,34 @@
+mod fee;
+mod gift;
+mod schedules;
 mod status;
+mod treasury;
 
 use crate::state::{HttpState, InitialTelegramSession};
 use axum::{extract::State, response::IntoResponse, Json};
 use cosmwasm_std::Uint256;
+use fee::quote_fee;
+use gift::query_gift;
 use layer_climb::prelude::CosmosAddr;
+use schedules::query_schedules;
 use status::query_status;
+use tg_contract_api::payments::{
+    handle::TgHandle,
+    msg::{GiveawaySplit, Proposal, Schedule, SendGrantResponse, SplitRecipient, TreasuryResponse},
+};
 use tg_utils::telegram::api::native::TelegramWebHookResponse;
 use tg_utils::telegram::{
     api::{
         bot::{
-            TelegramBotCommand, TelegramWavsAdminCommand, TelegramWavsAdminCommandPrefix,
-            TelegramWavsCommand, TelegramWavsCommandPrefix,
+            display_interval, DenomRegistry, GiveawayButton, InvoiceButton, ProposalButton,
+            StartParam, TelegramBotCommand, TelegramWavsAdminCommand,
+            TelegramWavsAdminCommandPrefix, TelegramWavsCommand, TelegramWavsCommandPrefix,
         },
         native::{TelegramChatType, TelegramUser, TelegramWebHookRequest},
     },
     error::{TelegramBotError, TgResult},
 };
+use treasury::query_treasury;
 
 #[cfg(debug_assertions)]
 #[axum::debug_handler]
@@ -46,6 +59,35 @@ async fn inner(
 ) -> anyhow::Result<Option<TelegramWebHookResponse>> {
     tracing::info!("GOT REQUEST: {:?}", req);
 
+    // Button presses are acted on by the operators, just acknowledge them here
+    if let Some(query) = req.callback_query {
+        let data = query.data.as_deref().unwrap_or_default();
+        let text = match (
+            InvoiceButton::parse(data),
+            ProposalButton::parse(data),
+            GiveawayButton::parse(data),
+        ) {
+            (Ok((InvoiceButton::Pay, invoice_id)), _, _) => format!("Paying invoice #{invoice_id}"),
+            (Ok((InvoiceButton::Decline, invoice_id)), _, _) => {
+                format!("Declining invoice #{invoice_id}")
+            }
+            (_, Ok((ProposalButton::Approve, proposal_id)), _) => {
+                format!("Approving proposal #{proposal_id}")
+            }
+            (_, Ok((ProposalButton::Reject, proposal_id)), _) => {
+                format!("Rejecting proposal #{proposal_id}")
+            }
+            (_, _, Ok((GiveawayButton::Claim, giveaway_id))) => {
+                format!("Claiming from giveaway #{giveaway_id}")
+            }
+            _ => "Unknown button".to_string(),
+        };
+        if let Err(e) = state.tg_bot().answer_callback_query(&query.id, &text).await {
+            tracing::error!("failed to answer callback query: {e:?}");
+        }
+        return Ok(None);
+    }
+
     let message = match req.message {
         Some(msg) => msg,
         None => {
@@ -85,8 +127,9 @@ async fn inner(
     let chat_type = message.chat.chat_type.clone();
     let chat_id = message.chat.id;
 
-    let response = match TelegramBotCommand::try_from(message) {
-        Ok(command) => match handle_command(state.clone(), command).await {
+    let denoms = state.denom_registry().await;
+    let response = match TelegramBotCommand::parse(message, &denoms) {
+        Ok(command) => match handle_command(state.clone(), command, &denoms).await {
             Ok(response) => Ok(response),
             Err(err) => Err(err),
         },
@@ -118,17 +161,94 @@ enum CommandResponse {
     Start {
         link: String,
     },
+    /// Opened from a gift link, see [`StartParam::ClaimGift`]
+    ClaimGift {
+        /// The gift as shown, None if it can't be claimed anymore
+        amount: Option<String>,
+        /// The sender, by username if they have one
+        from: String,
+        registered: bool,
+    },
     Status {
         address: Option<CosmosAddr>,
+        /// What the registered address has authorized the contract to send, see [`grant_note`].
+        /// None if unregistered.
+        grant: Option<String>,
         user: TelegramUser,
     },
     Receive {
         address: CosmosAddr,
     },
+    /// Amounts are as shown to users, see [`DenomRegistry::display`]
     Send {
-        handle: String,
-        amount: Uint256,
-        denom: String,
+        handle: TgHandle,
+        amount: String,
+        /// Charged on top, None if there is none
+        fee: Option<String>,
+    },
+    Split {
+        amount: String,
+        recipients: Vec<SplitRecipient>,
+    },
+    Tip {
+        /// The replied-to author, by username if they have one
+        to: String,
+        amount: String,
+        /// Charged on top, None if there is none
+        fee: Option<String>,
+    },
+    Request {
+        handle: TgHandle,
+        amount: String,
+    },
+    Undo {
+        escrow_id: Option<u64>,
+    },
+    Schedule {
+        handle: TgHandle,
+        amount: String,
+        interval: String,
+        /// Unix time
+        until: Option<u64>,
+    },
+    /// One line per recurring payment, already rendered
+    Schedules {
+        schedules: Vec<String>,
+    },
+    Unschedule {
+        schedule_id: u64,
+    },
+    Gift {
+        amount: String,
+        /// Charged on top, None if there is none
+        fee: Option<String>,
+        expires_in: Option<String>,
+    },
+    Giveaway {
+        amount: String,
+        /// Charged on top, None if there is none
+        fee: Option<String>,
+        count: u32,
+        split: GiveawaySplit,
+    },
+    /// Already rendered, see [`treasury_lines`]. Empty if the group has no treasury.
+    Treasury {
+        lines: Vec<String>,
+    },
+    CreateTreasury {
+        threshold: u32,
+        members: Vec<TgHandle>,
+    },
+    FundTreasury {
+        amount: String,
+    },
+    Propose {
+        handle: TgHandle,
+        amount: String,
+    },
+    Vote {
+        proposal_id: u64,
+        approve: bool,
     },
     GroupId {
         group_id: i64,
@@ -148,11 +268,36 @@ impl std::fmt::Display for CommandResponse {
             CommandResponse::Start { link } => {
                 write!(f, "Welcome to the bot!\n\nJoin the group to start receiving and sending WAVS payments.\n\n{link}")
             }
-            CommandResponse::Status { address, user } => match address {
+            CommandResponse::ClaimGift {
+                amount,
+                from,
+                registered,
+            } => match (amount, registered) {
+                (None, _) => write!(
+                    f,
+                    "This gift can't be claimed anymore, it was already claimed or it expired"
+                ),
+                (Some(amount), true) => {
+                    write!(f, "okay, you got it, claiming {amount} from {from}")
+                }
+                (Some(amount), false) => write!(
+                    f,
+                    "{from} sent you {amount}! It's yours, register to receive it with `{} {}` and then open the link again before it expires",
+                    TelegramWavsCommandPrefix::Receive,
+                    TelegramWavsCommandPrefix::Receive.format()
+                ),
+            },
+            CommandResponse::Status {
+                address,
+                grant,
+                user,
+            } => match address {
                 Some(addr) => write!(
                     f,
-                    "Hello, {}! Your account is registered with address: {}",
-                    user.first_name, addr
+                    "Hello, {}! Your account is registered with address: {}{}",
+                    user.first_name,
+                    addr,
+                    grant.as_deref().unwrap_or_default()
                 ),
                 None => write!(
                     f,
@@ -166,10 +311,135 @@ impl std::fmt::Display for CommandResponse {
             CommandResponse::Send {
                 handle,
                 amount,
-                denom,
+                fee,
             } => {
-                write!(f, "okay, you got it, sending {amount} {denom} to {handle}")
+                write!(
+                    f,
+                    "okay, you got it, sending {amount} to {}{}",
+                    handle.mention(),
+                    fee_note(fee.as_deref())
+                )
+            }
+            CommandResponse::Split { amount, recipients } => {
+                let recipients = recipients
+                    .iter()
+                    .map(|recipient| match recipient.weight {
+                        1 => recipient.to_tg.mention(),
+                        weight => format!("{} (x{weight})", recipient.to_tg.mention()),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "okay, you got it, splitting {amount} between {recipients}"
+                )
+            }
+            CommandResponse::Tip { to, amount, fee } => {
+                write!(
+                    f,
+                    "okay, you got it, tipping {to} {amount}{}",
+                    fee_note(fee.as_deref())
+                )
+            }
+            CommandResponse::Request { handle, amount } => {
+                write!(
+                    f,
+                    "okay, you got it, asking {} for {amount}",
+                    handle.mention()
+                )
+            }
+            CommandResponse::Undo { escrow_id } => match escrow_id {
+                Some(id) => write!(f, "okay, you got it, cancelling payment #{id}"),
+                None => write!(f, "okay, you got it, cancelling your latest payment"),
+            },
+            CommandResponse::Schedule {
+                handle,
+                amount,
+                interval,
+                until,
+            } => {
+                write!(
+                    f,
+                    "okay, you got it, paying {} {amount} every {interval}{}",
+                    handle.mention(),
+                    until
+                        .map(|until| format!(" until {until} (unix time)"))
+                        .unwrap_or_default()
+                )
+            }
+            CommandResponse::Schedules { schedules } => match schedules.is_empty() {
+                true => write!(f, "You have no recurring payments"),
+                false => write!(f, "Your recurring payments:\n{}", schedules.join("\n")),
+            },
+            CommandResponse::Unschedule { schedule_id } => {
+                write!(
+                    f,
+                    "okay, you got it, stopping recurring payment #{schedule_id}"
+                )
+            }
+            CommandResponse::Gift {
+                amount,
+                fee,
+                expires_in,
+            } => {
+                write!(
+                    f,
+                    "okay, you got it, locking {amount} in a gift link{}{}, I'll DM you the link once it's confirmed",
+                    expires_in
+                        .as_deref()
+                        .map(|expires_in| format!(" for {expires_in}"))
+                        .unwrap_or_default(),
+                    fee_note(fee.as_deref())
+                )
             }
+            CommandResponse::Giveaway {
+                amount,
+                fee,
+                count,
+                split,
+            } => {
+                write!(
+                    f,
+                    "okay, you got it, giving away {amount} to the first {count} to claim, in {split} shares{}",
+                    fee_note(fee.as_deref())
+                )
+            }
+            CommandResponse::Treasury { lines } => match lines.is_empty() {
+                true => write!(
+                    f,
+                    "This group has no treasury, set one up with `{} create <threshold> <handle> ...`",
+                    TelegramWavsCommandPrefix::Treasury
+                ),
+                false => write!(f, "{}", lines.join("\n")),
+            },
+            CommandResponse::CreateTreasury { threshold, members } => {
+                let members = members
+                    .iter()
+                    .map(TgHandle::mention)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "okay, you got it, setting up a treasury for you and {members}, paid out with {threshold} approvals"
+                )
+            }
+            CommandResponse::FundTreasury { amount } => {
+                write!(f, "okay, you got it, adding {amount} to the treasury")
+            }
+            CommandResponse::Propose { handle, amount } => {
+                write!(
+                    f,
+                    "okay, you got it, proposing to pay {} {amount} from the treasury",
+                    handle.mention()
+                )
+            }
+            CommandResponse::Vote {
+                proposal_id,
+                approve,
+            } => match approve {
+                true => write!(f, "okay, you got it, approving proposal #{proposal_id}"),
+                false => write!(f, "okay, you got it, rejecting proposal #{proposal_id}"),
+            },
             CommandResponse::GroupId { group_id } => {
                 write!(f, "Group ID is {group_id}")
             }
@@ -194,6 +464,18 @@ impl std::fmt::Display for CommandResponse {
                 `{}` - Get the current group chat ID
                 `{} {}` - Register to receive WAVS payments at the specified address
                 `{} {}` - Register to send WAVS payments to the specified handle
+                `{} {}` - Split a payment between several handles, evenly or by weight
+                `{} {}` - Reply to someone's message to tip them
+                `{} {}` - Ask the specified handle for a payment, they can pay or decline it
+                `{} {}` - Cancel a payment that is still held, your latest one if no id is given
+                `{} {}` - Pay the specified handle on a schedule, e.g. every week or every 2 weeks
+                `{}` - List your recurring payments
+                `{} {}` - Stop a recurring payment
+                `{} {}` - Lock a payment in a link that anyone you share it with can claim
+                `{} {}` - Give an amount away to the first members of the group to claim it
+                `{} {}` - Show, set up or fund the group's treasury
+                `{} {}` - Propose paying the specified handle from the group's treasury
+                `{} {}` - Vote on a treasury proposal, it pays out once enough members approve
                 `{}` - Get the current service information
                 `{} {}` - Set the service information (admin only)
                 ",
@@ -206,6 +488,29 @@ impl std::fmt::Display for CommandResponse {
                     TelegramWavsCommandPrefix::Receive.format(),
                     TelegramWavsCommandPrefix::Send,
                     TelegramWavsCommandPrefix::Send.format(),
+                    TelegramWavsCommandPrefix::Split,
+                    TelegramWavsCommandPrefix::Split.format(),
+                    TelegramWavsCommandPrefix::Tip,
+                    TelegramWavsCommandPrefix::Tip.format(),
+                    TelegramWavsCommandPrefix::Request,
+                    TelegramWavsCommandPrefix::Request.format(),
+                    TelegramWavsCommandPrefix::Undo,
+                    TelegramWavsCommandPrefix::Undo.format(),
+                    TelegramWavsCommandPrefix::Schedule,
+                    TelegramWavsCommandPrefix::Schedule.format(),
+                    TelegramWavsCommandPrefix::Schedules,
+                    TelegramWavsCommandPrefix::Unschedule,
+                    TelegramWavsCommandPrefix::Unschedule.format(),
+                    TelegramWavsCommandPrefix::Gift,
+                    TelegramWavsCommandPrefix::Gift.format(),
+                    TelegramWavsCommandPrefix::Giveaway,
+                    TelegramWavsCommandPrefix::Giveaway.format(),
+                    TelegramWavsCommandPrefix::Treasury,
+                    TelegramWavsCommandPrefix::Treasury.format(),
+                    TelegramWavsCommandPrefix::Propose,
+                    TelegramWavsCommandPrefix::Propose.format(),
+                    TelegramWavsCommandPrefix::Vote,
+                    TelegramWavsCommandPrefix::Vote.format(),
                     TelegramWavsCommandPrefix::Service,
                     TelegramWavsCommandPrefix::Admin(TelegramWavsAdminCommandPrefix::SetService),
                     TelegramWavsCommandPrefix::Admin(TelegramWavsAdminCommandPrefix::SetService)
@@ -225,18 +530,135 @@ impl std::fmt::Display for CommandResponse {
     }
 }
 
+fn grant_note(grant: &SendGrantResponse, denoms: &DenomRegistry) -> String {
+    if !grant.granted {
+        return "\nSending: not authorized, grant the payments contract a send authorization to pay from this address".to_string();
+    }
+
+    let limit = match &grant.spend_limit {
+        Some(limit) => format!(
+            "up to {}",
+            limit
+                .iter()
+                .map(|coin| denoms.display(coin.amount, &coin.denom))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        None => "with no cap".to_string(),
+    };
+    let expiry = grant
+        .expiration
+        .map(|time| format!(", until {} (unix time)", time.seconds()))
+        .unwrap_or_default();
+    format!("\nSending: authorized {limit}{expiry}")
+}
+
+fn fee_note(fee: Option<&str>) -> String {
+    fee.map(|fee| format!(", plus a {fee} fee"))
+        .unwrap_or_default()
+}
+
+/// `#3: 10 NTRN to @alice every week, next at 1767225600 (unix time)`
+fn schedule_line(schedule: &Schedule, denoms: &DenomRegistry) -> String {
+    format!(
+        "#{}: {} to {} every {}, next at {} (unix time){}",
+        schedule.id,
+        denoms.display(schedule.amount, &schedule.denom),
+        schedule.to_tg_handle.mention(),
+        display_interval(schedule.interval_seconds),
+        schedule.next_due.seconds(),
+        schedule
+            .until
+            .map(|until| format!(", until {}", until.seconds()))
+            .unwrap_or_default()
+    )
+}
+
+/// The treasury's members and balances, then one line per open proposal, e.g.
+/// `#3: 10 NTRN to @alice, 1 of 2 approvals`
+fn treasury_lines(
+    TreasuryResponse { treasury, balances }: &TreasuryResponse,
+    proposals: &[Proposal],
+    denoms: &DenomRegistry,
+) -> Vec<String> {
+    let Some(treasury) = treasury else {
+        return vec![];
+    };
+
+    let members = treasury
+        .members
+        .iter()
+        .map(|member| match &member.tg_handle {
+            Some(handle) => handle.mention(),
+            None => format!("user {}", member.tg_id),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let holds = match balances.is_empty() {
+        true => "nothing yet".to_string(),
+        false => balances
+            .iter()
+            .map(|coin| denoms.display(coin.amount, &coin.denom))
+            .collect::<Vec<_>>()
+            .join(", "),
+    };
+
+    let mut lines = vec![
+        format!(
+            "Treasury of {} members, {} approvals pay out: {members}",
+            treasury.members.len(),
+            treasury.threshold
+        ),
+        format!("Holds {holds}"),
+    ];
+    lines.extend(proposals.iter().map(|proposal| {
+        format!(
+            "#{}: {} to {}, {} of {} approvals{}",
+            proposal.id,
+            denoms.display(proposal.amount, &proposal.denom),
+            proposal.to_tg_handle.mention(),
+            proposal.approvals.len(),
+            treasury.threshold,
+            proposal
+                .memo
+                .as_ref()
+                .map(|memo| format!(" ({memo})"))
+                .unwrap_or_default()
+        )
+    }));
+    lines
+}
+
+/// The fee as shown, None if there is none
+fn display_fee(fee: Uint256, denom: &str, denoms: &DenomRegistry) -> Option<String> {
+    (!fee.is_zero()).then(|| denoms.display(fee, denom))
+}
+
 async fn handle_command(
     state: HttpState,
     TelegramBotCommand { command, raw }: TelegramBotCommand,
+    denoms: &DenomRegistry,
 ) -> TgResult<Option<CommandResponse>> {
     match command.clone() {
-        TelegramWavsCommand::Start => {
+        TelegramWavsCommand::Start { param } => {
             state.set_user_session(
                 raw.from.id,
                 InitialTelegramSession {
                     message: raw.clone(),
                 },
             );
+            // The operators claim it off the same message, this only tells the user how it went
+            if let Some(StartParam::ClaimGift { secret }) = param {
+                let (gift, registered) = query_gift(state, secret, raw.from.id).await?;
+                return Ok(Some(CommandResponse::ClaimGift {
+                    from: gift
+                        .as_ref()
+                        .and_then(|gift| gift.from_tg_handle.as_ref().map(TgHandle::mention))
+                        .unwrap_or_else(|| "Someone".to_string()),
+                    amount: gift.map(|gift| denoms.display(gift.amount, &gift.denom)),
+                    registered,
+                }));
+            }
             let link = state.tg_bot().generate_group_invite_link().await?;
             Ok(Some(CommandResponse::Start { link }))
         }
@@ -269,7 +691,7 @@ async fn handle_command(
                 Ok(None)
             }
         }
-        TelegramWavsCommand::Status {} => Ok(Some(query_status(state, raw.from).await?)),
+        TelegramWavsCommand::Status {} => Ok(Some(query_status(state, raw.from, denoms).await?)),
         TelegramWavsCommand::Receive { address, .. } => {
             Ok(Some(CommandResponse::Receive { address }))
         }
@@ -277,11 +699,132 @@ async fn handle_command(
             handle,
             amount,
             denom,
-        } => Ok(Some(CommandResponse::Send {
+        } => {
+            let fee = quote_fee(state, amount, denom.clone()).await?;
+            Ok(Some(CommandResponse::Send {
+                handle,
+                amount: denoms.display(amount, &denom),
+                fee: display_fee(fee, &denom, denoms),
+            }))
+        }
+        TelegramWavsCommand::Split {
+            amount,
+            denom,
+            recipients,
+        } => Ok(Some(CommandResponse::Split {
+            amount: denoms.display(amount, &denom),
+            recipients,
+        })),
+        TelegramWavsCommand::Tip { amount, denom } => {
+            // The parser already checked this is a reply
+            let to = raw
+                .reply_to_message
+                .map(|reply| match reply.from.username {
+                    Some(username) => format!("@{username}"),
+                    None => reply.from.first_name,
+                })
+                .unwrap_or_default();
+            let fee = quote_fee(state, amount, denom.clone()).await?;
+            Ok(Some(CommandResponse::Tip {
+                to,
+                amount: denoms.display(amount, &denom),
+                fee: display_fee(fee, &denom, denoms),
+            }))
+        }
+        TelegramWavsCommand::Request {
+            handle,
+            amount,
+            denom,
+            ..
+        } => Ok(Some(CommandResponse::Request {
+            handle,
+            amount: denoms.display(amount, &denom),
+        })),
+        TelegramWavsCommand::Undo { escrow_id } => Ok(Some(CommandResponse::Undo { escrow_id })),
+        TelegramWavsCommand::Schedule {
             handle,
             amount,
             denom,
+            interval_seconds,
+            until,
+        } => Ok(Some(CommandResponse::Schedule {
+            handle,
+            amount: denoms.display(amount, &denom),
+            interval: display_interval(interval_seconds),
+            until: until.map(|until| until.seconds()),
+        })),
+        TelegramWavsCommand::Schedules => {
+            let schedules = query_schedules(state, raw.from.id).await?;
+            Ok(Some(CommandResponse::Schedules {
+                schedules: schedules
+                    .iter()
+                    .map(|schedule| schedule_line(schedule, denoms))
+                    .collect(),
+            }))
+        }
+        TelegramWavsCommand::Unschedule { schedule_id } => {
+            Ok(Some(CommandResponse::Unschedule { schedule_id }))
+        }
+        TelegramWavsCommand::Gift {
+            amount,
+            denom,
+            expires_in_seconds,
+        } => {
+            let fee = quote_fee(state, amount, denom.clone()).await?;
+            Ok(Some(CommandResponse::Gift {
+                amount: denoms.display(amount, &denom),
+                fee: display_fee(fee, &denom, denoms),
+                expires_in: expires_in_seconds.map(display_interval),
+            }))
+        }
+        TelegramWavsCommand::Giveaway {
+            amount,
+            denom,
+            count,
+            split,
+        } => {
+            let fee = quote_fee(state, amount, denom.clone()).await?;
+            Ok(Some(CommandResponse::Giveaway {
+                amount: denoms.display(amount, &denom),
+                fee: display_fee(fee, &denom, denoms),
+                count,
+                split,
+            }))
+        }
+        TelegramWavsCommand::Treasury => {
+            let (treasury, proposals) = query_treasury(state, raw.chat.id).await?;
+            Ok(Some(CommandResponse::Treasury {
+                lines: treasury_lines(&treasury, &proposals, denoms),
+            }))
+        }
+        TelegramWavsCommand::CreateTreasury { threshold, members } => {
+            Ok(Some(CommandResponse::CreateTreasury { threshold, members }))
+        }
+        TelegramWavsCommand::FundTreasury { amount, denom } => {
+            Ok(Some(CommandResponse::FundTreasury {
+                amount: denoms.display(amount, &denom),
+            }))
+        }
+        TelegramWavsCommand::Propose {
+            handle,
+            amount,
+            denom,
+            ..
+        } => Ok(Some(CommandResponse::Propose {
+            handle,
+            amount: denoms.display(amount, &denom),
+        })),
+        TelegramWavsCommand::Vote {
+            proposal_id,
+            approve,
+        } => Ok(Some(CommandResponse::Vote {
+            proposal_id,
+            approve,
         })),
+        // Only ever sent as button presses, which are answered before getting here
+        TelegramWavsCommand::PayInvoice { .. }
+        | TelegramWavsCommand::DeclineInvoice { .. }
+        | TelegramWavsCommand::ClaimGiveaway { .. } => Ok(None),
         TelegramWavsCommand::GroupId { group_id } => {
             Ok(Some(CommandResponse::GroupId { group_id }))
         }
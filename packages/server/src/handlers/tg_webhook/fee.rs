@@ -0,0 +1,35 @@
+use cosmwasm_std::Uint256;
+use tg_utils::{
+    client::payments::PaymentsQuerier,
+    telegram::error::{TelegramBotError, TgResult},
+};
+use tokio::task::spawn_blocking;
+
+use crate::state::HttpState;
+
+/// What the payments contract will charge on top of a send, so it can be shown up front
+pub async fn quote_fee(state: HttpState, amount: Uint256, denom: String) -> TgResult<Uint256> {
+    let payments_address = state
+        .payments_contract_address()
+        .map_err(TelegramBotError::FeeQuote)?
+        .ok_or(TelegramBotError::PaymentsContractNotSet)?;
+
+    // Same as for the status query, the PaymentsQuerier may not be Send
+    let quote = spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(async move {
+            let query_client = state
+                .get_query_client()
+                .await
+                .map_err(TelegramBotError::FeeQuote)?;
+
+            PaymentsQuerier::new(query_client.into(), payments_address.into())
+                .fee_quote(amount, &denom)
+                .await
+                .map_err(TelegramBotError::FeeQuote)
+        })
+    })
+    .await
+    .map_err(|e| TelegramBotError::FeeQuote(e.into()))??;
+
+    Ok(quote.fee)
+}
@@ -0,0 +1,56 @@
+use tg_contract_api::payments::msg::Gift;
+use tg_utils::{
+    client::payments::PaymentsQuerier,
+    telegram::error::{TelegramBotError, TgResult},
+};
+use tokio::task::spawn_blocking;
+
+use crate::state::HttpState;
+
+/// The gift behind a claim link, if `user_id` can still claim it, and whether they have
+/// registered to receive it. Gifts that expired or were reserved by someone else are None.
+pub async fn query_gift(
+    state: HttpState,
+    secret: String,
+    user_id: i64,
+) -> TgResult<(Option<Gift>, bool)> {
+    let payments_address = state
+        .payments_contract_address()
+        .map_err(TelegramBotError::Gift)?
+        .ok_or(TelegramBotError::PaymentsContractNotSet)?;
+
+    // Same as for the status query, the PaymentsQuerier may not be Send
+    let (gift, registered) = spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(async move {
+            let query_client = state
+                .get_query_client()
+                .await
+                .map_err(TelegramBotError::Gift)?;
+            let querier = PaymentsQuerier::new(query_client.into(), payments_address.into());
+
+            let gift = querier
+                .gift_by_hash(Gift::hash_secret(&secret))
+                .await
+                .map_err(TelegramBotError::Gift)?;
+            let registered = querier
+                .addr_by_tg_id(user_id)
+                .await
+                .map_err(TelegramBotError::Gift)?
+                .is_some();
+
+            Ok::<_, TelegramBotError>((gift, registered))
+        })
+    })
+    .await
+    .map_err(|e| TelegramBotError::Gift(e.into()))??;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| TelegramBotError::Gift(e.into()))?
+        .as_secs();
+    let gift = gift.filter(|gift| {
+        gift.expires.seconds() > now && gift.claimer.is_none_or(|claimer| claimer == user_id)
+    });
+
+    Ok((gift, registered))
+}
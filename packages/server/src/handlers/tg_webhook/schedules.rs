@@ -0,0 +1,36 @@
+use tg_contract_api::payments::msg::Schedule;
+use tg_utils::{
+    client::payments::PaymentsQuerier,
+    telegram::error::{TelegramBotError, TgResult},
+};
+use tokio::task::spawn_blocking;
+
+use crate::state::HttpState;
+
+/// The user's recurring payments, so they know which id to `/unschedule`
+pub async fn query_schedules(state: HttpState, user_id: i64) -> TgResult<Vec<Schedule>> {
+    let payments_address = state
+        .payments_contract_address()
+        .map_err(TelegramBotError::Schedules)?
+        .ok_or(TelegramBotError::PaymentsContractNotSet)?;
+
+    // Same as for the status query, the PaymentsQuerier may not be Send
+    let schedules = spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(async move {
+            let query_client = state
+                .get_query_client()
+                .await
+                .map_err(TelegramBotError::Schedules)?;
+
+            // A sender can't have more than a page of them
+            PaymentsQuerier::new(query_client.into(), payments_address.into())
+                .schedules(Some(user_id), None, Some(30))
+                .await
+                .map_err(TelegramBotError::Schedules)
+        })
+    })
+    .await
+    .map_err(|e| TelegramBotError::Schedules(e.into()))??;
+
+    Ok(schedules)
+}
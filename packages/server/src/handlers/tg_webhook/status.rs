@@ -2,29 +2,31 @@ use layer_climb::prelude::CosmosAddr;
 use tg_utils::{
     client::payments::PaymentsQuerier,
     telegram::{
-        api::native::TelegramUser,
+        api::{bot::DenomRegistry, native::TelegramUser},
         error::{TelegramBotError, TgResult},
     },
 };
 use tokio::task::spawn_blocking;
 
-use crate::{handlers::tg_webhook::CommandResponse, state::HttpState};
+use crate::{
+    handlers::tg_webhook::{grant_note, CommandResponse},
+    state::HttpState,
+};
 
-pub async fn query_status(state: HttpState, user: TelegramUser) -> TgResult<CommandResponse> {
+pub async fn query_status(
+    state: HttpState,
+    user: TelegramUser,
+    denoms: &DenomRegistry,
+) -> TgResult<CommandResponse> {
     let payments_address = state
         .payments_contract_address()
         .map_err(TelegramBotError::StatusAny)?
         .ok_or(TelegramBotError::PaymentsContractNotSet)?;
 
-    let username = match &user.username {
-        Some(name) => name.clone(),
-        None => {
-            return Err(TelegramBotError::NoUsername);
-        }
-    };
+    let user_id = user.id;
     // Due to feature unification, we have to assume that the PaymentsQuerier
     // is non-Send, so we spawn a blocking task to run the query.
-    let user_address = spawn_blocking(move || {
+    let (address, grant) = spawn_blocking(move || {
         tokio::runtime::Handle::current().block_on(async move {
             let query_client = state
                 .get_query_client()
@@ -33,20 +35,30 @@ pub async fn query_status(state: HttpState, user: TelegramUser) -> TgResult<Comm
 
             let payments = PaymentsQuerier::new(query_client.into(), payments_address.into());
 
-            payments
-                .addr_by_tg_handle(username)
+            let Some(addr) = payments
+                .addr_by_tg_id(user_id)
                 .await
                 .map_err(TelegramBotError::StatusAny)?
-                .map(|addr| CosmosAddr::new_str(&addr, None))
-                .transpose()
-                .map_err(TelegramBotError::StatusAny)
+            else {
+                return Ok((None, None));
+            };
+
+            // Sends are paid through this grant, so without it they fail however they are set up
+            let grant = payments
+                .send_grant(addr.clone())
+                .await
+                .map_err(TelegramBotError::StatusAny)?;
+            let addr = CosmosAddr::new_str(&addr, None).map_err(TelegramBotError::StatusAny)?;
+
+            Ok::<_, TelegramBotError>((Some(addr), Some(grant)))
         })
     })
     .await
     .map_err(|e| TelegramBotError::StatusAny(e.into()))??;
 
     Ok(CommandResponse::Status {
-        address: user_address,
+        address,
+        grant: grant.map(|grant| grant_note(&grant, denoms)),
         user,
     })
 }
@@ -0,0 +1,46 @@
+use tg_contract_api::payments::msg::{Proposal, TreasuryResponse};
+use tg_utils::{
+    client::payments::PaymentsQuerier,
+    telegram::error::{TelegramBotError, TgResult},
+};
+use tokio::task::spawn_blocking;
+
+use crate::state::HttpState;
+
+/// The group's treasury and its open proposals, so members know which id to `/vote` on
+pub async fn query_treasury(
+    state: HttpState,
+    chat_id: i64,
+) -> TgResult<(TreasuryResponse, Vec<Proposal>)> {
+    let payments_address = state
+        .payments_contract_address()
+        .map_err(TelegramBotError::Treasury)?
+        .ok_or(TelegramBotError::PaymentsContractNotSet)?;
+
+    // Same as for the status query, the PaymentsQuerier may not be Send
+    let treasury = spawn_blocking(move || {
+        tokio::runtime::Handle::current().block_on(async move {
+            let query_client = state
+                .get_query_client()
+                .await
+                .map_err(TelegramBotError::Treasury)?;
+            let querier = PaymentsQuerier::new(query_client.into(), payments_address.into());
+
+            let treasury = querier
+                .treasury(chat_id)
+                .await
+                .map_err(TelegramBotError::Treasury)?;
+            // A treasury can't have more than a page of them
+            let proposals = querier
+                .proposals(chat_id, None, Some(30))
+                .await
+                .map_err(TelegramBotError::Treasury)?;
+
+            Ok::<_, TelegramBotError>((treasury, proposals))
+        })
+    })
+    .await
+    .map_err(|e| TelegramBotError::Treasury(e.into()))??;
+
+    Ok(treasury)
+}
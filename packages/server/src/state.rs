@@ -4,11 +4,17 @@ use std::{
     path::PathBuf,
     sync::Arc,
 };
+use tg_contract_api::payments::msg::{
+    CustomQueryMsg, DenomMetadata, DenomMetadataResponse, QueryMsg,
+};
 use tg_utils::{
     config::load_chain_configs_from_wavs,
     telegram::{
-        api::native::TelegramMessage,
-        error::TgResult,
+        api::{
+            bot::{gift_secret, DenomRegistry, StartParam},
+            native::TelegramMessage,
+        },
+        error::{TelegramBotError, TgResult},
         messenger::{any_client::TelegramMessengerExt, reqwest_client::TelegramMessenger},
     },
 };
@@ -166,6 +172,37 @@ impl HttpState {
         Ok(Some(service_uri))
     }
 
+    /// How amounts are typed and shown. Everything stays in base units if the payments
+    /// contract isn't set or can't be reached, so a bad query never blocks a command.
+    pub async fn denom_registry(&self) -> DenomRegistry {
+        match self.denom_metadata().await {
+            Ok(denoms) => DenomRegistry::new(denoms),
+            Err(e) => {
+                tracing::warn!("Could not load denom metadata, using base units: {e:?}");
+                DenomRegistry::default()
+            }
+        }
+    }
+
+    async fn denom_metadata(&self) -> anyhow::Result<Vec<DenomMetadata>> {
+        let address = match self.payments_contract_address()? {
+            Some(a) => a,
+            None => {
+                return Ok(Vec::new());
+            }
+        };
+        let query_client = self.get_query_client().await?;
+
+        let resp: DenomMetadataResponse = query_client
+            .contract_smart(
+                &address.into(),
+                &QueryMsg::Custom(CustomQueryMsg::DenomMetadata {}),
+            )
+            .await?;
+
+        Ok(resp.denoms)
+    }
+
     pub async fn get_query_client(&self) -> anyhow::Result<QueryClient> {
         let chain = self
             .service_manager_chain()?
@@ -214,12 +251,54 @@ impl TelegramBot {
         self.messenger.send_message(self.group_id, text).await
     }
 
+    /// A private chat with a user has the same id as the user
+    pub async fn send_message_to_user(
+        &self,
+        user_id: i64,
+        text: &str,
+    ) -> TgResult<TelegramMessage> {
+        self.messenger.send_message(user_id, text).await
+    }
+
+    pub async fn send_buttons_to_group(
+        &self,
+        text: &str,
+        buttons: &[(&str, String)],
+    ) -> TgResult<TelegramMessage> {
+        self.messenger
+            .send_message_with_buttons(self.group_id, text, buttons)
+            .await
+    }
+
+    pub async fn answer_callback_query(
+        &self,
+        callback_query_id: &str,
+        text: &str,
+    ) -> TgResult<bool> {
+        self.messenger
+            .answer_callback_query(callback_query_id, text)
+            .await
+    }
+
     pub async fn generate_group_invite_link(&self) -> TgResult<String> {
         self.messenger
             .generate_group_invite_link(self.group_id)
             .await
     }
 
+    /// The link that claims the gift created by the `/gift` message `message_id` in `chat_id`
+    pub async fn gift_link(&self, chat_id: i64, message_id: i64) -> TgResult<String> {
+        let username = self
+            .messenger
+            .get_me()
+            .await?
+            .username
+            .ok_or_else(|| TelegramBotError::Internal("the bot has no username".to_string()))?;
+        let secret = gift_secret(&self.messenger.token, chat_id, message_id);
+
+        Ok(StartParam::ClaimGift { secret }.link(&username))
+    }
+
     pub async fn send_miniapp_button(
         &self,
         user_id: i64,
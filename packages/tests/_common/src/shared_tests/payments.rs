@@ -1,3 +1,4 @@
+use tg_contract_api::payments::handle::TgHandle;
 use tg_utils::{
     addr::AnyAddr,
     client::payments::{PaymentsExecutor, PaymentsQuerier},
@@ -10,7 +11,8 @@ pub async fn get_admin(querier: &PaymentsQuerier, expected: &str) {
 
 pub struct RegisterReceivesOpenAccountProps {
     pub user_addr: AnyAddr,
-    pub tg_handle: String,
+    pub tg_user_id: i64,
+    pub tg_handle: TgHandle,
 }
 
 pub async fn register_recieves_open_account(
@@ -20,11 +22,12 @@ pub async fn register_recieves_open_account(
 ) {
     let RegisterReceivesOpenAccountProps {
         user_addr,
+        tg_user_id,
         tg_handle,
     } = props;
     // Register user to receive payments
     executor
-        .register_receive(tg_handle.clone(), &user_addr)
+        .register_receive(tg_user_id, Some(&tg_handle), &user_addr)
         .await
         .unwrap();
 
@@ -34,6 +37,11 @@ pub async fn register_recieves_open_account(
         user_addr.to_string()
     );
 
+    assert_eq!(
+        querier.addr_by_tg_id(tg_user_id).await.unwrap().unwrap(),
+        user_addr.to_string()
+    );
+
     // Query by address - should return None because this is only an OPEN account, not FUNDED
 
     assert_eq!(
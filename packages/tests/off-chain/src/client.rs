@@ -1,10 +1,15 @@
 //! Abstraction specifically for the off-chain multi-test environment
+pub mod cw20;
 pub mod payments;
+pub mod service_manager;
 use std::sync::Arc;
-use tg_utils::client::{AnyExecutor, AnyQuerier};
+use tg_utils::client::{
+    multitest::{send_grant_msg, AuthzStargate, MultiTestApp},
+    AnyExecutor, AnyQuerier,
+};
 
-use cosmwasm_std::{Addr, Coin};
-use cw_multi_test::App;
+use cosmwasm_std::{Addr, Coin, Timestamp, Uint128};
+use cw_multi_test::{AppBuilder, BankSudo, Executor, SudoMsg};
 
 #[derive(Clone)]
 pub struct AppClient {
@@ -14,19 +19,22 @@ pub struct AppClient {
 
 impl AppClient {
     pub fn new(admin: &str) -> Self {
-        let app = Arc::new(std::sync::Mutex::new(App::new(|router, api, storage| {
-            router
-                .bank
-                .init_balance(
-                    storage,
-                    &api.addr_make(admin),
-                    vec![Coin {
-                        denom: "utoken".to_string(),
-                        amount: 1_000_000u128.into(),
-                    }],
-                )
-                .unwrap();
-        })));
+        let app = AppBuilder::new()
+            .with_stargate(AuthzStargate)
+            .build(|router, api, storage| {
+                router
+                    .bank
+                    .init_balance(
+                        storage,
+                        &api.addr_make(admin),
+                        vec![Coin {
+                            denom: "utoken".to_string(),
+                            amount: 1_000_000u128.into(),
+                        }],
+                    )
+                    .unwrap();
+            });
+        let app = Arc::new(std::sync::Mutex::new(app));
 
         let admin = app.lock().unwrap().api().addr_make(admin);
 
@@ -36,21 +44,21 @@ impl AppClient {
         }
     }
 
-    pub fn with_app<T>(&self, f: impl FnOnce(&App) -> T) -> T {
+    pub fn with_app<T>(&self, f: impl FnOnce(&MultiTestApp) -> T) -> T {
         match &self.executor {
             AnyExecutor::MultiTest { app, .. } => f(&app.lock().unwrap()),
             _ => unreachable!(),
         }
     }
 
-    pub fn with_app_mut<T>(&self, f: impl FnOnce(&mut App) -> T) -> T {
+    pub fn with_app_mut<T>(&self, f: impl FnOnce(&mut MultiTestApp) -> T) -> T {
         match &self.executor {
             AnyExecutor::MultiTest { app, .. } => f(&mut app.lock().unwrap()),
             _ => unreachable!(),
         }
     }
 
-    pub fn clone_app(&self) -> Arc<std::sync::Mutex<App>> {
+    pub fn clone_app(&self) -> Arc<std::sync::Mutex<MultiTestApp>> {
         match &self.executor {
             AnyExecutor::MultiTest { app, .. } => app.clone(),
             _ => unreachable!(),
@@ -63,4 +71,43 @@ impl AppClient {
             _ => unreachable!(),
         }
     }
+
+    /// An executor that signs as `sender` instead of the admin
+    pub fn executor_for(&self, sender: &Addr) -> AnyExecutor {
+        (self.clone_app(), sender.clone()).into()
+    }
+
+    pub fn mint(&self, addr: &Addr, amount: Coin) {
+        self.with_app_mut(|app| {
+            app.sudo(SudoMsg::Bank(BankSudo::Mint {
+                to_address: addr.to_string(),
+                amount: vec![amount],
+            }))
+            .unwrap();
+        });
+    }
+
+    /// Has `granter` authorize `grantee` to send up to `spend_limit` on its behalf
+    pub fn grant_send(
+        &self,
+        granter: &Addr,
+        grantee: &Addr,
+        spend_limit: Vec<Coin>,
+        expiration: Option<Timestamp>,
+    ) {
+        self.with_app_mut(|app| {
+            app.execute(
+                granter.clone(),
+                send_grant_msg(granter, grantee, spend_limit, expiration),
+            )
+            .unwrap();
+        });
+    }
+
+    pub fn balance(&self, addr: &Addr, denom: &str) -> u128 {
+        self.with_app(|app| {
+            let amount = app.wrap().query_balance(addr, denom).unwrap().amount;
+            Uint128::try_from(amount).unwrap().u128()
+        })
+    }
 }
@@ -0,0 +1,166 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+    Uint128,
+};
+use cw_multi_test::{ContractWrapper, Executor};
+use cw_storage_plus::Map;
+use cw_utils::Expiration;
+use serde::{Deserialize, Serialize};
+use tg_contract_api::cw20::{AllowanceResponse, BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+
+use crate::client::AppClient;
+
+/// Just enough of a CW20 token to exercise the payments contract: balances, transfers and allowances
+pub struct MockCw20;
+
+impl MockCw20 {
+    pub fn instantiate(app_client: &AppClient, initial_balances: &[(&Addr, u128)]) -> Addr {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        let admin = app_client.admin();
+        let msg = InstantiateMsg {
+            initial_balances: initial_balances
+                .iter()
+                .map(|(addr, amount)| (addr.to_string(), Uint128::new(*amount)))
+                .collect(),
+        };
+
+        app_client.with_app_mut(|app| {
+            let code_id = app.store_code(Box::new(contract));
+            app.instantiate_contract(code_id, admin, &msg, &[], "mock-cw20", None)
+                .unwrap()
+        })
+    }
+
+    pub fn balance(app_client: &AppClient, token: &Addr, addr: &Addr) -> u128 {
+        app_client.with_app(|app| {
+            let resp: BalanceResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    token,
+                    &Cw20QueryMsg::Balance {
+                        address: addr.to_string(),
+                    },
+                )
+                .unwrap();
+            resp.balance.u128()
+        })
+    }
+
+    /// What a sender does instead of an authz grant, so the payments contract can pull the tokens
+    pub fn increase_allowance(
+        app_client: &AppClient,
+        token: &Addr,
+        owner: &Addr,
+        spender: &Addr,
+        amount: u128,
+    ) {
+        app_client.with_app_mut(|app| {
+            app.execute_contract(
+                owner.clone(),
+                token.clone(),
+                &Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: spender.to_string(),
+                    amount: Uint128::new(amount),
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+        });
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct InstantiateMsg {
+    initial_balances: Vec<(String, Uint128)>,
+}
+
+const BALANCES: Map<&Addr, Uint128> = Map::new("balances");
+const ALLOWANCES: Map<(&Addr, &Addr), Uint128> = Map::new("allowances");
+
+fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> StdResult<Response> {
+    for (addr, amount) in msg.initial_balances {
+        let addr = deps.api.addr_validate(&addr)?;
+        BALANCES.save(deps.storage, &addr, &amount)?;
+    }
+    Ok(Response::default())
+}
+
+fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: Cw20ExecuteMsg,
+) -> StdResult<Response> {
+    match msg {
+        Cw20ExecuteMsg::Transfer { recipient, amount } => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            move_tokens(deps, &info.sender, &recipient, amount)
+        }
+        Cw20ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            let recipient = deps.api.addr_validate(&recipient)?;
+            ALLOWANCES.update(deps.storage, (&owner, &info.sender), |allowance| {
+                allowance
+                    .unwrap_or_default()
+                    .checked_sub(amount)
+                    .map_err(|_| StdError::msg("insufficient allowance"))
+            })?;
+            move_tokens(deps, &owner, &recipient, amount)
+        }
+        Cw20ExecuteMsg::IncreaseAllowance {
+            spender, amount, ..
+        } => {
+            let spender = deps.api.addr_validate(&spender)?;
+            ALLOWANCES.update(deps.storage, (&info.sender, &spender), |allowance| {
+                Ok::<_, StdError>(allowance.unwrap_or_default() + amount)
+            })?;
+            Ok(Response::default())
+        }
+    }
+}
+
+fn move_tokens(deps: DepsMut, from: &Addr, to: &Addr, amount: Uint128) -> StdResult<Response> {
+    BALANCES.update(deps.storage, from, |balance| {
+        balance
+            .unwrap_or_default()
+            .checked_sub(amount)
+            .map_err(|_| StdError::msg("insufficient funds"))
+    })?;
+    BALANCES.update(deps.storage, to, |balance| {
+        Ok::<_, StdError>(balance.unwrap_or_default() + amount)
+    })?;
+    Ok(Response::default())
+}
+
+fn query(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+    match msg {
+        Cw20QueryMsg::Balance { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_json_binary(&BalanceResponse {
+                balance: BALANCES
+                    .may_load(deps.storage, &address)?
+                    .unwrap_or_default(),
+            })
+        }
+        Cw20QueryMsg::Allowance { owner, spender } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            let spender = deps.api.addr_validate(&spender)?;
+            to_json_binary(&AllowanceResponse {
+                allowance: ALLOWANCES
+                    .may_load(deps.storage, (&owner, &spender))?
+                    .unwrap_or_default(),
+                expires: Expiration::Never {},
+            })
+        }
+    }
+}
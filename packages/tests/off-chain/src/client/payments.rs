@@ -1,8 +1,10 @@
 use cosmwasm_std::Addr;
 use cw_multi_test::{ContractWrapper, Executor};
+use cw_utils::Duration;
+use tg_contract_api::payments::msg::Auth;
 use tg_utils::client::payments::{PaymentsExecutor, PaymentsQuerier};
 
-use crate::client::AppClient;
+use crate::client::{service_manager::MockServiceManager, AppClient};
 
 #[derive(Clone)]
 pub struct PaymentsClient {
@@ -17,20 +19,47 @@ impl PaymentsClient {
     }
 
     pub fn new_with_admin(app_client: AppClient, admin: Addr) -> Self {
+        Self::new_with_config(app_client, admin, None)
+    }
+
+    pub fn new_with_config(
+        app_client: AppClient,
+        admin: Addr,
+        pending_expiry: Option<Duration>,
+    ) -> Self {
+        Self::instantiate(app_client, Auth::Admin(admin.to_string()), pending_expiry)
+    }
+
+    /// Only accepts envelopes, validated by a mock service manager that approves all of them
+    pub fn new_with_service_manager(app_client: AppClient) -> Self {
+        let service_manager = MockServiceManager::instantiate(&app_client);
+        Self::instantiate(
+            app_client,
+            Auth::ServiceManager(service_manager.to_string()),
+            None,
+        )
+    }
+
+    fn instantiate(app_client: AppClient, auth: Auth, pending_expiry: Option<Duration>) -> Self {
         let contract = ContractWrapper::new(
             tg_contract_payments::execute,
             tg_contract_payments::instantiate,
             tg_contract_payments::query,
-        );
+        )
+        .with_reply(tg_contract_payments::reply);
         let code_id = app_client.with_app_mut(|app| app.store_code(Box::new(contract)));
 
         let msg = tg_contract_api::payments::msg::InstantiateMsg {
             allowed_denoms: vec!["untrn".to_string(), "uatom".to_string()],
-            auth: tg_contract_api::payments::msg::Auth::Admin(admin.to_string()),
+            auth,
+            pending_expiry,
+            owner: None,
+            fee: None,
         };
 
+        let sender = app_client.admin();
         let address = app_client.with_app_mut(|app| {
-            app.instantiate_contract(code_id, admin.clone(), &msg, &[], "telegram-payments", None)
+            app.instantiate_contract(code_id, sender, &msg, &[], "telegram-payments", None)
                 .unwrap()
         });
 
@@ -0,0 +1,43 @@
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+    StdResult,
+};
+use cw_multi_test::{ContractWrapper, Executor};
+use wavs_types::contracts::cosmwasm::service_manager::{
+    ServiceManagerQueryMessages, WavsValidateResult,
+};
+
+use crate::client::AppClient;
+
+/// Stands in for the WAVS service manager, accepts every envelope without checking signatures
+pub struct MockServiceManager;
+
+impl MockServiceManager {
+    pub fn instantiate(app_client: &AppClient) -> Addr {
+        let contract = ContractWrapper::new(execute, instantiate, query);
+        let admin = app_client.admin();
+
+        app_client.with_app_mut(|app| {
+            let code_id = app.store_code(Box::new(contract));
+            app.instantiate_contract(code_id, admin, &Empty {}, &[], "mock-service-manager", None)
+                .unwrap()
+        })
+    }
+}
+
+fn instantiate(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+    Ok(Response::default())
+}
+
+fn execute(_deps: DepsMut, _env: Env, _info: MessageInfo, _msg: Empty) -> StdResult<Response> {
+    Err(StdError::msg(
+        "mock service manager has no execute messages",
+    ))
+}
+
+fn query(_deps: Deps, _env: Env, msg: ServiceManagerQueryMessages) -> StdResult<Binary> {
+    match msg {
+        ServiceManagerQueryMessages::WavsValidate { .. } => to_json_binary(&WavsValidateResult::Ok),
+        _ => Err(StdError::msg("unsupported query")),
+    }
+}
@@ -1,6 +1,33 @@
-use off_chain_tests::client::{payments::PaymentsClient, AppClient};
+#![recursion_limit = "256"]
+
+use cosmwasm_std::{coin, Addr, Event, Timestamp, Uint256};
+use cw_utils::{Duration, Expiration};
+use off_chain_tests::client::{cw20::MockCw20, payments::PaymentsClient, AppClient};
+use tg_contract_api::payments::{
+    event::{
+        BatchEntryEvent, DenomMetadataUpdatedEvent, GiftClaimedEvent, GiftCreatedEvent,
+        GiftRefundedEvent, GiftReservedEvent, GiveawayClaimedEvent, GiveawayCreatedEvent,
+        GiveawayRefundedEvent, InvoicePaidEvent, PaymentCancelledEvent, PaymentFailedEvent,
+        PaymentHeldEvent, PaymentReleasedEvent, PendingClaimedEvent, ProposalCreatedEvent,
+        ProposalExecutedEvent, ProposalRejectedEvent, ProposalVotedEvent, ScheduleCreatedEvent,
+        ScheduleEndedEvent, ScheduleRunFailedEvent, SendPaymentEvent, SplitLeg, SplitPaymentEvent,
+        SplitPaymentLegEvent, TreasuryCreatedEvent, TreasuryFundedEvent,
+    },
+    handle::TgHandle,
+    msg::{
+        Auth, ClaimGiftMsg, ClaimGiveawayMsg, CreateGiftMsg, CreateGiveawayMsg, CreateTreasuryMsg,
+        CustomExecuteMsg, DenomMetadata, ExecuteMsg, FeeConfig, FundTreasuryMsg, Gift,
+        GiveawaySplit, IbcRoute, PaymentKind, ProposePayoutMsg, RegisterReceiveMsg,
+        SchedulePaymentMsg, ScheduleRun, SendPaymentMsg, SpendLimit, SplitRecipient, VoteMsg,
+        WavsPayload, MAX_BATCH_SIZE,
+    },
+};
 use tg_test_common::shared_tests::{self, payments::RegisterReceivesOpenAccountProps};
-use tg_utils::tracing::tracing_init;
+use tg_utils::{
+    client::{payments::PaymentsExecutor, AnyTxResponse},
+    tracing::tracing_init,
+};
+use wavs_types::contracts::cosmwasm::service_handler::{WavsEnvelope, WavsSignatureData};
 
 #[tokio::test]
 async fn get_admin() {
@@ -28,14 +55,15 @@ async fn register_receive_creates_open_account() {
             user_addr: app_client
                 .with_app(|app| app.api().addr_make("user123"))
                 .into(),
-            tg_handle: "@alice".to_string(),
+            tg_user_id: 1,
+            tg_handle: tg("@alice"),
         },
     )
     .await;
 }
 
 #[tokio::test]
-async fn register_receive_prevents_duplicate_tg_handle() {
+async fn register_receive_prevents_duplicate_tg_user() {
     tracing_init();
 
     let app_client = AppClient::new("admin");
@@ -44,26 +72,26 @@ async fn register_receive_prevents_duplicate_tg_handle() {
     let user1_addr = app_client.with_app(|app| app.api().addr_make("user1"));
     let user2_addr = app_client.with_app(|app| app.api().addr_make("user2"));
 
-    let tg_handle = "@alice".to_string();
+    let tg_handle = tg("@alice");
 
     // Register first user
     payments
         .executor
-        .register_receive(tg_handle.clone(), &user1_addr.into())
+        .register_receive(1, Some(&tg_handle), &user1_addr.into())
         .await
         .unwrap();
 
-    // Try to register second user with same Telegram handle - should fail
+    // Try to register the same Telegram user to another address - should fail
     let err = payments
         .executor
-        .register_receive(tg_handle.clone(), &user2_addr.into())
+        .register_receive(1, Some(&tg_handle), &user2_addr.into())
         .await
         .unwrap_err();
 
     assert!(
         err.to_string()
-            .contains(&format!("TG Handle {} is already registered", tg_handle)),
-        "Expected TgAlreadyRegistered error, got: {}",
+            .contains("Telegram user 1 is already registered"),
+        "Expected TgUserAlreadyRegistered error, got: {}",
         err
     );
 }
@@ -79,12 +107,12 @@ async fn register_receive_requires_admin() {
 
     let user_addr = app_client.with_app(|app| app.api().addr_make("user123"));
 
-    let tg_handle = "@alice".to_string();
+    let tg_handle = tg("@alice");
 
     // Try to register user with unauthorized client - should fail
     let err = payments
         .executor
-        .register_receive(tg_handle.clone(), &user_addr.into())
+        .register_receive(1, Some(&tg_handle), &user_addr.into())
         .await
         .unwrap_err();
 
@@ -102,7 +130,7 @@ async fn query_nonexistent_accounts() {
     let app_client = AppClient::new("admin");
     let payments = PaymentsClient::new(app_client.clone());
 
-    let tg_handle = "@alice".to_string();
+    let tg_handle = tg("@alice");
 
     // Query non-existent Telegram handle
     assert_eq!(
@@ -144,3 +172,3428 @@ async fn allowed_denoms() {
         vec!["uatom".to_string(), "untrn".to_string()]
     );
 }
+
+#[tokio::test]
+async fn payment_history_records_sends_and_releases() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    let carol = app_client.with_app(|app| app.api().addr_make("carol"));
+
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+
+    // Direct send to a registered user
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 100);
+
+    // Carol is not registered yet, so this is held by the contract
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@carol"), 50u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&carol, "untrn"), 0);
+
+    // Registering releases the held funds
+    payments
+        .executor
+        .register_receive(3, Some(&tg("@carol")), &carol.clone().into())
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&carol, "untrn"), 50);
+
+    // Alice sent twice, newest first
+    let history = payments
+        .querier
+        .payments_by_tg_handle(tg("@alice"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].kind, PaymentKind::Pending);
+    assert_eq!(history[0].to_tg_handle, Some(tg("@carol")));
+    assert_eq!(history[0].to_tg_id, None);
+    assert_eq!(history[1].kind, PaymentKind::Direct);
+    assert_eq!(history[1].to_address, bob);
+    assert_eq!(history[1].from_address, alice);
+
+    // Carol has the held payment and its release
+    let history = payments
+        .querier
+        .payments_by_tg_handle(tg("@carol"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].kind, PaymentKind::Released);
+    assert_eq!(history[0].from_tg_handle, None);
+    assert_eq!(history[0].to_tg_id, Some(3));
+    assert_eq!(history[0].amount, Uint256::from(50u128));
+
+    // By address only sees what actually landed at carol
+    let history = payments
+        .querier
+        .payments_by_addr(carol.to_string(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, PaymentKind::Released);
+
+    let history = payments
+        .querier
+        .payments_by_denom("untrn".to_string(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 3);
+
+    let history = payments
+        .querier
+        .payments_by_denom("uatom".to_string(), None, None)
+        .await
+        .unwrap();
+    assert!(history.is_empty());
+}
+
+#[tokio::test]
+async fn payment_history_paginates() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.into())
+        .await
+        .unwrap();
+
+    for amount in 1..=5u128 {
+        payments
+            .executor
+            .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), amount, "untrn")
+            .await
+            .unwrap();
+    }
+
+    let page = payments
+        .querier
+        .payments_by_tg_handle(tg("@bobby"), None, Some(2))
+        .await
+        .unwrap();
+    assert_eq!(
+        page.iter()
+            .map(|p| p.amount.to_string())
+            .collect::<Vec<_>>(),
+        vec!["5", "4"]
+    );
+
+    let page = payments
+        .querier
+        .payments_by_tg_handle(tg("@bobby"), Some(page[1].id), Some(2))
+        .await
+        .unwrap();
+    assert_eq!(
+        page.iter()
+            .map(|p| p.amount.to_string())
+            .collect::<Vec<_>>(),
+        vec!["3", "2"]
+    );
+
+    let page = payments
+        .querier
+        .payments_by_tg_handle(tg("@bobby"), Some(page[1].id), Some(2))
+        .await
+        .unwrap();
+    assert_eq!(
+        page.iter()
+            .map(|p| p.amount.to_string())
+            .collect::<Vec<_>>(),
+        vec!["1"]
+    );
+}
+
+#[tokio::test]
+async fn expired_pending_payments_are_refunded() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new_with_config(
+        app_client.clone(),
+        app_client.admin(),
+        Some(Duration::Time(60)),
+    );
+
+    assert_eq!(
+        payments.querier.pending_expiry().await.unwrap(),
+        Some(Duration::Time(60))
+    );
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@carol"), 50u128, "untrn")
+        .await
+        .unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@dave"), 20u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 70);
+
+    let pending = payments
+        .querier
+        .pending_payments_by_tg_handle(tg("@carol"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].from_tg_id, 1);
+    assert_eq!(pending[0].from_tg_handle, Some(tg("@alice")));
+    assert_eq!(pending[0].from_address, alice);
+    assert_eq!(pending[0].amount, Uint256::from(50u128));
+
+    // Nothing has expired yet
+    payments.executor.refund_expired(None).await.unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 70);
+
+    app_client
+        .with_app_mut(|app| app.update_block(|block| block.time = block.time.plus_seconds(61)));
+
+    // Dave registers in time to get his payment, even after it expired
+    let dave = app_client.with_app(|app| app.api().addr_make("dave"));
+    payments
+        .executor
+        .register_receive(4, Some(&tg("@dave")), &dave.clone().into())
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&dave, "untrn"), 20);
+    assert!(payments
+        .querier
+        .pending_payments_by_tg_handle(tg("@dave"), None, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Carol never showed up, so alice gets it back
+    payments.executor.refund_expired(None).await.unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 20);
+    assert!(payments
+        .querier
+        .pending_payments_by_tg_handle(tg("@carol"), None, None)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(payments
+        .querier
+        .pending_payments(tg("@carol"))
+        .await
+        .unwrap()
+        .is_empty());
+
+    let history = payments
+        .querier
+        .payments_by_tg_handle(tg("@alice"), None, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(history[0].kind, PaymentKind::Refunded);
+    assert_eq!(history[0].to_address, alice);
+    assert_eq!(history[0].amount, Uint256::from(50u128));
+
+    // Refunding again is a no-op
+    payments.executor.refund_expired(None).await.unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 20);
+}
+
+#[tokio::test]
+async fn pending_payments_without_expiry_are_never_refunded() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    assert_eq!(payments.querier.pending_expiry().await.unwrap(), None);
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@carol"), 50u128, "untrn")
+        .await
+        .unwrap();
+
+    app_client.with_app_mut(|app| {
+        app.update_block(|block| {
+            block.height += 1_000_000;
+            block.time = block.time.plus_days(365);
+        })
+    });
+
+    payments.executor.refund_expired(None).await.unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 50);
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments_by_tg_handle(tg("@carol"), None, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn pending_expiry_changes_apply_to_new_payments() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+
+    // Held before there was an expiry, so it is never refunded
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@carol"), 50u128, "untrn")
+        .await
+        .unwrap();
+
+    let err = payments
+        .executor
+        .set_pending_expiry(Some(Duration::Time(0)))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("more than zero"), "{err:?}");
+    let alice_payments = PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    );
+    let err = alice_payments
+        .set_pending_expiry(Some(Duration::Time(60)))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    payments
+        .executor
+        .set_pending_expiry(Some(Duration::Time(600)))
+        .await
+        .unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@dave"), 20u128, "untrn")
+        .await
+        .unwrap();
+    payments
+        .executor
+        .set_pending_expiry(Some(Duration::Height(5)))
+        .await
+        .unwrap();
+    assert_eq!(
+        payments.querier.pending_expiry().await.unwrap(),
+        Some(Duration::Height(5))
+    );
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@erin"), 7u128, "untrn")
+        .await
+        .unwrap();
+
+    // The later, shorter expiry comes first, and nothing waits behind carol's payment
+    app_client.with_app_mut(|app| app.update_block(|block| block.height += 5));
+    payments.executor.refund_expired(None).await.unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 70);
+
+    app_client
+        .with_app_mut(|app| app.update_block(|block| block.time = block.time.plus_seconds(600)));
+    payments.executor.refund_expired(None).await.unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 50);
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments_by_tg_handle(tg("@carol"), None, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+
+    payments.executor.set_pending_expiry(None).await.unwrap();
+    assert_eq!(payments.querier.pending_expiry().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn resubmitted_envelope_is_rejected() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new_with_service_manager(app_client.clone());
+
+    let alice = app_client.with_app(|app| app.api().addr_make("alice"));
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    app_client.mint(&alice, coin(1_000_000, "untrn"));
+
+    for (message_id, tg_handle, addr) in [(1, "@alice", &alice), (2, "@bobby", &bob)] {
+        submit(
+            &payments,
+            WavsPayload::Register(RegisterReceiveMsg {
+                chat_id: 100,
+                message_id,
+                tg_user_id: message_id,
+                tg_handle: Some(tg(tg_handle)),
+                chain_addr: addr.to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+    }
+    PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    )
+    .register_send(&tg("@alice"))
+    .await
+    .unwrap();
+
+    let send = WavsPayload::SendPayment(SendPaymentMsg {
+        chat_id: 100,
+        message_id: 3,
+        from_tg_id: 1,
+        from_tg: Some(tg("@alice")),
+        to_tg: tg("@bobby"),
+        amount: Uint256::from(100u128),
+        denom: "untrn".to_string(),
+    });
+    submit(&payments, send.clone()).await.unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 100);
+
+    // The exact same envelope again must not pay twice
+    let err = submit(&payments, send).await.unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("Telegram message 3 in chat 100 was already processed"),
+        "Expected MessageAlreadyProcessed error, got: {}",
+        err
+    );
+    assert_eq!(app_client.balance(&bob, "untrn"), 100);
+
+    // Message ids are only unique per chat
+    submit(
+        &payments,
+        WavsPayload::SendPayment(SendPaymentMsg {
+            chat_id: 200,
+            message_id: 3,
+            from_tg_id: 1,
+            from_tg: Some(tg("@alice")),
+            to_tg: tg("@bobby"),
+            amount: Uint256::from(100u128),
+            denom: "untrn".to_string(),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 200);
+}
+
+#[tokio::test]
+async fn batch_entries_fail_on_their_own() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new_with_service_manager(app_client.clone());
+
+    let alice = app_client.with_app(|app| app.api().addr_make("alice"));
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    app_client.mint(&alice, coin(1_000_000, "untrn"));
+
+    let register = |message_id: i64, tg_handle: &str, addr: &Addr| {
+        WavsPayload::Register(RegisterReceiveMsg {
+            chat_id: 100,
+            message_id,
+            tg_user_id: message_id,
+            tg_handle: Some(tg(tg_handle)),
+            chain_addr: addr.to_string(),
+        })
+    };
+    let send = |message_id: i64, amount: u128| {
+        WavsPayload::SendPayment(SendPaymentMsg {
+            chat_id: 100,
+            message_id,
+            from_tg_id: 1,
+            from_tg: Some(tg("@alice")),
+            to_tg: tg("@bobby"),
+            amount: amount.into(),
+            denom: "untrn".to_string(),
+        })
+    };
+
+    submit(
+        &payments,
+        WavsPayload::Batch(vec![
+            register(1, "@alice", &alice),
+            register(2, "@bobby", &bob),
+        ]),
+    )
+    .await
+    .unwrap();
+    PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    )
+    .register_send(&tg("@alice"))
+    .await
+    .unwrap();
+
+    // Bob registering again and a replayed message both fail, the sends around them don't
+    let resp = submit(
+        &payments,
+        WavsPayload::Batch(vec![
+            send(3, 10),
+            register(2, "@bobby", &bob),
+            send(3, 10),
+            send(4, 20),
+        ]),
+    )
+    .await
+    .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 30);
+
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let entries = resp
+        .events
+        .iter()
+        .filter_map(|event| BatchEntryEvent::try_from(event).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries
+            .iter()
+            .map(|entry| (entry.index, entry.message_id, entry.error.is_none()))
+            .collect::<Vec<_>>(),
+        vec![(0, 3, true), (1, 2, false), (2, 3, false), (3, 4, true)]
+    );
+    assert_eq!(
+        resp.events
+            .iter()
+            .filter_map(|event| SendPaymentEvent::try_from(event).ok())
+            .count(),
+        2
+    );
+
+    // The failed entries left nothing behind
+    assert_eq!(
+        payments
+            .querier
+            .payments_by_tg_handle(tg("@alice"), None, None)
+            .await
+            .unwrap()
+            .len(),
+        2
+    );
+
+    for invalid in [
+        WavsPayload::Batch(vec![]),
+        WavsPayload::Batch(
+            (0..=MAX_BATCH_SIZE as i64)
+                .map(|id| send(10 + id, 1))
+                .collect(),
+        ),
+    ] {
+        let err = submit(&payments, invalid).await.unwrap_err();
+        assert!(err.to_string().contains("A batch needs"), "{err}");
+    }
+    assert_eq!(app_client.balance(&bob, "untrn"), 30);
+}
+
+#[tokio::test]
+async fn batches_only_take_commands_from_messages() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new_with_service_manager(app_client.clone());
+
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    let register = WavsPayload::Register(RegisterReceiveMsg {
+        chat_id: 100,
+        message_id: 1,
+        tg_user_id: 2,
+        tg_handle: Some(tg("@bobby")),
+        chain_addr: bob.to_string(),
+    });
+
+    // Anything without a message of its own rejects the whole envelope, the valid entry included
+    for entry in [
+        WavsPayload::Batch(vec![register.clone()]),
+        WavsPayload::ReleaseMatured { limit: None },
+        WavsPayload::RunSchedules(vec![]),
+    ] {
+        let err = submit(&payments, WavsPayload::Batch(vec![register.clone(), entry]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("single Telegram message"), "{err}");
+    }
+    assert!(payments
+        .querier
+        .addr_by_tg_handle(tg("@bobby"))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn username_change_keeps_account() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+
+    // Alice renames herself and keeps sending, which refreshes her alias
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice_new")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 100);
+    assert_eq!(
+        payments
+            .querier
+            .addr_by_tg_handle(tg("@alice_new"))
+            .await
+            .unwrap(),
+        Some(alice.to_string())
+    );
+    assert_eq!(
+        payments
+            .querier
+            .tg_handle_by_addr(alice.to_string())
+            .await
+            .unwrap(),
+        Some(tg("@alice_new"))
+    );
+
+    // The old handle no longer points anywhere, so payments to it are held
+    assert_eq!(
+        payments
+            .querier
+            .addr_by_tg_handle(tg("@alice"))
+            .await
+            .unwrap(),
+        None
+    );
+
+    // Carol can pay alice under her new name
+    let carol = fund_sender(&app_client, &payments, 3, "@carol", "carol").await;
+    payments
+        .executor
+        .send_payment(3, Some(&tg("@carol")), &tg("@alice_new"), 10u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 100 + 10);
+    assert_eq!(app_client.balance(&carol, "untrn"), 1_000_000 - 10);
+
+    // And someone else can now register with the freed handle
+    let dave = app_client.with_app(|app| app.api().addr_make("dave"));
+    payments
+        .executor
+        .register_receive(4, Some(&tg("@alice")), &dave.clone().into())
+        .await
+        .unwrap();
+    assert_eq!(
+        payments
+            .querier
+            .addr_by_tg_handle(tg("@alice"))
+            .await
+            .unwrap(),
+        Some(dave.to_string())
+    );
+
+    // History follows the user id across the rename
+    let history = payments
+        .querier
+        .payments_by_tg_id(1, None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].to_tg_id, Some(1));
+    assert_eq!(history[0].to_tg_handle, Some(tg("@alice_new")));
+    assert_eq!(history[1].from_tg_id, Some(1));
+    assert_eq!(history[1].from_tg_handle, Some(tg("@alice_new")));
+}
+
+#[tokio::test]
+async fn user_without_username_can_register() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = app_client.with_app(|app| app.api().addr_make("alice"));
+    payments
+        .executor
+        .register_receive(1, None, &alice.clone().into())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        payments.querier.addr_by_tg_id(1).await.unwrap(),
+        Some(alice.to_string())
+    );
+    assert_eq!(payments.querier.addr_by_tg_id(2).await.unwrap(), None);
+
+    // Without a handle she can't be paid by name, so this is held for whoever claims "@alice"
+    let bob = fund_sender(&app_client, &payments, 2, "@bobby", "bob").await;
+    payments
+        .executor
+        .send_payment(2, Some(&tg("@bobby")), &tg("@alice"), 30u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 0);
+    assert_eq!(app_client.balance(&bob, "untrn"), 1_000_000 - 30);
+
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments_by_tg_handle(tg("@alice"), None, None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn non_canonical_handles_are_rejected() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new_with_service_manager(app_client.clone());
+
+    // Anything not lowercase and without the @ never makes it past message parsing
+    for (message_id, to_tg) in [(1, "@bobby"), (2, "Bobby"), (3, "bob")] {
+        let payload = format!(
+            r#"{{"send_payment":{{"chat_id":100,"message_id":{message_id},"from_tg_id":1,"from_tg":"alice","to_tg":"{to_tg}","amount":"1","denom":"untrn"}}}}"#
+        );
+        let err = submit_bytes(&payments, payload.into_bytes())
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:?}").contains("invalid Telegram handle"),
+            "Expected invalid handle error for {to_tg}, got: {err:?}"
+        );
+    }
+
+    // Users can type it however they like, it ends up as the same key
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    submit(
+        &payments,
+        WavsPayload::Register(RegisterReceiveMsg {
+            chat_id: 100,
+            message_id: 4,
+            tg_user_id: 2,
+            tg_handle: Some(tg("BobBy")),
+            chain_addr: bob.to_string(),
+        }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        payments
+            .querier
+            .addr_by_tg_handle(tg("@BOBBY"))
+            .await
+            .unwrap(),
+        Some(bob.to_string())
+    );
+}
+
+#[tokio::test]
+async fn ownership_transfer_takes_two_steps() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let new_owner = app_client.with_app(|app| app.api().addr_make("new_owner"));
+    let stranger = app_client.with_app(|app| app.api().addr_make("stranger"));
+    let as_new_owner = PaymentsExecutor::new(
+        app_client.executor_for(&new_owner),
+        payments.executor.addr.clone(),
+    );
+    let as_stranger = PaymentsExecutor::new(
+        app_client.executor_for(&stranger),
+        payments.executor.addr.clone(),
+    );
+
+    // Defaults to the instantiator
+    let owner = payments.querier.owner().await.unwrap();
+    assert_eq!(owner.owner, app_client.admin());
+    assert_eq!(owner.pending_owner, None);
+
+    let err = as_stranger
+        .transfer_ownership(&stranger.clone().into())
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    let err = as_new_owner.accept_ownership().await.unwrap_err();
+    assert!(
+        format!("{err:?}").contains("No ownership transfer in progress"),
+        "{err:?}"
+    );
+
+    payments
+        .executor
+        .transfer_ownership(&new_owner.clone().into())
+        .await
+        .unwrap();
+    let owner = payments.querier.owner().await.unwrap();
+    assert_eq!(owner.owner, app_client.admin());
+    assert_eq!(owner.pending_owner, Some(new_owner.clone()));
+
+    // Only the proposed owner can accept
+    let err = as_stranger.accept_ownership().await.unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    as_new_owner.accept_ownership().await.unwrap();
+    let owner = payments.querier.owner().await.unwrap();
+    assert_eq!(owner.owner, new_owner);
+    assert_eq!(owner.pending_owner, None);
+
+    // The previous owner has no say anymore
+    let err = payments
+        .executor
+        .add_denoms(vec!["uusdc".to_string()])
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    // A proposal can be withdrawn before it is accepted
+    as_new_owner
+        .transfer_ownership(&stranger.clone().into())
+        .await
+        .unwrap();
+    as_new_owner.cancel_ownership_transfer().await.unwrap();
+    assert!(as_stranger.accept_ownership().await.is_err());
+    assert_eq!(payments.querier.owner().await.unwrap().owner, new_owner);
+}
+
+#[tokio::test]
+async fn owner_manages_allowed_denoms() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    payments
+        .executor
+        .add_denoms(vec!["uusdc".to_string()])
+        .await
+        .unwrap();
+    let err = payments
+        .executor
+        .add_denoms(vec!["untrn".to_string()])
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Denom already allowed: untrn"),
+        "{err:?}"
+    );
+
+    payments
+        .executor
+        .remove_denoms(vec!["uatom".to_string()])
+        .await
+        .unwrap();
+    let err = payments
+        .executor
+        .remove_denoms(vec!["uatom".to_string()])
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Token not whitelisted: uatom"),
+        "{err:?}"
+    );
+
+    let mut allowed_denoms = payments.querier.allowed_denoms().await.unwrap();
+    allowed_denoms.sort();
+    assert_eq!(
+        allowed_denoms,
+        vec!["untrn".to_string(), "uusdc".to_string()]
+    );
+
+    let stranger = app_client.with_app(|app| app.api().addr_make("stranger"));
+    let err = PaymentsExecutor::new(
+        app_client.executor_for(&stranger),
+        payments.executor.addr.clone(),
+    )
+    .remove_denoms(vec!["untrn".to_string()])
+    .await
+    .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+}
+
+#[tokio::test]
+async fn owner_rotates_and_switches_auth() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let new_admin = app_client.with_app(|app| app.api().addr_make("new_admin"));
+    let as_new_admin = PaymentsExecutor::new(
+        app_client.executor_for(&new_admin),
+        payments.executor.addr.clone(),
+    );
+    let alice = app_client.with_app(|app| app.api().addr_make("alice"));
+
+    // Rotate the admin
+    payments
+        .executor
+        .set_auth(Auth::Admin(new_admin.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        payments.querier.auth().await.unwrap(),
+        Auth::Admin(new_admin.to_string())
+    );
+    assert_eq!(
+        payments.querier.admin().await.unwrap(),
+        Some(new_admin.to_string())
+    );
+
+    // The owner is no longer the admin, only the new one can act for WAVS
+    assert!(payments
+        .executor
+        .register_receive(1, Some(&tg("@alice")), &alice.clone().into())
+        .await
+        .is_err());
+    as_new_admin
+        .register_receive(1, Some(&tg("@alice")), &alice.clone().into())
+        .await
+        .unwrap();
+
+    // Switch to the service manager, the admin shortcut goes away
+    let service_manager = app_client.with_app(|app| app.api().addr_make("service_manager"));
+    payments
+        .executor
+        .set_auth(Auth::ServiceManager(service_manager.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(
+        payments.querier.auth().await.unwrap(),
+        Auth::ServiceManager(service_manager.to_string())
+    );
+    assert_eq!(payments.querier.admin().await.unwrap(), None);
+    assert!(as_new_admin
+        .register_receive(2, Some(&tg("@bobby")), &alice.into())
+        .await
+        .is_err());
+
+    // Only the owner can change it
+    let err = as_new_admin
+        .set_auth(Auth::Admin(new_admin.to_string()))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+}
+
+#[tokio::test]
+async fn cw20_payments_are_pulled_held_and_released() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new_with_config(
+        app_client.clone(),
+        app_client.admin(),
+        Some(Duration::Time(60)),
+    );
+    let payments_addr = Addr::unchecked(payments.executor.addr.to_string());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let token = MockCw20::instantiate(&app_client, &[(&alice, 1_000)]);
+    let denom = token.to_string();
+
+    // Not usable until the owner allows it
+    let err = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, &denom)
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Token not whitelisted"),
+        "{err:?}"
+    );
+    payments
+        .executor
+        .add_denoms(vec![denom.clone()])
+        .await
+        .unwrap();
+
+    // Without an allowance the contract can't move anything, and the sender is told why
+    let resp = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, &denom)
+        .await
+        .unwrap();
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let failure = resp
+        .events
+        .iter()
+        .find_map(|event| PaymentFailedEvent::try_from(event).ok())
+        .unwrap();
+    assert!(
+        failure.reason.contains("insufficient allowance"),
+        "{failure:?}"
+    );
+    assert!(payments
+        .querier
+        .pending_payments(tg("@bobby"))
+        .await
+        .unwrap()
+        .is_empty());
+    MockCw20::increase_allowance(&app_client, &token, &alice, &payments_addr, 500);
+
+    // Bobby isn't registered, so the contract holds it
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, &denom)
+        .await
+        .unwrap();
+    assert_eq!(MockCw20::balance(&app_client, &token, &alice), 900);
+    assert_eq!(MockCw20::balance(&app_client, &token, &payments_addr), 100);
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments(tg("@bobby"))
+            .await
+            .unwrap(),
+        vec![coin(100, &denom)]
+    );
+
+    // Released alongside native funds held for the same handle
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 7u128, "untrn")
+        .await
+        .unwrap();
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+    assert_eq!(MockCw20::balance(&app_client, &token, &bob), 100);
+    assert_eq!(MockCw20::balance(&app_client, &token, &payments_addr), 0);
+    assert_eq!(app_client.balance(&bob, "untrn"), 7);
+
+    // Now straight from alice to bob
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 50u128, &denom)
+        .await
+        .unwrap();
+    assert_eq!(MockCw20::balance(&app_client, &token, &alice), 850);
+    assert_eq!(MockCw20::balance(&app_client, &token, &bob), 150);
+
+    let history = payments
+        .querier
+        .payments_by_denom(denom.clone(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(
+        history.iter().map(|p| p.kind.clone()).collect::<Vec<_>>(),
+        vec![
+            PaymentKind::Direct,
+            PaymentKind::Released,
+            PaymentKind::Pending
+        ]
+    );
+
+    // Unclaimed CW20s go back to the sender once expired
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@carol"), 30u128, &denom)
+        .await
+        .unwrap();
+    app_client
+        .with_app_mut(|app| app.update_block(|block| block.time = block.time.plus_seconds(61)));
+    payments.executor.refund_expired(None).await.unwrap();
+    assert_eq!(MockCw20::balance(&app_client, &token, &alice), 850);
+    assert_eq!(MockCw20::balance(&app_client, &token, &payments_addr), 0);
+
+    // The allowance is spent down like any other
+    let resp = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 321u128, &denom)
+        .await
+        .unwrap();
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let failure = resp
+        .events
+        .iter()
+        .find_map(|event| PaymentFailedEvent::try_from(event).ok())
+        .unwrap();
+    assert!(
+        failure.reason.contains("insufficient allowance"),
+        "{failure:?}"
+    );
+    assert_eq!(MockCw20::balance(&app_client, &token, &alice), 850);
+}
+
+#[tokio::test]
+async fn split_payment_pays_every_leg_at_once() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+
+    let recipients = vec![
+        SplitRecipient {
+            to_tg: tg("@bobby"),
+            weight: 2,
+        },
+        SplitRecipient {
+            to_tg: tg("@carol"),
+            weight: 1,
+        },
+    ];
+
+    // 101 by 2:1 rounds down to 67 and 33, the unit left over goes to the first recipient
+    let resp = payments
+        .executor
+        .split_payment(1, Some(&tg("@alice")), recipients.clone(), 101u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 68);
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 101);
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments(tg("@carol"))
+            .await
+            .unwrap(),
+        vec![coin(33, "untrn")]
+    );
+
+    // One leg event each and a single summary, nothing that reports as a plain send
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let summaries = resp
+        .events
+        .iter()
+        .filter_map(|event| SplitPaymentEvent::try_from(event).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].total, Uint256::from(101u128));
+    assert_eq!(
+        summaries[0].legs,
+        vec![
+            SplitLeg {
+                to_tg_handle: tg("@bobby"),
+                amount: Uint256::from(68u128),
+                pending: false,
+            },
+            SplitLeg {
+                to_tg_handle: tg("@carol"),
+                amount: Uint256::from(33u128),
+                pending: true,
+            },
+        ]
+    );
+    let legs = resp
+        .events
+        .iter()
+        .filter_map(|event| SplitPaymentLegEvent::try_from(event).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(legs.len(), 2);
+    assert_eq!(legs[1].0.to_tg_id, None);
+    assert!(resp
+        .events
+        .iter()
+        .all(|event| SendPaymentEvent::try_from(event).is_err()));
+
+    // Each leg is in the history like any other send
+    let history = payments
+        .querier
+        .payments_by_tg_handle(tg("@alice"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].kind, PaymentKind::Pending);
+    assert_eq!(history[1].kind, PaymentKind::Direct);
+
+    // Alice can't cover it, so neither leg goes through
+    payments
+        .executor
+        .split_payment(
+            1,
+            Some(&tg("@alice")),
+            recipients.clone(),
+            2_000_000u128,
+            "untrn",
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(app_client.balance(&bob, "untrn"), 68);
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments(tg("@carol"))
+            .await
+            .unwrap(),
+        vec![coin(33, "untrn")]
+    );
+
+    // Splits that can't be divided are rejected up front
+    let err = payments
+        .executor
+        .split_payment(
+            1,
+            Some(&tg("@alice")),
+            vec![recipients[0].clone(), recipients[0].clone()],
+            10u128,
+            "untrn",
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("more than once"), "{err:?}");
+
+    let err = payments
+        .executor
+        .split_payment(
+            1,
+            Some(&tg("@alice")),
+            vec![SplitRecipient {
+                weight: 0,
+                ..recipients[0].clone()
+            }],
+            10u128,
+            "untrn",
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("greater than 0"), "{err:?}");
+
+    let err = payments
+        .executor
+        .split_payment(1, Some(&tg("@alice")), recipients, 1u128, "untrn")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("send 0 tokens"), "{err:?}");
+
+    let err = payments
+        .executor
+        .split_payment(1, Some(&tg("@alice")), vec![], 10u128, "untrn")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("recipients"), "{err:?}");
+}
+
+#[tokio::test]
+async fn invoices_are_paid_or_declined_by_the_payer() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = fund_sender(&app_client, &payments, 2, "@bobby", "bob").await;
+
+    payments
+        .executor
+        .request_payment(
+            2,
+            Some(&tg("@bobby")),
+            &tg("@alice"),
+            250u128,
+            "untrn",
+            Some("lunch"),
+        )
+        .await
+        .unwrap();
+
+    let invoices = payments
+        .querier
+        .invoices_by_payer(tg("@alice"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(invoices.len(), 1);
+    let invoice = &invoices[0];
+    assert_eq!(invoice.requester_tg_id, 2);
+    assert_eq!(invoice.to_address, bob);
+    assert_eq!(invoice.amount, Uint256::from(250u128));
+    assert_eq!(invoice.memo.as_deref(), Some("lunch"));
+
+    // Only the payer can answer it
+    let err = payments
+        .executor
+        .pay_invoice(2, Some(&tg("@bobby")), invoice.id)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    payments
+        .executor
+        .pay_invoice(1, Some(&tg("@alice")), invoice.id)
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 250);
+    assert_eq!(app_client.balance(&bob, "untrn"), 1_000_000 + 250);
+    assert_eq!(payments.querier.invoice(invoice.id).await.unwrap(), None);
+
+    let history = payments
+        .querier
+        .payments_by_tg_handle(tg("@alice"), None, None)
+        .await
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].kind, PaymentKind::Direct);
+    assert_eq!(history[0].to_tg_id, Some(2));
+
+    // Settled invoices can't be paid twice
+    let err = payments
+        .executor
+        .pay_invoice(1, Some(&tg("@alice")), invoice.id)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("not found"), "{err:?}");
+
+    // Declining closes it without moving any funds
+    payments
+        .executor
+        .request_payment(
+            2,
+            Some(&tg("@bobby")),
+            &tg("@alice"),
+            100u128,
+            "untrn",
+            None,
+        )
+        .await
+        .unwrap();
+    let invoice = payments
+        .querier
+        .invoices_by_payer(tg("@alice"), None, None)
+        .await
+        .unwrap()
+        .remove(0);
+    payments
+        .executor
+        .decline_invoice(1, Some(&tg("@alice")), invoice.id)
+        .await
+        .unwrap();
+    assert!(payments
+        .querier
+        .invoices_by_payer(tg("@alice"), None, None)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(app_client.balance(&bob, "untrn"), 1_000_000 + 250);
+
+    // Requesters must be able to receive, and can't bill themselves
+    let err = payments
+        .executor
+        .request_payment(3, Some(&tg("@carol")), &tg("@alice"), 10u128, "untrn", None)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("must register"), "{err:?}");
+
+    let err = payments
+        .executor
+        .request_payment(2, Some(&tg("@bobby")), &tg("@bobby"), 10u128, "untrn", None)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("yourself"), "{err:?}");
+
+    let err = payments
+        .executor
+        .request_payment(
+            2,
+            Some(&tg("@bobby")),
+            &tg("@alice"),
+            10u128,
+            "untrn",
+            Some(&"x".repeat(141)),
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Memo"), "{err:?}");
+}
+
+#[tokio::test]
+async fn tips_go_to_the_replied_to_author() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+
+    // Bob has no username, so a reply is the only way to pay him by name
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, None, &bob.clone().into())
+        .await
+        .unwrap();
+    let resp = payments
+        .executor
+        .tip(1, Some(&tg("@alice")), 2, None, 25u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 25);
+
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let sends = resp
+        .events
+        .iter()
+        .filter_map(|event| SendPaymentEvent::try_from(event).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(sends.len(), 1);
+    assert_eq!(sends[0].to_tg_id, Some(2));
+    assert_eq!(sends[0].to_tg_handle, None);
+
+    // Once he picks a username, the tip that told us about it keeps the alias up to date
+    payments
+        .executor
+        .tip(
+            1,
+            Some(&tg("@alice")),
+            2,
+            Some(&tg("@bobby")),
+            5u128,
+            "untrn",
+        )
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 30);
+    assert_eq!(
+        payments
+            .querier
+            .addr_by_tg_handle(tg("@bobby"))
+            .await
+            .unwrap(),
+        Some(bob.to_string())
+    );
+
+    // Carol hasn't registered, so her tip is held under her username
+    payments
+        .executor
+        .tip(
+            1,
+            Some(&tg("@alice")),
+            3,
+            Some(&tg("@carol")),
+            10u128,
+            "untrn",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments(tg("@carol"))
+            .await
+            .unwrap(),
+        vec![coin(10, "untrn")]
+    );
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 40);
+
+    // Without an account or a username there's nothing to hold it under
+    let err = payments
+        .executor
+        .tip(1, Some(&tg("@alice")), 4, None, 10u128, "untrn")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("no username"), "{err:?}");
+
+    let err = payments
+        .executor
+        .tip(
+            1,
+            Some(&tg("@alice")),
+            1,
+            Some(&tg("@alice")),
+            10u128,
+            "untrn",
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("tip yourself"), "{err:?}");
+}
+
+#[tokio::test]
+async fn failed_sends_are_reported_instead_of_reverting() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new_with_service_manager(app_client.clone());
+
+    let alice = app_client.with_app(|app| app.api().addr_make("alice"));
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    app_client.mint(&alice, coin(1_000_000, "untrn"));
+
+    for (message_id, tg_handle, addr) in [(1, "@alice", &alice), (2, "@bobby", &bob)] {
+        submit(
+            &payments,
+            WavsPayload::Register(RegisterReceiveMsg {
+                chat_id: -100,
+                message_id,
+                tg_user_id: message_id,
+                tg_handle: Some(tg(tg_handle)),
+                chain_addr: addr.to_string(),
+            }),
+        )
+        .await
+        .unwrap();
+    }
+    PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    )
+    .register_send(&tg("@alice"))
+    .await
+    .unwrap();
+
+    let send = |message_id: i64, to_tg: &str, amount: u128| {
+        WavsPayload::SendPayment(SendPaymentMsg {
+            chat_id: -100,
+            message_id,
+            from_tg_id: 1,
+            from_tg: Some(tg("@alice")),
+            to_tg: tg(to_tg),
+            amount: amount.into(),
+            denom: "untrn".to_string(),
+        })
+    };
+
+    // More than Alice has, so the transfer fails but the message itself goes through
+    let resp = submit(&payments, send(42, "@bobby", 2_000_000))
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000);
+    assert_eq!(app_client.balance(&bob, "untrn"), 0);
+
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let failures = resp
+        .events
+        .iter()
+        .filter_map(|event| PaymentFailedEvent::try_from(event).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].chat_id, -100);
+    assert_eq!(failures[0].message_id, 42);
+    assert_eq!(failures[0].to_tg_handle, Some(tg("@bobby")));
+    assert!(!failures[0].reason.is_empty());
+    assert!(resp
+        .events
+        .iter()
+        .all(|event| SendPaymentEvent::try_from(event).is_err()));
+
+    // A failed send to an unregistered handle leaves nothing held for it, or in the history
+    submit(&payments, send(43, "@carol", 2_000_000))
+        .await
+        .unwrap();
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments(tg("@carol"))
+            .await
+            .unwrap(),
+        vec![]
+    );
+    assert!(payments
+        .querier
+        .pending_payments_by_tg_handle(tg("@carol"), None, None)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(payments
+        .querier
+        .payments_by_tg_handle(tg("@alice"), None, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // A send that goes through is still reported as one
+    let resp = submit(&payments, send(44, "@bobby", 10)).await.unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 10);
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    assert_eq!(
+        resp.events
+            .iter()
+            .filter_map(|event| SendPaymentEvent::try_from(event).ok())
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn senders_cap_their_own_spending() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+    let alice_executor = PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    );
+    let limit = SpendLimit {
+        per_payment: Some(100u128.into()),
+        per_window: Some(250u128.into()),
+        window_seconds: 86_400,
+    };
+
+    // Only a registered sender can cap their account, and the cap has to mean something
+    let err = payments
+        .executor
+        .set_spend_limit("untrn", Some(limit.clone()))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("not registered"), "{err:?}");
+    let err = alice_executor
+        .set_spend_limit(
+            "untrn",
+            Some(SpendLimit {
+                window_seconds: 0,
+                ..limit.clone()
+            }),
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("window"), "{err:?}");
+    alice_executor
+        .set_spend_limit("untrn", Some(limit.clone()))
+        .await
+        .unwrap();
+
+    let err = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 150u128, "untrn")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("at most 100 untrn"), "{err:?}");
+
+    for _ in 0..2 {
+        payments
+            .executor
+            .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+            .await
+            .unwrap();
+    }
+    let resp = payments
+        .querier
+        .spend_limit(alice.to_string(), "untrn")
+        .await
+        .unwrap();
+    assert_eq!(resp.limit, Some(limit));
+    assert_eq!(resp.spent, Uint256::from(200u128));
+    assert_eq!(resp.remaining, Some(Uint256::from(50u128)));
+
+    // A split counts as one payment, against the same window
+    let err = payments
+        .executor
+        .split_payment(
+            1,
+            Some(&tg("@alice")),
+            vec![SplitRecipient {
+                to_tg: tg("@bobby"),
+                weight: 1,
+            }],
+            60u128,
+            "untrn",
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("at most 50 untrn"), "{err:?}");
+    assert_eq!(app_client.balance(&bob, "untrn"), 200);
+
+    // Spends drop out once they're older than the window
+    app_client
+        .with_app_mut(|app| app.update_block(|block| block.time = block.time.plus_seconds(86_400)));
+    let resp = payments
+        .querier
+        .spend_limit(alice.to_string(), "untrn")
+        .await
+        .unwrap();
+    assert_eq!(resp.spent, Uint256::zero());
+    assert_eq!(resp.remaining, Some(Uint256::from(100u128)));
+
+    alice_executor.set_spend_limit("untrn", None).await.unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 500u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 700);
+    assert_eq!(
+        payments
+            .querier
+            .spend_limit(alice.to_string(), "untrn")
+            .await
+            .unwrap()
+            .remaining,
+        None
+    );
+}
+
+#[tokio::test]
+async fn owner_charges_a_fee_on_sends() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+    let treasury = app_client.with_app(|app| app.api().addr_make("treasury"));
+    let fee = FeeConfig {
+        basis_points: 100,
+        flat: vec![coin(5, "untrn")],
+        treasury: treasury.to_string(),
+    };
+
+    let err = payments
+        .executor
+        .set_fee(Some(FeeConfig {
+            basis_points: 5000,
+            ..fee.clone()
+        }))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("basis points"), "{err:?}");
+    let stranger = app_client.with_app(|app| app.api().addr_make("stranger"));
+    let err = PaymentsExecutor::new(
+        app_client.executor_for(&stranger),
+        payments.executor.addr.clone(),
+    )
+    .set_fee(Some(fee.clone()))
+    .await
+    .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+    payments.executor.set_fee(Some(fee.clone())).await.unwrap();
+    assert_eq!(payments.querier.fee().await.unwrap(), Some(fee));
+
+    // 1% plus the flat amount, on top of what the recipient gets
+    let quote = payments.querier.fee_quote(1000u128, "untrn").await.unwrap();
+    assert_eq!(quote.fee, Uint256::from(15u128));
+    assert_eq!(quote.total, Uint256::from(1015u128));
+
+    let resp = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 1000u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 1000);
+    assert_eq!(app_client.balance(&treasury, "untrn"), 15);
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 1015);
+
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let sent = resp
+        .events
+        .iter()
+        .find_map(|event| SendPaymentEvent::try_from(event).ok())
+        .unwrap();
+    assert_eq!(sent.fee, Some(Uint256::from(15u128)));
+
+    // A split is charged once on its total, an invoice like a send
+    let resp = payments
+        .executor
+        .split_payment(
+            1,
+            Some(&tg("@alice")),
+            vec![
+                SplitRecipient {
+                    to_tg: tg("@bobby"),
+                    weight: 1,
+                },
+                SplitRecipient {
+                    to_tg: tg("@carol"),
+                    weight: 1,
+                },
+            ],
+            200u128,
+            "untrn",
+        )
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 1100);
+    assert_eq!(app_client.balance(&treasury, "untrn"), 22);
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 1015 - 207);
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let split = resp
+        .events
+        .iter()
+        .find_map(|event| SplitPaymentEvent::try_from(event).ok())
+        .unwrap();
+    assert_eq!(split.fee, Some(Uint256::from(7u128)));
+
+    payments
+        .executor
+        .request_payment(
+            2,
+            Some(&tg("@bobby")),
+            &tg("@alice"),
+            100u128,
+            "untrn",
+            None,
+        )
+        .await
+        .unwrap();
+    let invoice = payments
+        .querier
+        .invoices_by_payer(tg("@alice"), None, None)
+        .await
+        .unwrap()
+        .remove(0);
+    let resp = payments
+        .executor
+        .pay_invoice(1, Some(&tg("@alice")), invoice.id)
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 1200);
+    assert_eq!(app_client.balance(&treasury, "untrn"), 28);
+    assert_eq!(
+        app_client.balance(&alice, "untrn"),
+        1_000_000 - 1015 - 207 - 106
+    );
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let paid = resp
+        .events
+        .iter()
+        .find_map(|event| InvoicePaidEvent::try_from(event).ok())
+        .unwrap();
+    assert_eq!(paid.fee, Some(Uint256::from(6u128)));
+
+    // Nothing is charged for a send that didn't go through
+    let resp = payments
+        .executor
+        .send_payment(
+            1,
+            Some(&tg("@alice")),
+            &tg("@bobby"),
+            2_000_000u128,
+            "untrn",
+        )
+        .await
+        .unwrap();
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    assert!(resp
+        .events
+        .iter()
+        .any(|event| PaymentFailedEvent::try_from(event).is_ok()));
+    assert_eq!(app_client.balance(&treasury, "untrn"), 28);
+
+    payments.executor.set_fee(None).await.unwrap();
+    let quote = payments.querier.fee_quote(1000u128, "untrn").await.unwrap();
+    assert_eq!(quote.fee, Uint256::zero());
+    assert_eq!(quote.total, Uint256::from(1000u128));
+}
+
+#[tokio::test]
+async fn owner_pauses_and_blocklists() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+
+    let stranger = app_client.with_app(|app| app.api().addr_make("stranger"));
+    let err = PaymentsExecutor::new(
+        app_client.executor_for(&stranger),
+        payments.executor.addr.clone(),
+    )
+    .set_paused(true)
+    .await
+    .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    // Sends and registrations stop, refunds keep working
+    payments.executor.set_paused(true).await.unwrap();
+    assert!(payments.querier.paused().await.unwrap());
+    let err = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("paused"), "{err:?}");
+    let carol = app_client.with_app(|app| app.api().addr_make("carol"));
+    let err = payments
+        .executor
+        .register_receive(3, Some(&tg("@carol")), &carol.clone().into())
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("paused"), "{err:?}");
+    payments.executor.refund_expired(None).await.unwrap();
+
+    payments.executor.set_paused(false).await.unwrap();
+    assert!(!payments.querier.paused().await.unwrap());
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 100);
+
+    // A blocked recipient can't be paid
+    payments
+        .executor
+        .block(vec![tg("@bobby")], vec![])
+        .await
+        .unwrap();
+    assert!(payments
+        .querier
+        .blocked(Some(tg("@bobby")), None)
+        .await
+        .unwrap());
+    let err = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("@bobby is blocked"), "{err:?}");
+
+    // A blocked address can neither send nor register
+    payments
+        .executor
+        .block(vec![], vec![alice.to_string(), carol.to_string()])
+        .await
+        .unwrap();
+    assert!(payments
+        .querier
+        .blocked(None, Some(alice.to_string()))
+        .await
+        .unwrap());
+    let err = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("is blocked"), "{err:?}");
+    let err = payments
+        .executor
+        .register_receive(3, Some(&tg("@carol")), &carol.clone().into())
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains(&format!("{carol} is blocked")),
+        "{err:?}"
+    );
+
+    payments
+        .executor
+        .unblock(
+            vec![tg("@bobby")],
+            vec![alice.to_string(), carol.to_string()],
+        )
+        .await
+        .unwrap();
+    assert!(!payments
+        .querier
+        .blocked(Some(tg("@bobby")), Some(alice.to_string()))
+        .await
+        .unwrap());
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 200);
+    payments
+        .executor
+        .register_receive(3, Some(&tg("@carol")), &carol.into())
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn registry_listing_and_stats() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = fund_sender(&app_client, &payments, 2, "@bobby", "bob").await;
+    let carol = app_client.with_app(|app| app.api().addr_make("carol"));
+    payments
+        .executor
+        .register_receive(3, Some(&tg("@carol")), &carol.clone().into())
+        .await
+        .unwrap();
+
+    let page = payments.querier.list_accounts(None, Some(2)).await.unwrap();
+    assert_eq!(
+        page.iter()
+            .map(|account| account.tg_user_id)
+            .collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+    assert_eq!(page[1].tg_handle, Some(tg("@bobby")));
+    assert_eq!(page[1].address, bob);
+    let page = payments
+        .querier
+        .list_accounts(Some(2), Some(2))
+        .await
+        .unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0].address, carol);
+
+    let mut funded = payments
+        .querier
+        .list_funded_accounts(None, None)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|account| (account.tg_user_id, account.address))
+        .collect::<Vec<_>>();
+    funded.sort();
+    assert_eq!(funded, vec![(1, alice.clone()), (2, bob.clone())]);
+
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@daisy"), 50u128, "untrn")
+        .await
+        .unwrap();
+    payments
+        .executor
+        .send_payment(2, Some(&tg("@bobby")), &tg("@daisy"), 30u128, "untrn")
+        .await
+        .unwrap();
+    // Didn't go through, so it isn't counted
+    payments
+        .executor
+        .send_payment(
+            1,
+            Some(&tg("@alice")),
+            &tg("@bobby"),
+            2_000_000u128,
+            "untrn",
+        )
+        .await
+        .unwrap();
+
+    let pending = payments
+        .querier
+        .list_pending_payments(None, None)
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].handle, tg("@daisy"));
+    assert_eq!(pending[0].amount, vec![coin(80, "untrn")]);
+
+    let stats = payments.querier.stats().await.unwrap();
+    assert_eq!(stats.registered, 3);
+    assert_eq!(stats.funded, 2);
+    assert_eq!(stats.volume, vec![coin(180, "untrn")]);
+    assert_eq!(stats.pending, vec![coin(80, "untrn")]);
+
+    // Releasing held funds moves them out of pending, without counting them as volume again
+    let daisy = app_client.with_app(|app| app.api().addr_make("daisy"));
+    payments
+        .executor
+        .register_receive(4, Some(&tg("@daisy")), &daisy.into())
+        .await
+        .unwrap();
+    assert!(payments
+        .querier
+        .list_pending_payments(None, None)
+        .await
+        .unwrap()
+        .is_empty());
+    let stats = payments.querier.stats().await.unwrap();
+    assert_eq!(stats.registered, 4);
+    assert_eq!(stats.volume, vec![coin(180, "untrn")]);
+    assert!(stats.pending.is_empty());
+}
+
+#[tokio::test]
+async fn send_grant_reports_what_is_left() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let payments_addr = Addr::unchecked(payments.executor.addr.to_string());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+
+    let grant = payments
+        .querier
+        .send_grant(alice.to_string())
+        .await
+        .unwrap();
+    assert!(!grant.granted);
+    assert_eq!(grant.spend_limit, None);
+
+    let expiration = app_client.with_app(|app| app.block_info().time.plus_seconds(1000));
+    app_client.grant_send(
+        &alice,
+        &payments_addr,
+        vec![coin(500, "untrn")],
+        Some(expiration),
+    );
+    let grant = payments
+        .querier
+        .send_grant(alice.to_string())
+        .await
+        .unwrap();
+    assert!(grant.granted);
+    assert_eq!(grant.expiration, Some(expiration));
+    assert_eq!(grant.spend_limit, Some(vec![coin(500, "untrn")]));
+
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 200u128, "untrn")
+        .await
+        .unwrap();
+    let grant = payments
+        .querier
+        .send_grant(alice.to_string())
+        .await
+        .unwrap();
+    assert_eq!(grant.spend_limit, Some(vec![coin(300, "untrn")]));
+
+    // More than the grant allows doesn't go through, and leaves the grant as it was
+    let resp = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 400u128, "untrn")
+        .await
+        .unwrap();
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    assert!(resp
+        .events
+        .iter()
+        .any(|event| PaymentFailedEvent::try_from(event).is_ok()));
+    assert_eq!(app_client.balance(&bob, "untrn"), 200);
+    let grant = payments
+        .querier
+        .send_grant(alice.to_string())
+        .await
+        .unwrap();
+    assert_eq!(grant.spend_limit, Some(vec![coin(300, "untrn")]));
+
+    app_client
+        .with_app_mut(|app| app.update_block(|block| block.time = block.time.plus_seconds(1000)));
+    let grant = payments
+        .querier
+        .send_grant(alice.to_string())
+        .await
+        .unwrap();
+    assert!(!grant.granted);
+    assert_eq!(grant.expiration, Some(expiration));
+}
+
+#[tokio::test]
+async fn cross_chain_sends_need_a_route_and_are_refunded_on_failure() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let dave = "osmo1v84qsqlcs56j8dmh6s22eccnpn2d87fdrkd3r6";
+    let register_dave = ExecuteMsg::Custom(CustomExecuteMsg::RegisterReceive(RegisterReceiveMsg {
+        chat_id: -100,
+        message_id: 2,
+        tg_user_id: 2,
+        tg_handle: Some(tg("@dave")),
+        chain_addr: dave.to_string(),
+    }));
+    let route = IbcRoute {
+        prefix: "osmo".to_string(),
+        channel_id: "channel-0".to_string(),
+        chain_id: "osmosis-1".to_string(),
+        timeout_seconds: 600,
+    };
+
+    // Nowhere to send it yet
+    let err = payments
+        .executor
+        .exec(&register_dave, &[])
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("osmo"), "{err:?}");
+
+    let err = payments
+        .executor
+        .set_ibc_route(IbcRoute {
+            timeout_seconds: 0,
+            ..route.clone()
+        })
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("An IBC route needs"), "{err:?}");
+    let err = PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    )
+    .set_ibc_route(route.clone())
+    .await
+    .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+    payments
+        .executor
+        .set_ibc_route(route.clone())
+        .await
+        .unwrap();
+    assert_eq!(payments.querier.ibc_routes().await.unwrap(), vec![route]);
+
+    payments.executor.exec(&register_dave, &[]).await.unwrap();
+    assert_eq!(
+        payments.querier.addr_by_tg_id(2).await.unwrap(),
+        Some(dave.to_string())
+    );
+
+    // The test chain has no IBC, so the transfer fails once it has been pulled in
+    let resp = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@dave"), 1000u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000);
+    let contract: Addr = payments.executor.addr.clone().into();
+    assert_eq!(app_client.balance(&contract, "untrn"), 0);
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let failures = resp
+        .events
+        .iter()
+        .filter_map(|event| PaymentFailedEvent::try_from(event).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].to_tg_handle, Some(tg("@dave")));
+    assert!(resp
+        .events
+        .iter()
+        .all(|event| SendPaymentEvent::try_from(event).is_err()));
+    assert!(payments
+        .querier
+        .payments_by_tg_handle(tg("@alice"), None, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // Splits go out in one response, with no way to refund a single leg
+    let err = payments
+        .executor
+        .split_payment(
+            1,
+            Some(&tg("@alice")),
+            vec![SplitRecipient {
+                to_tg: tg("@dave"),
+                weight: 1,
+            }],
+            1000u128,
+            "untrn",
+        )
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("another chain"), "{err:?}");
+
+    payments
+        .executor
+        .remove_ibc_route("osmo".to_string())
+        .await
+        .unwrap();
+    assert!(payments.querier.ibc_routes().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn owner_sets_denom_metadata() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+
+    let ntrn = DenomMetadata {
+        denom: "untrn".to_string(),
+        symbol: "NTRN".to_string(),
+        exponent: 6,
+        aliases: vec!["neutron".to_string()],
+    };
+
+    let stranger = app_client.with_app(|app| app.api().addr_make("stranger"));
+    let err = PaymentsExecutor::new(
+        app_client.executor_for(&stranger),
+        payments.executor.addr.clone(),
+    )
+    .set_denom_metadata(ntrn.clone())
+    .await
+    .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    for invalid in [
+        DenomMetadata {
+            symbol: "N TRN".to_string(),
+            ..ntrn.clone()
+        },
+        DenomMetadata {
+            aliases: vec!["a,b".to_string()],
+            ..ntrn.clone()
+        },
+        DenomMetadata {
+            exponent: 19,
+            ..ntrn.clone()
+        },
+    ] {
+        let err = payments
+            .executor
+            .set_denom_metadata(invalid)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:?}").contains("Denom metadata needs"),
+            "{err:?}"
+        );
+    }
+
+    let resp = payments
+        .executor
+        .set_denom_metadata(ntrn.clone())
+        .await
+        .unwrap();
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let updated = resp
+        .events
+        .iter()
+        .find_map(|event| DenomMetadataUpdatedEvent::try_from(event).ok())
+        .unwrap();
+    assert_eq!(updated.metadata, Some(ntrn.clone()));
+    assert_eq!(
+        payments.querier.denom_metadata().await.unwrap(),
+        vec![ntrn.clone()]
+    );
+
+    // Names are typed case-insensitively, so another denom can't take them
+    let err = payments
+        .executor
+        .set_denom_metadata(DenomMetadata {
+            denom: "uatom".to_string(),
+            symbol: "ATOM".to_string(),
+            exponent: 6,
+            aliases: vec!["Neutron".to_string()],
+        })
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Neutron already names"),
+        "{err:?}"
+    );
+
+    // Setting it again replaces it
+    let ntrn = DenomMetadata {
+        aliases: vec![],
+        ..ntrn
+    };
+    payments
+        .executor
+        .set_denom_metadata(ntrn.clone())
+        .await
+        .unwrap();
+    assert_eq!(payments.querier.denom_metadata().await.unwrap(), vec![ntrn]);
+
+    payments
+        .executor
+        .remove_denom_metadata("untrn".to_string())
+        .await
+        .unwrap();
+    assert!(payments.querier.denom_metadata().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn pull_claims_hold_funds_until_claimed() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+
+    let alice_payments = PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    );
+    let err = alice_payments.set_pull_claims(true).await.unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    assert!(!payments.querier.pull_claims().await.unwrap());
+    payments.executor.set_pull_claims(true).await.unwrap();
+    assert!(payments.querier.pull_claims().await.unwrap());
+
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@carol"), 50u128, "untrn")
+        .await
+        .unwrap();
+
+    let claim = payments.querier.pending_claim(tg("@carol")).await.unwrap();
+    assert_eq!(claim.address, None);
+    assert!(claim.claimable.is_empty());
+    assert_eq!(claim.unregistered, vec![coin(50, "untrn")]);
+
+    // Registering no longer pays out
+    let carol = app_client.with_app(|app| app.api().addr_make("carol"));
+    payments
+        .executor
+        .register_receive(3, Some(&tg("@carol")), &carol.clone().into())
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&carol, "untrn"), 0);
+
+    let claim = payments.querier.pending_claim(tg("@carol")).await.unwrap();
+    assert_eq!(claim.address, Some(carol.to_string()));
+    assert_eq!(claim.claimable, vec![coin(50, "untrn")]);
+    assert!(claim.unregistered.is_empty());
+
+    // Only the registered address can claim
+    let err = alice_payments
+        .claim_pending(&tg("@carol"), None)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    // Carol registered the wrong address, so the claim goes somewhere else
+    let carol_payments = PaymentsExecutor::new(
+        app_client.executor_for(&carol),
+        payments.executor.addr.clone(),
+    );
+    let wallet = app_client.with_app(|app| app.api().addr_make("carol-wallet"));
+    let resp = carol_payments
+        .claim_pending(&tg("@carol"), Some(wallet.to_string()))
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&wallet, "untrn"), 50);
+    assert_eq!(app_client.balance(&carol, "untrn"), 0);
+
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    let claimed = resp
+        .events
+        .iter()
+        .find_map(|event| PendingClaimedEvent::try_from(event).ok())
+        .unwrap();
+    assert_eq!(claimed.tg_user_id, 3);
+    assert_eq!(claimed.tg_handle, tg("@carol"));
+    assert_eq!(claimed.address, wallet);
+    assert_eq!(claimed.amount, vec![coin(50, "untrn")]);
+
+    let history = payments
+        .querier
+        .payments_by_tg_handle(tg("@carol"), None, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(history[0].kind, PaymentKind::Released);
+    assert_eq!(history[0].to_address, wallet);
+
+    let err = carol_payments
+        .claim_pending(&tg("@carol"), None)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Nothing is held"), "{err:?}");
+
+    // Turned back off, registering pays out right away again
+    payments.executor.set_pull_claims(false).await.unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@dave"), 20u128, "untrn")
+        .await
+        .unwrap();
+    let dave = app_client.with_app(|app| app.api().addr_make("dave"));
+    payments
+        .executor
+        .register_receive(4, Some(&tg("@dave")), &dave.clone().into())
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&dave, "untrn"), 20);
+}
+
+#[tokio::test]
+async fn escrowed_sends_can_be_undone_until_released() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let contract: Addr = payments.executor.addr.clone().into();
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+    let treasury = app_client.with_app(|app| app.api().addr_make("treasury"));
+    payments
+        .executor
+        .set_fee(Some(FeeConfig {
+            basis_points: 0,
+            flat: vec![coin(5, "untrn")],
+            treasury: treasury.to_string(),
+        }))
+        .await
+        .unwrap();
+
+    let alice_payments = PaymentsExecutor::new(
+        app_client.executor_for(&alice),
+        payments.executor.addr.clone(),
+    );
+    let err = alice_payments
+        .set_escrow_window(Some(Duration::Height(10)))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+    let err = payments
+        .executor
+        .set_escrow_window(Some(Duration::Time(0)))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("more than zero"), "{err:?}");
+    payments
+        .executor
+        .set_escrow_window(Some(Duration::Height(10)))
+        .await
+        .unwrap();
+    assert_eq!(
+        payments.querier.escrow_window().await.unwrap(),
+        Some(Duration::Height(10))
+    );
+
+    // The send and its fee are pulled into the contract, nothing reaches Bob or the treasury yet
+    let resp = payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 100u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 105);
+    assert_eq!(app_client.balance(&contract, "untrn"), 105);
+    assert_eq!(app_client.balance(&bob, "untrn"), 0);
+    assert_eq!(app_client.balance(&treasury, "untrn"), 0);
+
+    let held = event::<PaymentHeldEvent>(&resp);
+    let height = app_client.with_app(|app| app.block_info().height);
+    assert_eq!(held.to_tg_handle, tg("@bobby"));
+    assert_eq!(held.fee, Some(Uint256::from(5u128)));
+    assert_eq!(held.releases, Expiration::AtHeight(height + 10));
+    assert!(events::<SendPaymentEvent>(&resp).is_empty());
+
+    // The latest one is undone when no id is given, and only by its sender
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 200u128, "untrn")
+        .await
+        .unwrap();
+    let escrows = payments.querier.escrows(Some(1), None, None).await.unwrap();
+    assert_eq!(escrows.len(), 2);
+    assert_eq!(escrows[0].id, held.id);
+
+    let err = payments
+        .executor
+        .cancel_payment(2, Some(escrows[1].id))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+
+    // Undoing hands the funds back, so it keeps working while paused
+    payments.executor.set_paused(true).await.unwrap();
+    let resp = payments.executor.cancel_payment(1, None).await.unwrap();
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 105);
+    assert_eq!(app_client.balance(&contract, "untrn"), 105);
+    let cancelled = event::<PaymentCancelledEvent>(&resp);
+    assert_eq!(cancelled.id, escrows[1].id);
+    assert_eq!(cancelled.amount, Uint256::from(200u128));
+    assert_eq!(cancelled.reason, None);
+
+    let err = payments
+        .executor
+        .cancel_payment(1, Some(escrows[1].id))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("No held payment"), "{err:?}");
+    payments.executor.set_paused(false).await.unwrap();
+
+    // Nothing is due before the window passes
+    assert_eq!(payments.querier.matured_escrows().await.unwrap(), 0);
+    payments.executor.release_matured(None).await.unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 0);
+
+    app_client.with_app_mut(|app| app.update_block(|block| block.height += 10));
+    assert_eq!(payments.querier.matured_escrows().await.unwrap(), 1);
+    let err = payments
+        .executor
+        .cancel_payment(1, Some(held.id))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("can't be cancelled"), "{err:?}");
+
+    // Anyone can release it, Bob gets the amount and the treasury the fee
+    let stranger = app_client.with_app(|app| app.api().addr_make("stranger"));
+    let resp = PaymentsExecutor::new(
+        app_client.executor_for(&stranger),
+        payments.executor.addr.clone(),
+    )
+    .release_matured(None)
+    .await
+    .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 100);
+    assert_eq!(app_client.balance(&treasury, "untrn"), 5);
+    assert_eq!(app_client.balance(&contract, "untrn"), 0);
+    assert!(payments
+        .querier
+        .escrows(None, None, None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    let released = event::<PaymentReleasedEvent>(&resp);
+    assert_eq!(released.id, held.id);
+    assert_eq!(released.to_tg_id, Some(2));
+    assert_eq!(released.to_address, bob);
+
+    let history = payments
+        .querier
+        .payments_by_tg_handle(tg("@bobby"), None, Some(1))
+        .await
+        .unwrap();
+    assert_eq!(history[0].kind, PaymentKind::Direct);
+    assert_eq!(history[0].from_address, alice);
+
+    // A handle that hasn't registered gets a pending payment on release
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@carol"), 30u128, "untrn")
+        .await
+        .unwrap();
+    app_client.with_app_mut(|app| app.update_block(|block| block.height += 10));
+    payments.executor.release_matured(None).await.unwrap();
+    assert_eq!(
+        payments
+            .querier
+            .pending_payments(tg("@carol"))
+            .await
+            .unwrap(),
+        vec![coin(30, "untrn")]
+    );
+    // Running it again does nothing
+    payments.executor.release_matured(None).await.unwrap();
+    assert_eq!(app_client.balance(&contract, "untrn"), 30);
+
+    // A send made after the window is shortened isn't held up behind an older one
+    payments
+        .executor
+        .set_escrow_window(Some(Duration::Time(30 * 86_400)))
+        .await
+        .unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 50u128, "untrn")
+        .await
+        .unwrap();
+    payments
+        .executor
+        .set_escrow_window(Some(Duration::Height(2)))
+        .await
+        .unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 60u128, "untrn")
+        .await
+        .unwrap();
+    app_client.with_app_mut(|app| app.update_block(|block| block.height += 2));
+    assert_eq!(payments.querier.matured_escrows().await.unwrap(), 1);
+    payments.executor.release_matured(None).await.unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 160);
+    assert_eq!(
+        payments.querier.escrows(Some(1), None, None).await.unwrap()[0].amount,
+        Uint256::from(50u128)
+    );
+
+    // Without a window sends go out right away again
+    payments.executor.set_escrow_window(None).await.unwrap();
+    payments
+        .executor
+        .send_payment(1, Some(&tg("@alice")), &tg("@bobby"), 40u128, "untrn")
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 200);
+}
+
+fn tg(handle: &str) -> TgHandle {
+    TgHandle::new(handle).unwrap()
+}
+
+/// Wraps the payload the way the aggregator would and submits it, signatures are not checked
+async fn submit(
+    payments: &PaymentsClient,
+    payload: WavsPayload,
+) -> anyhow::Result<tg_utils::client::AnyTxResponse> {
+    submit_bytes(payments, payload.encode().unwrap()).await
+}
+
+async fn submit_bytes(
+    payments: &PaymentsClient,
+    payload: Vec<u8>,
+) -> anyhow::Result<tg_utils::client::AnyTxResponse> {
+    let envelope = WavsEnvelope::new(wavs_types::Envelope {
+        eventId: Default::default(),
+        ordering: Default::default(),
+        payload: payload.into(),
+    });
+    let signature_data = WavsSignatureData {
+        signers: vec![],
+        signatures: vec![],
+        reference_block: 0,
+    };
+
+    payments
+        .executor
+        .handle_signed_envelope(envelope, signature_data)
+        .await
+}
+
+/// Every event of type `E` in a multitest response
+fn events<E>(resp: &AnyTxResponse) -> Vec<E>
+where
+    E: for<'a> TryFrom<&'a Event>,
+{
+    let AnyTxResponse::MultiTest(resp) = resp else {
+        panic!("expected a multitest response");
+    };
+    resp.events
+        .iter()
+        .filter_map(|event| E::try_from(event).ok())
+        .collect()
+}
+
+/// The first event of type `E` in a multitest response
+fn event<E>(resp: &AnyTxResponse) -> E
+where
+    E: for<'a> TryFrom<&'a Event>,
+{
+    events(resp).into_iter().next().expect("event not found")
+}
+
+/// Registers the Telegram user to receive and send, and mints it some untrn
+async fn fund_sender(
+    app_client: &AppClient,
+    payments: &PaymentsClient,
+    tg_user_id: i64,
+    tg_handle: &str,
+    name: &str,
+) -> Addr {
+    let addr = app_client.with_app(|app| app.api().addr_make(name));
+    app_client.mint(&addr, coin(1_000_000, "untrn"));
+
+    payments
+        .executor
+        .register_receive(tg_user_id, Some(&tg(tg_handle)), &addr.clone().into())
+        .await
+        .unwrap();
+
+    PaymentsExecutor::new(
+        app_client.executor_for(&addr),
+        payments.executor.addr.clone(),
+    )
+    .register_send(&tg(tg_handle))
+    .await
+    .unwrap();
+
+    addr
+}
+
+#[tokio::test]
+async fn recurring_payments_run_once_per_period() {
+    tracing_init();
+
+    const WEEK: u64 = 7 * 24 * 60 * 60;
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let bob = app_client.with_app(|app| app.api().addr_make("bob"));
+    payments
+        .executor
+        .register_receive(2, Some(&tg("@bobby")), &bob.clone().into())
+        .await
+        .unwrap();
+
+    let start = app_client.with_app(|app| Timestamp::from_seconds(app.block_info().time.seconds()));
+    let schedule = |interval_seconds, until| SchedulePaymentMsg {
+        chat_id: 0,
+        message_id: 0,
+        from_tg_id: 1,
+        from_tg: Some(tg("@alice")),
+        to_tg: tg("@bobby"),
+        amount: Uint256::from(100u128),
+        denom: "untrn".to_string(),
+        interval_seconds,
+        until,
+    };
+
+    let err = payments
+        .executor
+        .schedule_payment(schedule(60, None))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("at most every"), "{err:?}");
+
+    let resp = payments
+        .executor
+        .schedule_payment(schedule(WEEK, Some(start.plus_seconds(3 * WEEK))))
+        .await
+        .unwrap();
+    let created = event::<ScheduleCreatedEvent>(&resp);
+    assert_eq!(created.next_due, start);
+
+    // The first run is due right away
+    let due = payments.querier.due_schedules(None).await.unwrap();
+    assert_eq!(due.len(), 1);
+    let run = ScheduleRun {
+        id: created.id,
+        due: start,
+    };
+
+    // Several operators submitting the same run only pay it once
+    payments
+        .executor
+        .run_schedules(vec![run.clone()])
+        .await
+        .unwrap();
+    payments
+        .executor
+        .run_schedules(vec![run.clone()])
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 100);
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 100);
+    assert!(payments
+        .querier
+        .due_schedules(None)
+        .await
+        .unwrap()
+        .is_empty());
+
+    // A period missed while nothing ran is skipped rather than paid late
+    app_client.with_app_mut(|app| {
+        app.update_block(|block| block.time = block.time.plus_seconds(2 * WEEK + 60))
+    });
+    let due = payments.querier.due_schedules(None).await.unwrap();
+    assert_eq!(due[0].next_due, start.plus_seconds(WEEK));
+    payments
+        .executor
+        .run_schedules(vec![
+            run.clone(),
+            ScheduleRun {
+                id: created.id,
+                due: start.plus_seconds(WEEK),
+            },
+        ])
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 200);
+    let stored = payments
+        .querier
+        .schedule(created.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.next_due, start.plus_seconds(3 * WEEK));
+
+    // A run that fails skips its period, and the last one ends the schedule
+    payments
+        .executor
+        .block(vec![tg("@bobby")], vec![])
+        .await
+        .unwrap();
+    app_client
+        .with_app_mut(|app| app.update_block(|block| block.time = block.time.plus_seconds(WEEK)));
+    let resp = payments
+        .executor
+        .run_schedules(vec![ScheduleRun {
+            id: created.id,
+            due: start.plus_seconds(3 * WEEK),
+        }])
+        .await
+        .unwrap();
+    assert_eq!(app_client.balance(&bob, "untrn"), 200);
+    let failed = event::<ScheduleRunFailedEvent>(&resp);
+    assert_eq!(failed.due, start.plus_seconds(3 * WEEK));
+    assert!(failed.reason.contains("blocked"), "{}", failed.reason);
+    let ended = event::<ScheduleEndedEvent>(&resp);
+    assert!(!ended.cancelled);
+    assert_eq!(payments.querier.schedule(created.id).await.unwrap(), None);
+
+    // Only the sender can stop one early
+    payments
+        .executor
+        .unblock(vec![tg("@bobby")], vec![])
+        .await
+        .unwrap();
+    let resp = payments
+        .executor
+        .schedule_payment(schedule(WEEK, None))
+        .await
+        .unwrap();
+    let created = event::<ScheduleCreatedEvent>(&resp);
+    let err = payments
+        .executor
+        .unschedule(2, created.id)
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Unauthorized"), "{err:?}");
+    let resp = payments.executor.unschedule(1, created.id).await.unwrap();
+    let ended = event::<ScheduleEndedEvent>(&resp);
+    assert!(ended.cancelled);
+    assert!(payments
+        .querier
+        .schedules(Some(1), None, None)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn treasuries_pay_out_once_members_approve() {
+    tracing_init();
+
+    const CHAT: i64 = -100;
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    fund_sender(&app_client, &payments, 2, "@bobby", "bob").await;
+    fund_sender(&app_client, &payments, 3, "@carol", "carol").await;
+    fund_sender(&app_client, &payments, 5, "@erin", "erin").await;
+    let dave = app_client.with_app(|app| app.api().addr_make("dave"));
+    payments
+        .executor
+        .register_receive(4, Some(&tg("@dave")), &dave.clone().into())
+        .await
+        .unwrap();
+
+    let create = |chat_id, members: Vec<&str>, threshold| CreateTreasuryMsg {
+        chat_id,
+        message_id: 1,
+        from_tg_id: 1,
+        from_tg: Some(tg("@alice")),
+        members: members.into_iter().map(tg).collect(),
+        threshold,
+    };
+    let propose = |from_tg_id, from_tg: &str, amount: u128| ProposePayoutMsg {
+        chat_id: CHAT,
+        message_id: 3,
+        from_tg_id,
+        from_tg: Some(tg(from_tg)),
+        to_tg: tg("@dave"),
+        amount: Uint256::from(amount),
+        denom: "untrn".to_string(),
+        memo: Some("design work".to_string()),
+    };
+    let vote = |from_tg_id, from_tg: &str, proposal_id, approve| VoteMsg {
+        chat_id: CHAT,
+        message_id: 4,
+        from_tg_id,
+        from_tg: Some(tg(from_tg)),
+        proposal_id,
+        approve,
+    };
+
+    let err = payments
+        .executor
+        .create_treasury(create(1, vec!["@bobby"], 1))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("group chats"), "{err:?}");
+    let err = payments
+        .executor
+        .create_treasury(create(CHAT, vec!["@bobby", "@nobody"], 1))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("has to register"), "{err:?}");
+    let err = payments
+        .executor
+        .create_treasury(create(CHAT, vec!["@bobby"], 3))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("threshold"), "{err:?}");
+
+    // The creator is a member too
+    let resp = payments
+        .executor
+        .create_treasury(create(CHAT, vec!["@bobby", "@carol"], 2))
+        .await
+        .unwrap();
+    let created = event::<TreasuryCreatedEvent>(&resp);
+    assert_eq!(created.member_count, 3);
+    assert_eq!(created.threshold, 2);
+
+    let resp = payments
+        .executor
+        .fund_treasury(FundTreasuryMsg {
+            chat_id: CHAT,
+            message_id: 2,
+            from_tg_id: 1,
+            from_tg: Some(tg("@alice")),
+            amount: Uint256::from(500u128),
+            denom: "untrn".to_string(),
+        })
+        .await
+        .unwrap();
+    let funded = event::<TreasuryFundedEvent>(&resp);
+    assert_eq!(funded.balance, Uint256::from(500u128));
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 500);
+
+    let err = payments
+        .executor
+        .propose_payout(propose(5, "@erin", 300))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Only members"), "{err:?}");
+
+    // The proposer's own approval isn't enough
+    let resp = payments
+        .executor
+        .propose_payout(propose(1, "@alice", 300))
+        .await
+        .unwrap();
+    let proposed = event::<ProposalCreatedEvent>(&resp);
+    let proposal = payments
+        .querier
+        .proposal(proposed.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(proposal.approvals, vec![1]);
+    assert_eq!(app_client.balance(&dave, "untrn"), 0);
+
+    let err = payments
+        .executor
+        .vote(vote(1, "@alice", proposed.id, true))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Already voted"), "{err:?}");
+    let err = payments
+        .executor
+        .vote(vote(5, "@erin", proposed.id, true))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Only members"), "{err:?}");
+
+    let resp = payments
+        .executor
+        .vote(vote(2, "@bobby", proposed.id, true))
+        .await
+        .unwrap();
+    let executed = event::<ProposalExecutedEvent>(&resp);
+    assert_eq!(executed.to_address, dave);
+    assert_eq!(app_client.balance(&dave, "untrn"), 300);
+    assert_eq!(payments.querier.proposal(proposed.id).await.unwrap(), None);
+    let treasury = payments.querier.treasury(CHAT).await.unwrap();
+    assert_eq!(treasury.balances, vec![coin(200, "untrn")]);
+
+    // A payout the treasury can't cover fails until it is funded
+    let resp = payments
+        .executor
+        .propose_payout(propose(2, "@bobby", 300))
+        .await
+        .unwrap();
+    let proposed = event::<ProposalCreatedEvent>(&resp);
+    let err = payments
+        .executor
+        .vote(vote(3, "@carol", proposed.id, true))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("only holds"), "{err:?}");
+
+    // Once it can't reach the threshold anymore it is dropped
+    let resp = payments
+        .executor
+        .vote(vote(3, "@carol", proposed.id, false))
+        .await
+        .unwrap();
+    let voted = event::<ProposalVotedEvent>(&resp);
+    assert_eq!((voted.approvals, voted.rejections), (1, 1));
+    assert_eq!(
+        payments.querier.proposals(CHAT, None, None).await.unwrap(),
+        vec![payments
+            .querier
+            .proposal(proposed.id)
+            .await
+            .unwrap()
+            .unwrap()]
+    );
+    let resp = payments
+        .executor
+        .vote(vote(1, "@alice", proposed.id, false))
+        .await
+        .unwrap();
+    let rejected = event::<ProposalRejectedEvent>(&resp);
+    assert_eq!(rejected.id, proposed.id);
+    assert!(payments
+        .querier
+        .proposals(CHAT, None, None)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(app_client.balance(&dave, "untrn"), 300);
+}
+
+#[tokio::test]
+async fn gifts_are_claimed_from_the_link() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    fund_sender(&app_client, &payments, 2, "@bobby", "bob").await;
+
+    let create = |secret: &str, expires_in_seconds| CreateGiftMsg {
+        chat_id: 1,
+        message_id: 1,
+        from_tg_id: 1,
+        from_tg: Some(tg("@alice")),
+        amount: Uint256::from(300u128),
+        denom: "untrn".to_string(),
+        hash: Gift::hash_secret(secret),
+        expires_in_seconds,
+    };
+    let claim = |to_tg_id, secret: &str| ClaimGiftMsg {
+        chat_id: to_tg_id,
+        message_id: 2,
+        to_tg_id,
+        to_tg: None,
+        secret: secret.to_string(),
+    };
+
+    let resp = payments
+        .executor
+        .create_gift(create("s3cret", None))
+        .await
+        .unwrap();
+    let created = event::<GiftCreatedEvent>(&resp);
+    assert_eq!((created.chat_id, created.message_id), (1, 1));
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 300);
+
+    let err = payments
+        .executor
+        .create_gift(create("s3cret", None))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("already a gift"), "{err:?}");
+    let err = payments
+        .executor
+        .create_gift(create("other", Some(100 * 86_400)))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("32 byte hash"), "{err:?}");
+    let err = payments
+        .executor
+        .claim_gift(claim(7, "guess"))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("No gift"), "{err:?}");
+
+    // Opening the link before registering keeps the gift for whoever opened it, and tells them
+    let resp = payments
+        .executor
+        .claim_gift(claim(7, "s3cret"))
+        .await
+        .unwrap();
+    let reserved = event::<GiftReservedEvent>(&resp);
+    assert_eq!((reserved.id, reserved.to_tg_id), (created.id, 7));
+    assert_eq!(reserved.expires, created.expires);
+    let gift = payments
+        .querier
+        .gift_by_hash(Gift::hash_secret("s3cret"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(gift.claimer, Some(7));
+    let err = payments
+        .executor
+        .claim_gift(claim(2, "s3cret"))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("No gift"), "{err:?}");
+
+    let grace = app_client.with_app(|app| app.api().addr_make("grace"));
+    payments
+        .executor
+        .register_receive(7, None, &grace.clone().into())
+        .await
+        .unwrap();
+    let resp = payments
+        .executor
+        .claim_gift(claim(7, "s3cret"))
+        .await
+        .unwrap();
+    let claimed = event::<GiftClaimedEvent>(&resp);
+    assert_eq!(
+        (claimed.id, claimed.to_address.clone()),
+        (created.id, grace.clone())
+    );
+    assert_eq!(app_client.balance(&grace, "untrn"), 300);
+    assert_eq!(payments.querier.gift(created.id).await.unwrap(), None);
+    let history = payments
+        .querier
+        .payments_by_tg_id(7, None, None)
+        .await
+        .unwrap();
+    assert_eq!(history[0].from_address, alice);
+
+    // An unclaimed gift goes back to the sender once it expires
+    payments
+        .executor
+        .create_gift(create("later", Some(60)))
+        .await
+        .unwrap();
+    app_client
+        .with_app_mut(|app| app.update_block(|block| block.time = block.time.plus_seconds(61)));
+    let err = payments
+        .executor
+        .claim_gift(claim(2, "later"))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("expired"), "{err:?}");
+    let resp = payments.executor.refund_expired_gifts(None).await.unwrap();
+    let refunded = event::<GiftRefundedEvent>(&resp);
+    assert_eq!(refunded.from_address, alice);
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 300);
+    assert_eq!(
+        payments
+            .querier
+            .gift_by_hash(Gift::hash_secret("later"))
+            .await
+            .unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn giveaways_are_shared_between_the_first_claimers() {
+    tracing_init();
+
+    let app_client = AppClient::new("admin");
+    let payments = PaymentsClient::new(app_client.clone());
+    let alice = fund_sender(&app_client, &payments, 1, "@alice", "alice").await;
+    let mut claimers = vec![];
+    for tg_user_id in 2..=5 {
+        let addr = app_client.with_app(|app| app.api().addr_make(&format!("claimer{tg_user_id}")));
+        payments
+            .executor
+            .register_receive(tg_user_id, None, &addr.clone().into())
+            .await
+            .unwrap();
+        claimers.push(addr);
+    }
+
+    let create = |chat_id, amount: u128, count, split| CreateGiveawayMsg {
+        chat_id,
+        message_id: 1,
+        from_tg_id: 1,
+        from_tg: Some(tg("@alice")),
+        amount: Uint256::from(amount),
+        denom: "untrn".to_string(),
+        count,
+        split,
+    };
+    let claim = |to_tg_id, giveaway_id| ClaimGiveawayMsg {
+        chat_id: -100,
+        message_id: 2,
+        to_tg_id,
+        to_tg: None,
+        giveaway_id,
+    };
+
+    let err = payments
+        .executor
+        .create_giveaway(create(5, 300, 3, GiveawaySplit::Equal))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("group chats"), "{err:?}");
+    let err = payments
+        .executor
+        .create_giveaway(create(-100, 2, 3, GiveawaySplit::Equal))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("1 to 100 members"), "{err:?}");
+
+    // An equal split pays everyone the same, once each, and only to registered members
+    let resp = payments
+        .executor
+        .create_giveaway(create(-100, 300, 3, GiveawaySplit::Equal))
+        .await
+        .unwrap();
+    let equal = event::<GiveawayCreatedEvent>(&resp);
+    assert_eq!((equal.chat_id, equal.count), (-100, 3));
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 300);
+
+    let resp = payments
+        .executor
+        .claim_giveaway(claim(2, equal.id))
+        .await
+        .unwrap();
+    let claimed = event::<GiveawayClaimedEvent>(&resp);
+    assert_eq!(
+        (claimed.amount, claimed.claims, claimed.remaining),
+        (Uint256::from(100u128), 1, Uint256::from(200u128))
+    );
+    assert_eq!(app_client.balance(&claimers[0], "untrn"), 100);
+
+    let err = payments
+        .executor
+        .claim_giveaway(claim(2, equal.id))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("Already claimed"), "{err:?}");
+    let err = payments
+        .executor
+        .claim_giveaway(claim(9, equal.id))
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("Register to receive"),
+        "{err:?}"
+    );
+    let err = payments
+        .executor
+        .claim_giveaway(ClaimGiveawayMsg {
+            chat_id: -200,
+            ..claim(3, equal.id)
+        })
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("not found"), "{err:?}");
+
+    // A random split adds up to the total, and stops at the count
+    let before = app_client.balance(&claimers[0], "untrn");
+    let resp = payments
+        .executor
+        .create_giveaway(create(-100, 1_000, 3, GiveawaySplit::Random))
+        .await
+        .unwrap();
+    let random = event::<GiveawayCreatedEvent>(&resp);
+    for tg_user_id in 2..=4 {
+        payments
+            .executor
+            .claim_giveaway(claim(tg_user_id, random.id))
+            .await
+            .unwrap();
+    }
+    let shares = [
+        app_client.balance(&claimers[0], "untrn") - before,
+        app_client.balance(&claimers[1], "untrn"),
+        app_client.balance(&claimers[2], "untrn"),
+    ];
+    assert!(shares.iter().all(|share| *share > 0), "{shares:?}");
+    assert_eq!(shares.iter().sum::<u128>(), 1_000);
+    assert_eq!(payments.querier.giveaway(random.id).await.unwrap(), None);
+    let err = payments
+        .executor
+        .claim_giveaway(claim(5, random.id))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("not found"), "{err:?}");
+
+    // What is left goes back to the sender once it expires
+    app_client.with_app_mut(|app| {
+        app.update_block(|block| block.time = block.time.plus_seconds(24 * 60 * 60 + 1))
+    });
+    let err = payments
+        .executor
+        .claim_giveaway(claim(3, equal.id))
+        .await
+        .unwrap_err();
+    assert!(format!("{err:?}").contains("expired"), "{err:?}");
+    let resp = payments
+        .executor
+        .refund_expired_giveaways(None)
+        .await
+        .unwrap();
+    let refunded = event::<GiveawayRefundedEvent>(&resp);
+    assert_eq!(
+        (refunded.id, refunded.amount, refunded.claims),
+        (equal.id, Uint256::from(200u128), 1)
+    );
+    assert_eq!(app_client.balance(&alice, "untrn"), 1_000_000 - 100 - 1_000);
+    assert_eq!(payments.querier.giveaway(equal.id).await.unwrap(), None);
+}
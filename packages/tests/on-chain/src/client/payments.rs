@@ -19,6 +19,9 @@ impl PaymentsClient {
         let msg = tg_contract_api::payments::msg::InstantiateMsg {
             allowed_denoms: vec!["untrn".to_string(), "uatom".to_string()],
             auth: tg_contract_api::payments::msg::Auth::Admin(admin.to_string()),
+            pending_expiry: None,
+            owner: None,
+            fee: None,
         };
 
         let (address, _) = client
@@ -2,7 +2,10 @@ use cosmwasm_std::Addr;
 use layer_climb::prelude::*;
 use layer_climb_proto::Any;
 use on_chain_tests::client::{payments::PaymentsClient, AppClient};
-use tg_contract_api::payments::msg::{CustomExecuteMsg, ExecuteMsg};
+use tg_contract_api::payments::{
+    handle::TgHandle,
+    msg::{CustomExecuteMsg, ExecuteMsg},
+};
 use tg_test_common::shared_tests::{self, payments::RegisterReceivesOpenAccountProps};
 use tg_utils::tracing::tracing_init;
 
@@ -29,7 +32,8 @@ async fn register_receives_open_account() {
         &payments.querier,
         &payments.executor,
         RegisterReceivesOpenAccountProps {
-            tg_handle: "@alice".to_string(),
+            tg_user_id: 1,
+            tg_handle: TgHandle::new("@alice").unwrap(),
             user_addr: app_client.rand_address().await.into(),
         },
     )
@@ -46,10 +50,12 @@ async fn fund_account_and_send_workflow() {
     let payments = PaymentsClient::new(app_client.clone(), None).await;
 
     // Alice will send
-    let tg_alice = "@alice";
+    let tg_alice = TgHandle::new("@alice").unwrap();
+    let alice_id = 1;
     // Note: this also taps the facuet for 1_000_000_000 initial tokens
     let alice = app_client.rand_signing_client().await;
-    let tg_bob = "@bob";
+    let tg_bob = TgHandle::new("@bobby").unwrap();
+    let bob_id = 2;
     let bob_addr = app_client.rand_address().await; // Bob just needs to watch
 
     // Query balances and assert alice (non-zero), bob (zero)
@@ -65,14 +71,14 @@ async fn fund_account_and_send_workflow() {
     // WAVS Admin registers Alice to receive payments
     payments
         .executor
-        .register_receive(tg_alice.to_string(), &alice.addr.clone().into())
+        .register_receive(alice_id, Some(&tg_alice), &alice.addr.clone().into())
         .await
         .unwrap();
 
     // WAVS Admin registers Bob to receive payments
     payments
         .executor
-        .register_receive(tg_bob.to_string(), &bob_addr)
+        .register_receive(bob_id, Some(&tg_bob), &bob_addr)
         .await
         .unwrap();
 
@@ -81,7 +87,7 @@ async fn fund_account_and_send_workflow() {
     let grant = cosmwasm_std::coin(500_000u128, gas_denom);
     let msgs = build_registration_messages(
         &alice,
-        tg_alice,
+        &tg_alice,
         &payments.querier.addr.clone().into(),
         grant,
     )
@@ -92,7 +98,7 @@ async fn fund_account_and_send_workflow() {
     assert_eq!(
         payments
             .querier
-            .addr_by_tg_handle(tg_alice.to_string())
+            .addr_by_tg_handle(tg_alice.clone())
             .await
             .unwrap(),
         Some(alice.addr.to_string())
@@ -103,14 +109,14 @@ async fn fund_account_and_send_workflow() {
             .tg_handle_by_addr(alice.addr.to_string())
             .await
             .unwrap(),
-        Some(tg_alice.to_string())
+        Some(tg_alice.clone())
     );
 
     // WAVS Admin triggers send from alice to bob
     let send_amount = 200_000u128;
     payments
         .executor
-        .send_payment(tg_alice, tg_bob, send_amount, gas_denom)
+        .send_payment(alice_id, Some(&tg_alice), &tg_bob, send_amount, gas_denom)
         .await
         .unwrap();
 
@@ -140,16 +146,18 @@ async fn send_payment_then_register_receiver() {
     let payments = PaymentsClient::new(app_client.clone(), None).await;
 
     // Alice will send
-    let tg_alice = "@alice";
+    let tg_alice = TgHandle::new("@alice").unwrap();
+    let alice_id = 1;
     let alice = app_client.rand_signing_client().await;
     // Bob will receive
-    let tg_bob = "@bob";
+    let tg_bob = TgHandle::new("@bobby").unwrap();
+    let bob_id = 2;
     let bob_addr = app_client.rand_address().await; // Bob just needs to watch
 
     // WAVS Admin registers Alice to receive payments
     payments
         .executor
-        .register_receive(tg_alice.to_string(), &alice.addr.clone().into())
+        .register_receive(alice_id, Some(&tg_alice), &alice.addr.clone().into())
         .await
         .unwrap();
 
@@ -158,7 +166,7 @@ async fn send_payment_then_register_receiver() {
     let grant = cosmwasm_std::coin(500_000u128, gas_denom);
     let msgs = build_registration_messages(
         &alice,
-        tg_alice,
+        &tg_alice,
         &payments.querier.addr.clone().into(),
         grant,
     )
@@ -170,7 +178,7 @@ async fn send_payment_then_register_receiver() {
     let send_amount = 200_000u128;
     payments
         .executor
-        .send_payment(tg_alice, tg_bob, send_amount, gas_denom)
+        .send_payment(alice_id, Some(&tg_alice), &tg_bob, send_amount, gas_denom)
         .await
         .unwrap();
 
@@ -186,7 +194,7 @@ async fn send_payment_then_register_receiver() {
     // WAVS Admin registers Bob to receive payments
     payments
         .executor
-        .register_receive(tg_bob.to_string(), &bob_addr)
+        .register_receive(bob_id, Some(&tg_bob), &bob_addr)
         .await
         .unwrap();
 
@@ -212,23 +220,25 @@ async fn send_multiple_payments() {
     let payments = PaymentsClient::new(app_client.clone(), None).await;
 
     // Alice will send
-    let tg_alice = "@alice";
+    let tg_alice = TgHandle::new("@alice").unwrap();
+    let alice_id = 1;
     let alice = app_client.rand_signing_client().await;
     // Bob will receive
-    let tg_bob = "@bob";
+    let tg_bob = TgHandle::new("@bobby").unwrap();
+    let bob_id = 2;
     let bob_addr = app_client.rand_address().await;
 
     // WAVS Admin registers Alice to receive payments
     payments
         .executor
-        .register_receive(tg_alice.to_string(), &alice.addr.clone().into())
+        .register_receive(alice_id, Some(&tg_alice), &alice.addr.clone().into())
         .await
         .unwrap();
 
     // WAVS Admin registers Bob to receive payments
     payments
         .executor
-        .register_receive(tg_bob.to_string(), &bob_addr)
+        .register_receive(bob_id, Some(&tg_bob), &bob_addr)
         .await
         .unwrap();
 
@@ -237,7 +247,7 @@ async fn send_multiple_payments() {
     let grant = cosmwasm_std::coin(500_000u128, gas_denom);
     let msgs = build_registration_messages(
         &alice,
-        tg_alice,
+        &tg_alice,
         &payments.querier.addr.clone().into(),
         grant,
     )
@@ -251,7 +261,7 @@ async fn send_multiple_payments() {
     let send_amount_1 = 200_000u128;
     payments
         .executor
-        .send_payment(tg_alice, tg_bob, send_amount_1, gas_denom)
+        .send_payment(alice_id, Some(&tg_alice), &tg_bob, send_amount_1, gas_denom)
         .await
         .unwrap();
 
@@ -272,7 +282,7 @@ async fn send_multiple_payments() {
     let send_amount_2 = 250_000u128;
     payments
         .executor
-        .send_payment(tg_alice, tg_bob, send_amount_2, gas_denom)
+        .send_payment(alice_id, Some(&tg_alice), &tg_bob, send_amount_2, gas_denom)
         .await
         .unwrap();
 
@@ -293,7 +303,7 @@ async fn send_multiple_payments() {
     let send_amount_3 = 100_000u128;
     let result = payments
         .executor
-        .send_payment(tg_alice, tg_bob, send_amount_3, gas_denom)
+        .send_payment(alice_id, Some(&tg_alice), &tg_bob, send_amount_3, gas_denom)
         .await;
 
     // Assert that the third send failed
@@ -332,14 +342,14 @@ async fn get_balance(client: &SigningClient, addr: Option<Address>) -> u128 {
 /// It must be signed by the users private key and then submitted as a multi-msg tx
 async fn build_registration_messages(
     granter: &SigningClient,
-    tg_handle: &str,
+    tg_handle: &TgHandle,
     contract_addr: &Addr,
     grant_amount: cosmwasm_std::Coin,
 ) -> Vec<Any> {
     let contract_addr: Address = CosmosAddr::try_from(contract_addr).unwrap().into();
 
     let register_msg = ExecuteMsg::Custom(CustomExecuteMsg::RegisterSend {
-        tg_handle: tg_handle.to_string(),
+        tg_handle: tg_handle.clone(),
     });
 
     let exec_msg = granter
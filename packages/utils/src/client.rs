@@ -2,14 +2,18 @@
 //! Provides AnyQuerier and AnyExecutor to represent _any_ contract querier/executor
 //! The idea is that by moving the heavy-lifting here, we're free to write higher-level code
 //! that provides an idiomatic and clean API
+#[cfg(feature = "multitest")]
+pub mod multitest;
 pub mod payments;
 
 #[cfg(feature = "multitest")]
-use cw_multi_test::{App, Executor};
+use cw_multi_test::Executor;
+#[cfg(feature = "multitest")]
+use multitest::MultiTestApp;
 #[cfg(feature = "multitest")]
 use std::sync::Arc;
 #[cfg(feature = "multitest")]
-type AppWrapper = Arc<std::sync::Mutex<App>>;
+type AppWrapper = Arc<std::sync::Mutex<MultiTestApp>>;
 
 use anyhow::Result;
 use layer_climb::prelude::*;
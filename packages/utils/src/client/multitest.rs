@@ -0,0 +1,245 @@
+//! MultiTest app with a Stargate module that understands the authz messages the payments contract emits
+//! Grants for `MsgSend` can be given and queried, and their spend limits are drawn down. A `MsgExec`
+//! with no grant on record is treated as if the granter had authorized it.
+
+use cosmwasm_std::{
+    Addr, AnyMsg, Api, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, CustomMsg, CustomQuery,
+    GrpcQuery, Querier, StdError, StdResult, Storage, Timestamp, Uint256,
+};
+use cw_multi_test::{
+    App, AppResponse, BankKeeper, CosmosRouter, DistributionKeeper, FailingModule,
+    GovFailingModule, IbcFailingModule, StakeKeeper, Stargate, WasmKeeper,
+};
+use layer_climb::proto::{
+    authz::{Grant, MsgExec, MsgGrant, QueryGrantsRequest, QueryGrantsResponse},
+    bank::{MsgSend, SendAuthorization},
+    Any, Coin as ProtoCoin, Message, Name, Timestamp as ProtoTimestamp,
+};
+use serde::de::DeserializeOwned;
+
+pub type MultiTestApp = App<
+    BankKeeper,
+    cosmwasm_std::testing::MockApi,
+    cosmwasm_std::testing::MockStorage,
+    FailingModule<cosmwasm_std::Empty, cosmwasm_std::Empty, cosmwasm_std::Empty>,
+    WasmKeeper<cosmwasm_std::Empty, cosmwasm_std::Empty>,
+    StakeKeeper,
+    DistributionKeeper,
+    IbcFailingModule,
+    GovFailingModule,
+    AuthzStargate,
+>;
+
+#[derive(Default)]
+pub struct AuthzStargate;
+
+impl Stargate for AuthzStargate {
+    fn execute_any<ExecC, QueryC>(
+        &self,
+        api: &dyn Api,
+        storage: &mut dyn Storage,
+        router: &dyn CosmosRouter<ExecC = ExecC, QueryC = QueryC>,
+        block: &BlockInfo,
+        sender: Addr,
+        msg: AnyMsg,
+    ) -> StdResult<AppResponse>
+    where
+        ExecC: CustomMsg + DeserializeOwned + 'static,
+        QueryC: CustomQuery + DeserializeOwned + 'static,
+    {
+        if msg.type_url == MsgGrant::type_url() {
+            let grant = MsgGrant::decode(msg.value.as_slice()).map_err(StdError::msg)?;
+            if grant.granter != sender.as_str() {
+                return Err(StdError::msg(format!(
+                    "MsgGrant granter {} does not match sender {}",
+                    grant.granter, sender
+                )));
+            }
+            let grant_value = grant
+                .grant
+                .ok_or_else(|| StdError::msg("MsgGrant without a grant"))?;
+            storage.set(
+                &grant_key(&grant.granter, &grant.grantee),
+                &grant_value.encode_to_vec(),
+            );
+            return Ok(AppResponse::default());
+        }
+        if msg.type_url != MsgExec::type_url() {
+            return Err(StdError::msg(format!(
+                "Unexpected any execute: type_url={} from {}",
+                msg.type_url, sender
+            )));
+        }
+
+        let exec = MsgExec::decode(msg.value.as_slice()).map_err(StdError::msg)?;
+        if exec.grantee != sender.as_str() {
+            return Err(StdError::msg(format!(
+                "MsgExec grantee {} does not match sender {}",
+                exec.grantee, sender
+            )));
+        }
+
+        let mut resp = AppResponse::default();
+        for inner in exec.msgs {
+            if inner.type_url != MsgSend::type_url() {
+                return Err(StdError::msg(format!(
+                    "Unsupported authz message: {}",
+                    inner.type_url
+                )));
+            }
+            let send = MsgSend::decode(inner.value.as_slice()).map_err(StdError::msg)?;
+            let amount = send
+                .amount
+                .into_iter()
+                .map(|c| {
+                    Ok(Coin {
+                        denom: c.denom,
+                        amount: c.amount.parse::<Uint256>()?,
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            use_grant(storage, block, &send.from_address, &exec.grantee, &amount)?;
+
+            let sub = router.execute(
+                api,
+                storage,
+                block,
+                Addr::unchecked(send.from_address),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: send.to_address,
+                    amount,
+                }),
+            )?;
+            resp.events.extend(sub.events);
+        }
+
+        Ok(resp)
+    }
+
+    fn query_grpc(
+        &self,
+        _api: &dyn Api,
+        storage: &dyn Storage,
+        _querier: &dyn Querier,
+        _block: &BlockInfo,
+        request: GrpcQuery,
+    ) -> StdResult<Binary> {
+        if request.path != "/cosmos.authz.v1beta1.Query/Grants" {
+            return Err(StdError::msg(format!(
+                "Unexpected grpc query: path={}",
+                request.path
+            )));
+        }
+
+        let query = QueryGrantsRequest::decode(request.data.as_slice()).map_err(StdError::msg)?;
+        // Like the authz module, a missing grant is an error rather than an empty list
+        let grant = storage
+            .get(&grant_key(&query.granter, &query.grantee))
+            .ok_or_else(|| StdError::msg("authorization not found"))?;
+        let resp = QueryGrantsResponse {
+            grants: vec![Grant::decode(grant.as_slice()).map_err(StdError::msg)?],
+            pagination: None,
+        };
+
+        Ok(resp.encode_to_vec().into())
+    }
+}
+
+/// A `MsgGrant` of a `SendAuthorization` from `granter` to `grantee`, for `App::execute`
+pub fn send_grant_msg(
+    granter: &Addr,
+    grantee: &Addr,
+    spend_limit: Vec<Coin>,
+    expiration: Option<Timestamp>,
+) -> CosmosMsg {
+    let authorization = SendAuthorization {
+        spend_limit: spend_limit.into_iter().map(proto_coin).collect(),
+        allow_list: vec![],
+    };
+    let grant = MsgGrant {
+        granter: granter.to_string(),
+        grantee: grantee.to_string(),
+        grant: Some(Grant {
+            authorization: Some(Any {
+                type_url: SendAuthorization::type_url(),
+                value: authorization.encode_to_vec(),
+            }),
+            expiration: expiration.map(|time| ProtoTimestamp {
+                seconds: time.seconds() as i64,
+                nanos: time.subsec_nanos() as i32,
+            }),
+        }),
+    };
+
+    CosmosMsg::Any(AnyMsg {
+        type_url: MsgGrant::type_url(),
+        value: grant.encode_to_vec().into(),
+    })
+}
+
+/// Only `MsgSend` grants are tracked, so one grant per granter and grantee
+fn grant_key(granter: &str, grantee: &str) -> Vec<u8> {
+    format!("authz_grant/{granter}/{grantee}").into_bytes()
+}
+
+/// Draws the send down from the granter's spend limit, if they gave a grant
+fn use_grant(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    granter: &str,
+    grantee: &str,
+    amount: &[Coin],
+) -> StdResult<()> {
+    let key = grant_key(granter, grantee);
+    let Some(grant) = storage.get(&key) else {
+        return Ok(());
+    };
+    let mut grant = Grant::decode(grant.as_slice()).map_err(StdError::msg)?;
+
+    if let Some(expiration) = &grant.expiration {
+        if block.time.seconds() as i64 >= expiration.seconds {
+            return Err(StdError::msg("authorization expired"));
+        }
+    }
+
+    let Some(auth) = grant
+        .authorization
+        .as_mut()
+        .filter(|auth| auth.type_url == SendAuthorization::type_url())
+    else {
+        return Ok(());
+    };
+    let mut send_auth = SendAuthorization::decode(auth.value.as_slice()).map_err(StdError::msg)?;
+    for coin in amount {
+        let limit = send_auth
+            .spend_limit
+            .iter_mut()
+            .find(|limit| limit.denom == coin.denom)
+            .ok_or_else(|| StdError::msg("requested amount is more than spend limit"))?;
+        let remaining = limit
+            .amount
+            .parse::<Uint256>()?
+            .checked_sub(coin.amount)
+            .map_err(|_| StdError::msg("requested amount is more than spend limit"))?;
+        limit.amount = remaining.to_string();
+    }
+    send_auth
+        .spend_limit
+        .retain(|limit| limit.amount != Uint256::zero().to_string());
+
+    // Like the authz module, a grant that has been used up is deleted
+    if send_auth.spend_limit.is_empty() {
+        storage.remove(&key);
+    } else {
+        auth.value = send_auth.encode_to_vec();
+        storage.set(&key, &grant.encode_to_vec());
+    }
+    Ok(())
+}
+
+fn proto_coin(coin: Coin) -> ProtoCoin {
+    ProtoCoin {
+        denom: coin.denom,
+        amount: coin.amount.to_string(),
+    }
+}
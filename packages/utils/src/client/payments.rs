@@ -2,7 +2,8 @@
 //! Define helper methods here and they'll be available for all backends
 
 use anyhow::Result;
-use cosmwasm_std::Uint256;
+use cosmwasm_std::{HexBinary, Uint256};
+use cw_utils::Duration;
 use serde::de::DeserializeOwned;
 use std::fmt::Debug;
 
@@ -11,9 +12,25 @@ use crate::{
     client::{AnyExecutor, AnyQuerier, AnyTxResponse},
 };
 
+use tg_contract_api::payments::handle::TgHandle;
 use tg_contract_api::payments::msg::{
-    AdminResponse, ChainAddrResponse, CustomExecuteMsg, CustomQueryMsg, ExecuteMsg, QueryMsg,
-    RegisterReceiveMsg, SendPaymentMsg, TgHandleResponse,
+    Account, AccountsResponse, AdminResponse, Auth, BlockedResponse, CancelPaymentMsg,
+    ChainAddrResponse, ClaimGiftMsg, ClaimGiveawayMsg, CreateGiftMsg, CreateGiveawayMsg,
+    CreateTreasuryMsg, CustomExecuteMsg, CustomQueryMsg, DenomMetadata, DenomMetadataResponse,
+    Escrow, EscrowWindowResponse, EscrowsResponse, ExecuteMsg, FeeConfig, FeeQuoteResponse,
+    FeeResponse, FundTreasuryMsg, FundedAccount, FundedAccountsResponse, Gift, GiftResponse,
+    Giveaway, GiveawayResponse, IbcRoute, IbcRoutesResponse, Invoice, InvoiceActionMsg,
+    InvoiceResponse, InvoicesResponse, MaturedEscrowsResponse, OwnerResponse, PausedResponse,
+    PaymentHistoryResponse, PaymentRecord, PendingBalance, PendingBalancesResponse,
+    PendingClaimResponse, PendingExpiryResponse, PendingPayment, PendingPaymentsResponse, Proposal,
+    ProposalResponse, ProposalsResponse, ProposePayoutMsg, PullClaimsResponse, QueryMsg,
+    RegisterReceiveMsg, RequestPaymentMsg, Schedule, SchedulePaymentMsg, ScheduleResponse,
+    ScheduleRun, SchedulesResponse, SendGrantResponse, SendPaymentMsg, SpendLimit,
+    SpendLimitResponse, SplitPaymentMsg, SplitRecipient, StatsResponse, TgHandleResponse, TipMsg,
+    TreasuryResponse, UnscheduleMsg, VoteMsg,
+};
+use wavs_types::contracts::cosmwasm::service_handler::{
+    ServiceHandlerExecuteMessages, WavsEnvelope, WavsSignatureData,
 };
 
 #[derive(Clone)]
@@ -41,7 +58,16 @@ impl PaymentsQuerier {
         Ok(resp.admin)
     }
 
-    pub async fn addr_by_tg_handle(&self, tg_handle: String) -> Result<Option<String>> {
+    pub async fn owner(&self) -> Result<OwnerResponse> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::Owner {}))
+            .await
+    }
+
+    pub async fn auth(&self) -> Result<Auth> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::Auth {})).await
+    }
+
+    pub async fn addr_by_tg_handle(&self, tg_handle: TgHandle) -> Result<Option<String>> {
         let resp: ChainAddrResponse = self
             .query(&QueryMsg::Custom(CustomQueryMsg::AddrByTg {
                 handle: tg_handle,
@@ -51,7 +77,17 @@ impl PaymentsQuerier {
         Ok(resp.addr)
     }
 
-    pub async fn tg_handle_by_addr(&self, user_addr: String) -> Result<Option<String>> {
+    pub async fn addr_by_tg_id(&self, tg_user_id: i64) -> Result<Option<String>> {
+        let resp: ChainAddrResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::AddrByTgId {
+                user_id: tg_user_id,
+            }))
+            .await?;
+
+        Ok(resp.addr)
+    }
+
+    pub async fn tg_handle_by_addr(&self, user_addr: String) -> Result<Option<TgHandle>> {
         let resp: TgHandleResponse = self
             .query(&QueryMsg::Custom(CustomQueryMsg::TgByAddr {
                 account: user_addr,
@@ -65,6 +101,391 @@ impl PaymentsQuerier {
         self.query(&QueryMsg::Custom(CustomQueryMsg::AllowedDenoms {}))
             .await
     }
+
+    pub async fn pending_payments(&self, tg_handle: TgHandle) -> Result<Vec<cosmwasm_std::Coin>> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::PendingPayments {
+            handle: tg_handle,
+        }))
+        .await
+    }
+
+    pub async fn pending_payments_by_tg_handle(
+        &self,
+        tg_handle: TgHandle,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<PendingPayment>> {
+        let resp: PendingPaymentsResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::PendingPaymentsByTg {
+                handle: tg_handle,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.payments)
+    }
+
+    pub async fn invoice(&self, id: u64) -> Result<Option<Invoice>> {
+        let resp: InvoiceResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Invoice { id }))
+            .await?;
+
+        Ok(resp.invoice)
+    }
+
+    pub async fn invoices_by_payer(
+        &self,
+        tg_handle: TgHandle,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Invoice>> {
+        let resp: InvoicesResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::InvoicesByPayer {
+                handle: tg_handle,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.invoices)
+    }
+
+    pub async fn spend_limit(&self, user_addr: String, denom: &str) -> Result<SpendLimitResponse> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::SpendLimit {
+            account: user_addr,
+            denom: denom.to_string(),
+        }))
+        .await
+    }
+
+    /// Whether the user's account has authorized the contract to send on its behalf
+    pub async fn send_grant(&self, user_addr: String) -> Result<SendGrantResponse> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::SendGrant {
+            account: user_addr,
+        }))
+        .await
+    }
+
+    pub async fn fee(&self) -> Result<Option<FeeConfig>> {
+        let resp: FeeResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Fee {}))
+            .await?;
+
+        Ok(resp.fee)
+    }
+
+    pub async fn fee_quote(
+        &self,
+        amount: impl Into<Uint256>,
+        denom: &str,
+    ) -> Result<FeeQuoteResponse> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::FeeQuote {
+            amount: amount.into(),
+            denom: denom.to_string(),
+        }))
+        .await
+    }
+
+    pub async fn paused(&self) -> Result<bool> {
+        let resp: PausedResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Paused {}))
+            .await?;
+
+        Ok(resp.paused)
+    }
+
+    pub async fn blocked(
+        &self,
+        tg_handle: Option<TgHandle>,
+        user_addr: Option<String>,
+    ) -> Result<bool> {
+        let resp: BlockedResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Blocked {
+                handle: tg_handle,
+                account: user_addr,
+            }))
+            .await?;
+
+        Ok(resp.blocked)
+    }
+
+    pub async fn list_accounts(
+        &self,
+        start_after: Option<i64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Account>> {
+        let resp: AccountsResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::ListAccounts {
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.accounts)
+    }
+
+    pub async fn list_funded_accounts(
+        &self,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Vec<FundedAccount>> {
+        let resp: FundedAccountsResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::ListFundedAccounts {
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.accounts)
+    }
+
+    pub async fn list_pending_payments(
+        &self,
+        start_after: Option<TgHandle>,
+        limit: Option<u32>,
+    ) -> Result<Vec<PendingBalance>> {
+        let resp: PendingBalancesResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::ListPendingPayments {
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.balances)
+    }
+
+    pub async fn stats(&self) -> Result<StatsResponse> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::Stats {}))
+            .await
+    }
+
+    pub async fn ibc_routes(&self) -> Result<Vec<IbcRoute>> {
+        let resp: IbcRoutesResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::IbcRoutes {}))
+            .await?;
+
+        Ok(resp.routes)
+    }
+
+    pub async fn denom_metadata(&self) -> Result<Vec<DenomMetadata>> {
+        let resp: DenomMetadataResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::DenomMetadata {}))
+            .await?;
+
+        Ok(resp.denoms)
+    }
+
+    pub async fn pull_claims(&self) -> Result<bool> {
+        let resp: PullClaimsResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::PullClaims {}))
+            .await?;
+
+        Ok(resp.enabled)
+    }
+
+    pub async fn pending_claim(&self, handle: TgHandle) -> Result<PendingClaimResponse> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::PendingClaim { handle }))
+            .await
+    }
+
+    pub async fn escrow_window(&self) -> Result<Option<Duration>> {
+        let resp: EscrowWindowResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::EscrowWindow {}))
+            .await?;
+
+        Ok(resp.window)
+    }
+
+    pub async fn escrows(
+        &self,
+        from_tg_id: Option<i64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Escrow>> {
+        let resp: EscrowsResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Escrows {
+                from_tg_id,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.escrows)
+    }
+
+    pub async fn matured_escrows(&self) -> Result<u32> {
+        let resp: MaturedEscrowsResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::MaturedEscrows {}))
+            .await?;
+
+        Ok(resp.count)
+    }
+
+    pub async fn schedule(&self, id: u64) -> Result<Option<Schedule>> {
+        let resp: ScheduleResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Schedule { id }))
+            .await?;
+
+        Ok(resp.schedule)
+    }
+
+    pub async fn schedules(
+        &self,
+        from_tg_id: Option<i64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Schedule>> {
+        let resp: SchedulesResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Schedules {
+                from_tg_id,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.schedules)
+    }
+
+    pub async fn due_schedules(&self, limit: Option<u32>) -> Result<Vec<Schedule>> {
+        let resp: SchedulesResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::DueSchedules { limit }))
+            .await?;
+
+        Ok(resp.schedules)
+    }
+
+    pub async fn treasury(&self, chat_id: i64) -> Result<TreasuryResponse> {
+        self.query(&QueryMsg::Custom(CustomQueryMsg::Treasury { chat_id }))
+            .await
+    }
+
+    pub async fn proposal(&self, id: u64) -> Result<Option<Proposal>> {
+        let resp: ProposalResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Proposal { id }))
+            .await?;
+
+        Ok(resp.proposal)
+    }
+
+    pub async fn gift(&self, id: u64) -> Result<Option<Gift>> {
+        let resp: GiftResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Gift { id }))
+            .await?;
+
+        Ok(resp.gift)
+    }
+
+    /// The unclaimed gift locked under `hash`, see [`Gift::hash_secret`]
+    pub async fn gift_by_hash(&self, hash: HexBinary) -> Result<Option<Gift>> {
+        let resp: GiftResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::GiftByHash { hash }))
+            .await?;
+
+        Ok(resp.gift)
+    }
+
+    /// None once every share has been claimed or what was left has been refunded
+    pub async fn giveaway(&self, id: u64) -> Result<Option<Giveaway>> {
+        let resp: GiveawayResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Giveaway { id }))
+            .await?;
+
+        Ok(resp.giveaway)
+    }
+
+    pub async fn proposals(
+        &self,
+        chat_id: i64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<Proposal>> {
+        let resp: ProposalsResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::Proposals {
+                chat_id,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.proposals)
+    }
+
+    pub async fn pending_expiry(&self) -> Result<Option<Duration>> {
+        let resp: PendingExpiryResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::PendingExpiry {}))
+            .await?;
+
+        Ok(resp.expiry)
+    }
+
+    pub async fn payments_by_tg_handle(
+        &self,
+        tg_handle: TgHandle,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<PaymentRecord>> {
+        let resp: PaymentHistoryResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::PaymentsByTg {
+                handle: tg_handle,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.payments)
+    }
+
+    pub async fn payments_by_tg_id(
+        &self,
+        tg_user_id: i64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<PaymentRecord>> {
+        let resp: PaymentHistoryResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::PaymentsByTgId {
+                user_id: tg_user_id,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.payments)
+    }
+
+    pub async fn payments_by_addr(
+        &self,
+        user_addr: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<PaymentRecord>> {
+        let resp: PaymentHistoryResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::PaymentsByAddr {
+                account: user_addr,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.payments)
+    }
+
+    pub async fn payments_by_denom(
+        &self,
+        denom: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> Result<Vec<PaymentRecord>> {
+        let resp: PaymentHistoryResponse = self
+            .query(&QueryMsg::Custom(CustomQueryMsg::PaymentsByDenom {
+                denom,
+                start_after,
+                limit,
+            }))
+            .await?;
+
+        Ok(resp.payments)
+    }
 }
 
 #[derive(Clone)]
@@ -87,13 +508,16 @@ impl PaymentsExecutor {
 
     pub async fn register_receive(
         &self,
-        tg_handle: String,
+        tg_user_id: i64,
+        tg_handle: Option<&TgHandle>,
         user_addr: &AnyAddr,
     ) -> Result<AnyTxResponse> {
         self.exec(
             &ExecuteMsg::Custom(CustomExecuteMsg::RegisterReceive(RegisterReceiveMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
                 message_id: 0, // this is a dummy value, since we're spoofing a message
-                tg_handle,
+                tg_user_id,
+                tg_handle: tg_handle.cloned(),
                 chain_addr: user_addr.to_string(),
             })),
             &[],
@@ -101,23 +525,478 @@ impl PaymentsExecutor {
         .await
     }
 
+    /// Must be executed by the account that was registered to receive for this handle
+    pub async fn register_send(&self, tg_handle: &TgHandle) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RegisterSend {
+                tg_handle: tg_handle.clone(),
+            }),
+            &[],
+        )
+        .await
+    }
+
+    /// Must be executed by the sender's own account, None removes the cap
+    pub async fn set_spend_limit(
+        &self,
+        denom: &str,
+        limit: Option<SpendLimit>,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SetSpendLimit {
+                denom: denom.to_string(),
+                limit,
+            }),
+            &[],
+        )
+        .await
+    }
+
     pub async fn send_payment(
         &self,
-        from_tg: &str,
-        to_tg: &str,
+        from_tg_id: i64,
+        from_tg: Option<&TgHandle>,
+        to_tg: &TgHandle,
         amount: impl Into<Uint256>,
         denom: &str,
     ) -> Result<AnyTxResponse> {
         self.exec(
             &ExecuteMsg::Custom(CustomExecuteMsg::SendPayment(SendPaymentMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
+                message_id: 0, // this is a dummy value, since we're spoofing a message
+                from_tg_id,
+                from_tg: from_tg.cloned(),
+                to_tg: to_tg.clone(),
+                amount: amount.into(),
+                denom: denom.to_string(),
+            })),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn split_payment(
+        &self,
+        from_tg_id: i64,
+        from_tg: Option<&TgHandle>,
+        recipients: Vec<SplitRecipient>,
+        amount: impl Into<Uint256>,
+        denom: &str,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SplitPayment(SplitPaymentMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
+                message_id: 0, // this is a dummy value, since we're spoofing a message
+                from_tg_id,
+                from_tg: from_tg.cloned(),
+                amount: amount.into(),
+                denom: denom.to_string(),
+                recipients,
+            })),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn tip(
+        &self,
+        from_tg_id: i64,
+        from_tg: Option<&TgHandle>,
+        to_tg_id: i64,
+        to_tg: Option<&TgHandle>,
+        amount: impl Into<Uint256>,
+        denom: &str,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::Tip(TipMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
+                message_id: 0, // this is a dummy value, since we're spoofing a message
+                from_tg_id,
+                from_tg: from_tg.cloned(),
+                to_tg_id,
+                to_tg: to_tg.cloned(),
+                amount: amount.into(),
+                denom: denom.to_string(),
+            })),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn request_payment(
+        &self,
+        from_tg_id: i64,
+        from_tg: Option<&TgHandle>,
+        payer_tg: &TgHandle,
+        amount: impl Into<Uint256>,
+        denom: &str,
+        memo: Option<&str>,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RequestPayment(RequestPaymentMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
                 message_id: 0, // this is a dummy value, since we're spoofing a message
-                from_tg: from_tg.to_string(),
-                to_tg: to_tg.to_string(),
+                from_tg_id,
+                from_tg: from_tg.cloned(),
+                payer_tg: payer_tg.clone(),
                 amount: amount.into(),
                 denom: denom.to_string(),
+                memo: memo.map(str::to_string),
+            })),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn pay_invoice(
+        &self,
+        from_tg_id: i64,
+        from_tg: Option<&TgHandle>,
+        invoice_id: u64,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::PayInvoice(InvoiceActionMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
+                message_id: 0, // this is a dummy value, since we're spoofing a message
+                from_tg_id,
+                from_tg: from_tg.cloned(),
+                invoice_id,
             })),
             &[],
         )
         .await
     }
+
+    pub async fn decline_invoice(
+        &self,
+        from_tg_id: i64,
+        from_tg: Option<&TgHandle>,
+        invoice_id: u64,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::DeclineInvoice(InvoiceActionMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
+                message_id: 0, // this is a dummy value, since we're spoofing a message
+                from_tg_id,
+                from_tg: from_tg.cloned(),
+                invoice_id,
+            })),
+            &[],
+        )
+        .await
+    }
+
+    /// Anyone can trigger this, it only ever pays back the original senders
+    pub async fn refund_expired(&self, limit: Option<u32>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RefundExpired { limit }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn refund_expired_gifts(&self, limit: Option<u32>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RefundExpiredGifts { limit }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn refund_expired_giveaways(&self, limit: Option<u32>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RefundExpiredGiveaways { limit }),
+            &[],
+        )
+        .await
+    }
+
+    /// Cancels the sender's latest held send if `escrow_id` is None
+    pub async fn cancel_payment(
+        &self,
+        from_tg_id: i64,
+        escrow_id: Option<u64>,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::CancelPayment(CancelPaymentMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
+                message_id: 0, // this is a dummy value, since we're spoofing a message
+                from_tg_id,
+                escrow_id,
+            })),
+            &[],
+        )
+        .await
+    }
+
+    /// Anyone can trigger this, it only pays out sends whose window has passed
+    pub async fn release_matured(&self, limit: Option<u32>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::ReleaseMatured { limit }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn schedule_payment(&self, msg: SchedulePaymentMsg) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SchedulePayment(msg)),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn unschedule(&self, from_tg_id: i64, schedule_id: u64) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::Unschedule(UnscheduleMsg {
+                chat_id: 0,    // this is a dummy value, since we're spoofing a message
+                message_id: 0, // this is a dummy value, since we're spoofing a message
+                from_tg_id,
+                schedule_id,
+            })),
+            &[],
+        )
+        .await
+    }
+
+    /// Runs that aren't due, or whose period was already paid, are skipped
+    pub async fn run_schedules(&self, runs: Vec<ScheduleRun>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RunSchedules { runs }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn create_treasury(&self, msg: CreateTreasuryMsg) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::CreateTreasury(msg)),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn fund_treasury(&self, msg: FundTreasuryMsg) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::FundTreasury(msg)),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn propose_payout(&self, msg: ProposePayoutMsg) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::ProposePayout(msg)),
+            &[],
+        )
+        .await
+    }
+
+    /// Pays the proposal out if this vote brings it to the treasury's threshold
+    pub async fn vote(&self, msg: VoteMsg) -> Result<AnyTxResponse> {
+        self.exec(&ExecuteMsg::Custom(CustomExecuteMsg::Vote(msg)), &[])
+            .await
+    }
+
+    pub async fn create_gift(&self, msg: CreateGiftMsg) -> Result<AnyTxResponse> {
+        self.exec(&ExecuteMsg::Custom(CustomExecuteMsg::CreateGift(msg)), &[])
+            .await
+    }
+
+    /// Reserves the gift to the claimer instead if they haven't registered to receive yet
+    pub async fn claim_gift(&self, msg: ClaimGiftMsg) -> Result<AnyTxResponse> {
+        self.exec(&ExecuteMsg::Custom(CustomExecuteMsg::ClaimGift(msg)), &[])
+            .await
+    }
+
+    pub async fn create_giveaway(&self, msg: CreateGiveawayMsg) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::CreateGiveaway(msg)),
+            &[],
+        )
+        .await
+    }
+
+    /// Fails if the claimer hasn't registered to receive, or already claimed
+    pub async fn claim_giveaway(&self, msg: ClaimGiveawayMsg) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::ClaimGiveaway(msg)),
+            &[],
+        )
+        .await
+    }
+
+    /// Must be executed by the address registered to receive for `tg_handle`
+    pub async fn claim_pending(
+        &self,
+        tg_handle: &TgHandle,
+        to: Option<String>,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::ClaimPending {
+                tg_handle: tg_handle.clone(),
+                to,
+            }),
+            &[],
+        )
+        .await
+    }
+
+    /// Must be executed by the owner, takes effect once the new owner accepts
+    pub async fn transfer_ownership(&self, new_owner: &AnyAddr) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::TransferOwnership {
+                new_owner: new_owner.to_string(),
+            }),
+            &[],
+        )
+        .await
+    }
+
+    /// Must be executed by the proposed owner
+    pub async fn accept_ownership(&self) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::AcceptOwnership {}),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn cancel_ownership_transfer(&self) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::CancelOwnershipTransfer {}),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn add_denoms(&self, denoms: Vec<String>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::AddDenoms { denoms }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn remove_denoms(&self, denoms: Vec<String>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RemoveDenoms { denoms }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn set_auth(&self, auth: Auth) -> Result<AnyTxResponse> {
+        self.exec(&ExecuteMsg::Custom(CustomExecuteMsg::SetAuth { auth }), &[])
+            .await
+    }
+
+    pub async fn set_fee(&self, fee: Option<FeeConfig>) -> Result<AnyTxResponse> {
+        self.exec(&ExecuteMsg::Custom(CustomExecuteMsg::SetFee { fee }), &[])
+            .await
+    }
+
+    pub async fn set_paused(&self, paused: bool) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SetPaused { paused }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn set_pull_claims(&self, enabled: bool) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SetPullClaims { enabled }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn set_pending_expiry(&self, expiry: Option<Duration>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SetPendingExpiry { expiry }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn set_escrow_window(&self, window: Option<Duration>) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SetEscrowWindow { window }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn block(
+        &self,
+        handles: Vec<TgHandle>,
+        addresses: Vec<String>,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::Block { handles, addresses }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn unblock(
+        &self,
+        handles: Vec<TgHandle>,
+        addresses: Vec<String>,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::Unblock { handles, addresses }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn set_ibc_route(&self, route: IbcRoute) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SetIbcRoute { route }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn remove_ibc_route(&self, prefix: String) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RemoveIbcRoute { prefix }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn set_denom_metadata(&self, metadata: DenomMetadata) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::SetDenomMetadata { metadata }),
+            &[],
+        )
+        .await
+    }
+
+    pub async fn remove_denom_metadata(&self, denom: String) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Custom(CustomExecuteMsg::RemoveDenomMetadata { denom }),
+            &[],
+        )
+        .await
+    }
+
+    /// What the WAVS submitter calls, the envelope is validated by the service manager
+    pub async fn handle_signed_envelope(
+        &self,
+        envelope: WavsEnvelope,
+        signature_data: WavsSignatureData,
+    ) -> Result<AnyTxResponse> {
+        self.exec(
+            &ExecuteMsg::Wavs(ServiceHandlerExecuteMessages::WavsHandleSignedEnvelope {
+                envelope,
+                signature_data,
+            }),
+            &[],
+        )
+        .await
+    }
 }
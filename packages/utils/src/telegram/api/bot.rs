@@ -1,12 +1,16 @@
-use std::str::FromStr;
+use std::{collections::HashMap, str::FromStr};
 
 use crate::telegram::{
-    api::native::{TelegramChatType, TelegramMessage},
-    error::TelegramBotError,
+    api::native::{TelegramCallbackQuery, TelegramChatType, TelegramMessage},
+    error::{TelegramBotError, TgResult},
 };
-use cosmwasm_std::Uint256;
+use cosmwasm_std::{Timestamp, Uint256};
 use layer_climb::prelude::CosmosAddr;
 use serde::{Deserialize, Serialize};
+use tg_contract_api::payments::{
+    handle::TgHandle,
+    msg::{DenomMetadata, Gift, GiveawaySplit, SplitRecipient},
+};
 
 #[derive(Clone, Debug)]
 pub struct TelegramBotCommand {
@@ -16,7 +20,10 @@ pub struct TelegramBotCommand {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum TelegramWavsCommand {
-    Start,
+    /// `param` is set when the bot was opened from a deep link, see [`StartParam`]
+    Start {
+        param: Option<StartParam>,
+    },
     Help,
     Connect,
     GroupId {
@@ -26,10 +33,105 @@ pub enum TelegramWavsCommand {
         address: CosmosAddr,
     },
     Send {
-        handle: String,
+        handle: TgHandle,
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+    },
+    /// Divides `amount` between the recipients by weight
+    Split {
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+        recipients: Vec<SplitRecipient>,
+    },
+    /// Pays the author of the replied-to message, see [`TelegramMessage::reply_to_message`]
+    Tip {
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+    },
+    /// Asks `handle` to pay, they get a Pay / Decline prompt in the group
+    Request {
+        handle: TgHandle,
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+        memo: Option<String>,
+    },
+    /// Pressed on an invoice prompt, see [`InvoiceButton`]
+    PayInvoice {
+        invoice_id: u64,
+    },
+    /// Pressed on an invoice prompt, see [`InvoiceButton`]
+    DeclineInvoice {
+        invoice_id: u64,
+    },
+    /// Cancels a send still held in escrow, the sender's latest one if `escrow_id` is None
+    Undo {
+        escrow_id: Option<u64>,
+    },
+    /// Pays `handle` every `interval_seconds`, starting right away
+    Schedule {
+        handle: TgHandle,
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+        interval_seconds: u64,
+        /// Midnight UTC at the start of the date typed, no runs are due after it
+        until: Option<Timestamp>,
+    },
+    /// Lists the sender's recurring payments
+    Schedules,
+    Unschedule {
+        schedule_id: u64,
+    },
+    /// Locks `amount` under a link anyone can claim, the bot DMs it to the sender
+    Gift {
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+        /// The contract's default applies if None
+        expires_in_seconds: Option<u64>,
+    },
+    /// Puts `amount` up for the first `count` members to claim with the button the bot posts
+    Giveaway {
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+        count: u32,
+        split: GiveawaySplit,
+    },
+    /// Pressed on a giveaway, see [`GiveawayButton`]
+    ClaimGiveaway {
+        giveaway_id: u64,
+    },
+    /// Shows the group's treasury, what it holds and its open proposals
+    Treasury,
+    /// Sets up the group's treasury, whoever sends it is a member too
+    CreateTreasury {
+        threshold: u32,
+        members: Vec<TgHandle>,
+    },
+    /// Moves funds from the sender's account into the group's treasury
+    FundTreasury {
         amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
         denom: String,
     },
+    /// Proposes paying `handle` out of the group's treasury, the members vote on it
+    Propose {
+        handle: TgHandle,
+        amount: Uint256,
+        /// Base denom, or the CW20 contract address, see [`DenomRegistry::parse_amount`]
+        denom: String,
+        memo: Option<String>,
+    },
+    /// Typed, or pressed on a proposal prompt, see [`ProposalButton`]
+    Vote {
+        proposal_id: u64,
+        approve: bool,
+    },
     Admin(TelegramWavsAdminCommand),
     Service,
     Status,
@@ -51,6 +153,113 @@ impl TelegramWavsAdminCommand {
     }
 }
 
+/// CW20 tokens the bot knows by symbol, so users don't have to type the contract address.
+/// Configured as `SYMBOL=address,SYMBOL=address`, symbols match case-insensitively.
+#[derive(Clone, Debug, Default)]
+pub struct Cw20Symbols(HashMap<String, String>);
+
+impl Cw20Symbols {
+    /// The contract address for a configured symbol, anything else is returned unchanged
+    pub fn resolve<'a>(&'a self, denom: &'a str) -> &'a str {
+        self.0
+            .get(&denom.to_ascii_uppercase())
+            .map(String::as_str)
+            .unwrap_or(denom)
+    }
+}
+
+impl FromStr for Cw20Symbols {
+    type Err = TelegramBotError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| match entry.split_once('=') {
+                Some((symbol, address)) if !symbol.trim().is_empty() => Ok((
+                    symbol.trim().to_ascii_uppercase(),
+                    address.trim().to_string(),
+                )),
+                _ => Err(TelegramBotError::Parse(format!(
+                    "could not parse CW20 symbol {entry}, expected SYMBOL=address"
+                ))),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// How amounts are typed and shown, from the payments contract's denom metadata.
+/// Denoms without metadata, and the configured CW20 symbols, stay in base units.
+#[derive(Clone, Debug, Default)]
+pub struct DenomRegistry {
+    denoms: Vec<DenomMetadata>,
+    cw20_symbols: Cw20Symbols,
+}
+
+impl DenomRegistry {
+    pub fn new(denoms: Vec<DenomMetadata>) -> Self {
+        Self {
+            denoms,
+            cw20_symbols: Cw20Symbols::default(),
+        }
+    }
+
+    pub fn with_cw20_symbols(mut self, cw20_symbols: Cw20Symbols) -> Self {
+        self.cw20_symbols = cw20_symbols;
+        self
+    }
+
+    /// An amount as typed, in base units of the denom it resolves to.
+    ///
+    /// A symbol or alias such as `1.5 NTRN` is in display units, anything else, like
+    /// `1500000 untrn` or a configured CW20 symbol, is a whole number of base units.
+    pub fn parse_amount(&self, amount: &str, denom: &str) -> TgResult<(Uint256, String)> {
+        let named = self.denoms.iter().find(|metadata| {
+            metadata.denom != denom
+                && std::iter::once(&metadata.symbol)
+                    .chain(&metadata.aliases)
+                    .any(|name| name.eq_ignore_ascii_case(denom))
+        });
+        let (exponent, denom) = match named {
+            Some(metadata) => (metadata.exponent, metadata.denom.clone()),
+            None => (0, self.cw20_symbols.resolve(denom).to_string()),
+        };
+
+        let invalid = || TelegramBotError::Parse(format!("could not parse {amount} {denom}"));
+        let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+        if (whole.is_empty() && fraction.is_empty())
+            || fraction.len() > exponent as usize
+            || !whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let digits = format!("{whole}{fraction:0<width$}", width = exponent as usize);
+        let amount = digits.parse::<Uint256>().map_err(|_| invalid())?;
+
+        Ok((amount, denom))
+    }
+
+    /// An amount in base units as users see it, e.g. `1.5 NTRN`, or `1500000 untrn` when the
+    /// denom has no metadata
+    pub fn display(&self, amount: Uint256, denom: &str) -> String {
+        let Some(metadata) = self.denoms.iter().find(|metadata| metadata.denom == denom) else {
+            return format!("{amount} {denom}");
+        };
+
+        let digits = format!("{amount:0>width$}", width = metadata.exponent as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - metadata.exponent as usize);
+        match fraction.trim_end_matches('0') {
+            "" => format!("{whole} {}", metadata.symbol),
+            fraction => format!("{whole}.{fraction} {}", metadata.symbol),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Copy, Eq, PartialEq)]
 pub enum TelegramWavsCommandPrefix {
     Start,
@@ -58,6 +267,18 @@ pub enum TelegramWavsCommandPrefix {
     GroupId,
     Receive,
     Send,
+    Split,
+    Tip,
+    Request,
+    Undo,
+    Schedule,
+    Schedules,
+    Unschedule,
+    Gift,
+    Giveaway,
+    Treasury,
+    Propose,
+    Vote,
     Connect,
     Status,
     Admin(TelegramWavsAdminCommandPrefix),
@@ -76,6 +297,22 @@ impl TelegramWavsCommandPrefix {
             TelegramWavsCommandPrefix::GroupId => "",
             TelegramWavsCommandPrefix::Receive => "<address>",
             TelegramWavsCommandPrefix::Send => "<handle> <amount> <denom>",
+            TelegramWavsCommandPrefix::Split => "<amount> <denom> <handle>[:weight] ...",
+            TelegramWavsCommandPrefix::Tip => "<amount> <denom>, as a reply",
+            TelegramWavsCommandPrefix::Request => "<handle> <amount> <denom> [memo]",
+            TelegramWavsCommandPrefix::Undo => "[id]",
+            TelegramWavsCommandPrefix::Schedule => {
+                "<handle> <amount> <denom> every <interval> [until YYYY-MM-DD]"
+            }
+            TelegramWavsCommandPrefix::Schedules => "",
+            TelegramWavsCommandPrefix::Unschedule => "<id>",
+            TelegramWavsCommandPrefix::Gift => "<amount> <denom> [for <interval>]",
+            TelegramWavsCommandPrefix::Giveaway => "<total> <denom> <count> [random|equal]",
+            TelegramWavsCommandPrefix::Treasury => {
+                "[create <threshold> <handle> ... | fund <amount> <denom>]"
+            }
+            TelegramWavsCommandPrefix::Propose => "<handle> <amount> <denom> [memo]",
+            TelegramWavsCommandPrefix::Vote => "<id> yes|no",
             TelegramWavsCommandPrefix::Status => "",
             TelegramWavsCommandPrefix::Connect => "",
             TelegramWavsCommandPrefix::Admin(admin) => match admin {
@@ -96,6 +333,18 @@ impl FromStr for TelegramWavsCommandPrefix {
             "/groupId" => Ok(TelegramWavsCommandPrefix::GroupId),
             "/receive" => Ok(TelegramWavsCommandPrefix::Receive),
             "/send" => Ok(TelegramWavsCommandPrefix::Send),
+            "/split" => Ok(TelegramWavsCommandPrefix::Split),
+            "/tip" => Ok(TelegramWavsCommandPrefix::Tip),
+            "/request" => Ok(TelegramWavsCommandPrefix::Request),
+            "/undo" => Ok(TelegramWavsCommandPrefix::Undo),
+            "/schedule" => Ok(TelegramWavsCommandPrefix::Schedule),
+            "/schedules" => Ok(TelegramWavsCommandPrefix::Schedules),
+            "/unschedule" => Ok(TelegramWavsCommandPrefix::Unschedule),
+            "/gift" => Ok(TelegramWavsCommandPrefix::Gift),
+            "/giveaway" => Ok(TelegramWavsCommandPrefix::Giveaway),
+            "/treasury" => Ok(TelegramWavsCommandPrefix::Treasury),
+            "/propose" => Ok(TelegramWavsCommandPrefix::Propose),
+            "/vote" => Ok(TelegramWavsCommandPrefix::Vote),
             "/status" => Ok(TelegramWavsCommandPrefix::Status),
             "/connect" => Ok(TelegramWavsCommandPrefix::Connect),
             "/admin set-service" => Ok(TelegramWavsCommandPrefix::Admin(
@@ -115,6 +364,18 @@ impl std::fmt::Display for TelegramWavsCommandPrefix {
             TelegramWavsCommandPrefix::GroupId => write!(f, "/groupId"),
             TelegramWavsCommandPrefix::Receive => write!(f, "/receive"),
             TelegramWavsCommandPrefix::Send => write!(f, "/send"),
+            TelegramWavsCommandPrefix::Split => write!(f, "/split"),
+            TelegramWavsCommandPrefix::Tip => write!(f, "/tip"),
+            TelegramWavsCommandPrefix::Request => write!(f, "/request"),
+            TelegramWavsCommandPrefix::Undo => write!(f, "/undo"),
+            TelegramWavsCommandPrefix::Schedule => write!(f, "/schedule"),
+            TelegramWavsCommandPrefix::Schedules => write!(f, "/schedules"),
+            TelegramWavsCommandPrefix::Unschedule => write!(f, "/unschedule"),
+            TelegramWavsCommandPrefix::Gift => write!(f, "/gift"),
+            TelegramWavsCommandPrefix::Giveaway => write!(f, "/giveaway"),
+            TelegramWavsCommandPrefix::Treasury => write!(f, "/treasury"),
+            TelegramWavsCommandPrefix::Propose => write!(f, "/propose"),
+            TelegramWavsCommandPrefix::Vote => write!(f, "/vote"),
             TelegramWavsCommandPrefix::Status => write!(f, "/status"),
             TelegramWavsCommandPrefix::Connect => write!(f, "/connect"),
             TelegramWavsCommandPrefix::Admin(TelegramWavsAdminCommandPrefix::SetService) => {
@@ -125,11 +386,9 @@ impl std::fmt::Display for TelegramWavsCommandPrefix {
     }
 }
 
-impl TryFrom<TelegramMessage> for TelegramBotCommand {
-    type Error = TelegramBotError;
-
-    fn try_from(message: TelegramMessage) -> Result<Self, Self::Error> {
-        let command = TelegramWavsCommand::try_from(&message)?;
+impl TelegramBotCommand {
+    pub fn parse(message: TelegramMessage, denoms: &DenomRegistry) -> TgResult<Self> {
+        let command = TelegramWavsCommand::parse(&message, denoms)?;
         Ok(TelegramBotCommand {
             command,
             raw: message,
@@ -137,10 +396,57 @@ impl TryFrom<TelegramMessage> for TelegramBotCommand {
     }
 }
 
+impl TelegramBotCommand {
+    /// A button pressed on one of the bot's messages. The command is attributed to whoever
+    /// pressed it, and keyed by the message the button was on.
+    pub fn from_callback(query: TelegramCallbackQuery) -> TgResult<Self> {
+        let data = query.data.ok_or(TelegramBotError::BadCommand)?;
+        let mut raw = query.message.ok_or(TelegramBotError::BadCommand)?;
+        raw.from = query.from;
+
+        let command = match InvoiceButton::parse(&data) {
+            Ok((InvoiceButton::Pay, invoice_id)) => TelegramWavsCommand::PayInvoice { invoice_id },
+            Ok((InvoiceButton::Decline, invoice_id)) => {
+                TelegramWavsCommand::DeclineInvoice { invoice_id }
+            }
+            Err(_) => match GiveawayButton::parse(&data) {
+                Ok((GiveawayButton::Claim, giveaway_id)) => {
+                    TelegramWavsCommand::ClaimGiveaway { giveaway_id }
+                }
+                Err(_) => {
+                    let (button, proposal_id) = ProposalButton::parse(&data)?;
+                    TelegramWavsCommand::Vote {
+                        proposal_id,
+                        approve: button == ProposalButton::Approve,
+                    }
+                }
+            },
+        };
+
+        Ok(TelegramBotCommand { command, raw })
+    }
+}
+
+impl TryFrom<TelegramMessage> for TelegramBotCommand {
+    type Error = TelegramBotError;
+
+    /// Amounts are in base units of the denom as typed, see [`TelegramBotCommand::parse`] to
+    /// resolve symbols
+    fn try_from(message: TelegramMessage) -> Result<Self, Self::Error> {
+        Self::parse(message, &DenomRegistry::default())
+    }
+}
+
 impl TryFrom<&TelegramMessage> for TelegramWavsCommand {
     type Error = TelegramBotError;
 
     fn try_from(message: &TelegramMessage) -> Result<Self, Self::Error> {
+        Self::parse(message, &DenomRegistry::default())
+    }
+}
+
+impl TelegramWavsCommand {
+    pub fn parse(message: &TelegramMessage, denoms: &DenomRegistry) -> TgResult<Self> {
         let (prefix, parts) = match message.text.clone() {
             Some(text) => {
                 let mut iter = text.split_whitespace();
@@ -164,7 +470,9 @@ impl TryFrom<&TelegramMessage> for TelegramWavsCommand {
         };
 
         match prefix {
-            TelegramWavsCommandPrefix::Start => Ok(TelegramWavsCommand::Start),
+            TelegramWavsCommandPrefix::Start => Ok(TelegramWavsCommand::Start {
+                param: parts.first().and_then(|param| StartParam::parse(param)),
+            }),
             TelegramWavsCommandPrefix::Help => Ok(TelegramWavsCommand::Help),
             TelegramWavsCommandPrefix::Connect => Ok(TelegramWavsCommand::Connect),
             TelegramWavsCommandPrefix::Admin(TelegramWavsAdminCommandPrefix::SetService) => {
@@ -179,15 +487,196 @@ impl TryFrom<&TelegramMessage> for TelegramWavsCommand {
                 }
             }
             TelegramWavsCommandPrefix::Send => match &parts[..] {
-                [handle, amount, denom] => Ok(TelegramWavsCommand::Send {
-                    handle: handle.to_string(),
-                    amount: amount.parse().map_err(|e| {
-                        TelegramBotError::Parse(format!("could not parse {amount}: {e:?}"))
+                [handle, amount, denom] => {
+                    let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                    Ok(TelegramWavsCommand::Send {
+                        handle: TgHandle::new(handle)?,
+                        amount,
+                        denom,
+                    })
+                }
+                _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+            },
+            TelegramWavsCommandPrefix::Split => match &parts[..] {
+                [amount, denom, recipients @ ..] if !recipients.is_empty() => {
+                    let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                    Ok(TelegramWavsCommand::Split {
+                        amount,
+                        denom,
+                        recipients: recipients
+                            .iter()
+                            .map(|recipient| parse_split_recipient(recipient))
+                            .collect::<TgResult<_>>()?,
+                    })
+                }
+                _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+            },
+            TelegramWavsCommandPrefix::Tip => match &parts[..] {
+                [amount, denom] => {
+                    ensure_tip_reply(message)?;
+                    let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                    Ok(TelegramWavsCommand::Tip { amount, denom })
+                }
+                _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+            },
+            TelegramWavsCommandPrefix::Request => match &parts[..] {
+                [handle, amount, denom, memo @ ..] => {
+                    let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                    Ok(TelegramWavsCommand::Request {
+                        handle: TgHandle::new(handle)?,
+                        amount,
+                        denom,
+                        memo: match memo.join(" ") {
+                            memo if memo.is_empty() => None,
+                            memo => Some(memo),
+                        },
+                    })
+                }
+                _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+            },
+            TelegramWavsCommandPrefix::Undo => match &parts[..] {
+                [] => Ok(TelegramWavsCommand::Undo { escrow_id: None }),
+                [id] => Ok(TelegramWavsCommand::Undo {
+                    escrow_id: Some(id.trim_start_matches('#').parse().map_err(|_| {
+                        TelegramBotError::Parse(format!("could not parse {id} as a payment id"))
+                    })?),
+                }),
+                _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+            },
+            TelegramWavsCommandPrefix::Schedule => match &parts[..] {
+                [handle, amount, denom, every, rest @ ..]
+                    if every.eq_ignore_ascii_case("every") =>
+                {
+                    let (interval, until) = match rest {
+                        [interval @ .., until, date] if until.eq_ignore_ascii_case("until") => {
+                            (interval, Some(parse_date(date)?))
+                        }
+                        interval => (interval, None),
+                    };
+                    let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                    Ok(TelegramWavsCommand::Schedule {
+                        handle: TgHandle::new(handle)?,
+                        amount,
+                        denom,
+                        interval_seconds: parse_interval(interval)?,
+                        until,
+                    })
+                }
+                _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+            },
+            TelegramWavsCommandPrefix::Schedules => Ok(TelegramWavsCommand::Schedules),
+            TelegramWavsCommandPrefix::Unschedule => match &parts[..] {
+                [id] => Ok(TelegramWavsCommand::Unschedule {
+                    schedule_id: id.trim_start_matches('#').parse().map_err(|_| {
+                        TelegramBotError::Parse(format!("could not parse {id} as a schedule id"))
                     })?,
-                    denom: denom.to_string(),
                 }),
                 _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
             },
+            TelegramWavsCommandPrefix::Gift => match &parts[..] {
+                [amount, denom, rest @ ..] => {
+                    let expires_in_seconds = match rest {
+                        [] => None,
+                        [r#for, interval @ ..] if r#for.eq_ignore_ascii_case("for") => {
+                            Some(parse_interval(interval)?)
+                        }
+                        _ => return Err(TelegramBotError::InvalidCommandFormat { prefix }),
+                    };
+                    let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                    Ok(TelegramWavsCommand::Gift {
+                        amount,
+                        denom,
+                        expires_in_seconds,
+                    })
+                }
+                _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+            },
+            TelegramWavsCommandPrefix::Giveaway => {
+                group_chat_id(message)?;
+                match &parts[..] {
+                    [amount, denom, count, split @ ..] if split.len() <= 1 => {
+                        let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                        Ok(TelegramWavsCommand::Giveaway {
+                            amount,
+                            denom,
+                            count: count.parse().map_err(|_| {
+                                TelegramBotError::Parse(format!(
+                                    "could not parse {count} as a number of claims"
+                                ))
+                            })?,
+                            split: match split.first() {
+                                Some(split) => split
+                                    .parse()
+                                    .map_err(|e| TelegramBotError::Parse(format!("{e}")))?,
+                                None => GiveawaySplit::Random,
+                            },
+                        })
+                    }
+                    _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+                }
+            }
+            TelegramWavsCommandPrefix::Treasury => {
+                group_chat_id(message)?;
+                match &parts[..] {
+                    [] => Ok(TelegramWavsCommand::Treasury),
+                    [create, threshold, members @ ..]
+                        if create.eq_ignore_ascii_case("create") && !members.is_empty() =>
+                    {
+                        Ok(TelegramWavsCommand::CreateTreasury {
+                            threshold: threshold.parse().map_err(|_| {
+                                TelegramBotError::Parse(format!(
+                                    "could not parse {threshold} as a number of votes"
+                                ))
+                            })?,
+                            members: members
+                                .iter()
+                                .map(|member| TgHandle::new(member))
+                                .collect::<Result<_, _>>()?,
+                        })
+                    }
+                    [fund, amount, denom] if fund.eq_ignore_ascii_case("fund") => {
+                        let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                        Ok(TelegramWavsCommand::FundTreasury { amount, denom })
+                    }
+                    _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+                }
+            }
+            TelegramWavsCommandPrefix::Propose => {
+                group_chat_id(message)?;
+                match &parts[..] {
+                    [handle, amount, denom, memo @ ..] => {
+                        let (amount, denom) = denoms.parse_amount(amount, denom)?;
+                        Ok(TelegramWavsCommand::Propose {
+                            handle: TgHandle::new(handle)?,
+                            amount,
+                            denom,
+                            memo: match memo.join(" ") {
+                                memo if memo.is_empty() => None,
+                                memo => Some(memo),
+                            },
+                        })
+                    }
+                    _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+                }
+            }
+            TelegramWavsCommandPrefix::Vote => {
+                group_chat_id(message)?;
+                match &parts[..] {
+                    [id, vote] => Ok(TelegramWavsCommand::Vote {
+                        proposal_id: id.trim_start_matches('#').parse().map_err(|_| {
+                            TelegramBotError::Parse(format!(
+                                "could not parse {id} as a proposal id"
+                            ))
+                        })?,
+                        approve: match vote.to_ascii_lowercase().as_str() {
+                            "yes" | "approve" => true,
+                            "no" | "reject" => false,
+                            _ => return Err(TelegramBotError::InvalidCommandFormat { prefix }),
+                        },
+                    }),
+                    _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
+                }
+            }
             TelegramWavsCommandPrefix::Receive => match &parts[..] {
                 [address] => Ok(TelegramWavsCommand::Receive {
                     address: address.parse().map_err(|e| {
@@ -197,16 +686,799 @@ impl TryFrom<&TelegramMessage> for TelegramWavsCommand {
                 _ => Err(TelegramBotError::InvalidCommandFormat { prefix }),
             },
             TelegramWavsCommandPrefix::Status => Ok(TelegramWavsCommand::Status),
-            TelegramWavsCommandPrefix::GroupId => match message.chat.chat_type {
-                TelegramChatType::Group
-                | TelegramChatType::SuperGroup
-                | TelegramChatType::Channel => match message.chat.id {
-                    id if id < 0 => Ok(TelegramWavsCommand::GroupId { group_id: id }),
-                    _ => Err(TelegramBotError::InvalidGroupId),
-                },
-                _ => Err(TelegramBotError::NotGroupChat),
-            },
+            TelegramWavsCommandPrefix::GroupId => Ok(TelegramWavsCommand::GroupId {
+                group_id: group_chat_id(message)?,
+            }),
             TelegramWavsCommandPrefix::Service => Ok(TelegramWavsCommand::Service),
         }
     }
 }
+
+/// What a `t.me/<bot>?start=<param>` deep link carries into the `/start` it opens with
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum StartParam {
+    /// Redeems the gift locked under the hash of `secret`, see [`gift_secret`]
+    ClaimGift { secret: String },
+}
+
+impl StartParam {
+    /// Telegram only passes up to 64 of `A-Z`, `a-z`, `0-9`, `_` and `-` through, anything
+    /// else, or a param this bot doesn't know, is None
+    pub fn parse(param: &str) -> Option<Self> {
+        if param.is_empty()
+            || param.len() > 64
+            || !param
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return None;
+        }
+
+        match param.split_once('_') {
+            Some(("claim", secret)) if !secret.is_empty() => Some(StartParam::ClaimGift {
+                secret: secret.to_string(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn link(&self, bot_username: &str) -> String {
+        match self {
+            StartParam::ClaimGift { secret } => {
+                format!("https://t.me/{bot_username}?start=claim_{secret}")
+            }
+        }
+    }
+}
+
+/// The secret behind the gift created by the `/gift` message `message_id` in `chat_id`.
+/// Only the bot token holders can derive it, so the operators lock the gift under its hash and
+/// the bot DMs the link without the secret ever going on chain.
+pub fn gift_secret(bot_token: &str, chat_id: i64, message_id: i64) -> String {
+    let hash = Gift::hash_secret(&format!("{bot_token}:gift:{chat_id}:{message_id}"));
+    hash.to_hex()[..32].to_string()
+}
+
+/// The buttons on an invoice prompt. Telegram hands the callback data back when one is pressed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InvoiceButton {
+    Pay,
+    Decline,
+}
+
+impl InvoiceButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            InvoiceButton::Pay => "Pay",
+            InvoiceButton::Decline => "Decline",
+        }
+    }
+
+    /// `invoice-pay:<id>` or `invoice-decline:<id>`, well within Telegram's 64 byte limit
+    pub fn callback_data(&self, invoice_id: u64) -> String {
+        match self {
+            InvoiceButton::Pay => format!("invoice-pay:{invoice_id}"),
+            InvoiceButton::Decline => format!("invoice-decline:{invoice_id}"),
+        }
+    }
+
+    pub fn parse(data: &str) -> TgResult<(Self, u64)> {
+        let (button, invoice_id) = match data.split_once(':') {
+            Some(("invoice-pay", id)) => (InvoiceButton::Pay, id),
+            Some(("invoice-decline", id)) => (InvoiceButton::Decline, id),
+            _ => return Err(TelegramBotError::UnknownCommand(data.to_string())),
+        };
+        let invoice_id = invoice_id.parse().map_err(|e| {
+            TelegramBotError::Parse(format!("could not parse invoice id {invoice_id}: {e:?}"))
+        })?;
+
+        Ok((button, invoice_id))
+    }
+}
+
+/// The buttons on a payout proposal prompt, like [`InvoiceButton`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ProposalButton {
+    Approve,
+    Reject,
+}
+
+impl ProposalButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProposalButton::Approve => "Approve",
+            ProposalButton::Reject => "Reject",
+        }
+    }
+
+    /// `proposal-approve:<id>` or `proposal-reject:<id>`
+    pub fn callback_data(&self, proposal_id: u64) -> String {
+        match self {
+            ProposalButton::Approve => format!("proposal-approve:{proposal_id}"),
+            ProposalButton::Reject => format!("proposal-reject:{proposal_id}"),
+        }
+    }
+
+    pub fn parse(data: &str) -> TgResult<(Self, u64)> {
+        let (button, proposal_id) = match data.split_once(':') {
+            Some(("proposal-approve", id)) => (ProposalButton::Approve, id),
+            Some(("proposal-reject", id)) => (ProposalButton::Reject, id),
+            _ => return Err(TelegramBotError::UnknownCommand(data.to_string())),
+        };
+        let proposal_id = proposal_id.parse().map_err(|e| {
+            TelegramBotError::Parse(format!("could not parse proposal id {proposal_id}: {e:?}"))
+        })?;
+
+        Ok((button, proposal_id))
+    }
+}
+
+/// The single button on a giveaway, like [`InvoiceButton`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GiveawayButton {
+    Claim,
+}
+
+impl GiveawayButton {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GiveawayButton::Claim => "Claim",
+        }
+    }
+
+    /// `giveaway-claim:<id>`
+    pub fn callback_data(&self, giveaway_id: u64) -> String {
+        match self {
+            GiveawayButton::Claim => format!("giveaway-claim:{giveaway_id}"),
+        }
+    }
+
+    pub fn parse(data: &str) -> TgResult<(Self, u64)> {
+        let (button, giveaway_id) = match data.split_once(':') {
+            Some(("giveaway-claim", id)) => (GiveawayButton::Claim, id),
+            _ => return Err(TelegramBotError::UnknownCommand(data.to_string())),
+        };
+        let giveaway_id = giveaway_id.parse().map_err(|e| {
+            TelegramBotError::Parse(format!("could not parse giveaway id {giveaway_id}: {e:?}"))
+        })?;
+
+        Ok((button, giveaway_id))
+    }
+}
+
+/// The id of the group chat a message was sent in, which keys its treasury and giveaways
+fn group_chat_id(message: &TelegramMessage) -> TgResult<i64> {
+    match message.chat.chat_type {
+        TelegramChatType::Group | TelegramChatType::SuperGroup | TelegramChatType::Channel => {
+            match message.chat.id {
+                id if id < 0 => Ok(id),
+                _ => Err(TelegramBotError::InvalidGroupId),
+            }
+        }
+        _ => Err(TelegramBotError::NotGroupChat),
+    }
+}
+
+/// A tip goes to whoever wrote the replied-to message, which has to be a person
+fn ensure_tip_reply(message: &TelegramMessage) -> TgResult<()> {
+    match &message.reply_to_message {
+        Some(reply) if reply.from.is_bot => Err(TelegramBotError::TipBot),
+        Some(_) => Ok(()),
+        None => Err(TelegramBotError::NotAReply),
+    }
+}
+
+/// `@alice` for an even share, `@alice:2` for a weighted one
+fn parse_split_recipient(recipient: &str) -> TgResult<SplitRecipient> {
+    let (handle, weight) = match recipient.split_once(':') {
+        Some((handle, weight)) => (
+            handle,
+            weight.parse().map_err(|e| {
+                TelegramBotError::Parse(format!("could not parse weight {weight}: {e:?}"))
+            })?,
+        ),
+        None => (recipient, 1),
+    };
+
+    Ok(SplitRecipient {
+        to_tg: TgHandle::new(handle)?,
+        weight,
+    })
+}
+
+const HOUR_SECONDS: u64 = 60 * 60;
+const DAY_SECONDS: u64 = 24 * HOUR_SECONDS;
+const WEEK_SECONDS: u64 = 7 * DAY_SECONDS;
+/// Months are taken as 30 days, so every run is the same length apart
+const MONTH_SECONDS: u64 = 30 * DAY_SECONDS;
+
+/// `week`, `2 weeks` or `2w`, in hours, days, weeks or months
+fn parse_interval(parts: &[String]) -> TgResult<u64> {
+    let (count, unit) = match parts {
+        [count, unit] => (count.as_str(), unit.as_str()),
+        [unit] => match unit.find(|c: char| !c.is_ascii_digit()) {
+            Some(0) => ("1", unit.as_str()),
+            Some(idx) => unit.split_at(idx),
+            None => (unit.as_str(), ""),
+        },
+        _ => {
+            return Err(TelegramBotError::Parse(
+                "expected an interval like 2 weeks".into(),
+            ))
+        }
+    };
+    let count: u64 = count
+        .parse()
+        .map_err(|_| TelegramBotError::Parse(format!("could not parse {count} as a count")))?;
+    let unit_seconds = match unit.to_ascii_lowercase().trim_end_matches('s') {
+        "h" | "hour" => HOUR_SECONDS,
+        "d" | "day" => DAY_SECONDS,
+        "w" | "week" => WEEK_SECONDS,
+        "mo" | "month" => MONTH_SECONDS,
+        _ => {
+            return Err(TelegramBotError::Parse(format!(
+                "unknown interval {unit}, use hours, days, weeks or months"
+            )))
+        }
+    };
+
+    match count.checked_mul(unit_seconds) {
+        Some(seconds) if seconds > 0 => Ok(seconds),
+        _ => Err(TelegramBotError::Parse(format!(
+            "invalid interval {count} {unit}"
+        ))),
+    }
+}
+
+/// How an interval reads in the chat, in the largest unit it divides into
+pub fn display_interval(seconds: u64) -> String {
+    let (count, unit) = [
+        (MONTH_SECONDS, "month"),
+        (WEEK_SECONDS, "week"),
+        (DAY_SECONDS, "day"),
+        (HOUR_SECONDS, "hour"),
+        (60, "minute"),
+    ]
+    .into_iter()
+    .find(|(unit_seconds, _)| seconds.is_multiple_of(*unit_seconds))
+    .map(|(unit_seconds, unit)| (seconds / unit_seconds, unit))
+    .unwrap_or((seconds, "second"));
+
+    match count {
+        1 => unit.to_string(),
+        count => format!("{count} {unit}s"),
+    }
+}
+
+/// `YYYY-MM-DD`, as midnight UTC at the start of that day
+fn parse_date(date: &str) -> TgResult<Timestamp> {
+    let invalid = || TelegramBotError::Parse(format!("could not parse {date} as YYYY-MM-DD"));
+    let mut fields = date.splitn(3, '-').map(|field| field.parse::<u32>());
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) =
+        (fields.next(), fields.next(), fields.next())
+    else {
+        return Err(invalid());
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        1..=12 => 31,
+        _ => return Err(invalid()),
+    };
+    if year < 1970 || day == 0 || day > days_in_month {
+        return Err(invalid());
+    }
+
+    // Days since 1970-01-01 in the proleptic Gregorian calendar, with the year starting in March
+    // so the leap day comes last
+    let (year, month, day) = (year as u64, month as u64, day as u64);
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Ok(Timestamp::from_seconds(days * DAY_SECONDS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::telegram::api::native::{TelegramChat, TelegramUser};
+
+    fn registry() -> DenomRegistry {
+        DenomRegistry::new(vec![DenomMetadata {
+            denom: "untrn".to_string(),
+            symbol: "NTRN".to_string(),
+            exponent: 6,
+            aliases: vec!["neutron".to_string()],
+        }])
+        .with_cw20_symbols("USDC=neutron1usdc".parse().unwrap())
+    }
+
+    fn user(id: i64, is_bot: bool) -> TelegramUser {
+        TelegramUser {
+            id,
+            is_bot,
+            first_name: format!("user{id}"),
+            username: Some(format!("user{id}")),
+        }
+    }
+
+    fn message_in(text: &str, chat_id: i64, chat_type: TelegramChatType) -> TelegramMessage {
+        TelegramMessage {
+            message_id: 1,
+            message_thread_id: None,
+            from: user(1, false),
+            chat: TelegramChat {
+                id: chat_id,
+                chat_type,
+                title: None,
+                username: None,
+                first_name: None,
+                last_name: None,
+            },
+            date: 0,
+            text: Some(text.to_string()),
+            new_chat_members: None,
+            left_chat_member: None,
+            reply_to_message: None,
+        }
+    }
+
+    fn parse(text: &str) -> TgResult<TelegramWavsCommand> {
+        TelegramWavsCommand::parse(&message_in(text, 1, TelegramChatType::Private), &registry())
+    }
+
+    fn handle(handle: &str) -> TgHandle {
+        TgHandle::new(handle).unwrap()
+    }
+
+    #[test]
+    fn amounts_by_symbol_are_in_display_units() {
+        let denoms = registry();
+        for (amount, denom, expected) in [
+            ("1.5", "NTRN", 1_500_000u128),
+            ("1.5", "ntrn", 1_500_000),
+            ("2", "Neutron", 2_000_000),
+            ("0.000001", "NTRN", 1),
+            (".25", "NTRN", 250_000),
+        ] {
+            assert_eq!(
+                denoms.parse_amount(amount, denom).unwrap(),
+                (Uint256::from(expected), "untrn".to_string()),
+                "{amount} {denom}"
+            );
+        }
+
+        assert!(denoms.parse_amount("0.0000001", "NTRN").is_err());
+        assert!(denoms.parse_amount("1.5.1", "NTRN").is_err());
+        assert!(denoms.parse_amount(".", "NTRN").is_err());
+        assert!(denoms.parse_amount("-1", "NTRN").is_err());
+    }
+
+    #[test]
+    fn amounts_by_denom_are_in_base_units() {
+        let denoms = registry();
+        assert_eq!(
+            denoms.parse_amount("1000000", "untrn").unwrap(),
+            (Uint256::from(1_000_000u128), "untrn".to_string())
+        );
+        assert_eq!(
+            denoms.parse_amount("7", "usdc").unwrap(),
+            (Uint256::from(7u128), "neutron1usdc".to_string())
+        );
+        assert!(denoms.parse_amount("1.5", "untrn").is_err());
+        assert!(denoms.parse_amount("1.5", "uatom").is_err());
+    }
+
+    #[test]
+    fn amounts_display_with_trailing_zeros_trimmed() {
+        let denoms = registry();
+        for (amount, expected) in [
+            (1_500_000u128, "1.5 NTRN"),
+            (2_000_000, "2 NTRN"),
+            (1, "0.000001 NTRN"),
+            (0, "0 NTRN"),
+        ] {
+            assert_eq!(denoms.display(Uint256::from(amount), "untrn"), expected);
+        }
+        assert_eq!(denoms.display(Uint256::from(42u128), "uatom"), "42 uatom");
+    }
+
+    #[test]
+    fn intervals_and_dates_parse() {
+        for (interval, expected) in [
+            ("week", WEEK_SECONDS),
+            ("2 weeks", 2 * WEEK_SECONDS),
+            ("3d", 3 * DAY_SECONDS),
+            ("12h", 12 * HOUR_SECONDS),
+            ("1 Month", MONTH_SECONDS),
+        ] {
+            let parts = interval
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            assert_eq!(parse_interval(&parts).unwrap(), expected, "{interval}");
+        }
+        for interval in ["0 days", "fortnight", "2 weeks please", ""] {
+            let parts = interval
+                .split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>();
+            assert!(parse_interval(&parts).is_err(), "{interval}");
+        }
+
+        assert_eq!(display_interval(WEEK_SECONDS), "week");
+        assert_eq!(display_interval(3 * DAY_SECONDS), "3 days");
+        assert_eq!(display_interval(90 * 60), "90 minutes");
+
+        assert_eq!(
+            parse_date("1970-01-01").unwrap(),
+            Timestamp::from_seconds(0)
+        );
+        assert_eq!(
+            parse_date("2024-02-29").unwrap(),
+            Timestamp::from_seconds(1_709_164_800)
+        );
+        assert_eq!(
+            parse_date("2026-12-31").unwrap(),
+            Timestamp::from_seconds(1_798_675_200)
+        );
+        for date in [
+            "2023-02-29",
+            "2026-13-01",
+            "2026-00-10",
+            "31/12/2026",
+            "1969-12-31",
+        ] {
+            assert!(parse_date(date).is_err(), "{date}");
+        }
+    }
+
+    #[test]
+    fn start_params_round_trip_through_links() {
+        let secret = gift_secret("123:token", -100, 42);
+        assert_eq!(secret.len(), 32);
+        assert_ne!(secret, gift_secret("123:token", -100, 43));
+
+        let param = StartParam::ClaimGift { secret };
+        let link = param.link("paybot");
+        let (_, encoded) = link.split_once("?start=").unwrap();
+        assert_eq!(StartParam::parse(encoded), Some(param));
+
+        for encoded in [
+            "",
+            "claim_",
+            "claim",
+            "invite_abc",
+            "claim_a b",
+            &"a".repeat(65),
+        ] {
+            assert_eq!(StartParam::parse(encoded), None, "{encoded}");
+        }
+    }
+
+    #[test]
+    fn giveaway_buttons_round_trip_through_callback_data() {
+        let data = GiveawayButton::Claim.callback_data(7);
+        assert_eq!(
+            GiveawayButton::parse(&data).unwrap(),
+            (GiveawayButton::Claim, 7)
+        );
+        assert!(data.len() <= 64);
+
+        for data in ["giveaway-claim:", "giveaway-claim:x", "invoice-pay:7"] {
+            assert!(GiveawayButton::parse(data).is_err(), "{data}");
+        }
+    }
+
+    #[test]
+    fn split_commands_parse_even_and_weighted_recipients() {
+        let TelegramWavsCommand::Split {
+            amount,
+            denom,
+            recipients,
+        } = parse("/split 3 NTRN @Alice charlie:2").unwrap()
+        else {
+            panic!("expected a split");
+        };
+        assert_eq!(amount, Uint256::from(3_000_000u128));
+        assert_eq!(denom, "untrn");
+        assert_eq!(
+            recipients,
+            vec![
+                SplitRecipient {
+                    to_tg: handle("alice"),
+                    weight: 1,
+                },
+                SplitRecipient {
+                    to_tg: handle("charlie"),
+                    weight: 2,
+                },
+            ]
+        );
+
+        for text in [
+            "/split 3 NTRN",
+            "/split 3 NTRN @alice:x",
+            "/split 3 NTRN @alice:-1",
+            "/split 3 NTRN @al",
+            "/split NTRN @alice",
+        ] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn request_commands_parse_with_an_optional_memo() {
+        let TelegramWavsCommand::Request {
+            handle: payer,
+            amount,
+            denom,
+            memo,
+        } = parse("/request @Alice 2.5 NTRN for the pizza").unwrap()
+        else {
+            panic!("expected a request");
+        };
+        assert_eq!(payer, handle("alice"));
+        assert_eq!(amount, Uint256::from(2_500_000u128));
+        assert_eq!(denom, "untrn");
+        assert_eq!(memo.as_deref(), Some("for the pizza"));
+
+        let TelegramWavsCommand::Request { memo, .. } = parse("/request @alice 7 USDC").unwrap()
+        else {
+            panic!("expected a request");
+        };
+        assert_eq!(memo, None);
+
+        for text in [
+            "/request @alice 2.5",
+            "/request 2.5 NTRN",
+            "/request @al 2.5 NTRN",
+            "/request @alice lots NTRN",
+        ] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn tip_commands_need_a_reply_to_a_person() {
+        let mut message = message_in("/tip 1 NTRN", 1, TelegramChatType::Private);
+        assert!(matches!(
+            TelegramWavsCommand::parse(&message, &registry()),
+            Err(TelegramBotError::NotAReply)
+        ));
+
+        let mut replied_to = message_in("gm", 1, TelegramChatType::Private);
+        replied_to.from = user(2, true);
+        message.reply_to_message = Some(Box::new(replied_to.clone()));
+        assert!(matches!(
+            TelegramWavsCommand::parse(&message, &registry()),
+            Err(TelegramBotError::TipBot)
+        ));
+
+        replied_to.from = user(2, false);
+        message.reply_to_message = Some(Box::new(replied_to));
+        let TelegramWavsCommand::Tip { amount, denom } =
+            TelegramWavsCommand::parse(&message, &registry()).unwrap()
+        else {
+            panic!("expected a tip");
+        };
+        assert_eq!(amount, Uint256::from(1_000_000u128));
+        assert_eq!(denom, "untrn");
+
+        message.text = Some("/tip @alice 1 NTRN".to_string());
+        assert!(matches!(
+            TelegramWavsCommand::parse(&message, &registry()),
+            Err(TelegramBotError::InvalidCommandFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn schedule_commands_parse_an_interval_and_end_date() {
+        let TelegramWavsCommand::Schedule {
+            handle: recipient,
+            amount,
+            denom,
+            interval_seconds,
+            until,
+        } = parse("/schedule @Alice 5 NTRN every 2 weeks until 2026-12-31").unwrap()
+        else {
+            panic!("expected a schedule");
+        };
+        assert_eq!(recipient, handle("alice"));
+        assert_eq!(amount, Uint256::from(5_000_000u128));
+        assert_eq!(denom, "untrn");
+        assert_eq!(interval_seconds, 2 * WEEK_SECONDS);
+        assert_eq!(until, Some(Timestamp::from_seconds(1_798_675_200)));
+
+        let TelegramWavsCommand::Schedule {
+            interval_seconds,
+            until,
+            ..
+        } = parse("/schedule @alice 5 NTRN Every month").unwrap()
+        else {
+            panic!("expected a schedule");
+        };
+        assert_eq!(interval_seconds, MONTH_SECONDS);
+        assert_eq!(until, None);
+
+        for text in [
+            "/schedule @alice 5 NTRN 2 weeks",
+            "/schedule @alice 5 NTRN every",
+            "/schedule @alice 5 NTRN every fortnight",
+            "/schedule @alice 5 NTRN every week until",
+            "/schedule @alice 5 NTRN every week until 2026-13-01",
+            "/schedule @alice 5 every week",
+        ] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn treasury_commands_parse_in_groups_only() {
+        let group = |text: &str| {
+            TelegramWavsCommand::parse(
+                &message_in(text, -100, TelegramChatType::SuperGroup),
+                &registry(),
+            )
+        };
+
+        assert!(matches!(
+            group("/treasury").unwrap(),
+            TelegramWavsCommand::Treasury
+        ));
+        let TelegramWavsCommand::CreateTreasury { threshold, members } =
+            group("/treasury create 2 @Alice charlie").unwrap()
+        else {
+            panic!("expected a treasury");
+        };
+        assert_eq!(threshold, 2);
+        assert_eq!(members, vec![handle("alice"), handle("charlie")]);
+        let TelegramWavsCommand::FundTreasury { amount, denom } =
+            group("/treasury fund 10 NTRN").unwrap()
+        else {
+            panic!("expected a deposit");
+        };
+        assert_eq!(amount, Uint256::from(10_000_000u128));
+        assert_eq!(denom, "untrn");
+
+        let TelegramWavsCommand::Propose {
+            handle: recipient,
+            amount,
+            memo,
+            ..
+        } = group("/propose @charlie 4 NTRN venue deposit").unwrap()
+        else {
+            panic!("expected a proposal");
+        };
+        assert_eq!(recipient, handle("charlie"));
+        assert_eq!(amount, Uint256::from(4_000_000u128));
+        assert_eq!(memo.as_deref(), Some("venue deposit"));
+
+        assert!(matches!(
+            group("/vote #3 yes").unwrap(),
+            TelegramWavsCommand::Vote {
+                proposal_id: 3,
+                approve: true
+            }
+        ));
+        assert!(matches!(
+            group("/vote 3 reject").unwrap(),
+            TelegramWavsCommand::Vote {
+                proposal_id: 3,
+                approve: false
+            }
+        ));
+
+        for text in [
+            "/treasury create 2",
+            "/treasury create two @alice",
+            "/treasury create 2 @al",
+            "/treasury fund 10",
+            "/treasury close",
+            "/propose @charlie 4",
+            "/vote 3 maybe",
+            "/vote three yes",
+        ] {
+            assert!(group(text).is_err(), "{text}");
+        }
+
+        for text in ["/treasury", "/propose @charlie 4 NTRN", "/vote 3 yes"] {
+            assert!(
+                matches!(parse(text), Err(TelegramBotError::NotGroupChat)),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn gift_commands_parse_an_optional_expiry() {
+        let TelegramWavsCommand::Gift {
+            amount,
+            denom,
+            expires_in_seconds,
+        } = parse("/gift 1.5 NTRN for 3 days").unwrap()
+        else {
+            panic!("expected a gift");
+        };
+        assert_eq!(amount, Uint256::from(1_500_000u128));
+        assert_eq!(denom, "untrn");
+        assert_eq!(expires_in_seconds, Some(3 * DAY_SECONDS));
+
+        let TelegramWavsCommand::Gift {
+            expires_in_seconds, ..
+        } = parse("/gift 7 USDC").unwrap()
+        else {
+            panic!("expected a gift");
+        };
+        assert_eq!(expires_in_seconds, None);
+
+        for text in [
+            "/gift 1.5",
+            "/gift 1.5 NTRN 3 days",
+            "/gift 1.5 NTRN for",
+            "/gift 1.5 NTRN for ever",
+            "/gift some NTRN",
+        ] {
+            assert!(parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn giveaway_commands_parse_in_groups_only() {
+        let group = |text: &str| {
+            TelegramWavsCommand::parse(
+                &message_in(text, -100, TelegramChatType::Group),
+                &registry(),
+            )
+        };
+
+        let TelegramWavsCommand::Giveaway {
+            amount,
+            denom,
+            count,
+            split,
+        } = group("/giveaway 10 NTRN 5").unwrap()
+        else {
+            panic!("expected a giveaway");
+        };
+        assert_eq!(amount, Uint256::from(10_000_000u128));
+        assert_eq!(denom, "untrn");
+        assert_eq!(count, 5);
+        assert_eq!(split, GiveawaySplit::Random);
+
+        let TelegramWavsCommand::Giveaway { split, .. } =
+            group("/giveaway 10 NTRN 5 equal").unwrap()
+        else {
+            panic!("expected a giveaway");
+        };
+        assert_eq!(split, GiveawaySplit::Equal);
+
+        for text in [
+            "/giveaway 10 NTRN",
+            "/giveaway 10 NTRN five",
+            "/giveaway 10 NTRN -1",
+            "/giveaway 10 NTRN 5 fair",
+            "/giveaway 10 NTRN 5 equal now",
+        ] {
+            assert!(group(text).is_err(), "{text}");
+        }
+
+        assert!(matches!(
+            parse("/giveaway 10 NTRN 5"),
+            Err(TelegramBotError::NotGroupChat)
+        ));
+        assert!(matches!(
+            TelegramWavsCommand::parse(
+                &message_in("/giveaway 10 NTRN 5", 100, TelegramChatType::Group),
+                &registry()
+            ),
+            Err(TelegramBotError::InvalidGroupId)
+        ));
+    }
+}
@@ -1,4 +1,3 @@
 pub mod bot;
 pub mod native;
-// TODO
-// pub mod state_machine;
+pub mod state_machine;
@@ -58,6 +58,7 @@ pub struct TelegramMessage {
     pub text: Option<String>,
     pub new_chat_members: Option<Vec<TelegramUser>>,
     pub left_chat_member: Option<TelegramUser>,
+    pub reply_to_message: Option<Box<TelegramMessage>>,
 }
 
 #[derive(Clone, Deserialize, Serialize, Debug)]
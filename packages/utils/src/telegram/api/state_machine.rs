@@ -1,3 +1,9 @@
+use anyhow::bail;
+use layer_climb::prelude::CosmosAddr;
+use tg_contract_api::payments::handle::TgHandle;
+
+use crate::telegram::api::bot::{DenomRegistry, TelegramWavsCommand};
+
 /// These are types for the state machine of parsing commands.
 /// Only contains intermediate state, we get:
 /// (State, Text) -> (State, Option<Command>)
@@ -6,8 +12,9 @@ pub enum TGChatState {
     Wait,
     WavsReceive,
     WavsSend,
-    WavsSendHandle(String),
-    WavsSendHandleAmount(String, u64),
+    WavsSendHandle(TgHandle),
+    /// The amount as typed, it can only be parsed once we know the denom
+    WavsSendHandleAmount(TgHandle, String),
 }
 
 impl TGChatState {
@@ -19,17 +26,25 @@ impl TGChatState {
                 Some("What blockchain address would you like to receive to?".to_string())
             }
             TGChatState::WavsSend => Some("Who would you like to send to?".to_string()),
-            TGChatState::WavsSendHandle(handle) => {
-                Some(format!("How much would you like to send to {}?", handle))
-            }
+            TGChatState::WavsSendHandle(handle) => Some(format!(
+                "How much would you like to send to {}?",
+                handle.mention()
+            )),
             TGChatState::WavsSendHandleAmount(_, _) => Some("Which denom?".to_string()),
         }
     }
 
-    pub fn next_state(self, text: &str) -> anyhow::Result<(Self, Option<TelegramWavsCommand>)> {
+    pub fn next_state(
+        self,
+        text: &str,
+        denoms: &DenomRegistry,
+    ) -> anyhow::Result<(Self, Option<TelegramWavsCommand>)> {
         match self {
             Self::Wait => match text {
-                "/start" => Ok((TGChatState::Wait, Some(TelegramWavsCommand::Start))),
+                "/start" => Ok((
+                    TGChatState::Wait,
+                    Some(TelegramWavsCommand::Start { param: None }),
+                )),
                 "/help" => Ok((TGChatState::Wait, Some(TelegramWavsCommand::Help))),
                 "/status" => Ok((TGChatState::Wait, Some(TelegramWavsCommand::Status))),
                 "/send" => Ok((TGChatState::WavsSend, None)),
@@ -38,42 +53,43 @@ impl TGChatState {
             },
             Self::WavsReceive => {
                 if text.starts_with("/") {
-                    return Self::Wait.next_state(text);
+                    return Self::Wait.next_state(text, denoms);
                 }
                 let address = text.parse::<CosmosAddr>()?;
                 Ok((Self::Wait, Some(TelegramWavsCommand::Receive { address })))
             }
             TGChatState::WavsSend => {
                 if text.starts_with("/") {
-                    return Self::Wait.next_state(text);
+                    return Self::Wait.next_state(text, denoms);
                 }
                 // get handle
-                let handle = text.trim();
-                if !handle.starts_with("@") {
-                    bail!("Provide a telegram username, starting with @");
-                }
-                Ok((TGChatState::WavsSendHandle(handle.to_string()), None))
+                let handle = TgHandle::new(text)?;
+                Ok((TGChatState::WavsSendHandle(handle), None))
             }
             TGChatState::WavsSendHandle(handle) => {
                 if text.starts_with("/") {
-                    return Self::Wait.next_state(text);
+                    return Self::Wait.next_state(text, denoms);
+                }
+                // get amount, with the denom too if it was typed as "1.5 NTRN"
+                match text.split_whitespace().collect::<Vec<_>>()[..] {
+                    [amount] => Ok((Self::WavsSendHandleAmount(handle, amount.to_string()), None)),
+                    [amount, denom] => Self::WavsSendHandleAmount(handle, amount.to_string())
+                        .next_state(denom, denoms),
+                    _ => bail!("expected an amount, like 1.5 NTRN"),
                 }
-                // get amount
-                let amount: u64 = text.trim().parse()?;
-                Ok((Self::WavsSendHandleAmount(handle.to_string(), amount), None))
             }
             TGChatState::WavsSendHandleAmount(handle, amount) => {
                 if text.starts_with("/") {
-                    return Self::Wait.next_state(text);
+                    return Self::Wait.next_state(text, denoms);
                 }
                 // get denom
-                let denom = text.trim();
+                let (amount, denom) = denoms.parse_amount(&amount, text.trim())?;
                 Ok((
                     Self::Wait,
                     Some(TelegramWavsCommand::Send {
-                        handle: handle.to_string(),
+                        handle,
                         amount,
-                        denom: denom.to_string(),
+                        denom,
                     }),
                 ))
             }
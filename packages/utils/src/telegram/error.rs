@@ -1,3 +1,4 @@
+use tg_contract_api::payments::handle::TgHandleError;
 use thiserror::Error;
 
 use crate::telegram::api::bot::TelegramWavsCommandPrefix;
@@ -22,6 +23,8 @@ pub enum TelegramBotError {
     BadCommand,
     #[error("Parse: {0}")]
     Parse(String),
+    #[error("{0}")]
+    InvalidHandle(#[from] TgHandleError),
     #[error("This is not a group chat ;)")]
     NotGroupChat,
     #[error("Internal: {0}")]
@@ -36,8 +39,20 @@ pub enum TelegramBotError {
     GetService(anyhow::Error),
     #[error("Error getting status: {0:?}")]
     StatusAny(anyhow::Error),
+    #[error("Error quoting the fee: {0:?}")]
+    FeeQuote(anyhow::Error),
+    #[error("Error listing recurring payments: {0:?}")]
+    Schedules(anyhow::Error),
+    #[error("Error looking up the treasury: {0:?}")]
+    Treasury(anyhow::Error),
+    #[error("Error looking up the gift: {0:?}")]
+    Gift(anyhow::Error),
     #[error("User does not have a username set")]
     NoUsername,
+    #[error("Reply to someone's message to tip them")]
+    NotAReply,
+    #[error("Bots can't be tipped")]
+    TipBot,
 }
 
 impl TelegramBotError {
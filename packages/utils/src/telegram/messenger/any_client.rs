@@ -115,6 +115,47 @@ pub trait TelegramMessengerExt {
         self._make_request_params("sendMessage", params).await
     }
 
+    /// A message with a single row of buttons, each a `(label, callback_data)` pair
+    async fn send_message_with_buttons(
+        &self,
+        chat_id: i64,
+        text: &str,
+        buttons: &[(&str, String)],
+    ) -> TgResult<TelegramMessage> {
+        let mut params = HashMap::new();
+
+        let text = escape_markdown_v2(text);
+        let keyboard = serde_json::json!({
+            "inline_keyboard": [buttons
+                .iter()
+                .map(|(label, data)| serde_json::json!({
+                    "text": label,
+                    "callback_data": data,
+                }))
+                .collect::<Vec<_>>()]
+        });
+
+        params.insert("chat_id".to_string(), chat_id.to_string());
+        params.insert("text".to_string(), text.to_string());
+        params.insert("parse_mode".to_string(), "MarkdownV2".to_string());
+        params.insert("reply_markup".to_string(), keyboard.to_string());
+
+        self._make_request_params("sendMessage", params).await
+    }
+
+    /// Must be called for every button press, or the client keeps showing a spinner
+    async fn answer_callback_query(&self, callback_query_id: &str, text: &str) -> TgResult<bool> {
+        let mut params = HashMap::new();
+        params.insert(
+            "callback_query_id".to_string(),
+            callback_query_id.to_string(),
+        );
+        params.insert("text".to_string(), text.to_string());
+
+        self._make_request_params("answerCallbackQuery", params)
+            .await
+    }
+
     async fn send_message(&self, chat_id: i64, text: &str) -> TgResult<TelegramMessage> {
         let mut params = HashMap::new();
 